@@ -0,0 +1,721 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ggez::event::{KeyCode, KeyMods};
+use ggez::input::keyboard;
+use ggez::Context;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::Deserialize;
+
+use crate::help::Key;
+
+// everything a key can do. the names are what the [keys] table in the config uses,
+// e.g. `reseed = "Shift+R"` or `pause = ["Space", "P"]`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    ToggleHelp,
+    Reseed,
+    ResetLeft,
+    ResetRight,
+    Reset,
+    Preset1,
+    Preset2,
+    Preset3,
+    Preset4,
+    Preset5,
+    Preset6,
+    Preset7,
+    Preset8,
+    Preset9,
+    Preset10,
+    NextPreset,
+    PreviousPreset,
+    Pause,
+    Step,
+    SlowDown,
+    SpeedUp,
+    GravityCoarseUp,
+    GravityCoarseDown,
+    GravityCoarseLeft,
+    GravityCoarseRight,
+    GravityUp,
+    GravityDown,
+    GravityLeft,
+    GravityRight,
+    ToggleGravity,
+    FlipGravity,
+    ToggleDrag,
+    ToggleWells,
+    NextBoundary,
+    ToggleRepulsion,
+    Select,
+    DeleteSelected,
+    PinSelected,
+    UndoDelete,
+    ClearTracers,
+    SaveTracers,
+    RemoveLastWall,
+    ClearObstacles,
+    OpenDividers,
+    ToggleGate,
+    ToggleDemon,
+    NextWeather,
+    Reheat,
+    WallTool,
+    PillarTool,
+    WellTool,
+    BlackHoleTool,
+    BlockTool,
+    EmitterTool,
+    SaveScene,
+    LoadScene,
+    TogglePanel,
+    NextTheme,
+    NextColorPolicy,
+    ToggleGrid,
+    ToggleHeatMap,
+    ToggleCollisionMath,
+    ToggleSquash,
+    ToggleGlow,
+    NextParticleStyle,
+    Explode,
+    Burst,
+    TogglePlexus,
+    NextLabels,
+    ToggleCenterOfMass,
+    ToggleGas,
+    ToggleMaxwell,
+    ToggleBoids,
+    ToggleBoidRadius,
+    NextInsetTarget,
+    ToggleInset,
+    NextClearMode,
+    ToggleSound,
+    ToggleFps,
+    ToggleFullscreen,
+    DemoReel,
+    StressTest,
+    Menu,
+    Quit,
+}
+
+impl Action {
+    // the ones meant to be held, going again on each of the key's repeats. everything else
+    // happens once a press, so holding a toggle down doesn't flick it back and forth
+    pub fn repeats(self) -> bool {
+        matches!(
+            self,
+            Action::Step
+                | Action::GravityCoarseUp
+                | Action::GravityCoarseDown
+                | Action::GravityCoarseLeft
+                | Action::GravityCoarseRight
+                | Action::GravityUp
+                | Action::GravityDown
+                | Action::GravityLeft
+                | Action::GravityRight
+                | Action::SlowDown
+                | Action::SpeedUp
+        )
+    }
+}
+
+pub struct ActionInfo {
+    pub action: Action,
+    pub keys: &'static [Key],
+    pub description: &'static str,
+}
+
+// every action with its default keys, in the order the help lists them
+pub static ACTIONS: &[ActionInfo] = &[
+    ActionInfo {
+        action: Action::ToggleHelp,
+        keys: &[Key::plain(KeyCode::H), Key::plain(KeyCode::F1)],
+        description: "show/hide this help",
+    },
+    ActionInfo {
+        action: Action::Reseed,
+        keys: &[Key::shift(KeyCode::R)],
+        description: "reset with a new random seed",
+    },
+    ActionInfo {
+        action: Action::ResetLeft,
+        keys: &[Key::ctrl(KeyCode::R)],
+        description: "reset the left side of a comparison only",
+    },
+    ActionInfo {
+        action: Action::ResetRight,
+        keys: &[Key::alt(KeyCode::R)],
+        description: "reset the right side of a comparison only",
+    },
+    ActionInfo {
+        action: Action::Reset,
+        keys: &[Key::plain(KeyCode::R)],
+        description: "reset to the same starting scene",
+    },
+    ActionInfo {
+        action: Action::Preset1,
+        keys: &[Key::plain(KeyCode::Key1)],
+        description: "preset: ring of particles (the default)",
+    },
+    ActionInfo {
+        action: Action::Preset2,
+        keys: &[Key::plain(KeyCode::Key2)],
+        description: "preset: two equal masses head-on",
+    },
+    ActionInfo {
+        action: Action::Preset3,
+        keys: &[Key::plain(KeyCode::Key3)],
+        description: "preset: heavy vs light head-on",
+    },
+    ActionInfo {
+        action: Action::Preset4,
+        keys: &[Key::plain(KeyCode::Key4)],
+        description: "preset: newton's cradle",
+    },
+    ActionInfo {
+        action: Action::Preset5,
+        keys: &[Key::plain(KeyCode::Key5)],
+        description: "preset: billiards rack and cue ball",
+    },
+    ActionInfo {
+        action: Action::Preset6,
+        keys: &[Key::plain(KeyCode::Key6)],
+        description: "preset: dense gas, no gravity",
+    },
+    ActionInfo {
+        action: Action::Preset7,
+        keys: &[Key::plain(KeyCode::Key7)],
+        description: "preset: rain under strong gravity",
+    },
+    ActionInfo {
+        action: Action::Preset8,
+        keys: &[Key::plain(KeyCode::Key8)],
+        description: "preset: brownian motion",
+    },
+    ActionInfo {
+        action: Action::Preset9,
+        keys: &[Key::plain(KeyCode::Key9)],
+        description: "preset: two clusters colliding",
+    },
+    ActionInfo {
+        action: Action::Preset10,
+        keys: &[Key::plain(KeyCode::Key0)],
+        description: "preset: hourglass",
+    },
+    ActionInfo {
+        action: Action::NextPreset,
+        keys: &[Key::ctrl(KeyCode::Right)],
+        description: "next preset, past the number keys' ten too",
+    },
+    ActionInfo {
+        action: Action::PreviousPreset,
+        keys: &[Key::ctrl(KeyCode::Left)],
+        description: "previous preset",
+    },
+    ActionInfo {
+        action: Action::Pause,
+        keys: &[Key::plain(KeyCode::Space)],
+        description: "pause/resume",
+    },
+    ActionInfo {
+        action: Action::Step,
+        keys: &[Key::plain(KeyCode::Period)],
+        description: "while paused, advance one physics step (hold to keep stepping)",
+    },
+    ActionInfo {
+        action: Action::SlowDown,
+        keys: &[Key::plain(KeyCode::LBracket)],
+        description: "slow down time",
+    },
+    ActionInfo {
+        action: Action::SpeedUp,
+        keys: &[Key::plain(KeyCode::RBracket)],
+        description: "speed up time",
+    },
+    ActionInfo {
+        action: Action::GravityCoarseUp,
+        keys: &[Key::shift(KeyCode::Up)],
+        description: "gravity a big step up",
+    },
+    ActionInfo {
+        action: Action::GravityCoarseDown,
+        keys: &[Key::shift(KeyCode::Down)],
+        description: "gravity a big step down",
+    },
+    ActionInfo {
+        action: Action::GravityCoarseLeft,
+        keys: &[Key::shift(KeyCode::Left)],
+        description: "gravity a big step left",
+    },
+    ActionInfo {
+        action: Action::GravityCoarseRight,
+        keys: &[Key::shift(KeyCode::Right)],
+        description: "gravity a big step right",
+    },
+    ActionInfo {
+        action: Action::GravityUp,
+        keys: &[Key::plain(KeyCode::Up)],
+        description: "gravity a step up, pointing it more that way",
+    },
+    ActionInfo {
+        action: Action::GravityDown,
+        keys: &[Key::plain(KeyCode::Down)],
+        description: "gravity a step down, pointing it more that way",
+    },
+    ActionInfo {
+        action: Action::GravityLeft,
+        keys: &[Key::plain(KeyCode::Left)],
+        description: "gravity a step left, pointing it more that way",
+    },
+    ActionInfo {
+        action: Action::GravityRight,
+        keys: &[Key::plain(KeyCode::Right)],
+        description: "gravity a step right, pointing it more that way",
+    },
+    ActionInfo {
+        action: Action::ToggleGravity,
+        keys: &[Key::plain(KeyCode::G)],
+        description: "switch gravity off/on, keeping its setting",
+    },
+    ActionInfo {
+        action: Action::FlipGravity,
+        keys: &[Key::shift(KeyCode::G)],
+        description: "turn gravity upside down, to turn the hourglass over",
+    },
+    ActionInfo {
+        action: Action::ToggleDrag,
+        keys: &[Key::plain(KeyCode::D)],
+        description: "switch drag off/on",
+    },
+    ActionInfo {
+        action: Action::ToggleWells,
+        keys: &[Key::shift(KeyCode::A)],
+        description: "switch the gravity wells' pull off/on",
+    },
+    ActionInfo {
+        action: Action::NextBoundary,
+        keys: &[Key::ctrl(KeyCode::B)],
+        description: "cycle the edges: walls, wrapping round, open top, a channel",
+    },
+    ActionInfo {
+        action: Action::ToggleRepulsion,
+        keys: &[Key::plain(KeyCode::F)],
+        description: "toggle the cursor pushing particles away",
+    },
+    ActionInfo {
+        action: Action::Select,
+        keys: &[Key::plain(KeyCode::S)],
+        description: "hold while left dragging to select the particles in a rectangle",
+    },
+    ActionInfo {
+        action: Action::DeleteSelected,
+        keys: &[Key::plain(KeyCode::Delete), Key::plain(KeyCode::Back)],
+        description: "delete the selected particles",
+    },
+    ActionInfo {
+        action: Action::PinSelected,
+        keys: &[Key::plain(KeyCode::T)],
+        description: "pin the selected particles in place, or let them go",
+    },
+    ActionInfo {
+        action: Action::UndoDelete,
+        keys: &[Key::ctrl(KeyCode::Z)],
+        description: "undo the last delete",
+    },
+    ActionInfo {
+        action: Action::ClearTracers,
+        keys: &[Key::shift(KeyCode::T)],
+        description: "start the marked particles' paths again from where they are",
+    },
+    ActionInfo {
+        action: Action::SaveTracers,
+        keys: &[Key::ctrl(KeyCode::T)],
+        description: "save the marked particles' paths to tracer_file as csv",
+    },
+    ActionInfo {
+        action: Action::RemoveLastWall,
+        keys: &[Key::shift(KeyCode::W)],
+        description: "remove the last wall drawn",
+    },
+    ActionInfo {
+        action: Action::ClearObstacles,
+        keys: &[Key::ctrl(KeyCode::W)],
+        description: "remove every wall, pillar, block, well, black hole and emitter",
+    },
+    ActionInfo {
+        action: Action::OpenDividers,
+        keys: &[Key::plain(KeyCode::U)],
+        description: "take out the divider walls, like the one between the two gases",
+    },
+    ActionInfo {
+        action: Action::ToggleGate,
+        keys: &[Key::shift(KeyCode::U)],
+        description: "open or close the gates by hand, taking them off the demon",
+    },
+    ActionInfo {
+        action: Action::ToggleDemon,
+        keys: &[Key::ctrl(KeyCode::U)],
+        description: "hand the gates to the demon, or take them back",
+    },
+    ActionInfo {
+        action: Action::NextWeather,
+        keys: &[Key::plain(KeyCode::Y)],
+        description: "cycle the weather: off, rain or snow coming back in at the top",
+    },
+    ActionInfo {
+        action: Action::Reheat,
+        keys: &[Key::plain(KeyCode::Z)],
+        description: "heat everything back up to reheat_temperature, melting any crystals",
+    },
+    ActionInfo {
+        action: Action::WallTool,
+        keys: &[Key::plain(KeyCode::W)],
+        description: "toggle drawing walls with the left mouse button",
+    },
+    ActionInfo {
+        action: Action::PillarTool,
+        keys: &[Key::plain(KeyCode::K)],
+        description: "toggle placing pillars with the left mouse button",
+    },
+    ActionInfo {
+        action: Action::WellTool,
+        keys: &[Key::plain(KeyCode::A)],
+        description: "toggle placing gravity wells with the left mouse button",
+    },
+    ActionInfo {
+        action: Action::BlackHoleTool,
+        keys: &[Key::shift(KeyCode::H)],
+        description: "toggle placing black holes with the left mouse button",
+    },
+    ActionInfo {
+        action: Action::BlockTool,
+        keys: &[Key::plain(KeyCode::J)],
+        description: "toggle dragging out solid blocks with the left mouse button",
+    },
+    ActionInfo {
+        action: Action::EmitterTool,
+        keys: &[Key::plain(KeyCode::V)],
+        description: "toggle placing emitters, dragging the way they point",
+    },
+    ActionInfo {
+        action: Action::SaveScene,
+        keys: &[Key::ctrl(KeyCode::S)],
+        description: "save the scene, walls, pillars, wells and black holes included",
+    },
+    ActionInfo {
+        action: Action::LoadScene,
+        keys: &[Key::ctrl(KeyCode::O)],
+        description: "load the saved scene",
+    },
+    ActionInfo {
+        action: Action::TogglePanel,
+        keys: &[Key::plain(KeyCode::Tab)],
+        description: "show/hide the settings panel",
+    },
+    ActionInfo {
+        action: Action::NextTheme,
+        keys: &[Key::plain(KeyCode::P)],
+        description: "cycle color theme",
+    },
+    ActionInfo {
+        action: Action::NextColorPolicy,
+        keys: &[Key::plain(KeyCode::C)],
+        description: "cycle color policy: original, recolor on collision, blend",
+    },
+    ActionInfo {
+        action: Action::ToggleGrid,
+        keys: &[Key::plain(KeyCode::F2)],
+        description: "toggle background grid",
+    },
+    ActionInfo {
+        action: Action::ToggleHeatMap,
+        keys: &[Key::plain(KeyCode::F4)],
+        description: "toggle the heat map, each part of the box colored by how hot it is",
+    },
+    ActionInfo {
+        action: Action::ToggleCollisionMath,
+        keys: &[Key::shift(KeyCode::C)],
+        description: "paused or in slow motion, show the workings of the last collision",
+    },
+    ActionInfo {
+        action: Action::ToggleSquash,
+        keys: &[Key::plain(KeyCode::Q)],
+        description: "toggle squash and stretch",
+    },
+    ActionInfo {
+        action: Action::ToggleGlow,
+        keys: &[Key::plain(KeyCode::O)],
+        description: "toggle glow",
+    },
+    ActionInfo {
+        action: Action::NextParticleStyle,
+        keys: &[Key::shift(KeyCode::E)],
+        description: "cycle filled, outlined and both",
+    },
+    ActionInfo {
+        action: Action::Explode,
+        keys: &[Key::plain(KeyCode::E)],
+        description: "explosion at the cursor",
+    },
+    ActionInfo {
+        action: Action::Burst,
+        keys: &[Key::plain(KeyCode::B)],
+        description: "burst of particles flying out from the cursor",
+    },
+    ActionInfo {
+        action: Action::TogglePlexus,
+        keys: &[Key::plain(KeyCode::X)],
+        description: "toggle plexus lines",
+    },
+    ActionInfo {
+        action: Action::NextLabels,
+        keys: &[Key::plain(KeyCode::L)],
+        description: "cycle particle labels",
+    },
+    ActionInfo {
+        action: Action::ToggleCenterOfMass,
+        keys: &[Key::plain(KeyCode::M)],
+        description: "toggle center of mass",
+    },
+    ActionInfo {
+        action: Action::ToggleGas,
+        keys: &[Key::shift(KeyCode::M)],
+        description: "toggle the gas temperature and pressure readouts",
+    },
+    ActionInfo {
+        action: Action::ToggleMaxwell,
+        keys: &[Key::ctrl(KeyCode::M)],
+        description: "toggle the speed histogram and maxwell-boltzmann fit",
+    },
+    ActionInfo {
+        action: Action::ToggleBoids,
+        keys: &[Key::ctrl(KeyCode::F)],
+        description: "toggle flocking, which turns gravity off",
+    },
+    ActionInfo {
+        action: Action::ToggleBoidRadius,
+        keys: &[Key::shift(KeyCode::F)],
+        description: "show/hide how far each particle can see when flocking",
+    },
+    ActionInfo {
+        action: Action::NextInsetTarget,
+        keys: &[Key::shift(KeyCode::I)],
+        description: "inset follows the fastest particle, or stays on the current one",
+    },
+    ActionInfo {
+        action: Action::ToggleInset,
+        keys: &[Key::plain(KeyCode::I)],
+        description: "show/hide the zoomed inset",
+    },
+    ActionInfo {
+        action: Action::NextClearMode,
+        keys: &[Key::shift(KeyCode::B)],
+        description: "cycle hard clear, fade clear and no clear",
+    },
+    ActionInfo {
+        action: Action::ToggleSound,
+        keys: &[Key::plain(KeyCode::N)],
+        description: "mute/unmute sounds",
+    },
+    ActionInfo {
+        action: Action::ToggleFps,
+        keys: &[Key::plain(KeyCode::F3)],
+        description: "toggle fps counter",
+    },
+    ActionInfo {
+        action: Action::ToggleFullscreen,
+        keys: &[Key::plain(KeyCode::F11), Key::alt(KeyCode::Return)],
+        description: "toggle borderless fullscreen",
+    },
+    ActionInfo {
+        action: Action::DemoReel,
+        keys: &[Key::plain(KeyCode::F5)],
+        description:
+            "play the presets one after another as a demo reel, or skip to the next, esc stops",
+    },
+    ActionInfo {
+        action: Action::StressTest,
+        keys: &[Key::plain(KeyCode::F6)],
+        description: "find how many particles it keeps up with, or start again, esc stops",
+    },
+    ActionInfo {
+        action: Action::Menu,
+        keys: &[Key::plain(KeyCode::Escape)],
+        description: "pause menu: resume, reset, fullscreen, settings, quit",
+    },
+    ActionInfo {
+        action: Action::Quit,
+        keys: &[Key::ctrl(KeyCode::Q)],
+        description: "quit",
+    },
+];
+
+// one or more keys for an action in the config, a single one can go without the brackets
+#[derive(Clone)]
+pub struct KeyList(Vec<Key>);
+
+impl<'de> Deserialize<'de> for KeyList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<KeyList, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = KeyList;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a key like \"Shift+R\", or a list of them")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<KeyList, E> {
+                let key = text.parse().map_err(E::custom)?;
+                Ok(KeyList(vec![key]))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<KeyList, A::Error> {
+                let mut keys = Vec::new();
+                while let Some(key) = seq.next_element()? {
+                    keys.push(key);
+                }
+                Ok(KeyList(keys))
+            }
+        }
+
+        deserializer.deserialize_any(KeysVisitor)
+    }
+}
+
+// which key does what, the defaults with the config's [keys] table on top. key handling and
+// the help overlay both go through this, so the help always shows what the keys really do
+pub struct KeyMap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl KeyMap {
+    // an action in `overrides` loses its default keys. a key bound to two actions goes to
+    // whichever the config mentions, or whichever the help lists first, with a warning
+    pub fn new(overrides: &BTreeMap<Action, KeyList>) -> KeyMap {
+        let mut bindings: Vec<(Key, Action)> = Vec::new();
+        let mut bind =
+            |key: Key, action: Action| match bindings.iter().find(|(bound, _)| *bound == key) {
+                Some((_, other)) if *other != action => println!(
+                    "warning: {} is bound to both {:?} and {:?}, keeping {:?}",
+                    key.name(),
+                    other,
+                    action,
+                    other
+                ),
+                Some(_) => {}
+                None => bindings.push((key, action)),
+            };
+
+        for (action, keys) in overrides {
+            for key in &keys.0 {
+                bind(*key, *action);
+            }
+        }
+        for info in ACTIONS {
+            if !overrides.contains_key(&info.action) {
+                for key in info.keys {
+                    bind(*key, info.action);
+                }
+            }
+        }
+
+        // shift is lenient, so a plain binding would also catch a shifted press. check the
+        // ones with more modifiers first so shift+key finds its own binding
+        bindings.sort_by_key(|(key, _)| std::cmp::Reverse(key.mods.bits().count_ones()));
+        KeyMap { bindings }
+    }
+
+    pub fn find(&self, code: KeyCode, mods: KeyMods) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(key, _)| key.matches(code, mods))
+            .map(|(_, action)| *action)
+    }
+
+    // what a key going down does, none when it's an auto-repeat of one that isn't held
+    pub fn press(&self, code: KeyCode, mods: KeyMods, repeat: bool) -> Option<Action> {
+        self.find(code, mods)
+            .filter(|action| !repeat || action.repeats())
+    }
+
+    // whether a key for `action` is down right now, for the ones that change what the
+    // mouse does while they're held
+    pub fn held(&self, ctx: &Context, action: Action) -> bool {
+        let mods = keyboard::active_mods(ctx);
+        self.bindings.iter().any(|(key, bound)| {
+            *bound == action
+                && keyboard::is_key_pressed(ctx, key.code)
+                && key.matches(key.code, mods)
+        })
+    }
+
+    // the keys for every action that has any, one row each in the usual order
+    pub fn rows(&self) -> Vec<(String, &'static str)> {
+        ACTIONS
+            .iter()
+            .filter_map(|info| {
+                self.keys_for(info.action)
+                    .map(|names| (names, info.description))
+            })
+            .collect()
+    }
+
+    // the names of the keys for `action`, none if it hasn't any
+    pub fn keys_for(&self, action: Action) -> Option<String> {
+        let names: Vec<_> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(key, _)| key.name())
+            .collect();
+        if names.is_empty() {
+            None
+        } else {
+            Some(names.join(" / "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_held_toggle_goes_once_and_a_held_step_keeps_going() {
+        let keys = KeyMap::new(&BTreeMap::new());
+        let press = |code, mods, repeat| keys.press(code, mods, repeat);
+        assert_eq!(
+            press(KeyCode::Space, KeyMods::NONE, false),
+            Some(Action::Pause)
+        );
+        assert_eq!(press(KeyCode::Space, KeyMods::NONE, true), None);
+        assert_eq!(press(KeyCode::Return, KeyMods::ALT, true), None);
+        assert_eq!(press(KeyCode::F11, KeyMods::NONE, true), None);
+        assert_eq!(
+            press(KeyCode::Period, KeyMods::NONE, true),
+            Some(Action::Step)
+        );
+        assert_eq!(
+            press(KeyCode::Up, KeyMods::SHIFT, true),
+            Some(Action::GravityCoarseUp)
+        );
+    }
+
+    #[test]
+    fn gravity_and_select_go_wherever_they_are_bound() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert(Action::GravityUp, KeyList(vec![Key::plain(KeyCode::Home)]));
+        overrides.insert(Action::Select, KeyList(vec![Key::plain(KeyCode::End)]));
+        let keys = KeyMap::new(&overrides);
+        assert_eq!(
+            keys.find(KeyCode::Home, KeyMods::NONE),
+            Some(Action::GravityUp)
+        );
+        assert_eq!(keys.find(KeyCode::Up, KeyMods::NONE), None);
+        assert_eq!(keys.find(KeyCode::End, KeyMods::NONE), Some(Action::Select));
+        assert_eq!(keys.find(KeyCode::S, KeyMods::NONE), None);
+    }
+}
@@ -0,0 +1,64 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+use crate::config::SimulationConfig;
+use crate::Point;
+
+// the most a single touch of the belt changes a particle's speed by
+const MAX_KICK: f32 = 40.0;
+
+const BELT_HEIGHT: f32 = 10.0;
+const STRIPE_SPACING: f32 = 24.0;
+const STRIPE_WIDTH: f32 = 8.0;
+const BELT_COLOR: Color = Color::new(0.25, 0.25, 0.25, 1.0);
+const STRIPE_COLOR: Color = Color::new(0.9, 0.75, 0.2, 1.0);
+
+// the change to a particle's sideways speed as it bounces off the belt: belt_grip of the way
+// to the belt's speed, so anything sitting on it picks up speed until it's going along with it
+pub fn kick(vel_x: f32, config: &SimulationConfig) -> f32 {
+    (config.belt_grip * (config.belt_speed - vel_x)).clamp(-MAX_KICK, MAX_KICK)
+}
+
+// how far the stripes have moved after `time_elapsed` more seconds, kept small
+pub fn scroll(offset: f32, time_elapsed: f32, config: &SimulationConfig) -> f32 {
+    (offset + config.belt_speed * time_elapsed).rem_euclid(STRIPE_SPACING)
+}
+
+// a bar along the floor with slanted stripes, `offset` along so they move with the belt
+pub fn mesh(ctx: &mut Context, offset: f32, width: f32, height: f32) -> GameResult<Mesh> {
+    let top = height - BELT_HEIGHT;
+    let mut builder = MeshBuilder::new();
+    builder.rectangle(
+        DrawMode::fill(),
+        Rect::new(0.0, top, width, BELT_HEIGHT),
+        BELT_COLOR,
+    );
+
+    // each stripe leans over by its own width, and only whole ones are drawn
+    let mut x = offset - STRIPE_SPACING;
+    while x < width {
+        if x >= 0.0 && x + 2.0 * STRIPE_WIDTH <= width {
+            builder.polygon(
+                DrawMode::fill(),
+                &[
+                    Point { x, y: height },
+                    Point {
+                        x: x + STRIPE_WIDTH,
+                        y: height,
+                    },
+                    Point {
+                        x: x + 2.0 * STRIPE_WIDTH,
+                        y: top,
+                    },
+                    Point {
+                        x: x + STRIPE_WIDTH,
+                        y: top,
+                    },
+                ],
+                STRIPE_COLOR,
+            )?;
+        }
+        x += STRIPE_SPACING;
+    }
+    builder.build(ctx)
+}
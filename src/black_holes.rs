@@ -0,0 +1,197 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{Point, Vector};
+
+// a hole's mass is its pull this far from the center, like a well's strength
+const PULL_REFERENCE: f32 = 100.0;
+// the event horizon's radius goes with the square root of the mass, so its area grows with it
+const HORIZON_SCALE: f32 = 0.5;
+// clicks on the horizon or this close outside it pick a hole
+const PICK_MARGIN: f32 = 5.0;
+// how many of the last colors swallowed make up the accretion ring
+const RING_COLORS: usize = 24;
+
+const DISC_COLOR: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+const RIM_COLOR: Color = Color::new(0.5, 0.5, 0.5, 0.5);
+const RIM_WIDTH: f32 = 1.0;
+// the ring sits this far out, as a multiple of the horizon, and is this faint
+const RING_SCALE: f32 = 1.6;
+const RING_WIDTH: f32 = 3.0;
+const RING_ALPHA: f32 = 0.35;
+const RING_POINTS: usize = 4;
+
+// a point pulling everything in with 1 / distance^2, swallowing whatever gets inside its
+// event horizon and growing for it. an acceleration like a well's, so heavy and light
+// particles fall in alike
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlackHole {
+    pub pos: Point,
+    pub mass: f32,
+    // since the last reset, not saved
+    #[serde(skip)]
+    pub consumed: usize,
+    // the colors of the last RING_COLORS swallowed, oldest first
+    #[serde(skip)]
+    pub ring: Vec<Color>,
+}
+
+impl BlackHole {
+    pub fn new(pos: Point, mass: f32) -> BlackHole {
+        BlackHole {
+            pos,
+            mass,
+            consumed: 0,
+            ring: Vec::new(),
+        }
+    }
+
+    pub fn horizon(&self) -> f32 {
+        HORIZON_SCALE * self.mass.max(0.0).sqrt()
+    }
+
+    pub fn near(&self, p: Point) -> bool {
+        let (dx, dy) = (p.x - self.pos.x, p.y - self.pos.y);
+        let reach = self.horizon() + PICK_MARGIN;
+        dx * dx + dy * dy <= reach * reach
+    }
+
+    // past the point of no return
+    pub fn inside(&self, p: Point) -> bool {
+        let (dx, dy) = (p.x - self.pos.x, p.y - self.pos.y);
+        dx * dx + dy * dy < self.horizon() * self.horizon()
+    }
+
+    // softened by the horizon, so it's half the bare pull at the horizon itself and never more
+    // than the bare pull there, however close. anything skimming just outside isn't flung off
+    // at some enormous speed in a single step
+    pub fn accel(&self, p: Point) -> Vector {
+        let (dx, dy) = (self.pos.x - p.x, self.pos.y - p.y);
+        let distance_sq = dx * dx + dy * dy;
+        let distance = distance_sq.sqrt();
+        if distance <= f32::EPSILON {
+            return Vector { x: 0.0, y: 0.0 };
+        }
+
+        let horizon = self.horizon();
+        let accel = self.mass * PULL_REFERENCE * PULL_REFERENCE / (distance_sq + horizon * horizon);
+        Vector {
+            x: dx / distance * accel,
+            y: dy / distance * accel,
+        }
+    }
+
+    // `growth` heavier for something of `color` crossing the horizon
+    pub fn swallow(&mut self, color: Color, growth: f32) {
+        self.consumed += 1;
+        self.mass += growth;
+        self.ring.push(color);
+        if self.ring.len() > RING_COLORS {
+            self.ring.remove(0);
+        }
+    }
+}
+
+// a black disc the size of the horizon with a faint rim, and round it a ring of arcs in the
+// colors it's swallowed lately, the newest at the top
+pub fn mesh(ctx: &mut Context, holes: &[BlackHole]) -> GameResult<Option<Mesh>> {
+    if holes.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for hole in holes {
+        let horizon = hole.horizon().max(1.0);
+        builder.circle(DrawMode::fill(), hole.pos, horizon, 0.5, DISC_COLOR);
+        builder.circle(
+            DrawMode::stroke(RIM_WIDTH),
+            hole.pos,
+            horizon,
+            0.5,
+            RIM_COLOR,
+        );
+
+        let radius = RING_SCALE * horizon;
+        let arc = std::f32::consts::PI * 2.0 / RING_COLORS as f32;
+        for (i, &color) in hole.ring.iter().rev().enumerate() {
+            let start = -std::f32::consts::FRAC_PI_2 + arc * i as f32;
+            let points: Vec<Point> = (0..=RING_POINTS)
+                .map(|k| {
+                    let angle = start + arc * k as f32 / RING_POINTS as f32;
+                    Point {
+                        x: hole.pos.x + radius * angle.cos(),
+                        y: hole.pos.y + radius * angle.sin(),
+                    }
+                })
+                .collect();
+            let color = Color {
+                a: RING_ALPHA,
+                ..color
+            };
+            builder.line(&points, RING_WIDTH, color)?;
+        }
+    }
+    builder.build(ctx).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::world::{Particle, World};
+
+    #[test]
+    fn pulls_as_the_inverse_square_outside_the_horizon() {
+        let hole = BlackHole::new(Point { x: 0.0, y: 0.0 }, 1000.0);
+        let near = hole.accel(Point { x: 100.0, y: 0.0 });
+        let far = hole.accel(Point { x: 200.0, y: 0.0 });
+        assert!(near.x < 0.0 && far.x < 0.0);
+        assert!((near.x / far.x - 4.0).abs() < 0.1, "{} {}", near.x, far.x);
+    }
+
+    #[test]
+    fn pulls_half_as_hard_at_the_horizon_and_no_harder_inside() {
+        let hole = BlackHole::new(Point { x: 0.0, y: 0.0 }, 1000.0);
+        let cap = hole.mass * PULL_REFERENCE * PULL_REFERENCE / (hole.horizon() * hole.horizon());
+        let at = hole.accel(Point {
+            x: hole.horizon(),
+            y: 0.0,
+        });
+        assert!((at.x + 0.5 * cap).abs() < 1e-2, "{} {}", at.x, cap);
+        for &x in &[1.0, 0.01] {
+            let accel = hole.accel(Point { x, y: 0.0 });
+            assert!(accel.x.is_finite() && -accel.x <= cap + 1e-2);
+        }
+    }
+
+    #[test]
+    fn swallows_what_falls_in_and_grows_for_it() {
+        let config = testing::config();
+        let mut world = World::new(800.0, 800.0);
+        world
+            .black_holes
+            .push(BlackHole::new(Point { x: 400.0, y: 400.0 }, 1000.0));
+        let color = Color::new(1.0, 0.5, 0.0, 1.0);
+        for i in 0..8 {
+            let angle = std::f32::consts::PI * 2.0 * i as f32 / 8.0;
+            let (x, y) = (400.0 + 200.0 * angle.cos(), 400.0 + 200.0 * angle.sin());
+            world.add_particle(Particle {
+                color,
+                ..testing::particle(x, y, 4.0)
+            });
+        }
+        let mut consumed = 0;
+        for _ in 0..600 {
+            testing::run(&mut world, 1, &config);
+            consumed += world.events.consumed.len();
+        }
+        // they're gone, and it's heavier and bigger for them, with a ring of their colors
+        let hole = &world.black_holes[0];
+        assert!(world.particles.is_empty());
+        assert_eq!((hole.consumed, consumed), (8, 8));
+        assert_eq!(hole.mass, 1000.0 + 8.0 * config.black_hole_growth);
+        assert!(hole.horizon() > BlackHole::new(hole.pos, 1000.0).horizon());
+        assert_eq!(hole.ring.len(), 8);
+        assert!(hole.ring.iter().all(|&c| c == color));
+    }
+}
@@ -0,0 +1,200 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+
+use crate::config::SimulationConfig;
+use crate::world::Particle;
+use crate::Vector;
+
+// how quickly a boid gets back up, or down, to boid_speed, per second
+const CRUISE_RESPONSE: f32 = 1.0;
+const RADIUS_COLOR: Color = Color::new(0.6, 0.9, 1.0, 0.25);
+const RADIUS_WIDTH: f32 = 1.0;
+
+// the acceleration steering a boid going at `vel`, from the neighbours within boid_radius
+// as (where they are from it, how fast they're going): away from the ones too close, harder
+// the closer they are, towards the way they're going, and towards the middle of them, each
+// by its weight. on top of that it's brought round to boid_speed, so a flock keeps moving
+pub fn steering(vel: Vector, neighbours: &[(Vector, Vector)], config: &SimulationConfig) -> Vector {
+    let speed = (vel.x * vel.x + vel.y * vel.y).sqrt();
+    let mut accel = if speed > f32::EPSILON {
+        let push = CRUISE_RESPONSE * (config.boid_speed - speed) / speed;
+        Vector {
+            x: vel.x * push,
+            y: vel.y * push,
+        }
+    } else {
+        Vector { x: 0.0, y: 0.0 }
+    };
+    if neighbours.is_empty() {
+        return accel;
+    }
+
+    let mut separation = Vector { x: 0.0, y: 0.0 };
+    let mut heading = Vector { x: 0.0, y: 0.0 };
+    let mut center = Vector { x: 0.0, y: 0.0 };
+    for (offset, other) in neighbours {
+        let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+        if distance > f32::EPSILON {
+            let closeness = (1.0 - distance / config.boid_radius).max(0.0);
+            separation.x -= offset.x / distance * closeness;
+            separation.y -= offset.y / distance * closeness;
+        }
+        heading.x += other.x;
+        heading.y += other.y;
+        center.x += offset.x;
+        center.y += offset.y;
+    }
+    let n = neighbours.len() as f32;
+    accel.x += config.boid_separation * separation.x
+        + config.boid_alignment * (heading.x / n - vel.x)
+        + config.boid_cohesion * center.x / n;
+    accel.y += config.boid_separation * separation.y
+        + config.boid_alignment * (heading.y / n - vel.y)
+        + config.boid_cohesion * center.y / n;
+    accel
+}
+
+// a ring boid_radius round every particle, what each one can see
+pub fn mesh(
+    ctx: &mut Context,
+    particles: &[Particle],
+    config: &SimulationConfig,
+) -> GameResult<Option<Mesh>> {
+    if particles.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for particle in particles {
+        builder.circle(
+            DrawMode::stroke(RADIUS_WIDTH),
+            particle.pos,
+            config.boid_radius,
+            0.5,
+            RADIUS_COLOR,
+        );
+    }
+    builder.build(ctx).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::world::World;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const VEL: Vector = Vector { x: 10.0, y: 0.0 };
+    const STILL: Vector = Vector { x: 0.0, y: 0.0 };
+
+    #[test]
+    fn alone_at_cruising_speed_nothing_steers() {
+        let config = SimulationConfig {
+            boid_radius: 50.0,
+            boid_separation: 100.0,
+            boid_alignment: 1.0,
+            boid_cohesion: 1.0,
+            boid_speed: 10.0,
+            ..testing::config()
+        };
+        let alone = steering(VEL, &[], &config);
+        assert!(alone.x.abs() < 1e-4 && alone.y.abs() < 1e-4);
+    }
+
+    #[test]
+    fn separation_steers_away_harder_the_closer() {
+        let config = SimulationConfig {
+            boid_radius: 50.0,
+            boid_separation: 100.0,
+            boid_alignment: 0.0,
+            boid_cohesion: 0.0,
+            boid_speed: 10.0,
+            ..testing::config()
+        };
+        let near = steering(VEL, &[(Vector { x: 0.0, y: 10.0 }, VEL)], &config);
+        let far = steering(VEL, &[(Vector { x: 0.0, y: 40.0 }, VEL)], &config);
+        assert!(near.y < far.y && far.y < 0.0, "{} {}", near.y, far.y);
+    }
+
+    #[test]
+    fn cohesion_steers_towards_the_middle() {
+        let config = SimulationConfig {
+            boid_radius: 50.0,
+            boid_separation: 0.0,
+            boid_alignment: 0.0,
+            boid_cohesion: 1.0,
+            boid_speed: 10.0,
+            ..testing::config()
+        };
+        let pulled = steering(VEL, &[(Vector { x: 0.0, y: 40.0 }, VEL)], &config);
+        assert!((pulled.y - 40.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn alignment_steers_round_to_the_way_they_go() {
+        let config = SimulationConfig {
+            boid_radius: 50.0,
+            boid_separation: 0.0,
+            boid_alignment: 1.0,
+            boid_cohesion: 0.0,
+            boid_speed: 10.0,
+            ..testing::config()
+        };
+        let turned = steering(STILL, &[(Vector { x: 0.0, y: 40.0 }, VEL)], &config);
+        assert!((turned.x - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn a_scattered_crowd_lines_up_into_flocks_without_overlapping() {
+        let config = SimulationConfig {
+            boids: true,
+            ..testing::config()
+        };
+        let crowd = || {
+            let mut world = World::new(600.0, 600.0);
+            let mut rng = StdRng::seed_from_u64(3);
+            for i in 0..64 {
+                let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                let (x, y) = (40.0 + 70.0 * (i % 8) as f32, 40.0 + 70.0 * (i / 8) as f32);
+                world.add_particle(Particle {
+                    vel: Vector {
+                        x: config.boid_speed * angle.cos(),
+                        y: config.boid_speed * angle.sin(),
+                    },
+                    ..testing::particle(x, y, 6.0)
+                });
+            }
+            world
+        };
+        // how much the neighbours within boid_radius go the same way, 1 all alike
+        let order = |world: &World| {
+            let mut total = 0.0;
+            let mut pairs = 0;
+            for a in &world.particles {
+                for b in &world.particles {
+                    if a.id == b.id || a.distance(b) > config.boid_radius {
+                        continue;
+                    }
+                    let dot = a.vel.x * b.vel.x + a.vel.y * b.vel.y;
+                    total += dot / (a.vel_magnitude() * b.vel_magnitude()).max(f32::EPSILON);
+                    pairs += 1;
+                }
+            }
+            total / pairs.max(1) as f32
+        };
+        // collisions are on in both, only one flocks
+        let (mut world, mut plain) = (crowd(), crowd());
+        let plain_config = SimulationConfig {
+            boids: false,
+            ..config.clone()
+        };
+        testing::run(&mut world, 1200, &config);
+        testing::run(&mut plain, 1200, &plain_config);
+        let (flocking, not) = (order(&world), order(&plain));
+        assert!(flocking > 0.5 && not < 0.3, "{} against {}", flocking, not);
+        for a in &world.particles {
+            for b in world.particles.iter().filter(|b| b.id > a.id) {
+                assert!(a.distance(b) > 0.8 * (a.rad + b.rad));
+            }
+        }
+    }
+}
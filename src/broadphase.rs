@@ -0,0 +1,153 @@
+use crate::{Particle, Point};
+
+// cells never get smaller than this, however small the particles are
+const MIN_CELL_SIZE: f32 = 8.0;
+
+// uniform grid over the screen, so pair queries only look at nearby particles.
+// cells are plain vecs indexed in row order, so iteration order is deterministic
+pub struct SpatialGrid {
+    width: f32,
+    height: f32,
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    // cells along one edge neighbour the ones along the opposite edge, side to side and top
+    // to bottom
+    wrap: (bool, bool),
+}
+
+impl SpatialGrid {
+    pub fn new(width: f32, height: f32) -> SpatialGrid {
+        SpatialGrid {
+            width,
+            height,
+            cell_size: MIN_CELL_SIZE,
+            cols: 0,
+            rows: 0,
+            cells: Vec::new(),
+            wrap: (false, false),
+        }
+    }
+
+    // the box changed size, the next rebuild covers the new one
+    pub fn set_bounds(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    // bucket every particle by its center. particles outside the bounds land in the edge cells.
+    // along whichever way it `wrap`s, x then y, the queries reach round to the far side too
+    pub fn rebuild(&mut self, particles: &[Particle], cell_size: f32, wrap: (bool, bool)) {
+        self.wrap = wrap;
+        self.cell_size = cell_size.max(MIN_CELL_SIZE);
+        self.cols = ((self.width / self.cell_size).ceil() as usize).max(1);
+        self.rows = ((self.height / self.cell_size).ceil() as usize).max(1);
+
+        let num_cells = self.cols * self.rows;
+        self.cells.resize_with(num_cells, Vec::new);
+        self.cells.truncate(num_cells);
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+
+        for (i, particle) in particles.iter().enumerate() {
+            let (cx, cy) = self.cell_of(particle.pos);
+            self.cells[cy * self.cols + cx].push(i);
+        }
+    }
+
+    // the particle at `index` has been taken out of the list, so everything after it moves
+    // down one. keeps the grid pointing at the right ones until it's next rebuilt
+    pub fn remove(&mut self, index: usize) {
+        for cell in &mut self.cells {
+            cell.retain(|&i| i != index);
+            for i in cell.iter_mut().filter(|i| **i > index) {
+                *i -= 1;
+            }
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    // every cell with the particles bucketed in it, by column and row, in row order
+    pub fn for_each_cell(&self, mut f: impl FnMut(usize, usize, &[usize])) {
+        for (i, cell) in self.cells.iter().enumerate() {
+            f(i % self.cols, i / self.cols, cell);
+        }
+    }
+
+    fn cell_of(&self, pos: Point) -> (usize, usize) {
+        let clamp = |v: f32, n: usize| {
+            if v.is_nan() || v < 0.0 {
+                0
+            } else {
+                (v as usize).min(n - 1)
+            }
+        };
+        (
+            clamp(pos.x / self.cell_size, self.cols),
+            clamp(pos.y / self.cell_size, self.rows),
+        )
+    }
+
+    // the cells within `span` of cell `c` along an axis `n` cells long, each once,
+    // wrapping round past either end when the grid wraps that way
+    fn neighbours(c: usize, span: usize, n: usize, wrap: bool) -> impl Iterator<Item = usize> {
+        let (first, count) = if !wrap {
+            let first = c.saturating_sub(span);
+            (first, (c + span).min(n - 1) - first + 1)
+        } else if 2 * span + 1 >= n {
+            (0, n)
+        } else {
+            ((c + n - span) % n, 2 * span + 1)
+        };
+        (0..count).map(move |k| (first + k) % n)
+    }
+
+    // every particle whose center might be within `reach` of `pos`
+    pub fn for_each_near(&self, pos: Point, reach: f32, mut f: impl FnMut(usize)) {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let span = (reach / self.cell_size).ceil().max(1.0) as usize;
+        let (cx, cy) = self.cell_of(pos);
+        for y in Self::neighbours(cy, span, self.rows, self.wrap.1) {
+            for x in Self::neighbours(cx, span, self.cols, self.wrap.0) {
+                for &i in &self.cells[y * self.cols + x] {
+                    f(i);
+                }
+            }
+        }
+    }
+
+    // every pair i < j whose centers might be within `reach` of each other, each visited once
+    pub fn for_each_pair(
+        &self,
+        particles: &[Particle],
+        reach: f32,
+        mut f: impl FnMut(usize, usize),
+    ) {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let span = (reach / self.cell_size).ceil().max(1.0) as usize;
+
+        for (i, particle) in particles.iter().enumerate() {
+            let (cx, cy) = self.cell_of(particle.pos);
+            for y in Self::neighbours(cy, span, self.rows, self.wrap.1) {
+                for x in Self::neighbours(cx, span, self.cols, self.wrap.0) {
+                    for &j in &self.cells[y * self.cols + x] {
+                        if j > i {
+                            f(i, j);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+use ggez::graphics::{self, Color, DrawParam, MeshBuilder};
+use ggez::{Context, GameResult};
+
+use crate::{Particle, Point, Vector};
+
+const TRAIL_LENGTH: usize = 240;
+const CROSSHAIR_SIZE: f32 = 10.0;
+const MARKER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.9);
+const TRAIL_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.35);
+
+// mass weighted position and velocity, None when there's nothing to weigh
+pub fn compute(particles: &[Particle]) -> Option<(Point, Vector)> {
+    let total_mass: f32 = particles.iter().map(|p| p.mass).sum();
+    if particles.is_empty() || total_mass <= 0.0 {
+        return None;
+    }
+
+    let (mut x, mut y, mut vx, mut vy) = (0.0, 0.0, 0.0, 0.0);
+    for p in particles {
+        x += p.pos.x * p.mass;
+        y += p.pos.y * p.mass;
+        vx += p.vel.x * p.mass;
+        vy += p.vel.y * p.mass;
+    }
+
+    Some((
+        Point {
+            x: x / total_mass,
+            y: y / total_mass,
+        },
+        Vector {
+            x: vx / total_mass,
+            y: vy / total_mass,
+        },
+    ))
+}
+
+// with no external forces the center of mass should travel in a straight line
+#[derive(Default)]
+pub struct CenterOfMass {
+    trail: VecDeque<Point>,
+}
+
+impl CenterOfMass {
+    pub fn record(&mut self, particles: &[Particle]) {
+        if let Some((pos, _)) = compute(particles) {
+            if self.trail.len() == TRAIL_LENGTH {
+                self.trail.pop_front();
+            }
+            self.trail.push_back(pos);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.trail.clear();
+    }
+
+    pub fn draw(&self, ctx: &mut Context, particles: &[Particle], show_trail: bool) -> GameResult {
+        let pos = match compute(particles) {
+            Some((pos, _)) => pos,
+            None => return Ok(()),
+        };
+
+        let mut builder = MeshBuilder::new();
+
+        if show_trail && self.trail.len() >= 2 {
+            let points: Vec<Point> = self.trail.iter().copied().collect();
+            builder.line(&points, 1.5, TRAIL_COLOR)?;
+        }
+
+        builder.line(
+            &[
+                Point {
+                    x: pos.x - CROSSHAIR_SIZE,
+                    y: pos.y,
+                },
+                Point {
+                    x: pos.x + CROSSHAIR_SIZE,
+                    y: pos.y,
+                },
+            ],
+            2.0,
+            MARKER_COLOR,
+        )?;
+        builder.line(
+            &[
+                Point {
+                    x: pos.x,
+                    y: pos.y - CROSSHAIR_SIZE,
+                },
+                Point {
+                    x: pos.x,
+                    y: pos.y + CROSSHAIR_SIZE,
+                },
+            ],
+            2.0,
+            MARKER_COLOR,
+        )?;
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
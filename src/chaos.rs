@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use ggez::graphics::{self, Color, DrawParam, Rect};
+use ggez::{Context, GameResult};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::chart::History;
+use crate::config::SimulationConfig;
+use crate::presets::{Layout, PRESETS};
+use crate::render::{self, DrawOrder};
+use crate::theme::{self, Theme, THEMES};
+use crate::world::{Particle, World};
+
+// how far the copy's particle is nudged along x, in pixels
+pub const PERTURBATION: f32 = 1e-4;
+// how see-through the copy's drawn over the original
+const SHADOW_ALPHA: f32 = 0.4;
+const CHART_COLOR: Color = Color::new(1.0, 0.55, 0.2, 1.0);
+// how long --chaos runs for, in simulated seconds, and how often a row goes in the file
+const HEADLESS_TIME: f32 = 30.0;
+const HEADLESS_ROW_TIME: f32 = 0.1;
+const HEADLESS_SEED: u64 = 1;
+const HEADLESS_STEP: f32 = 1.0 / 120.0;
+
+// a second copy of the world with one particle nudged by PERTURBATION, stepped in lockstep
+// with the first at the same dt, and how far apart the two have drifted since
+pub struct Chaos {
+    shadow: World,
+    history: History,
+    divergence: f32,
+    time: f32,
+    draw_order: DrawOrder,
+    // the copy's particles as drawn, part way between the last two steps
+    snapshot: Vec<Particle>,
+}
+
+impl Chaos {
+    // a copy of `world` as it's just been loaded, with the particle at `index` moved over.
+    // ids are handed out in the same order on load, so each particle's copy has its id
+    pub fn new(world: &World, index: usize) -> Chaos {
+        let mut shadow = World::new(world.width, world.height);
+        shadow.segments = world.segments.clone();
+        shadow.pillars = world.pillars.clone();
+        shadow.blocks = world.blocks.clone();
+        shadow.gates = world.gates.clone();
+        shadow.wells = world.wells.clone();
+        shadow.load(world.particles.clone());
+        if let Some(particle) = shadow.particles.get_mut(index) {
+            particle.pos.x += PERTURBATION;
+            particle.prev_pos.x += PERTURBATION;
+        }
+        let mut chaos = Chaos {
+            shadow,
+            history: History::default(),
+            divergence: 0.0,
+            time: 0.0,
+            draw_order: DrawOrder::default(),
+            snapshot: Vec::new(),
+        };
+        chaos.divergence = divergence(world, &chaos.shadow);
+        chaos.history.record(vec![chaos.divergence], 0.0);
+        chaos
+    }
+
+    // `steps` steps of the copy, after the same ones of `world`
+    pub fn advance(
+        &mut self,
+        world: &World,
+        steps: usize,
+        step_time: f32,
+        config: &SimulationConfig,
+        theme: &Theme,
+    ) {
+        // resizing the window resizes the original
+        if (self.shadow.width, self.shadow.height) != (world.width, world.height) {
+            self.shadow.set_bounds(world.width, world.height);
+        }
+        self.shadow.events.clear();
+        for _ in 0..steps {
+            self.shadow.step(step_time, config, theme);
+        }
+        let time_elapsed = steps as f32 * step_time;
+        self.time += time_elapsed;
+        self.divergence = divergence(world, &self.shadow);
+        self.history.record(vec![self.divergence], time_elapsed);
+    }
+
+    pub fn interpolate(&mut self, alpha: f32) {
+        self.snapshot.clear();
+        self.snapshot
+            .extend(self.shadow.particles.iter().map(|particle| {
+                let mut particle = particle.clone();
+                particle.pos.x =
+                    particle.prev_pos.x + (particle.pos.x - particle.prev_pos.x) * alpha;
+                particle.pos.y =
+                    particle.prev_pos.y + (particle.pos.y - particle.prev_pos.y) * alpha;
+                particle.color.a *= SHADOW_ALPHA;
+                particle
+            }));
+    }
+
+    // the copy over the original, in pane coordinates
+    pub fn draw_shadow(&mut self, ctx: &mut Context, config: &SimulationConfig) -> GameResult {
+        let order = self.draw_order.update(&self.snapshot);
+        if let Some(mesh) = render::particle_mesh(ctx, &self.snapshot, order, config)? {
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+        Ok(())
+    }
+
+    pub fn hud_line(&self) -> String {
+        format!(
+            "divergence: {:.2e} px after {:.1}s, from a nudge of {:.0e}",
+            self.divergence, self.time, PERTURBATION
+        )
+    }
+
+    // the divergence over time, on a log scale
+    pub fn draw(&self, ctx: &mut Context, pane: Rect) -> GameResult {
+        self.history.draw_log(ctx, pane, CHART_COLOR)
+    }
+}
+
+// how far every particle is from its copy, added up. wrapping boundaries aren't taken into
+// account, a particle that's wrapped when its copy hasn't is as far off as it looks
+pub fn divergence(world: &World, shadow: &World) -> f32 {
+    let copies: BTreeMap<u64, &Particle> = shadow.particles.iter().map(|p| (p.id, p)).collect();
+    world
+        .particles
+        .iter()
+        .filter_map(|a| copies.get(&a.id).map(|b| a.distance(b)))
+        .sum()
+}
+
+// run the chaos preset without a window for HEADLESS_TIME, writing the divergence every
+// HEADLESS_ROW_TIME to `path` as csv, and print how far it got. false if the file couldn't
+// be written
+pub fn run_headless(config: &SimulationConfig, path: &str) -> bool {
+    let theme = &THEMES[theme::find(&config.theme).unwrap_or(0)];
+    let layout = Layout {
+        width: config.window_width,
+        height: config.window_height,
+        config,
+        theme,
+    };
+    let preset = PRESETS
+        .iter()
+        .find(|preset| preset.name == "chaos")
+        .expect("a chaos preset");
+    let scene = (preset.build)(&layout, &mut StdRng::seed_from_u64(HEADLESS_SEED));
+    let config = SimulationConfig {
+        gravity: scene.gravity.unwrap_or(config.gravity),
+        restitution: scene.restitution.unwrap_or(config.restitution),
+        log_collisions: false,
+        ..config.clone()
+    };
+
+    let mut world = World::new(layout.width, layout.height);
+    world.load(scene.particles);
+    let mut chaos = Chaos::new(&world, scene.chaos.unwrap_or(0));
+    let start = chaos.divergence;
+    let mut csv = String::from("time,divergence\n");
+    csv.push_str(&format!("0,{:e}\n", start));
+    let steps = (HEADLESS_TIME / HEADLESS_STEP) as usize;
+    let row_steps = ((HEADLESS_ROW_TIME / HEADLESS_STEP).round() as usize).max(1);
+    for step in 1..=steps {
+        world.events.clear();
+        world.step(HEADLESS_STEP, &config, theme);
+        chaos.advance(&world, 1, HEADLESS_STEP, &config, theme);
+        if step % row_steps == 0 {
+            csv.push_str(&format!("{:.2},{:e}\n", chaos.time, chaos.divergence));
+        }
+    }
+
+    match fs::write(path, csv) {
+        Ok(()) => {
+            println!(
+                "chaos: divergence {:.2e} px to {:.2e} px after {}s, written to {}",
+                start, chaos.divergence, HEADLESS_TIME, path
+            );
+            true
+        }
+        Err(e) => {
+            println!("chaos: couldn't write {}: {}", path, e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, scattered, STEP};
+
+    #[test]
+    fn a_tiny_nudge_grows_until_the_copies_are_unrelated() {
+        let config = SimulationConfig {
+            restitution: 1.0,
+            ..testing::config()
+        };
+        let mut world = scattered();
+        let mut chaos = Chaos::new(&world, 0);
+        assert!((chaos.divergence - PERTURBATION).abs() < 0.5 * PERTURBATION);
+        // orders of magnitude over the run, and by the end it's as big as the box
+        testing::run_each(&mut world, 120, &config, |world| {
+            chaos.advance(world, 1, STEP, &config, &THEMES[0])
+        });
+        assert!(chaos.divergence < 1.0, "{}", chaos.divergence);
+        testing::run_each(&mut world, 19 * 120, &config, |world| {
+            chaos.advance(world, 1, STEP, &config, &THEMES[0])
+        });
+        assert!(chaos.divergence > 1000.0, "{}", chaos.divergence);
+    }
+
+    #[test]
+    fn with_nothing_nudged_the_copies_stay_exactly_together() {
+        let config = SimulationConfig {
+            restitution: 1.0,
+            ..testing::config()
+        };
+        let mut world = scattered();
+        let mut chaos = Chaos::new(&world, 0);
+        chaos.shadow.load(world.particles.clone());
+        testing::run_each(&mut world, 5 * 120, &config, |world| {
+            chaos.advance(world, 1, STEP, &config, &THEMES[0])
+        });
+        assert_eq!(chaos.divergence, 0.0);
+    }
+}
@@ -0,0 +1,143 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+use crate::Point;
+
+// how often a sample's taken to start with, in simulated seconds. once MAX_SAMPLES are in,
+// every other one goes and the gap doubles, so the whole run always fits
+const SAMPLE_TIME: f32 = 0.25;
+const MAX_SAMPLES: usize = 240;
+const CHART_WIDTH: f32 = 320.0;
+const CHART_HEIGHT: f32 = 100.0;
+const MARGIN: f32 = 10.0;
+const BACKING_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+// the log chart's lines. anything smaller than the floor is drawn at it
+const DECADE_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.15);
+const LOG_LINE_WIDTH: f32 = 1.5;
+const LOG_FLOOR: f32 = 1e-12;
+
+// counts over time for the modes that follow populations, drawn stacked one on another
+#[derive(Default)]
+pub struct History {
+    samples: Vec<Vec<f32>>,
+    // since the last sample, and between samples
+    since_sample: f32,
+    interval: f32,
+}
+
+impl History {
+    // the counts `time_elapsed` after the last record, taken if a sample's due
+    pub fn record(&mut self, values: Vec<f32>, time_elapsed: f32) {
+        if self.interval == 0.0 {
+            self.interval = SAMPLE_TIME;
+        } else {
+            self.since_sample += time_elapsed;
+            if self.since_sample < self.interval {
+                return;
+            }
+            self.since_sample -= self.interval;
+        }
+        self.samples.push(values);
+        if self.samples.len() > MAX_SAMPLES {
+            self.samples = self.samples.iter().step_by(2).cloned().collect();
+            self.interval *= 2.0;
+        }
+    }
+
+    // along the bottom of `pane`, `colors` from the bottom of the stack up. each sample's a
+    // column, so the run fills the chart from the left
+    pub fn draw_stacked(&self, ctx: &mut Context, pane: Rect, colors: &[Color]) -> GameResult {
+        if self.samples.is_empty() {
+            return Ok(());
+        }
+        let chart = Rect::new(
+            pane.x + 0.5 * (pane.w - CHART_WIDTH),
+            pane.y + pane.h - MARGIN - CHART_HEIGHT,
+            CHART_WIDTH,
+            CHART_HEIGHT,
+        );
+        let tallest = self
+            .samples
+            .iter()
+            .map(|values| values.iter().sum::<f32>())
+            .fold(f32::EPSILON, f32::max);
+
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(DrawMode::fill(), chart, BACKING_COLOR);
+        let column = chart.w / MAX_SAMPLES as f32;
+        for (i, values) in self.samples.iter().enumerate() {
+            let mut bottom = chart.y + chart.h;
+            for (value, color) in values.iter().zip(colors) {
+                let h = chart.h * value / tallest;
+                if h > 0.0 {
+                    builder.rectangle(
+                        DrawMode::fill(),
+                        Rect::new(chart.x + column * i as f32, bottom - h, column, h),
+                        *color,
+                    );
+                }
+                bottom -= h;
+            }
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+
+    // the first value of each sample as a line along the bottom of `pane`, on a log scale
+    // from the power of ten below the smallest to the one above the biggest, with a faint
+    // line at each power of ten between
+    pub fn draw_log(&self, ctx: &mut Context, pane: Rect, color: Color) -> GameResult {
+        let logs: Vec<f32> = self
+            .samples
+            .iter()
+            .filter_map(|values| values.first())
+            .map(|value| value.max(LOG_FLOOR).log10())
+            .collect();
+        if logs.len() < 2 {
+            return Ok(());
+        }
+        let chart = Rect::new(
+            pane.x + 0.5 * (pane.w - CHART_WIDTH),
+            pane.y + pane.h - MARGIN - CHART_HEIGHT,
+            CHART_WIDTH,
+            CHART_HEIGHT,
+        );
+        let low = logs.iter().copied().fold(f32::INFINITY, f32::min).floor();
+        let high = logs
+            .iter()
+            .copied()
+            .fold(f32::NEG_INFINITY, f32::max)
+            .ceil()
+            .max(low + 1.0);
+        let y = |log: f32| chart.y + chart.h * (1.0 - (log - low) / (high - low));
+
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(DrawMode::fill(), chart, BACKING_COLOR);
+        for power in (low as i32 + 1)..(high as i32) {
+            let y = y(power as f32);
+            builder.line(
+                &[
+                    Point { x: chart.x, y },
+                    Point {
+                        x: chart.x + chart.w,
+                        y,
+                    },
+                ],
+                1.0,
+                DECADE_COLOR,
+            )?;
+        }
+        let column = chart.w / MAX_SAMPLES as f32;
+        let points: Vec<Point> = logs
+            .iter()
+            .enumerate()
+            .map(|(i, &log)| Point {
+                x: chart.x + column * (i as f32 + 0.5),
+                y: y(log),
+            })
+            .collect();
+        builder.line(&points, LOG_LINE_WIDTH, color)?;
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
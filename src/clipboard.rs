@@ -0,0 +1,23 @@
+use arboard::Clipboard as SystemClipboard;
+
+// the system clipboard, opened the first time something's copied and kept open after. on
+// linux what's been copied is only there for as long as it's open, served from a thread of
+// its own, so copying never waits on anything outside the process
+#[derive(Default)]
+pub struct Clipboard {
+    system: Option<SystemClipboard>,
+}
+
+impl Clipboard {
+    // an error saying why when there's no clipboard to be had
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        let system = match self.system.take() {
+            Some(system) => system,
+            None => SystemClipboard::new().map_err(|e| e.to_string())?,
+        };
+        self.system
+            .insert(system)
+            .set_text(text)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,346 @@
+use ggez::graphics::{
+    self, Color, DrawMode, DrawParam, FilterMode, Font, MeshBuilder, Rect, Scale, Text,
+};
+use ggez::{Context, GameResult};
+
+use crate::config::SimulationConfig;
+use crate::events::CollisionEvent;
+use crate::world::World;
+use crate::{Point, Vector};
+
+// a velocity's arrow is as long as it goes in this many seconds
+const ARROW_TIME: f32 = 0.25;
+const ARROW_WIDTH: f32 = 2.0;
+const HEAD_LENGTH: f32 = 8.0;
+const NORMAL_LENGTH: f32 = 50.0;
+const NORMAL_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.9);
+// `a` in blues and `b` in oranges, fainter going in than coming out
+const A_BEFORE_COLOR: Color = Color::new(0.45, 0.75, 1.0, 0.55);
+const A_AFTER_COLOR: Color = Color::new(0.2, 0.5, 1.0, 1.0);
+const B_BEFORE_COLOR: Color = Color::new(1.0, 0.8, 0.4, 0.55);
+const B_AFTER_COLOR: Color = Color::new(1.0, 0.5, 0.1, 1.0);
+const FONT_SCALE: f32 = 14.0;
+const PADDING: f32 = 6.0;
+// how far the box sits from the contact
+const OFFSET: f32 = 30.0;
+const BACKING_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.75);
+
+// a collision to show the workings of, and where the two were once the step was over
+struct Annotation {
+    event: CollisionEvent,
+    centers: [Point; 2],
+}
+
+// for following collisions in slow motion or a step at a time: the last one, or the last the
+// selected particle was in, drawn with its contact normal, both particles' velocities going
+// in and coming out, and the numbers the impulse came from
+#[derive(Default)]
+pub struct CollisionMath {
+    annotation: Option<Annotation>,
+}
+
+impl CollisionMath {
+    pub fn clear(&mut self) {
+        self.annotation = None;
+    }
+
+    // after a step, or a frame's worth. with it on and paused, stepping through one at a
+    // time, each step shows its own collision or none. in slow motion the last one's left up
+    // until the next, so there's time to read it, and back at full speed there's nothing to
+    // follow so it goes
+    pub fn follow(
+        &mut self,
+        world: &World,
+        selected: Option<u64>,
+        paused: bool,
+        config: &SimulationConfig,
+    ) {
+        if config.collision_math && (paused || config.time_scale < 1.0) {
+            self.record(world, selected, !paused);
+        } else {
+            self.clear();
+        }
+    }
+
+    // the last collision the steps had, or the last `selected` was in. without, the last
+    // one's left up if `keep`
+    fn record(&mut self, world: &World, selected: Option<u64>, keep: bool) {
+        let event = world
+            .events
+            .collisions
+            .iter()
+            .rev()
+            .find(|event| selected.is_none_or(|id| event.a == id || event.b == id));
+        let center = |id: u64| world.particles.iter().find(|p| p.id == id).map(|p| p.pos);
+        match event.and_then(|event| Some((event, [center(event.a)?, center(event.b)?]))) {
+            Some((event, centers)) => {
+                self.annotation = Some(Annotation {
+                    event: event.clone(),
+                    centers,
+                })
+            }
+            None if keep => {}
+            None => self.annotation = None,
+        }
+    }
+
+    // the workings, each line a step of them
+    fn lines(&self) -> Vec<String> {
+        let event = match &self.annotation {
+            Some(annotation) => &annotation.event,
+            None => return Vec::new(),
+        };
+        let [m_a, m_b] = event.masses;
+        let along = |v: [Vector; 2]| {
+            (v[1].x - v[0].x) * event.normal.x + (v[1].y - v[0].y) * event.normal.y
+        };
+        let closing = -along(event.before);
+        let parting = along(event.after);
+        let particle = |name: &str, id: u64, mass: f32, i: usize| {
+            format!(
+                "{} #{}: mass {:.2}, in ({:.1}, {:.1}), out ({:.1}, {:.1}) px/s",
+                name,
+                id,
+                mass,
+                event.before[i].x,
+                event.before[i].y,
+                event.after[i].x,
+                event.after[i].y
+            )
+        };
+        vec![
+            particle("a (blue)", event.a, m_a, 0),
+            particle("b (orange)", event.b, m_b, 1),
+            format!(
+                "restitution e = {:.2}: closing at {:.1} px/s, parting at {:.1} px/s",
+                event.restitution, closing, parting
+            ),
+            format!(
+                "impulse J = (1 + e) × closing / (1/m_a + 1/m_b) = {:.2} × {:.1} / {:.3} = {:.1}",
+                1.0 + event.restitution,
+                closing,
+                1.0 / m_a.max(f32::EPSILON) + 1.0 / m_b.max(f32::EPSILON),
+                event.impulse
+            ),
+        ]
+    }
+
+    // in pane coordinates, the box kept inside the pane
+    pub fn draw(&self, ctx: &mut Context, font: Font, width: f32, height: f32) -> GameResult {
+        let annotation = match &self.annotation {
+            Some(annotation) => annotation,
+            None => return Ok(()),
+        };
+        let event = &annotation.event;
+        let mut builder = MeshBuilder::new();
+        let from = Point {
+            x: event.point.x - event.normal.x * 0.5 * NORMAL_LENGTH,
+            y: event.point.y - event.normal.y * 0.5 * NORMAL_LENGTH,
+        };
+        let normal = Vector {
+            x: event.normal.x * NORMAL_LENGTH,
+            y: event.normal.y * NORMAL_LENGTH,
+        };
+        arrow(&mut builder, from, normal, NORMAL_COLOR)?;
+        let colors = [
+            (A_BEFORE_COLOR, A_AFTER_COLOR),
+            (B_BEFORE_COLOR, B_AFTER_COLOR),
+        ];
+        for (i, &(before, after)) in colors.iter().enumerate() {
+            let center = annotation.centers[i];
+            arrow(&mut builder, center, scaled(event.before[i]), before)?;
+            arrow(&mut builder, center, scaled(event.after[i]), after)?;
+        }
+
+        let mut text = Text::new(self.lines().join("\n"));
+        text.set_font(font, Scale::uniform(FONT_SCALE));
+        let (w, h) = text.dimensions(ctx);
+        let (w, h) = (w as f32 + 2.0 * PADDING, h as f32 + 2.0 * PADDING);
+        let x = (event.point.x + OFFSET).min(width - w).max(0.0);
+        let y = (event.point.y + OFFSET).min(height - h).max(0.0);
+        builder.rectangle(DrawMode::fill(), Rect::new(x, y, w, h), BACKING_COLOR);
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+        let dest = Point {
+            x: x + PADDING,
+            y: y + PADDING,
+        };
+        graphics::queue_text(ctx, &text, dest, None);
+        graphics::draw_queued_text(ctx, DrawParam::default(), None, FilterMode::Linear)
+    }
+}
+
+fn scaled(vel: Vector) -> Vector {
+    Vector {
+        x: vel.x * ARROW_TIME,
+        y: vel.y * ARROW_TIME,
+    }
+}
+
+// a line from `from` along `offset` with a head on the end, nothing if it's too short to see
+fn arrow(builder: &mut MeshBuilder, from: Point, offset: Vector, color: Color) -> GameResult {
+    let length = (offset.x * offset.x + offset.y * offset.y).sqrt();
+    if length < 1.0 {
+        return Ok(());
+    }
+    let (ux, uy) = (offset.x / length, offset.y / length);
+    let tip = Point {
+        x: from.x + offset.x,
+        y: from.y + offset.y,
+    };
+    let head = HEAD_LENGTH.min(length);
+    let base = Point {
+        x: tip.x - ux * head,
+        y: tip.y - uy * head,
+    };
+    if length > head {
+        builder.line(&[from, base], ARROW_WIDTH, color)?;
+    }
+    builder.polygon(
+        DrawMode::fill(),
+        &[
+            tip,
+            Point {
+                x: base.x - uy * 0.5 * head,
+                y: base.y + ux * 0.5 * head,
+            },
+            Point {
+                x: base.x + uy * 0.5 * head,
+                y: base.y - ux * 0.5 * head,
+            },
+        ],
+        color,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, touch};
+    use crate::world::Particle;
+
+    fn ball(x: f32, vx: f32, mass: f32) -> Particle {
+        Particle {
+            vel: Vector { x: vx, y: 0.0 },
+            mass,
+            ..testing::particle(x, 100.0, 10.0)
+        }
+    }
+
+    // head on, the light one from the left into the heavy one sitting still, stepped until
+    // they've hit, with a third well out of the way
+    fn bump(config: &SimulationConfig) -> World {
+        let mut world = World::new(400.0, 200.0);
+        world.add_particle(ball(100.0, 120.0, 1.0));
+        world.add_particle(ball(200.0, 0.0, 3.0));
+        world.add_particle(ball(300.0, 0.0, 1.0));
+        while world.events.collisions.is_empty() {
+            testing::run(&mut world, 1, config);
+        }
+        world
+    }
+
+    #[test]
+    fn works_through_the_impulse_of_the_last_collision() {
+        let config = SimulationConfig {
+            restitution: 0.5,
+            collision_math: true,
+            time_scale: 1.0,
+            ..testing::config()
+        };
+        let world = bump(&config);
+        let mut math = CollisionMath::default();
+        math.follow(&world, None, true, &config);
+        let lines = math.lines();
+        assert!(lines[0].starts_with("a (blue) #0: mass 1.00, in (120.0, 0.0), out (-15.0, 0.0)"));
+        assert!(lines[1].starts_with("b (orange) #1: mass 3.00, in (0.0, 0.0), out (45.0, 0.0)"));
+        // half the closing speed comes back out, from J = 1.5 × 120 / (4/3)
+        assert_eq!(
+            lines[2],
+            "restitution e = 0.50: closing at 120.0 px/s, parting at 60.0 px/s"
+        );
+        assert!(
+            lines[3].ends_with("= 1.50 × 120.0 / 1.333 = 135.0"),
+            "{}",
+            lines[3]
+        );
+    }
+
+    #[test]
+    fn paused_each_step_shows_its_own_collision_or_none() {
+        let config = SimulationConfig {
+            restitution: 0.5,
+            collision_math: true,
+            time_scale: 1.0,
+            ..testing::config()
+        };
+        let mut world = bump(&config);
+        let mut math = CollisionMath::default();
+        math.follow(&world, None, true, &config);
+        assert!(!math.lines().is_empty());
+        testing::run(&mut world, 1, &config);
+        math.follow(&world, None, true, &config);
+        assert!(math.lines().is_empty());
+    }
+
+    #[test]
+    fn in_slow_motion_the_last_one_stays_up_until_the_next() {
+        let config = SimulationConfig {
+            restitution: 0.5,
+            collision_math: true,
+            time_scale: 0.25,
+            ..testing::config()
+        };
+        let mut world = bump(&config);
+        let mut math = CollisionMath::default();
+        math.follow(&world, None, false, &config);
+        testing::run(&mut world, 1, &config);
+        math.follow(&world, None, false, &config);
+        assert!(math.lines()[0].starts_with("a (blue) #0"));
+        world.events.collisions = vec![touch(1, 2)];
+        math.follow(&world, None, false, &config);
+        assert!(math.lines()[0].starts_with("a (blue) #1"));
+    }
+
+    #[test]
+    fn at_full_speed_or_turned_off_it_goes() {
+        let config = SimulationConfig {
+            restitution: 0.5,
+            collision_math: true,
+            time_scale: 1.0,
+            ..testing::config()
+        };
+        let world = bump(&config);
+        let mut math = CollisionMath::default();
+        math.follow(&world, None, true, &config);
+        math.follow(&world, None, false, &config);
+        assert!(math.lines().is_empty());
+
+        let off = SimulationConfig {
+            collision_math: false,
+            time_scale: 0.25,
+            ..config.clone()
+        };
+        math.follow(&world, None, true, &config);
+        math.follow(&world, None, true, &off);
+        assert!(math.lines().is_empty());
+    }
+
+    #[test]
+    fn with_a_particle_selected_only_its_collisions_count() {
+        let config = SimulationConfig {
+            restitution: 0.5,
+            collision_math: true,
+            time_scale: 1.0,
+            ..testing::config()
+        };
+        let mut world = bump(&config);
+        world.events.collisions = vec![touch(1, 2)];
+        let mut math = CollisionMath::default();
+        math.follow(&world, Some(0), true, &config);
+        assert!(math.lines().is_empty());
+        math.follow(&world, Some(2), true, &config);
+        assert!(math.lines()[0].starts_with("a (blue) #1"));
+    }
+}
@@ -0,0 +1,140 @@
+use ggez::graphics::Color;
+use serde::Deserialize;
+
+use crate::Vector;
+
+// how far a blending particle moves towards the other's color per collision
+const BLEND_AMOUNT: f32 = 0.2;
+
+// what happens to particle colors when they collide
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorPolicy {
+    // keep the spawn color forever
+    Original,
+    // both take a color from the collision direction
+    Recolor,
+    // each drifts a little towards the other's color
+    Blend,
+}
+
+impl ColorPolicy {
+    pub fn next(self) -> ColorPolicy {
+        match self {
+            ColorPolicy::Original => ColorPolicy::Recolor,
+            ColorPolicy::Recolor => ColorPolicy::Blend,
+            ColorPolicy::Blend => ColorPolicy::Original,
+        }
+    }
+}
+
+// `from` moved towards `to` by BLEND_AMOUNT
+pub fn blend(from: Color, to: Color) -> Color {
+    let mix = |a: f32, b: f32| a + (b - a) * BLEND_AMOUNT;
+    Color::new(
+        mix(from.r, to.r),
+        mix(from.g, to.g),
+        mix(from.b, to.b),
+        mix(from.a, to.a),
+    )
+}
+
+// color for a pair of particles that just collided: the hue follows the direction of the
+// collision normal, at full saturation and value so repeated collisions never drift to black
+pub fn collision_color(normal: Vector) -> Color {
+    let (r, g, b) = hsv_to_rgb(normal.y.atan2(normal.x).to_degrees(), 1.0, 1.0);
+    Color::from_rgb(r, g, b)
+}
+
+// hue in degrees (any range, it wraps), saturation and value in 0..=1
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> (u8, u8, u8) {
+    let hue = hue.rem_euclid(360.0);
+    let saturation = saturation.clamp(0.0, 1.0);
+    let value = value.clamp(0.0, 1.0);
+
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = value - chroma;
+
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    (
+        unit_to_byte(r + m),
+        unit_to_byte(g + m),
+        unit_to_byte(b + m),
+    )
+}
+
+// 0..=1 onto 0..=255. scaling by 256 instead lets 1.0 wrap round to 0
+pub fn unit_to_byte(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_to_byte_boundaries() {
+        assert_eq!(unit_to_byte(0.0), 0);
+        assert_eq!(unit_to_byte(1.0), 255);
+        assert_eq!(unit_to_byte(0.5), 128);
+        assert_eq!(unit_to_byte(-0.5), 0);
+        assert_eq!(unit_to_byte(1.5), 255);
+        assert_eq!(unit_to_byte(f32::NAN), 0);
+    }
+
+    #[test]
+    fn primary_hues() {
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(120.0, 1.0, 1.0), (0, 255, 0));
+        assert_eq!(hsv_to_rgb(240.0, 1.0, 1.0), (0, 0, 255));
+        assert_eq!(hsv_to_rgb(60.0, 1.0, 1.0), (255, 255, 0));
+    }
+
+    #[test]
+    fn hue_wraps() {
+        assert_eq!(hsv_to_rgb(360.0, 1.0, 1.0), hsv_to_rgb(0.0, 1.0, 1.0));
+        assert_eq!(hsv_to_rgb(-120.0, 1.0, 1.0), hsv_to_rgb(240.0, 1.0, 1.0));
+        assert_eq!(hsv_to_rgb(359.999, 1.0, 1.0), (255, 0, 0));
+    }
+
+    #[test]
+    fn saturation_and_value_extremes() {
+        assert_eq!(hsv_to_rgb(200.0, 0.0, 1.0), (255, 255, 255));
+        assert_eq!(hsv_to_rgb(200.0, 1.0, 0.0), (0, 0, 0));
+        assert_eq!(hsv_to_rgb(200.0, 2.0, 2.0), hsv_to_rgb(200.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn blend_moves_part_way() {
+        let black = Color::new(0.0, 0.0, 0.0, 1.0);
+        let white = Color::new(1.0, 1.0, 1.0, 1.0);
+        let mixed = blend(black, white);
+        assert!((mixed.r - BLEND_AMOUNT).abs() < 1e-6);
+        assert!((mixed.a - 1.0).abs() < 1e-6);
+        assert_eq!(blend(white, white), white);
+    }
+
+    #[test]
+    fn collision_colors_stay_bright() {
+        let normals = [
+            Vector { x: 1.0, y: 0.0 },
+            Vector { x: 0.0, y: 1.0 },
+            Vector { x: -1.0, y: -1.0 },
+            Vector { x: 0.3, y: -0.7 },
+            Vector { x: 0.0, y: 0.0 },
+        ];
+        for normal in normals.iter() {
+            let color = collision_color(*normal);
+            let brightest = color.r.max(color.g).max(color.b);
+            assert!((brightest - 1.0).abs() < 1e-6, "{:?} too dark", color);
+        }
+    }
+}
@@ -0,0 +1,792 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use ggez::{GameError, GameResult};
+use serde::Deserialize;
+
+use crate::{
+    actions::{Action, KeyList},
+    color::ColorPolicy,
+    container::Container,
+    demo::Transition,
+    demon, determinism,
+    inset::InsetTarget,
+    labels::LabelField,
+    motion_blur::ClearMode,
+    piston::PistonMode,
+    render::ParticleStyle,
+    theme,
+    thermostat::Thermostats,
+    weather::Weather,
+    world::{Boundary, Escaped, WallStyle},
+    Point, Vector,
+};
+
+// everything the physics reads each frame, so it can be tuned at runtime.
+// any field can be overridden from the config file, the rest keep their defaults
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    // initial window size, the simulation bounds follow the window after that
+    pub window_width: f32,
+    pub window_height: f32,
+    // pretend the display has this many pixels per logical pixel, for checking hidpi
+    // rendering on a 1x screen. only x11 honours it, elsewhere the os decides
+    pub hidpi_factor: Option<f32>,
+    // only read at startup, ggez can't switch vsync on a live window
+    pub vsync: bool,
+    // software frame cap on top of vsync, none for uncapped
+    pub max_fps: Option<f32>,
+
+    // acceleration
+    pub gravity: Vector,
+    // restitution coefficient
+    pub restitution: f32,
+    // resistance increases with vel squared, going with the speed through the air, which
+    // moves at `wind` to the right
+    pub drag: f32,
+    pub wind: f32,
+    // rain or snow coming back in at the top, see weather::Weather
+    pub weather: Weather,
+    // walls, wrap, open-top or channel, see world::Boundary. also --boundary wrap
+    pub boundary: Boundary,
+    // with an open top, what happens to particles escape_margin past it, see world::Escaped
+    pub escaped: Escaped,
+    pub escape_margin: f32,
+    // hard or soft, see world::WallStyle. soft walls push back wall_stiffness times as hard as
+    // a particle is into them, up to wall_margin deep, which nothing gets past
+    pub wall_style: WallStyle,
+    pub wall_stiffness: f32,
+    pub wall_damping: f32,
+    pub wall_margin: f32,
+    // how much a wall, pillar or block slows anything sliding along it, like a coefficient of
+    // friction: each contact takes up to this times the bounce off the sliding speed
+    pub wall_friction: f32,
+    // the same between particles, off the impulse between them
+    pub particle_friction: f32,
+    // particles going slower than sleep_speed for sleep_time stop being moved until something
+    // touches them faster than that, or the gravity changes, so a settled pile stays still
+    pub sleeping: bool,
+    pub sleep_speed: f32,
+    pub sleep_time: f32,
+    // passes over the touching pairs each step, the ones after the first only taking out the
+    // closing speed that's left, for stacks that should come to rest
+    pub solver_iterations: usize,
+    // how many bits a breakable wall scatters when it goes, see walls::Segment
+    pub wall_debris: usize,
+    // box walls held at a temperature, see thermostat::Thermostats, e.g.
+    // thermostats = { left = 400.0, right = 100.0 }. anything bouncing off one goes
+    // thermostat_mix of the way from its own speed to one the wall picks
+    pub thermostats: Thermostats,
+    pub thermostat_mix: f32,
+    // every pair of particles closer than lj_range times touching pulls together like atoms,
+    // see crystal::attraction, with a well lj_depth deep (mass px^2/s^2) at touching
+    pub lennard_jones: bool,
+    pub lj_depth: f32,
+    pub lj_range: f32,
+    // the thermostats lose cooling_rate of their temperature a second, and Z turns them back
+    // up to reheat_temperature. the crystallization preset starts there and cools
+    pub cooling: bool,
+    pub cooling_rate: f32,
+    pub reheat_temperature: f32,
+    // how the demon minding a gate decides what to let through, one of demon::POLICIES
+    pub demon_policy: String,
+    // off, oscillate or sweep, see piston::PistonMode. the right side moves between
+    // piston_min and piston_max of the box's width
+    pub piston: PistonMode,
+    pub piston_min: f32,
+    pub piston_max: f32,
+    // how fast a sweeping piston moves, and how long an oscillating one takes to go in and out
+    pub piston_speed: f32,
+    pub piston_period: f32,
+    // turn the floor into a conveyor belt running at belt_speed, positive to the right.
+    // each bounce off it takes belt_grip of the difference off a particle's sideways speed
+    pub belt: bool,
+    pub belt_speed: f32,
+    pub belt_grip: f32,
+    // corners of a convex polygon to keep everything inside, as [x, y] pairs in logical
+    // pixels from the top left. empty for just the window
+    pub container: Vec<Point>,
+    // switch a force off without losing its setting, G, D and shift+A while running
+    pub gravity_enabled: bool,
+    pub drag_enabled: bool,
+    pub wells_enabled: bool,
+    // multiplier for the time elapsed between frames, 0.5 starts in slow motion.
+    // the physics takes more fixed steps rather than bigger ones, so 4x is as stable as 1x
+    pub time_scale: f32,
+
+    // how many particles?
+    pub num_particles: usize,
+    // spawning stops here, whether from the button or the mouse
+    pub max_particles: usize,
+    // the gap between the hourglass preset's chambers. it's filled with particles
+    // spawn_radius_min across, so it needs to be a good bit wider than that to flow
+    pub hourglass_neck: f32,
+    // the billiards preset's rack is nudged about a little, off for the same break every time
+    pub rack_jitter: bool,
+    // the epidemic preset: initial_infected start out ill, every touch between someone ill and
+    // someone who's never had it passes it on with infection_chance, and it's over
+    // recovery_time seconds after it's caught
+    pub initial_infected: usize,
+    pub infection_chance: f32,
+    pub recovery_time: f32,
+    // the predator-prey preset: a predator starts with predator_energy seconds before it
+    // starves, each prey it eats is worth predator_meal more, and at twice predator_energy it
+    // splits in two. prey breed at prey_birth_rate each a second, up to prey_cap of them
+    pub predator_energy: f32,
+    pub predator_meal: f32,
+    pub prey_birth_rate: f32,
+    pub prey_cap: usize,
+    // the reactions preset: an A and a B hitting each other closing at activation_speed or
+    // faster turn into reaction_products (1 or 2) Cs, see reactions::react
+    pub activation_speed: f32,
+    pub reaction_products: usize,
+    // the chain reaction preset: a neutron hitting fuel splits it with fission_chance, letting
+    // go two or three more at neutron_speed, see fission::split
+    pub fission_chance: f32,
+    pub neutron_speed: f32,
+    pub spawn_radius_min: f32,
+    pub spawn_radius_max: f32,
+    pub spawn_mass_min: f32,
+    pub spawn_mass_max: f32,
+    // mass multipliers for particles spawned with shift (heavy) or ctrl (light) held
+    pub heavy_mass_factor: f32,
+    pub light_mass_factor: f32,
+
+    // debug overlays
+    pub show_velocity_lines: bool,
+    pub log_collisions: bool,
+    pub squash_stretch: bool,
+    // higher is smoother, circles get more vertices
+    pub circle_quality: f32,
+    pub glow: bool,
+    // fill, stroke or both. strokes are stroke_width wide, thinner on small particles
+    pub particle_style: ParticleStyle,
+    pub stroke_width: f32,
+    // hard, fade or none. fade draws the background at fade_alpha over the last frame
+    pub clear_mode: ClearMode,
+    pub fade_alpha: f32,
+    // little bursts at the contact point of collisions with at least this much impulse
+    pub sparks: bool,
+    pub spark_threshold: f32,
+    // a fading highlight on the wall wherever a particle bounces
+    pub wall_flashes: bool,
+    // shake the scene after collisions with at least shake_threshold impulse
+    pub screen_shake: bool,
+    pub shake_threshold: f32,
+    pub shake_intensity: f32,
+    // a click for collisions with at least sound_threshold impulse, louder for harder hits
+    pub sound: bool,
+    pub sound_volume: f32,
+    pub sound_threshold: f32,
+    // a duller thud when a particle hits a wall
+    pub wall_sound: bool,
+    // drawn instead of circles, e.g. "/ball.png" for resources/ball.png
+    pub particle_image: Option<String>,
+    // lines between particles closer than plexus_distance
+    pub plexus: bool,
+    pub plexus_distance: f32,
+    pub show_grid: bool,
+    // the box in cells heat_map_cell across colored by how hot it is in each, from blue up to
+    // red at heat_map_max, see heat_map::HeatMap
+    pub heat_map: bool,
+    pub heat_map_cell: f32,
+    pub heat_map_max: f32,
+    // text next to each particle: off, id, mass, speed or position
+    pub labels: LabelField,
+    // paused or in slow motion, the workings of the last collision drawn over it, see
+    // collision_math::CollisionMath
+    pub collision_math: bool,
+    pub grid_spacing: f32,
+    pub show_center_of_mass: bool,
+    pub center_of_mass_trail: bool,
+    // temperature and pressure in the hud, see gas::Reading for the units. the pressure is
+    // averaged over the last gas_window seconds, and P A against N T is printed once a window
+    pub show_gas: bool,
+    pub gas_window: f32,
+    // the speed histogram with the 2d maxwell-boltzmann distribution over it, once an elastic
+    // gas with no gravity has been left maxwell_settle seconds, see maxwell::Fit
+    pub maxwell: bool,
+    pub maxwell_settle: f32,
+    // every particle steered like a flocking bird by the others within boid_radius, see
+    // boids::steering. separation is an acceleration, alignment a rate and cohesion a rate
+    // squared, and boid_speed the speed they all cruise at. show_boid_radius rings each one
+    pub boids: bool,
+    pub boid_radius: f32,
+    pub boid_separation: f32,
+    pub boid_alignment: f32,
+    pub boid_cohesion: f32,
+    pub boid_speed: f32,
+    pub show_boid_radius: bool,
+    pub show_fps: bool,
+    // the cursor pushes particles within repulsion_radius away, strength is the
+    // acceleration right under it
+    pub repulsion: bool,
+    pub repulsion_radius: f32,
+    pub repulsion_strength: f32,
+    // E kicks everything within blast_radius of the cursor away from it,
+    // by blast_strength / distance / mass
+    pub blast_radius: f32,
+    pub blast_strength: f32,
+    // B throws burst_count particles out from somewhere within burst_radius of the cursor,
+    // at up to burst_speed
+    pub burst_count: usize,
+    pub burst_radius: f32,
+    pub burst_speed: f32,
+    // emitters placed with V spray emitter_rate particles a second, anywhere up to half of
+    // emitter_spread degrees either side of the way they point, at between the two speeds
+    pub emitter_rate: f32,
+    pub emitter_spread: f32,
+    pub emitter_speed_min: f32,
+    pub emitter_speed_max: f32,
+    // force zones ease in over this far inside their edges, 0 for all at once
+    pub zone_margin: f32,
+    // holding the middle mouse button pulls particles within attraction_radius in,
+    // strength is the force 100 pixels from the cursor
+    pub attraction_radius: f32,
+    pub attraction_strength: f32,
+    // gravity wells start at well_strength, the pull 100 pixels out. it goes with
+    // 1 / distance^well_falloff, as if nothing were closer than well_softening
+    pub well_strength: f32,
+    pub well_falloff: f32,
+    pub well_softening: f32,
+    // black holes placed with Shift+H start at black_hole_mass, the pull 100 pixels out, and
+    // get black_hole_growth heavier for every particle they swallow
+    pub black_hole_mass: f32,
+    pub black_hole_growth: f32,
+    // a magnified view in the corner, centred on the fastest particle or a locked one
+    pub inset: bool,
+    pub inset_zoom: f32,
+    pub inset_target: InsetTarget,
+
+    // stop the simulation while the help overlay is open
+    pub help_pauses: bool,
+    // where ctrl+s saves the scene and ctrl+o loads it from, as json if it ends in .json
+    pub scene_file: String,
+    // where ctrl+t saves the marked particles' paths, as csv
+    pub tracer_file: String,
+    // how long each preset in the demo reel runs for, in seconds, and how it goes on to the
+    // next, unless demo_file says otherwise
+    pub demo_seconds: f32,
+    pub demo_transition: Transition,
+    // the presets the demo reel plays and for how long, see demo::Reel. all of them in order
+    // without one
+    pub demo_file: Option<String>,
+    // start the demo reel straight away, set with `--demo`, optionally followed by a demo_file
+    #[serde(skip)]
+    pub run_demo: bool,
+    // the stress test starts with stress_start particles of stress_radius and adds
+    // stress_batch more at a time until a frame takes longer than stress_budget milliseconds,
+    // see stress::Stress
+    pub stress_start: usize,
+    pub stress_batch: usize,
+    pub stress_budget: f32,
+    pub stress_radius: f32,
+    // start the stress test straight away, set with `--stress`. `--stress headless` runs it
+    // without a window, timing only the physics, and exits
+    #[serde(skip)]
+    pub run_stress: bool,
+    #[serde(skip)]
+    pub stress_headless: bool,
+    // load scene_file straight away, set with `--scene scenes/pinball.toml`
+    #[serde(skip)]
+    pub open_scene: bool,
+    // run the maxwell-boltzmann check without a window and exit, set with `--check-maxwell`
+    #[serde(skip)]
+    pub check_maxwell: bool,
+    // run the epidemic preset without a window and exit, set with `--epidemic`, optionally
+    // followed by settings for it, e.g. `--epidemic infection_chance=0.2,recovery_time=5`
+    #[serde(skip)]
+    pub run_epidemic: bool,
+    // run the crystallization preset without a window and exit, set with `--crystallize`
+    #[serde(skip)]
+    pub run_crystallize: bool,
+    // run the chaos preset without a window, write how far its two copies drift apart to this
+    // file as csv and exit, set with `--chaos divergence.csv`
+    #[serde(skip)]
+    pub chaos_file: Option<String>,
+    // build every preset twice from the same seed, step the two in lockstep checking they stay
+    // bit for bit the same, and exit, set with `--verify-determinism`, optionally followed by
+    // how many steps, see determinism::run_headless
+    #[serde(skip)]
+    pub verify_determinism: Option<usize>,
+    // walls traced from a polyline or svg file on startup, see paths::Course. also
+    // `--walls scenes/course.path`
+    pub walls_file: Option<String>,
+
+    // run a second world beside the first with these physics settings changed,
+    // also set with `--compare restitution=0.7,drag=0.001`
+    pub compare: Option<CompareOverrides>,
+    // keys for actions, replacing their defaults: `pause = "P"` or `reseed = ["Shift+R", "F5"]`
+    // under [keys]. the names are actions::Action's, an unknown one fails the load
+    pub keys: BTreeMap<Action, KeyList>,
+
+    // name of the starting theme, see theme::THEMES
+    pub theme: String,
+    // recolor existing particles when the theme changes
+    pub remap_colors: bool,
+    // original, recolor or blend, see color::ColorPolicy
+    pub color_policy: ColorPolicy,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> SimulationConfig {
+        SimulationConfig {
+            window_width: 1280.0,
+            window_height: 720.0,
+            hidpi_factor: None,
+            vsync: true,
+            max_fps: None,
+
+            gravity: Vector { x: -1.0, y: 2.0 },
+            restitution: 1.0,
+            drag: 0.0,
+            wind: 0.0,
+            weather: Weather::Off,
+            boundary: Boundary::Walls,
+            escaped: Escaped::Respawn,
+            escape_margin: 100.0,
+            wall_style: WallStyle::Hard,
+            wall_stiffness: 3000.0,
+            wall_damping: 5.0,
+            wall_margin: 20.0,
+            wall_friction: 0.0,
+            particle_friction: 0.0,
+            sleeping: false,
+            sleep_speed: 20.0,
+            sleep_time: 0.5,
+            solver_iterations: 1,
+            wall_debris: 6,
+            thermostats: Thermostats::default(),
+            thermostat_mix: 0.5,
+            lennard_jones: false,
+            lj_depth: 800.0,
+            lj_range: 2.0,
+            cooling: false,
+            cooling_rate: 0.025,
+            reheat_temperature: 200.0,
+            demon_policy: String::from("sorting"),
+            piston: PistonMode::Off,
+            piston_min: 0.4,
+            piston_max: 1.0,
+            piston_speed: 60.0,
+            piston_period: 10.0,
+            belt: false,
+            belt_speed: 150.0,
+            belt_grip: 0.3,
+            container: Vec::new(),
+            gravity_enabled: true,
+            drag_enabled: true,
+            wells_enabled: true,
+            time_scale: 1.0,
+
+            num_particles: 40,
+            max_particles: 2000,
+            hourglass_neck: 40.0,
+            rack_jitter: true,
+            initial_infected: 3,
+            infection_chance: 0.5,
+            recovery_time: 8.0,
+            predator_energy: 12.0,
+            predator_meal: 2.0,
+            prey_birth_rate: 0.1,
+            prey_cap: 300,
+            activation_speed: 300.0,
+            reaction_products: 1,
+            fission_chance: 0.8,
+            neutron_speed: 300.0,
+            spawn_radius_min: 7.5,
+            spawn_radius_max: 12.5,
+            spawn_mass_min: 1.0,
+            spawn_mass_max: 1.25,
+            heavy_mass_factor: 10.0,
+            light_mass_factor: 0.1,
+
+            show_velocity_lines: true,
+            log_collisions: true,
+            squash_stretch: false,
+            circle_quality: 1.0,
+            glow: false,
+            particle_style: ParticleStyle::Fill,
+            stroke_width: 2.0,
+            clear_mode: ClearMode::Hard,
+            fade_alpha: 0.15,
+            sparks: true,
+            spark_threshold: 20.0,
+            wall_flashes: true,
+            screen_shake: true,
+            shake_threshold: 150.0,
+            shake_intensity: 1.0,
+            sound: true,
+            sound_volume: 0.5,
+            sound_threshold: 10.0,
+            wall_sound: true,
+            particle_image: None,
+            plexus: false,
+            plexus_distance: 80.0,
+            show_grid: false,
+            heat_map: false,
+            heat_map_cell: 50.0,
+            heat_map_max: 400.0,
+            collision_math: false,
+            labels: LabelField::Off,
+            grid_spacing: 100.0,
+            show_center_of_mass: false,
+            center_of_mass_trail: true,
+            show_gas: false,
+            gas_window: 2.0,
+            maxwell: false,
+            maxwell_settle: 20.0,
+            boids: false,
+            boid_radius: 60.0,
+            boid_separation: 400.0,
+            boid_alignment: 1.0,
+            boid_cohesion: 0.5,
+            boid_speed: 100.0,
+            show_boid_radius: false,
+            show_fps: false,
+            repulsion: false,
+            repulsion_radius: 150.0,
+            repulsion_strength: 3000.0,
+            blast_radius: 200.0,
+            blast_strength: 30000.0,
+            burst_count: 25,
+            burst_radius: 60.0,
+            burst_speed: 150.0,
+            emitter_rate: 5.0,
+            emitter_spread: 20.0,
+            emitter_speed_min: 150.0,
+            emitter_speed_max: 250.0,
+            zone_margin: 20.0,
+            well_strength: 300.0,
+            well_falloff: 2.0,
+            well_softening: 20.0,
+            black_hole_mass: 1000.0,
+            black_hole_growth: 5.0,
+            attraction_radius: 400.0,
+            attraction_strength: 800.0,
+            inset: false,
+            inset_zoom: 4.0,
+            inset_target: InsetTarget::Fastest,
+
+            help_pauses: true,
+            scene_file: String::from("scene.toml"),
+            tracer_file: String::from("tracers.csv"),
+            demo_seconds: 20.0,
+            demo_transition: Transition::Crossfade,
+            demo_file: None,
+            run_demo: false,
+            stress_start: 1000,
+            stress_batch: 500,
+            stress_budget: 16.6,
+            stress_radius: 2.0,
+            run_stress: false,
+            stress_headless: false,
+            open_scene: false,
+            check_maxwell: false,
+            run_epidemic: false,
+            run_crystallize: false,
+            chaos_file: None,
+            verify_determinism: None,
+            walls_file: None,
+
+            compare: None,
+            keys: BTreeMap::new(),
+
+            theme: String::from("classic"),
+            remap_colors: true,
+            color_policy: ColorPolicy::Recolor,
+        }
+    }
+}
+
+impl SimulationConfig {
+    // gravity and drag as the physics sees them, zero while switched off
+    pub fn active_gravity(&self) -> Vector {
+        if self.gravity_enabled {
+            self.gravity
+        } else {
+            Vector { x: 0.0, y: 0.0 }
+        }
+    }
+    pub fn active_drag(&self) -> f32 {
+        if self.drag_enabled {
+            self.drag
+        } else {
+            0.0
+        }
+    }
+
+    // a missing file just means the defaults
+    pub fn load(path: &Path) -> GameResult<SimulationConfig> {
+        if !path.exists() {
+            return Ok(SimulationConfig::default());
+        }
+
+        let text = fs::read_to_string(path)?;
+        let config: SimulationConfig = toml::from_str(&text)
+            .map_err(|e| GameError::ConfigError(format!("{}: {}", path.display(), e)))?;
+
+        if let Some(factor) = config.hidpi_factor {
+            if !(factor.is_finite() && factor > 0.0) {
+                return Err(GameError::ConfigError(format!(
+                    "{}: hidpi_factor must be greater than 0, got {}",
+                    path.display(),
+                    factor
+                )));
+            }
+        }
+
+        if let Err(e) = config.container() {
+            return Err(GameError::ConfigError(format!("{}: {}", path.display(), e)));
+        }
+
+        if demon::find(&config.demon_policy).is_none() {
+            let names: Vec<_> = demon::POLICIES.iter().map(|&(name, _)| name).collect();
+            return Err(GameError::ConfigError(format!(
+                "{}: unknown demon_policy `{}`, expected one of {}",
+                path.display(),
+                config.demon_policy,
+                names.join(", ")
+            )));
+        }
+
+        if theme::find(&config.theme).is_none() {
+            let names: Vec<_> = theme::THEMES.iter().map(|t| t.name).collect();
+            return Err(GameError::ConfigError(format!(
+                "{}: unknown theme `{}`, expected one of {}",
+                path.display(),
+                config.theme,
+                names.join(", ")
+            )));
+        }
+
+        Ok(config)
+    }
+
+    // command line flags override the file
+    pub fn apply_args(&mut self, args: impl IntoIterator<Item = String>) -> GameResult {
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--compare" => {
+                    let spec = args.next().ok_or_else(|| {
+                        GameError::ConfigError(String::from(
+                            "--compare needs settings, e.g. --compare restitution=0.7",
+                        ))
+                    })?;
+                    let overrides = self.compare.get_or_insert_with(Default::default);
+                    for setting in spec.split(',').filter(|s| !s.is_empty()) {
+                        overrides.set(setting).map_err(GameError::ConfigError)?;
+                    }
+                }
+                "--scene" => {
+                    self.scene_file = args.next().ok_or_else(|| {
+                        GameError::ConfigError(String::from(
+                            "--scene needs a file, e.g. --scene scenes/pinball.toml",
+                        ))
+                    })?;
+                    self.open_scene = true;
+                }
+                "--demo" => {
+                    self.run_demo = true;
+                    // the file's optional, so only the next argument if it isn't a flag
+                    if let Some(path) = args.peek().filter(|path| !path.starts_with("--")).cloned()
+                    {
+                        args.next();
+                        self.demo_file = Some(path);
+                    }
+                }
+                "--stress" => {
+                    self.run_stress = true;
+                    if args.peek().map(String::as_str) == Some("headless") {
+                        args.next();
+                        self.stress_headless = true;
+                    }
+                }
+                "--check-maxwell" => self.check_maxwell = true,
+                "--crystallize" => self.run_crystallize = true,
+                "--chaos" => {
+                    self.chaos_file = Some(args.next().ok_or_else(|| {
+                        GameError::ConfigError(String::from(
+                            "--chaos needs a file, e.g. --chaos divergence.csv",
+                        ))
+                    })?);
+                }
+                "--epidemic" => {
+                    self.run_epidemic = true;
+                    // settings are optional, so only the next argument if it's some
+                    if let Some(spec) = args.peek().filter(|spec| spec.contains('=')).cloned() {
+                        args.next();
+                        for setting in spec.split(',').filter(|s| !s.is_empty()) {
+                            self.set_epidemic(setting).map_err(GameError::ConfigError)?;
+                        }
+                    }
+                }
+                "--verify-determinism" => {
+                    // the count's optional, so only the next argument if it's a number
+                    let steps = args.peek().and_then(|steps| steps.parse().ok());
+                    if steps.is_some() {
+                        args.next();
+                    }
+                    self.verify_determinism = Some(steps.unwrap_or(determinism::HEADLESS_STEPS));
+                }
+                "--walls" => {
+                    self.walls_file = Some(args.next().ok_or_else(|| {
+                        GameError::ConfigError(String::from(
+                            "--walls needs a file, e.g. --walls scenes/course.path",
+                        ))
+                    })?);
+                }
+                "--boundary" => {
+                    self.boundary = match args.next().as_deref() {
+                        Some("walls") => Boundary::Walls,
+                        Some("wrap") => Boundary::Wrap,
+                        Some("open-top") => Boundary::OpenTop,
+                        Some("channel") => Boundary::Channel,
+                        _ => {
+                            return Err(GameError::ConfigError(String::from(
+                                "--boundary needs walls, wrap, open-top or channel",
+                            )))
+                        }
+                    };
+                }
+                _ => {
+                    return Err(GameError::ConfigError(format!(
+                        "unknown argument `{}`",
+                        arg
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // one `name=value` of the epidemic's settings from the command line
+    fn set_epidemic(&mut self, setting: &str) -> Result<(), String> {
+        let (name, value) = setting
+            .split_once('=')
+            .ok_or_else(|| format!("expected name=value, got `{}`", setting))?;
+        let value: f32 = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{}` isn't a number in `{}`", value, setting))?;
+        match name.trim() {
+            "infection_chance" => self.infection_chance = value,
+            "recovery_time" => self.recovery_time = value,
+            "initial_infected" => self.initial_infected = value.max(0.0) as usize,
+            other => {
+                return Err(format!(
+                    "`{}` isn't an epidemic setting, expected infection_chance, recovery_time \
+                     or initial_infected",
+                    other
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    // none without any vertices, otherwise the container if they make a good one
+    pub fn container(&self) -> Result<Option<Container>, String> {
+        if self.container.is_empty() {
+            Ok(None)
+        } else {
+            Container::new(self.container.clone()).map(Some)
+        }
+    }
+
+    pub fn spawn_radius(&self, rng: &mut impl rand::Rng) -> f32 {
+        random_between(rng, self.spawn_radius_min, self.spawn_radius_max)
+    }
+    pub fn spawn_mass(&self, rng: &mut impl rand::Rng) -> f32 {
+        random_between(rng, self.spawn_mass_min, self.spawn_mass_max)
+    }
+    // the middle of the spawn radius range, and the mass a particle of that size would have
+    // if it had the same density as the middle of both ranges. mass goes with area
+    pub fn default_radius(&self) -> f32 {
+        0.5 * (self.spawn_radius_min + self.spawn_radius_max)
+    }
+    pub fn mass_for_radius(&self, rad: f32) -> f32 {
+        let mid_mass = 0.5 * (self.spawn_mass_min + self.spawn_mass_max);
+        let mid_rad = self.default_radius().max(f32::EPSILON);
+        mid_mass * (rad / mid_rad).powi(2)
+    }
+}
+
+// physics settings that differ on the right hand side of the comparison mode.
+// everything else, including anything changed at runtime, is shared by both sides. there's
+// no integrator to compare: every world steps with the same semi-implicit Euler, which the
+// orbits preset relies on, so there's nothing for the right hand side to switch to
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompareOverrides {
+    pub restitution: Option<f32>,
+    pub drag: Option<f32>,
+    pub gravity_x: Option<f32>,
+    pub gravity_y: Option<f32>,
+}
+
+impl CompareOverrides {
+    // `base` with the overrides applied, for stepping the second world
+    pub fn apply(&self, base: &SimulationConfig) -> SimulationConfig {
+        let mut config = base.clone();
+        config.restitution = self.restitution.unwrap_or(config.restitution);
+        config.drag = self.drag.unwrap_or(config.drag);
+        config.gravity.x = self.gravity_x.unwrap_or(config.gravity.x);
+        config.gravity.y = self.gravity_y.unwrap_or(config.gravity.y);
+        config
+    }
+
+    // the overridden settings as they are in `config`, e.g. "restitution 0.70"
+    pub fn describe(&self, config: &SimulationConfig) -> String {
+        let mut parts = Vec::new();
+        if self.restitution.is_some() {
+            parts.push(format!("restitution {:.2}", config.restitution));
+        }
+        if self.drag.is_some() {
+            parts.push(format!("drag {:.4}", config.drag));
+        }
+        if self.gravity_x.is_some() || self.gravity_y.is_some() {
+            parts.push(format!(
+                "gravity ({:.2}, {:.2})",
+                config.gravity.x, config.gravity.y
+            ));
+        }
+        parts.join(", ")
+    }
+
+    // one `name=value` from the command line
+    fn set(&mut self, setting: &str) -> Result<(), String> {
+        let (name, value) = setting
+            .split_once('=')
+            .ok_or_else(|| format!("expected name=value, got `{}`", setting))?;
+        let value: f32 = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{}` isn't a number in `{}`", value, setting))?;
+
+        let field = match name.trim() {
+            "restitution" => &mut self.restitution,
+            "drag" => &mut self.drag,
+            "gravity_x" => &mut self.gravity_x,
+            "gravity_y" => &mut self.gravity_y,
+            other => {
+                return Err(format!(
+                    "can't compare `{}`, expected restitution, drag, gravity_x or gravity_y",
+                    other
+                ))
+            }
+        };
+        *field = Some(value);
+        Ok(())
+    }
+}
+
+// the sliders can push min past max, so don't rely on gen_range's ordering
+pub(crate) fn random_between(rng: &mut impl rand::Rng, a: f32, b: f32) -> f32 {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    if hi - lo <= f32::EPSILON {
+        lo
+    } else {
+        rng.gen_range(lo..hi)
+    }
+}
@@ -0,0 +1,141 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+
+use crate::events::WallEvent;
+use crate::walls::Segment;
+use crate::world::Particle;
+use crate::Point;
+
+const OUTLINE_WIDTH: f32 = 3.0;
+const OUTLINE_COLOR: Color = Color::new(0.8, 0.8, 0.8, 1.0);
+
+// a convex polygon the particles are kept inside of, on top of the window's own edges.
+// each side is a fixed wall, so the corners bounce like wall ends do
+#[derive(Clone)]
+pub struct Container {
+    vertices: Vec<Point>,
+    edges: Vec<Segment>,
+    // the sign of the turn at every corner, which says which side of each edge is inside
+    winding: f32,
+}
+
+impl Container {
+    // an error saying what's wrong unless `vertices` make a convex polygon that doesn't
+    // cross itself. either winding is fine
+    pub fn new(vertices: Vec<Point>) -> Result<Container, String> {
+        let n = vertices.len();
+        if n < 3 {
+            return Err(format!("a container needs at least 3 vertices, got {}", n));
+        }
+
+        let mut winding = 0.0;
+        let mut turned = 0.0;
+        for i in 0..n {
+            let (a, b, c) = (vertices[i], vertices[(i + 1) % n], vertices[(i + 2) % n]);
+            let (ux, uy) = (b.x - a.x, b.y - a.y);
+            let (vx, vy) = (c.x - b.x, c.y - b.y);
+            if ux * ux + uy * uy <= f32::EPSILON {
+                return Err(format!(
+                    "container vertices {} and {} are the same",
+                    i,
+                    (i + 1) % n
+                ));
+            }
+            let cross = ux * vy - uy * vx;
+            if cross.abs() <= f32::EPSILON {
+                return Err(format!(
+                    "container vertex {} is in a straight line",
+                    (i + 1) % n
+                ));
+            }
+            if winding * cross < 0.0 {
+                return Err(format!(
+                    "the container isn't convex, it turns the other way at vertex {}",
+                    (i + 1) % n
+                ));
+            }
+            winding = cross.signum();
+            turned += cross.atan2(ux * vx + uy * vy);
+        }
+        // turning the same way at every corner but going round more than once is a star
+        if (turned.abs() - std::f32::consts::PI * 2.0).abs() > 0.01 {
+            return Err(String::from("the container crosses over itself"));
+        }
+
+        let edges = (0..n)
+            .map(|i| Segment::new(vertices[i], vertices[(i + 1) % n]))
+            .collect();
+        Ok(Container {
+            vertices,
+            edges,
+            winding,
+        })
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    // whether `p` is inside with at least `margin` to spare from every side
+    pub fn contains(&self, p: Point, margin: f32) -> bool {
+        self.edges.iter().all(|edge| {
+            let (dx, dy) = (edge.b.x - edge.a.x, edge.b.y - edge.a.y);
+            let cross = dx * (p.y - edge.a.y) - dy * (p.x - edge.a.x);
+            cross * self.winding >= margin * edge.length()
+        })
+    }
+
+    pub fn area(&self) -> f32 {
+        let n = self.vertices.len();
+        let twice: f32 = (0..n)
+            .map(|i| {
+                let (a, b) = (self.vertices[i], self.vertices[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        0.5 * twice.abs()
+    }
+
+    pub fn perimeter(&self) -> f32 {
+        self.edges.iter().map(Segment::length).sum()
+    }
+
+    // the corners of the smallest box around it, top left then bottom right
+    pub fn bounds(&self) -> (Point, Point) {
+        let mut min = self.vertices[0];
+        let mut max = self.vertices[0];
+        for v in &self.vertices {
+            min.x = min.x.min(v.x);
+            min.y = min.y.min(v.y);
+            max.x = max.x.max(v.x);
+            max.y = max.y.max(v.y);
+        }
+        (min, max)
+    }
+
+    pub fn collide(
+        &self,
+        particle: &mut Particle,
+        restitution: f32,
+        friction: f32,
+        resting: f32,
+        events: &mut Vec<WallEvent>,
+    ) {
+        for edge in &self.edges {
+            if let Some(mut event) = edge.collide(particle, restitution, friction, resting) {
+                event.enclosing = true;
+                events.push(event);
+            }
+        }
+    }
+}
+
+pub fn mesh(ctx: &mut Context, container: &Container) -> GameResult<Mesh> {
+    let mut builder = MeshBuilder::new();
+    builder.polygon(
+        DrawMode::stroke(OUTLINE_WIDTH),
+        &container.vertices,
+        OUTLINE_COLOR,
+    )?;
+    builder.build(ctx)
+}
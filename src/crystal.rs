@@ -0,0 +1,252 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::config::SimulationConfig;
+use crate::presets::{Layout, PRESETS};
+use crate::theme::{self, THEMES};
+use crate::thermostat::Thermostats;
+use crate::world::World;
+use crate::Vector;
+
+// neighbours closer than this times their radii added together count as at the spacing. the
+// next ring out in a hexagonal packing is root 3 times as far
+const SHELL: f32 = 1.2;
+// how long --crystallize cools for, in simulated seconds, and the order it has to reach.
+// six is a perfect packing, the particles round the edges of a raft have fewer
+const HEADLESS_TIME: f32 = 150.0;
+const HEADLESS_PASS: f32 = 3.5;
+const HEADLESS_SEED: u64 = 1;
+const HEADLESS_STEP: f32 = 1.0 / 120.0;
+
+// the force on a particle from another `offset` away, `touching` apart when they just touch.
+// a lennard-jones potential with its well lj_depth deep right at touching, so only the
+// attraction's left outside it: 12 depth / r ((touching / r)^12 - (touching / r)^6), pulling
+// hardest just past touching and fading out fast. the collisions keep them apart, so nothing
+// closer than touching pulls, and nothing past lj_range times it
+pub fn attraction(offset: Vector, touching: f32, config: &SimulationConfig) -> Vector {
+    let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+    if distance <= touching || distance >= config.lj_range * touching {
+        return Vector { x: 0.0, y: 0.0 };
+    }
+    let six = (touching / distance).powi(6);
+    let pull = 12.0 * config.lj_depth / distance * (six - six * six);
+    Vector {
+        x: offset.x / distance * pull,
+        y: offset.y / distance * pull,
+    }
+}
+
+// every wall held at `temperature`, cooling from there at cooling_rate, with the attraction on
+pub fn start(config: &mut SimulationConfig, temperature: f32) {
+    config.lennard_jones = true;
+    config.cooling = true;
+    config.thermostats = Thermostats {
+        left: Some(temperature),
+        right: Some(temperature),
+        top: Some(temperature),
+        bottom: Some(temperature),
+    };
+}
+
+// back to the defaults
+pub fn stop(config: &mut SimulationConfig) {
+    let defaults = SimulationConfig::default();
+    config.lennard_jones = defaults.lennard_jones;
+    config.cooling = defaults.cooling;
+    config.thermostats = defaults.thermostats;
+}
+
+// every thermostat `time_elapsed` cooler, cooling_rate of its temperature a second
+pub fn cool(config: &mut SimulationConfig, time_elapsed: f32) {
+    if !config.cooling {
+        return;
+    }
+    let factor = (-config.cooling_rate.max(0.0) * time_elapsed).exp();
+    for temperature in config.thermostats.temperatures_mut() {
+        *temperature *= factor;
+    }
+}
+
+// the walls that are held at a temperature back up to `temperature`, all four if none are,
+// and every particle thrown off in a random direction as if it had just left one, so whatever
+// has frozen melts straight away. the rng comes from the step and the id like a thermostat's
+pub fn heat(world: &mut World, config: &mut SimulationConfig, temperature: f32) {
+    if !config.thermostats.any() {
+        start(config, temperature);
+    }
+    for wall in config.thermostats.temperatures_mut() {
+        *wall = temperature;
+    }
+
+    for particle in world.particles.iter_mut().filter(|p| !p.pinned) {
+        let mut rng =
+            StdRng::seed_from_u64(world.step_count.wrapping_mul(u64::from(u32::MAX)) ^ particle.id);
+        let scale = temperature / particle.mass.max(f32::EPSILON).sqrt();
+        // a 2d gas's speeds go as a rayleigh distribution
+        let speed = scale * (-2.0 * (1.0 - rng.gen::<f32>()).ln()).sqrt();
+        let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+        particle.vel = Vector {
+            x: speed * angle.cos(),
+            y: speed * angle.sin(),
+        };
+        particle.wake();
+    }
+}
+
+// the average number of neighbours each particle has at the spacing, 0 for a gas and close
+// to 6 for a hexagonal packing
+pub fn order(world: &World) -> f32 {
+    let particles = &world.particles;
+    if particles.is_empty() {
+        return 0.0;
+    }
+    let mut bonds = 0;
+    for (i, a) in particles.iter().enumerate() {
+        for b in &particles[i + 1..] {
+            if a.distance(b) < SHELL * (a.rad + b.rad) {
+                bonds += 1;
+            }
+        }
+    }
+    2.0 * bonds as f32 / particles.len() as f32
+}
+
+// the warmest wall, none with no thermostats
+fn temperature(config: &SimulationConfig) -> Option<f32> {
+    config
+        .thermostats
+        .walls()
+        .iter()
+        .flatten()
+        .copied()
+        .reduce(f32::max)
+}
+
+pub fn hud_line(world: &World, config: &SimulationConfig) -> Option<String> {
+    if !config.lennard_jones {
+        return None;
+    }
+    let walls = match temperature(config) {
+        Some(temperature) if config.cooling => format!(", walls cooling at {:.0}", temperature),
+        Some(temperature) => format!(", walls at {:.0}", temperature),
+        None => String::new(),
+    };
+    Some(format!(
+        "order: {:.2} neighbours each at the spacing{}",
+        order(world),
+        walls
+    ))
+}
+
+// run the crystallization preset without a window for HEADLESS_TIME and print how ordered it
+// ended up, true if it reached HEADLESS_PASS
+pub fn run_headless(config: &SimulationConfig) -> bool {
+    let theme = &THEMES[theme::find(&config.theme).unwrap_or(0)];
+    let layout = Layout {
+        width: config.window_width,
+        height: config.window_height,
+        config,
+        theme,
+    };
+    let preset = PRESETS
+        .iter()
+        .find(|preset| preset.name == "crystallization")
+        .expect("a crystallization preset");
+    let scene = (preset.build)(&layout, &mut StdRng::seed_from_u64(HEADLESS_SEED));
+    let mut config = SimulationConfig {
+        gravity: scene.gravity.unwrap_or(config.gravity),
+        restitution: scene.restitution.unwrap_or(config.restitution),
+        log_collisions: false,
+        ..config.clone()
+    };
+    if let Some(temperature) = scene.cooling {
+        start(&mut config, temperature);
+    }
+
+    let mut world = World::new(layout.width, layout.height);
+    world.load(scene.particles);
+    let steps = (HEADLESS_TIME / HEADLESS_STEP) as usize;
+    for _ in 0..steps {
+        world.events.clear();
+        world.step(HEADLESS_STEP, &config, theme);
+        cool(&mut config, HEADLESS_STEP);
+    }
+    let order = order(&world);
+    let passed = order >= HEADLESS_PASS;
+    println!(
+        "crystallization: order {:.2} after {}s at cooling_rate {}, walls down to {:.1}, {} (at least {})",
+        order,
+        HEADLESS_TIME,
+        config.cooling_rate,
+        temperature(&config).unwrap_or(0.0),
+        if passed { "passed" } else { "failed" },
+        HEADLESS_PASS
+    );
+    passed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::world::Particle;
+
+    const TOUCHING: f32 = 10.0;
+
+    // seven rows of seven in a hexagonal packing just touching
+    fn patch() -> World {
+        let mut world = World::new(400.0, 400.0);
+        for row in -3i32..=3 {
+            for column in -3i32..=3 {
+                let x = 200.0 + TOUCHING * (column as f32 + 0.5 * row.rem_euclid(2) as f32);
+                let y = 200.0 + TOUCHING * 0.75f32.sqrt() * row as f32;
+                world.add_particle(Particle {
+                    mass: 0.25,
+                    ..testing::particle(x, y, 0.5 * TOUCHING)
+                });
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn attracts_hardest_just_past_touching() {
+        let config = SimulationConfig::default();
+        let pull = |x: f32| attraction(Vector { x, y: 0.0 }, TOUCHING, &config).x;
+        // towards the other one, and nothing closer or far off
+        assert!(pull(11.0) > pull(14.0) && pull(14.0) > 0.0);
+        assert_eq!(pull(9.0), 0.0);
+        assert_eq!(pull(config.lj_range * TOUCHING + 1.0), 0.0);
+    }
+
+    #[test]
+    fn order_counts_the_neighbours_at_the_spacing() {
+        // six round each in the middle, fewer round the edge
+        let mut world = patch();
+        assert!((4.0..6.0).contains(&order(&world)), "{}", order(&world));
+        world.particles.iter_mut().enumerate().for_each(|(i, p)| {
+            p.pos.x = 20.0 + 40.0 * (i % 7) as f32;
+            p.pos.y = 20.0 + 40.0 * (i / 7) as f32;
+        });
+        assert_eq!(order(&world), 0.0);
+    }
+
+    #[test]
+    fn cooling_takes_the_walls_down_and_heating_puts_them_back() {
+        let mut config = SimulationConfig {
+            cooling_rate: 0.1,
+            ..SimulationConfig::default()
+        };
+        start(&mut config, 200.0);
+        for _ in 0..10 {
+            cool(&mut config, 1.0);
+        }
+        let cooled = config.thermostats.left.unwrap();
+        assert!((cooled - 200.0 * (-1.0f32).exp()).abs() < 0.1, "{}", cooled);
+
+        // and everything frozen is thrown off again
+        let mut world = patch();
+        heat(&mut world, &mut config, 200.0);
+        assert_eq!(config.thermostats.bottom, Some(200.0));
+        assert!(world.particles.iter().all(|p| p.vel_magnitude() > 0.0));
+    }
+}
@@ -0,0 +1,287 @@
+use std::{fs, path::Path};
+
+use ggez::graphics::{self, Canvas, Color, DrawParam, FilterMode, Font, Scale, Text};
+use ggez::{conf::NumSamples, Context, GameError, GameResult};
+use serde::Deserialize;
+
+use crate::config::SimulationConfig;
+use crate::presets::PRESETS;
+use crate::Point;
+
+const CAPTION_SCALE: f32 = 40.0;
+const CAPTION_MARGIN: f32 = 40.0;
+const CAPTION_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.85);
+// how long the last item takes to fade out over the next one
+const FADE_TIME: f32 = 1.5;
+
+// from one item in the reel to the next
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transition {
+    // the last item fades out over the next one
+    Crossfade,
+    // straight from one to the next
+    Cut,
+}
+
+// a demo file: presets by name in the order they're played, each for its own `seconds` or
+// the file's, or demo_seconds
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReelFile {
+    seconds: Option<f32>,
+    transition: Option<Transition>,
+    #[serde(default)]
+    items: Vec<ReelItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReelItem {
+    preset: String,
+    seconds: Option<f32>,
+}
+
+// what a demo plays, by index into PRESETS and for how long
+#[derive(Debug)]
+pub struct Reel {
+    items: Vec<(usize, f32)>,
+    transition: Transition,
+}
+
+impl Reel {
+    // every preset in order, demo_seconds each
+    pub fn every_preset(config: &SimulationConfig) -> Reel {
+        Reel {
+            items: (0..PRESETS.len())
+                .map(|preset| (preset, config.demo_seconds))
+                .collect(),
+            transition: config.demo_transition,
+        }
+    }
+
+    pub fn load(path: &Path, config: &SimulationConfig) -> GameResult<Reel> {
+        let text = fs::read_to_string(path)?;
+        Reel::parse(&text, config)
+            .map_err(|e| GameError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    fn parse(text: &str, config: &SimulationConfig) -> Result<Reel, String> {
+        let file: ReelFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let seconds = file.seconds.unwrap_or(config.demo_seconds);
+        let mut items = Vec::new();
+        for item in &file.items {
+            let preset = PRESETS
+                .iter()
+                .position(|preset| preset.name == item.preset)
+                .ok_or_else(|| {
+                    let names: Vec<_> = PRESETS.iter().map(|preset| preset.name).collect();
+                    format!(
+                        "unknown preset `{}`, expected one of {}",
+                        item.preset,
+                        names.join(", ")
+                    )
+                })?;
+            let seconds = item.seconds.unwrap_or(seconds);
+            if !(seconds.is_finite() && seconds > 0.0) {
+                return Err(format!(
+                    "`{}` needs to run for more than 0 seconds, got {}",
+                    item.preset, seconds
+                ));
+            }
+            items.push((preset, seconds));
+        }
+        if items.is_empty() {
+            return Err(String::from("no items to play"));
+        }
+        Ok(Reel {
+            items,
+            transition: file.transition.unwrap_or(config.demo_transition),
+        })
+    }
+}
+
+// the presets of a reel one after another, round and round until it's stopped, each starting
+// from the config as it was when the demo started
+pub struct Demo {
+    reel: Reel,
+    // the item playing, none before the first
+    current: Option<usize>,
+    elapsed: f32,
+    // the next item's due, and loaded at the next draw so the last frame can be kept to fade
+    // out over it
+    due: bool,
+    pub base: SimulationConfig,
+    // the last item as it was left, and how much longer it's fading out for
+    fade: Option<(Canvas, f32)>,
+}
+
+impl Demo {
+    pub fn new(reel: Reel, base: SimulationConfig) -> Demo {
+        Demo {
+            reel,
+            current: None,
+            elapsed: 0.0,
+            due: true,
+            base,
+            fade: None,
+        }
+    }
+
+    // `dt` of real time, paused or not, so a preset that stops itself doesn't stop the reel
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+        let seconds = self.current.map_or(0.0, |i| self.reel.items[i].1);
+        if self.elapsed >= seconds {
+            self.due = true;
+        }
+    }
+
+    pub fn skip(&mut self) {
+        self.due = true;
+    }
+
+    // the preset to load now, if it's time for the next one
+    pub fn next_due(&mut self) -> Option<usize> {
+        if !self.due {
+            return None;
+        }
+        self.due = false;
+        self.elapsed = 0.0;
+        let next = self.current.map_or(0, |i| (i + 1) % self.reel.items.len());
+        self.current = Some(next);
+        Some(self.reel.items[next].0)
+    }
+
+    // whether the next item's due with something playing to fade out, so it needs keeping
+    pub fn fades(&self) -> bool {
+        self.due && self.current.is_some() && self.reel.transition == Transition::Crossfade
+    }
+
+    // draw the last item into a canvas of its own, from now until `end_capture`
+    pub fn begin_capture(&mut self, ctx: &mut Context, background: Color) -> GameResult {
+        let (width, height) = graphics::drawable_size(ctx);
+        let factor = graphics::window(ctx).get_hidpi_factor() as f32;
+        let canvas = Canvas::new(
+            ctx,
+            ((width * factor) as u16).max(1),
+            ((height * factor) as u16).max(1),
+            NumSamples::One,
+        )?;
+        graphics::set_canvas(ctx, Some(&canvas));
+        graphics::clear(ctx, background);
+        self.fade = Some((canvas, FADE_TIME));
+        Ok(())
+    }
+
+    pub fn end_capture(&mut self, ctx: &mut Context) {
+        graphics::set_canvas(ctx, None);
+    }
+
+    // the last item fading out over the scene, `dt` further on than last frame
+    pub fn draw_fade(&mut self, ctx: &mut Context, dt: f32) -> GameResult {
+        let (canvas, left) = match &mut self.fade {
+            Some(fade) => fade,
+            None => return Ok(()),
+        };
+        let screen = graphics::screen_coordinates(ctx);
+        let image = canvas.image();
+        let param = DrawParam::default()
+            .dest([screen.x, screen.y])
+            .scale([
+                screen.w / f32::from(image.width()),
+                screen.h / f32::from(image.height()),
+            ])
+            .color(Color::new(
+                1.0,
+                1.0,
+                1.0,
+                (*left / FADE_TIME).clamp(0.0, 1.0),
+            ));
+        graphics::draw(ctx, &*canvas, param)?;
+        *left -= dt;
+        if *left <= 0.0 {
+            self.fade = None;
+        }
+        Ok(())
+    }
+
+    // the preset's name along the bottom, and where it is in the reel
+    pub fn draw_caption(
+        &self,
+        ctx: &mut Context,
+        font: Font,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> GameResult {
+        let current = match self.current {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+        let mut text = Text::new(format!(
+            "{}  ({} of {})",
+            PRESETS[self.reel.items[current].0].name,
+            current + 1,
+            self.reel.items.len()
+        ));
+        text.set_font(font, Scale::uniform(CAPTION_SCALE));
+        let (w, h) = text.dimensions(ctx);
+        let dest = Point {
+            x: ((screen_width - w as f32) / 2.0).max(0.0),
+            y: screen_height - CAPTION_MARGIN - h as f32,
+        };
+        graphics::queue_text(ctx, &text, dest, Some(CAPTION_COLOR));
+        graphics::draw_queued_text(ctx, DrawParam::default(), None, FilterMode::Linear)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_the_reel_round_and_round() {
+        let config = SimulationConfig::default();
+        let reel = Reel::parse(
+            r#"
+            seconds = 5.0
+            transition = "cut"
+
+            [[items]]
+            preset = "chaos"
+
+            [[items]]
+            preset = "ring"
+            seconds = 2.0
+            "#,
+            &config,
+        )
+        .unwrap();
+        let chaos = PRESETS.iter().position(|p| p.name == "chaos").unwrap();
+        assert_eq!(reel.items, [(chaos, 5.0), (0, 2.0)]);
+        assert_eq!(reel.transition, Transition::Cut);
+
+        // the first straight away, nothing to fade out from
+        let mut demo = Demo::new(reel, config.clone());
+        assert!(!demo.fades());
+        assert_eq!(demo.next_due(), Some(chaos));
+        demo.advance(4.0);
+        assert_eq!(demo.next_due(), None);
+        demo.advance(1.0);
+        assert_eq!(demo.next_due(), Some(0));
+        // skipped early, and back round to the start at the end
+        demo.advance(0.5);
+        demo.skip();
+        assert_eq!(demo.next_due(), Some(chaos));
+
+        // names are checked, and every preset's in the default
+        let bad = Reel::parse("[[items]]\npreset = \"nope\"\n", &config).unwrap_err();
+        assert!(bad.starts_with("unknown preset `nope`"), "{}", bad);
+        assert!(Reel::parse("seconds = 5.0\n", &config).is_err());
+        let bundled = Reel::parse(include_str!("../scenes/demo.toml"), &config).unwrap();
+        assert_eq!(bundled.items.len(), 6);
+        let every = Reel::every_preset(&config);
+        assert_eq!(every.items.len(), PRESETS.len());
+        assert_eq!(every.transition, Transition::Crossfade);
+    }
+}
@@ -0,0 +1,274 @@
+use ggez::graphics::{Color, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SimulationConfig;
+use crate::walls::Segment;
+use crate::world::{Particle, World};
+use crate::Point;
+
+const GATE_WIDTH: f32 = 3.0;
+const CLOSED_COLOR: Color = Color::new(0.95, 0.6, 0.2, 1.0);
+const OPEN_COLOR: Color = Color::new(0.95, 0.6, 0.2, 0.25);
+// a demon decides on whatever will reach the gate within this many steps
+const LOOKAHEAD_STEPS: f32 = 2.0;
+
+// a gap in a wall down the box that opens and closes, with the wall either side of it as
+// ordinary walls. closed, it's a wall itself. open, particles go through, and the ends of the
+// walls either side are fixed points they bounce off, so nothing clips the corners
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Gate {
+    pub x: f32,
+    // the gap's top and bottom
+    pub top: f32,
+    pub bottom: f32,
+    #[serde(default)]
+    pub open: bool,
+    // minded by the demon, opening and closing by demon_policy, rather than by the gate key
+    #[serde(default)]
+    pub demon: bool,
+}
+
+impl Gate {
+    pub fn segment(&self) -> Segment {
+        Segment::new(
+            Point {
+                x: self.x,
+                y: self.top,
+            },
+            Point {
+                x: self.x,
+                y: self.bottom,
+            },
+        )
+    }
+
+    // level with the gap, near enough to go through it or clip its corners
+    fn level_with(&self, particle: &Particle) -> bool {
+        particle.pos.y > self.top - particle.rad && particle.pos.y < self.bottom + particle.rad
+    }
+
+    // part way through, where closing would shut on it
+    fn in_the_way(&self, particle: &Particle) -> bool {
+        self.level_with(particle) && (particle.pos.x - self.x).abs() < particle.rad
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+// a particle about to reach a gate, for a policy to decide on
+pub struct Approach<'a> {
+    pub particle: &'a Particle,
+    // the side it's coming from
+    pub from: Side,
+    // the mean speed of everything in the box
+    pub typical_speed: f32,
+}
+
+// whether to let a particle through. write another and add it to POLICIES to try it out
+pub type Policy = fn(&Approach) -> bool;
+
+// by the names demon_policy takes
+pub const POLICIES: &[(&str, Policy)] = &[("sorting", sorting), ("one way", one_way)];
+
+// maxwell's: faster than typical into the right, slower into the left, so the right warms up
+// and the left cools down without anything doing work on them
+pub fn sorting(approach: &Approach) -> bool {
+    let fast = approach.particle.vel_magnitude() > approach.typical_speed;
+    match approach.from {
+        Side::Left => fast,
+        Side::Right => !fast,
+    }
+}
+
+// anything into the right and nothing back out, so they crowd in there
+pub fn one_way(approach: &Approach) -> bool {
+    approach.from == Side::Left
+}
+
+pub fn find(name: &str) -> Option<Policy> {
+    POLICIES
+        .iter()
+        .find(|(policy, _)| *policy == name)
+        .map(|&(_, policy)| policy)
+}
+
+// one step of the demons, before anything moves. each gate with a demon opens if something's
+// about to reach it that demon_policy lets through and nothing it doesn't, and closes
+// otherwise. while anything's part way through a gate it's left as it is, so it never shuts on
+// a particle and pushes it back the way it came
+pub fn operate(world: &mut World, time_elapsed: f32, config: &SimulationConfig) {
+    if !world.gates.iter().any(|gate| gate.demon) {
+        return;
+    }
+    let policy = find(&config.demon_policy).unwrap_or(sorting);
+    let moving: Vec<&Particle> = world.particles.iter().filter(|p| !p.pinned).collect();
+    let typical_speed =
+        moving.iter().map(|p| p.vel_magnitude()).sum::<f32>() / moving.len().max(1) as f32;
+
+    for gate in world.gates.iter_mut().filter(|gate| gate.demon) {
+        if moving.iter().any(|p| gate.in_the_way(p)) {
+            continue;
+        }
+        let (mut admitted, mut refused) = (false, false);
+        for &particle in moving.iter().filter(|p| gate.level_with(p)) {
+            let offset = gate.x - particle.pos.x;
+            let closing = offset.signum() * particle.vel.x;
+            if closing <= 0.0
+                || (offset.abs() - particle.rad) / closing > LOOKAHEAD_STEPS * time_elapsed
+            {
+                continue;
+            }
+            let from = if offset > 0.0 {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            if policy(&Approach {
+                particle,
+                from,
+                typical_speed,
+            }) {
+                admitted = true;
+            } else {
+                refused = true;
+            }
+        }
+        gate.open = admitted && !refused;
+    }
+}
+
+// how many particles there are either side of the first gate and their mean kinetic energy,
+// left then right
+pub fn chambers(world: &World) -> Option<[(usize, f32); 2]> {
+    let gate = world.gates.first()?;
+    let mut chambers = [(0, 0.0); 2];
+    for particle in &world.particles {
+        let side = if particle.pos.x < gate.x { 0 } else { 1 };
+        let speed = particle.vel_magnitude();
+        chambers[side].0 += 1;
+        chambers[side].1 += 0.5 * particle.mass * speed * speed;
+    }
+    for chamber in &mut chambers {
+        chamber.1 /= chamber.0.max(1) as f32;
+    }
+    Some(chambers)
+}
+
+pub fn hud_line(world: &World, config: &SimulationConfig) -> Option<String> {
+    let [left, right] = chambers(world)?;
+    let gate = &world.gates[0];
+    Some(format!(
+        "{}: left {} at {:.0}, right {} at {:.0} mean kinetic energy, gate {}",
+        if gate.demon {
+            format!("demon ({})", config.demon_policy)
+        } else {
+            String::from("gate")
+        },
+        left.0,
+        left.1,
+        right.0,
+        right.1,
+        if gate.open { "open" } else { "closed" }
+    ))
+}
+
+// a bar across each gap, solid while it's closed and faint while it's open
+pub fn mesh(ctx: &mut Context, gates: &[Gate]) -> GameResult<Option<Mesh>> {
+    if gates.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for gate in gates {
+        let segment = gate.segment();
+        let color = if gate.open { OPEN_COLOR } else { CLOSED_COLOR };
+        builder.line(&[segment.a, segment.b], GATE_WIDTH, color)?;
+    }
+    builder.build(ctx).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, STEP};
+    use crate::theme::THEMES;
+    use crate::Vector;
+
+    // a box split down the middle with a gate halfway up, and particles either side at a
+    // spread of speeds heading every which way
+    fn chambers_world(demon: bool) -> World {
+        let mut world = World::new(400.0, 300.0);
+        world.segments.push(Segment::new(
+            Point { x: 200.0, y: 0.0 },
+            Point { x: 200.0, y: 125.0 },
+        ));
+        world.segments.push(Segment::new(
+            Point { x: 200.0, y: 175.0 },
+            Point { x: 200.0, y: 300.0 },
+        ));
+        world.gates.push(Gate {
+            x: 200.0,
+            top: 125.0,
+            bottom: 175.0,
+            open: false,
+            demon,
+        });
+        for i in 0..80 {
+            let angle = 2.4 * i as f32;
+            let speed = 50.0 + 5.0 * (i % 60) as f32;
+            let column = (i % 16) as f32;
+            let x = 12.0 + 24.0 * column + if column >= 8.0 { 8.0 } else { 0.0 };
+            world.add_particle(Particle {
+                vel: Vector {
+                    x: speed * angle.cos(),
+                    y: speed * angle.sin(),
+                },
+                ..testing::particle(x, 30.0 + 48.0 * (i / 16) as f32, 5.0)
+            });
+        }
+        world
+    }
+
+    #[test]
+    fn closed_nothing_gets_from_one_side_to_the_other() {
+        // corners and all
+        let config = SimulationConfig {
+            restitution: 1.0,
+            ..testing::config()
+        };
+        let mut world = chambers_world(false);
+        let sides = |world: &World| -> Vec<bool> {
+            world.particles.iter().map(|p| p.pos.x < 200.0).collect()
+        };
+        let start = sides(&world);
+        for _ in 0..20 * 120 {
+            world.step(STEP, &config, &THEMES[0]);
+            assert_eq!(sides(&world), start);
+        }
+    }
+
+    #[test]
+    fn the_demon_sorts_fast_from_slow() {
+        // the right ends up a good deal hotter than the left
+        let config = SimulationConfig {
+            restitution: 1.0,
+            ..testing::config()
+        };
+        let mut world = chambers_world(true);
+        let [left, right] = chambers(&world).unwrap();
+        assert!(right.1 < 1.5 * left.1 && left.1 < 1.5 * right.1);
+        let mut opened = 0;
+        for _ in 0..120 * 120 {
+            world.step(STEP, &config, &THEMES[0]);
+            opened += world.gates[0].open as usize;
+        }
+        let [left, right] = chambers(&world).unwrap();
+        assert!(opened > 0);
+        assert!(right.1 > 2.0 * left.1, "{:?} {:?}", left, right);
+        assert_eq!(left.0 + right.0, 80);
+    }
+}
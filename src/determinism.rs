@@ -0,0 +1,353 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::config::SimulationConfig;
+use crate::crystal;
+use crate::events::Events;
+use crate::presets::{Layout, Preset, PRESETS};
+use crate::theme::{self, Theme, THEMES};
+use crate::world::{Particle, World};
+
+// how many steps --verify-determinism runs each preset for, without a count after it
+pub const HEADLESS_STEPS: usize = 600;
+const HEADLESS_SEED: u64 = 1;
+const HEADLESS_STEP: f32 = 1.0 / 120.0;
+
+// a float compared bit for bit, so 0.0 isn't -0.0 and a NaN is only ever the same NaN
+struct Bits(f32);
+
+impl PartialEq for Bits {
+    fn eq(&self, other: &Bits) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl fmt::Debug for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} ({:#010x})", self.0, self.0.to_bits())
+    }
+}
+
+// the first thing two worlds stepped the same way don't agree on
+#[derive(Debug)]
+pub struct Divergence {
+    // how many steps in, from 1
+    pub step: usize,
+    // the first world's particle, by id. none for the particle count and the events
+    pub particle: Option<u64>,
+    pub field: String,
+    pub a: String,
+    pub b: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "diverged at step {}", self.step)?;
+        if let Some(id) = self.particle {
+            write!(f, ", particle {}", id)?;
+        }
+        write!(f, ", {}: {} vs {}", self.field, self.a, self.b)
+    }
+}
+
+// a field and how it reads in each world
+type Difference = (String, String, String);
+
+fn differs<T: PartialEq + fmt::Debug>(field: &str, a: &T, b: &T) -> Option<Difference> {
+    if a == b {
+        return None;
+    }
+    Some((field.to_string(), format!("{:?}", a), format!("{:?}", b)))
+}
+
+fn floats(fields: &[(&str, f32, f32)]) -> Option<Difference> {
+    fields
+        .iter()
+        .find_map(|&(field, a, b)| differs(field, &Bits(a), &Bits(b)))
+}
+
+// everything a step can change about a particle
+fn particle_difference(a: &Particle, b: &Particle) -> Option<Difference> {
+    differs("id", &a.id, &b.id)
+        .or_else(|| {
+            floats(&[
+                ("pos.x", a.pos.x, b.pos.x),
+                ("pos.y", a.pos.y, b.pos.y),
+                ("prev_pos.x", a.prev_pos.x, b.prev_pos.x),
+                ("prev_pos.y", a.prev_pos.y, b.prev_pos.y),
+                ("vel.x", a.vel.x, b.vel.x),
+                ("vel.y", a.vel.y, b.vel.y),
+                ("rad", a.rad, b.rad),
+                ("mass", a.mass, b.mass),
+                ("still_for", a.still_for, b.still_for),
+                ("color.r", a.color.r, b.color.r),
+                ("color.g", a.color.g, b.color.g),
+                ("color.b", a.color.b, b.color.b),
+                ("color.a", a.color.a, b.color.a),
+            ])
+        })
+        .or_else(|| differs("pinned", &a.pinned, &b.pinned))
+        .or_else(|| differs("asleep", &a.asleep, &b.asleep))
+        .or_else(|| differs("health", &a.health, &b.health))
+        .or_else(|| differs("species", &a.species, &b.species))
+        .or_else(|| differs("gas", &a.gas, &b.gas))
+        .or_else(|| differs("reagent", &a.reagent, &b.reagent))
+        .or_else(|| differs("nuclide", &a.nuclide, &b.nuclide))
+}
+
+// the i-th of a kind of event, named for where it is in the step's list
+fn each<T>(
+    kind: &str,
+    a: &[T],
+    b: &[T],
+    difference: impl Fn(&T, &T) -> Option<Difference>,
+) -> Option<Difference> {
+    if let Some((_, a, b)) = differs("", &a.len(), &b.len()) {
+        return Some((format!("{} count", kind), a, b));
+    }
+    a.iter().zip(b).enumerate().find_map(|(i, (a, b))| {
+        difference(a, b).map(|(field, a, b)| (format!("{}[{}].{}", kind, i, field), a, b))
+    })
+}
+
+// what the step reported, in the order it reported it
+fn event_difference(a: &Events, b: &Events) -> Option<Difference> {
+    each("collisions", &a.collisions, &b.collisions, |a, b| {
+        differs("a", &a.a, &b.a)
+            .or_else(|| differs("b", &a.b, &b.b))
+            .or_else(|| {
+                floats(&[
+                    ("point.x", a.point.x, b.point.x),
+                    ("point.y", a.point.y, b.point.y),
+                    ("normal.x", a.normal.x, b.normal.x),
+                    ("normal.y", a.normal.y, b.normal.y),
+                    ("impulse", a.impulse, b.impulse),
+                    ("combined_mass", a.combined_mass, b.combined_mass),
+                    ("restitution", a.restitution, b.restitution),
+                    ("masses[0]", a.masses[0], b.masses[0]),
+                    ("masses[1]", a.masses[1], b.masses[1]),
+                    ("before[0].x", a.before[0].x, b.before[0].x),
+                    ("before[0].y", a.before[0].y, b.before[0].y),
+                    ("before[1].x", a.before[1].x, b.before[1].x),
+                    ("before[1].y", a.before[1].y, b.before[1].y),
+                    ("after[0].x", a.after[0].x, b.after[0].x),
+                    ("after[0].y", a.after[0].y, b.after[0].y),
+                    ("after[1].x", a.after[1].x, b.after[1].x),
+                    ("after[1].y", a.after[1].y, b.after[1].y),
+                ])
+            })
+    })
+    .or_else(|| {
+        each("walls", &a.walls, &b.walls, |a, b| {
+            differs("id", &a.id, &b.id)
+                .or_else(|| differs("enclosing", &a.enclosing, &b.enclosing))
+                .or_else(|| {
+                    floats(&[
+                        ("point.x", a.point.x, b.point.x),
+                        ("point.y", a.point.y, b.point.y),
+                        ("normal.x", a.normal.x, b.normal.x),
+                        ("normal.y", a.normal.y, b.normal.y),
+                        ("impulse", a.impulse, b.impulse),
+                    ])
+                })
+        })
+    })
+    .or_else(|| {
+        each("consumed", &a.consumed, &b.consumed, |a, b| {
+            floats(&[
+                ("pos.x", a.pos.x, b.pos.x),
+                ("pos.y", a.pos.y, b.pos.y),
+                ("rad", a.rad, b.rad),
+            ])
+        })
+    })
+    .or_else(|| {
+        each("splashes", &a.splashes, &b.splashes, |a, b| {
+            floats(&[("pos.x", a.pos.x, b.pos.x), ("pos.y", a.pos.y, b.pos.y)])
+        })
+    })
+}
+
+// the first difference between two worlds after `step` steps of each, particles in order
+// and then the step's events
+fn compare(step: usize, a: &World, b: &World) -> Option<Divergence> {
+    let divergence = |particle, (field, a, b): Difference| Divergence {
+        step,
+        particle,
+        field,
+        a,
+        b,
+    };
+    if let Some(difference) = differs("particle count", &a.particles.len(), &b.particles.len()) {
+        return Some(divergence(None, difference));
+    }
+    for (p, q) in a.particles.iter().zip(&b.particles) {
+        if let Some(difference) = particle_difference(p, q) {
+            return Some(divergence(Some(p.id), difference));
+        }
+    }
+    event_difference(&a.events, &b.events).map(|difference| divergence(None, difference))
+}
+
+// `steps` steps of both worlds, one after the other, checking them against each other after
+// every one. each step's events are cleared first so they're compared a step at a time
+pub fn lockstep(
+    a: &mut World,
+    b: &mut World,
+    steps: usize,
+    config: &mut SimulationConfig,
+    theme: &Theme,
+) -> Result<(), Divergence> {
+    for step in 1..=steps {
+        a.events.clear();
+        b.events.clear();
+        a.step(HEADLESS_STEP, config, theme);
+        b.step(HEADLESS_STEP, config, theme);
+        if let Some(divergence) = compare(step, a, b) {
+            return Err(divergence);
+        }
+        crystal::cool(config, HEADLESS_STEP);
+    }
+    Ok(())
+}
+
+// a world with everything a preset puts in the box, and the config it runs under, as it's
+// loaded in the window
+pub fn build(preset: &Preset, layout: &Layout) -> (World, SimulationConfig) {
+    let scene = (preset.build)(layout, &mut StdRng::seed_from_u64(HEADLESS_SEED));
+    let mut config = SimulationConfig {
+        gravity: scene.gravity.unwrap_or(layout.config.gravity),
+        restitution: scene.restitution.unwrap_or(layout.config.restitution),
+        log_collisions: false,
+        ..layout.config.clone()
+    };
+    if let Some(grains) = scene.grains {
+        config.wall_friction = grains.friction;
+        config.particle_friction = grains.friction;
+        config.sleeping = true;
+        config.solver_iterations = grains.solver_iterations;
+    }
+    if let Some(weather) = scene.weather {
+        config.weather = weather;
+        if let Some((drag, wind)) = weather.air() {
+            config.drag = drag;
+            config.wind = wind;
+        }
+    }
+    if let Some(temperature) = scene.cooling {
+        crystal::start(&mut config, temperature);
+    }
+
+    let mut world = World::new(layout.width, layout.height);
+    world.segments = scene.walls.unwrap_or_default();
+    world.wells = scene.wells.unwrap_or_default();
+    world.pillars = scene.pillars.unwrap_or_default();
+    world.emitters = scene.emitters.unwrap_or_default();
+    world.zones = scene.zones.unwrap_or_default();
+    world.voids = scene.voids.unwrap_or_default();
+    world.gates = scene.gates.unwrap_or_default();
+    world.blocks = scene.blocks.unwrap_or_default();
+    world.black_holes = scene.black_holes.unwrap_or_default();
+    world.portals = scene.portals.unwrap_or_default();
+    world.load(scene.particles);
+    (world, config)
+}
+
+// one preset built twice from the same seed and stepped in lockstep
+pub fn verify(preset: &Preset, steps: usize, config: &SimulationConfig) -> Result<(), Divergence> {
+    let theme = &THEMES[theme::find(&config.theme).unwrap_or(0)];
+    let layout = Layout {
+        width: config.window_width,
+        height: config.window_height,
+        config,
+        theme,
+    };
+    let (mut a, mut config) = build(preset, &layout);
+    let (mut b, _) = build(preset, &layout);
+    // nothing's stepped yet, but the two have to start out the same for anything after to
+    // mean anything
+    if let Some(divergence) = compare(0, &a, &b) {
+        return Err(divergence);
+    }
+    lockstep(&mut a, &mut b, steps, &mut config, theme)
+}
+
+// every preset twice over without a window for `steps` steps, printing how each one went.
+// false if any of them diverged
+pub fn run_headless(config: &SimulationConfig, steps: usize) -> bool {
+    let mut diverged = 0;
+    for preset in PRESETS {
+        match verify(preset, steps, config) {
+            Ok(()) => println!("determinism: {} identical for {} steps", preset.name, steps),
+            Err(divergence) => {
+                println!("determinism: {} {}", preset.name, divergence);
+                diverged += 1;
+            }
+        }
+    }
+    println!(
+        "determinism: {} of {} presets diverged, {}",
+        diverged,
+        PRESETS.len(),
+        if diverged == 0 { "passed" } else { "failed" }
+    );
+    diverged == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, scattered};
+    use crate::Vector;
+
+    #[test]
+    fn the_same_start_stays_the_same() {
+        let mut config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 300.0 },
+            ..testing::config()
+        };
+        let (mut a, mut b) = (scattered(), scattered());
+        lockstep(&mut a, &mut b, 240, &mut config, &THEMES[0]).unwrap();
+        assert_eq!(a.step_count, 240);
+    }
+
+    #[test]
+    fn the_smallest_nudge_is_found_the_step_after_on_that_particle() {
+        let mut config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 300.0 },
+            ..testing::config()
+        };
+        let (mut a, mut b) = (scattered(), scattered());
+        b.particles[7].vel.y = f32::from_bits(b.particles[7].vel.y.to_bits() ^ 1);
+        let divergence = lockstep(&mut a, &mut b, 240, &mut config, &THEMES[0]).unwrap_err();
+        assert_eq!(divergence.step, 1);
+        assert_eq!(divergence.particle, Some(b.particles[7].id));
+        // it's already moved by the time it's compared
+        assert!(divergence.field == "pos.y" || divergence.field == "vel.y");
+    }
+
+    #[test]
+    fn a_dropped_event_is_found_with_the_particles_alike() {
+        let mut config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 300.0 },
+            ..testing::config()
+        };
+        let (mut a, mut b) = (scattered(), scattered());
+        while a.events.collisions.is_empty() {
+            lockstep(&mut a, &mut b, 1, &mut config, &THEMES[0]).unwrap();
+        }
+        b.events.collisions.pop();
+        let divergence = compare(a.step_count as usize, &a, &b).unwrap();
+        assert_eq!(divergence.particle, None);
+        assert_eq!(divergence.field, "collisions count");
+    }
+
+    #[test]
+    fn presets_with_walls_and_gates_stay_the_same() {
+        for name in &["billiards", "maxwell's demon"] {
+            let preset = PRESETS.iter().find(|preset| preset.name == *name).unwrap();
+            verify(preset, 60, &SimulationConfig::default()).unwrap();
+        }
+    }
+}
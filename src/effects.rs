@@ -0,0 +1,344 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+use rand::Rng;
+
+use crate::events::{CollisionEvent, ConsumedEvent, SplashEvent, WallEvent};
+use crate::{Point, Vector};
+
+// hard caps on live effects, a dense scene would otherwise spawn thousands a second
+const MAX_SPARKS: usize = 400;
+const MAX_FLASHES: usize = 100;
+const MAX_PUFFS: usize = 50;
+const MAX_BLASTS: usize = 20;
+const MAX_SHRINKS: usize = 100;
+const MAX_DROPLETS: usize = 300;
+
+const SPARKS_PER_HIT: usize = 6;
+const SPARK_LIFETIME: f32 = 0.3;
+const SPARK_SIZE: f32 = 2.0;
+// how far off the tangent a spark may fly, in radians
+const SPREAD: f32 = 0.5;
+const SPEED_PER_IMPULSE: f32 = 1.5;
+const MAX_SPEED: f32 = 400.0;
+const SPARK_COLOR: Color = Color::new(1.0, 0.85, 0.5, 1.0);
+
+const FLASH_LIFETIME: f32 = 0.4;
+const FLASH_WIDTH: f32 = 3.0;
+// flash length as a multiple of the particle radius
+const FLASH_LENGTH: f32 = 3.0;
+
+// a deleted particle's outline grows to PUFF_GROWTH times its radius as it fades
+const PUFF_LIFETIME: f32 = 0.35;
+const PUFF_GROWTH: f32 = 2.0;
+const PUFF_WIDTH: f32 = 2.0;
+
+// a particle that fell into a void shrinks away to nothing
+const SHRINK_LIFETIME: f32 = 0.3;
+
+// a raindrop hitting the floor throws a few droplets up, anywhere up to DROPLET_SPREAD off
+// straight up, which fall back as they fade
+const DROPLETS_PER_SPLASH: usize = 4;
+const DROPLET_LIFETIME: f32 = 0.3;
+const DROPLET_SIZE: f32 = 1.5;
+const DROPLET_SPREAD: f32 = 1.0;
+const DROPLET_SPEED_MIN: f32 = 60.0;
+const DROPLET_SPEED_MAX: f32 = 140.0;
+const DROPLET_GRAVITY: f32 = 600.0;
+
+// an explosion's ring reaches its blast radius at the end of its lifetime
+const BLAST_LIFETIME: f32 = 0.4;
+const BLAST_WIDTH: f32 = 3.0;
+const BLAST_COLOR: Color = Color::new(1.0, 0.7, 0.3, 0.8);
+
+struct Spark {
+    pos: Point,
+    vel: Vector,
+    age: f32,
+}
+
+// a highlight on the wall where a particle bounced
+struct WallFlash {
+    from: Point,
+    to: Point,
+    color: Color,
+    age: f32,
+}
+
+// a ring left behind where a particle was deleted
+struct Puff {
+    pos: Point,
+    rad: f32,
+    color: Color,
+    age: f32,
+}
+
+// a particle on its way out down a void
+struct Shrink {
+    pos: Point,
+    rad: f32,
+    color: Color,
+    age: f32,
+}
+
+// thrown up where a raindrop splashed
+struct Droplet {
+    pos: Point,
+    vel: Vector,
+    color: Color,
+    age: f32,
+}
+
+// a ring growing out to the edge of an explosion
+struct Blast {
+    pos: Point,
+    radius: f32,
+    age: f32,
+}
+
+// short lived decorations driven by the physics events. they never collide with anything
+#[derive(Default)]
+pub struct Effects {
+    sparks: Vec<Spark>,
+    flashes: Vec<WallFlash>,
+    puffs: Vec<Puff>,
+    shrinks: Vec<Shrink>,
+    droplets: Vec<Droplet>,
+    blasts: Vec<Blast>,
+}
+
+impl Effects {
+    pub fn clear(&mut self) {
+        self.sparks.clear();
+        self.flashes.clear();
+        self.puffs.clear();
+        self.shrinks.clear();
+        self.droplets.clear();
+        self.blasts.clear();
+    }
+
+    // a burst at the contact point of every collision harder than `threshold`
+    pub fn spawn_sparks(&mut self, collisions: &[CollisionEvent], threshold: f32) {
+        let mut rng = rand::thread_rng();
+
+        for event in collisions.iter().filter(|e| e.impulse >= threshold) {
+            // flying out roughly along the contact tangent, split between both directions
+            let tangent = Vector {
+                x: -event.normal.y,
+                y: event.normal.x,
+            };
+            let speed = (event.impulse * SPEED_PER_IMPULSE).min(MAX_SPEED);
+
+            for k in 0..SPARKS_PER_HIT {
+                if self.sparks.len() >= MAX_SPARKS {
+                    return;
+                }
+                let side = if k % 2 == 0 { 1.0 } else { -1.0 };
+                let angle = tangent.y.atan2(tangent.x) + rng.gen_range(-SPREAD..SPREAD);
+                let speed = speed * rng.gen_range(0.5..1.0);
+                self.sparks.push(Spark {
+                    pos: event.point,
+                    vel: Vector {
+                        x: side * speed * angle.cos(),
+                        y: side * speed * angle.sin(),
+                    },
+                    age: 0.0,
+                });
+            }
+        }
+    }
+
+    // a segment along the wall, centred where the particle touched it
+    pub fn spawn_wall_flashes(&mut self, walls: &[WallEvent]) {
+        for event in walls {
+            if self.flashes.len() >= MAX_FLASHES {
+                return;
+            }
+            let half = 0.5 * FLASH_LENGTH * event.rad;
+            let along = Vector {
+                x: -event.normal.y * half,
+                y: event.normal.x * half,
+            };
+            self.flashes.push(WallFlash {
+                from: Point {
+                    x: event.point.x - along.x,
+                    y: event.point.y - along.y,
+                },
+                to: Point {
+                    x: event.point.x + along.x,
+                    y: event.point.y + along.y,
+                },
+                color: event.color,
+                age: 0.0,
+            });
+        }
+    }
+
+    pub fn spawn_puff(&mut self, pos: Point, rad: f32, color: Color) {
+        if self.puffs.len() < MAX_PUFFS {
+            self.puffs.push(Puff {
+                pos,
+                rad,
+                color,
+                age: 0.0,
+            });
+        }
+    }
+
+    pub fn spawn_shrinks(&mut self, consumed: &[ConsumedEvent]) {
+        for event in consumed {
+            if self.shrinks.len() >= MAX_SHRINKS {
+                return;
+            }
+            self.shrinks.push(Shrink {
+                pos: event.pos,
+                rad: event.rad,
+                color: event.color,
+                age: 0.0,
+            });
+        }
+    }
+
+    pub fn spawn_splashes(&mut self, splashes: &[SplashEvent]) {
+        let mut rng = rand::thread_rng();
+        for event in splashes {
+            for _ in 0..DROPLETS_PER_SPLASH {
+                if self.droplets.len() >= MAX_DROPLETS {
+                    return;
+                }
+                let angle =
+                    -std::f32::consts::FRAC_PI_2 + rng.gen_range(-DROPLET_SPREAD..DROPLET_SPREAD);
+                let speed = rng.gen_range(DROPLET_SPEED_MIN..DROPLET_SPEED_MAX);
+                self.droplets.push(Droplet {
+                    pos: event.pos,
+                    vel: Vector {
+                        x: speed * angle.cos(),
+                        y: speed * angle.sin(),
+                    },
+                    color: event.color,
+                    age: 0.0,
+                });
+            }
+        }
+    }
+
+    pub fn spawn_blast(&mut self, pos: Point, radius: f32) {
+        if self.blasts.len() < MAX_BLASTS {
+            self.blasts.push(Blast {
+                pos,
+                radius,
+                age: 0.0,
+            });
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for spark in &mut self.sparks {
+            spark.pos.x += spark.vel.x * dt;
+            spark.pos.y += spark.vel.y * dt;
+            spark.age += dt;
+        }
+        self.sparks.retain(|spark| spark.age < SPARK_LIFETIME);
+
+        for flash in &mut self.flashes {
+            flash.age += dt;
+        }
+        self.flashes.retain(|flash| flash.age < FLASH_LIFETIME);
+
+        for puff in &mut self.puffs {
+            puff.age += dt;
+        }
+        self.puffs.retain(|puff| puff.age < PUFF_LIFETIME);
+
+        for shrink in &mut self.shrinks {
+            shrink.age += dt;
+        }
+        self.shrinks.retain(|shrink| shrink.age < SHRINK_LIFETIME);
+
+        for droplet in &mut self.droplets {
+            droplet.vel.y += DROPLET_GRAVITY * dt;
+            droplet.pos.x += droplet.vel.x * dt;
+            droplet.pos.y += droplet.vel.y * dt;
+            droplet.age += dt;
+        }
+        self.droplets
+            .retain(|droplet| droplet.age < DROPLET_LIFETIME);
+
+        for blast in &mut self.blasts {
+            blast.age += dt;
+        }
+        self.blasts.retain(|blast| blast.age < BLAST_LIFETIME);
+    }
+
+    // everything fading out over its lifetime, one mesh for all of it
+    pub fn mesh(&self, ctx: &mut Context) -> GameResult<Option<Mesh>> {
+        if self.sparks.is_empty()
+            && self.flashes.is_empty()
+            && self.puffs.is_empty()
+            && self.shrinks.is_empty()
+            && self.droplets.is_empty()
+            && self.blasts.is_empty()
+        {
+            return Ok(None);
+        }
+
+        let mut builder = MeshBuilder::new();
+        for flash in &self.flashes {
+            let life = 1.0 - flash.age / FLASH_LIFETIME;
+            let color = Color {
+                a: flash.color.a * life,
+                ..flash.color
+            };
+            builder.line(&[flash.from, flash.to], FLASH_WIDTH, color)?;
+        }
+        for spark in &self.sparks {
+            let life = 1.0 - spark.age / SPARK_LIFETIME;
+            let color = Color {
+                a: SPARK_COLOR.a * life,
+                ..SPARK_COLOR
+            };
+            builder.circle(
+                DrawMode::fill(),
+                spark.pos,
+                (SPARK_SIZE * life).max(0.5),
+                0.5,
+                color,
+            );
+        }
+        for puff in &self.puffs {
+            let t = puff.age / PUFF_LIFETIME;
+            let color = Color {
+                a: puff.color.a * (1.0 - t),
+                ..puff.color
+            };
+            let rad = puff.rad * (1.0 + (PUFF_GROWTH - 1.0) * t);
+            builder.circle(DrawMode::stroke(PUFF_WIDTH), puff.pos, rad, 0.5, color);
+        }
+        for shrink in &self.shrinks {
+            let life = 1.0 - shrink.age / SHRINK_LIFETIME;
+            let color = Color {
+                a: shrink.color.a * life,
+                ..shrink.color
+            };
+            let rad = (shrink.rad * life).max(0.5);
+            builder.circle(DrawMode::fill(), shrink.pos, rad, 0.5, color);
+        }
+        for droplet in &self.droplets {
+            let life = 1.0 - droplet.age / DROPLET_LIFETIME;
+            let color = Color {
+                a: droplet.color.a * life,
+                ..droplet.color
+            };
+            builder.circle(DrawMode::fill(), droplet.pos, DROPLET_SIZE, 0.5, color);
+        }
+        for blast in &self.blasts {
+            let t = blast.age / BLAST_LIFETIME;
+            let color = Color {
+                a: BLAST_COLOR.a * (1.0 - t),
+                ..BLAST_COLOR
+            };
+            let rad = (blast.radius * t).max(1.0);
+            builder.circle(DrawMode::stroke(BLAST_WIDTH), blast.pos, rad, 0.5, color);
+        }
+        Ok(Some(builder.build(ctx)?))
+    }
+}
@@ -0,0 +1,158 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{random_between, SimulationConfig};
+use crate::{Point, Vector};
+
+// clicks this close to an emitter pick it
+const PICK_RADIUS: f32 = 15.0;
+
+const NOZZLE_LENGTH: f32 = 14.0;
+const NOZZLE_WIDTH: f32 = 6.0;
+const ARROW_LENGTH: f32 = 22.0;
+const ARROW_WIDTH: f32 = 2.0;
+const NOZZLE_COLOR: Color = Color::new(0.6, 0.8, 0.6, 0.9);
+const ARROW_COLOR: Color = Color::new(0.6, 0.8, 0.6, 0.6);
+
+// a nozzle that sprays particles out `direction` (degrees clockwise from the right), anywhere
+// up to half of `spread` either side of it, `rate` a second
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Emitter {
+    pub pos: Point,
+    pub direction: f32,
+    #[serde(default)]
+    pub spread: f32,
+    pub rate: f32,
+    pub speed_min: f32,
+    pub speed_max: f32,
+    pub rad_min: f32,
+    pub rad_max: f32,
+    // it stops once it's let out this many, none to go on forever
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total: Option<usize>,
+    // particles owed but not out yet. a fraction carries over from step to step, so rates
+    // below one a step still come out on time
+    #[serde(skip)]
+    pub budget: f32,
+    #[serde(skip)]
+    pub emitted: usize,
+}
+
+impl Emitter {
+    // one pointing `direction` with the configured rate, spread and speeds, and particles
+    // sized like the mouse spawns them
+    pub fn new(pos: Point, direction: f32, config: &SimulationConfig) -> Emitter {
+        Emitter {
+            pos,
+            direction,
+            spread: config.emitter_spread,
+            rate: config.emitter_rate,
+            speed_min: config.emitter_speed_min,
+            speed_max: config.emitter_speed_max,
+            rad_min: config.spawn_radius_min,
+            rad_max: config.spawn_radius_max,
+            max_total: None,
+            budget: 0.0,
+            emitted: 0,
+        }
+    }
+
+    pub fn near(&self, p: Point) -> bool {
+        let (dx, dy) = (p.x - self.pos.x, p.y - self.pos.y);
+        dx * dx + dy * dy <= PICK_RADIUS * PICK_RADIUS
+    }
+
+    pub fn finished(&self) -> bool {
+        self.max_total.is_some_and(|max| self.emitted >= max)
+    }
+
+    // add this step's share to the budget, and whether there's a whole particle's worth in it
+    pub fn due(&mut self, time_elapsed: f32) -> bool {
+        if self.finished() {
+            self.budget = 0.0;
+            return false;
+        }
+        self.budget += self.rate.max(0.0) * time_elapsed;
+        self.budget >= 1.0
+    }
+
+    // the next particle is out, or couldn't be: either way there's no point saving up more than
+    // one while it's blocked, or everything owed would pour out at once when it clears
+    pub fn spent(&mut self, emitted: bool) {
+        if emitted {
+            self.budget -= 1.0;
+            self.emitted += 1;
+        } else {
+            self.budget = self.budget.min(1.0);
+        }
+    }
+
+    // where the next particle starts, just clear of the nozzle, and how fast it's going
+    pub fn launch(&self, rad: f32, rng: &mut impl Rng) -> (Point, Vector) {
+        let half = 0.5 * self.spread.abs();
+        let angle = if half > 0.0 {
+            self.direction + rng.gen_range(-half..half)
+        } else {
+            self.direction
+        }
+        .to_radians();
+        let speed = random_between(rng, self.speed_min, self.speed_max);
+        let (sin, cos) = angle.sin_cos();
+        let out = self.direction.to_radians();
+        let pos = Point {
+            x: self.pos.x + out.cos() * (NOZZLE_LENGTH + rad),
+            y: self.pos.y + out.sin() * (NOZZLE_LENGTH + rad),
+        };
+        (
+            pos,
+            Vector {
+                x: cos * speed,
+                y: sin * speed,
+            },
+        )
+    }
+
+    pub fn radius(&self, rng: &mut impl Rng) -> f32 {
+        random_between(rng, self.rad_min, self.rad_max)
+    }
+}
+
+// a nozzle widening out the way each emitter points, with an arrow beyond it
+pub fn mesh(ctx: &mut Context, emitters: &[Emitter]) -> GameResult<Option<Mesh>> {
+    if emitters.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for emitter in emitters {
+        let (sin, cos) = emitter.direction.to_radians().sin_cos();
+        let along = |d: f32, across: f32| Point {
+            x: emitter.pos.x + cos * d - sin * across,
+            y: emitter.pos.y + sin * d + cos * across,
+        };
+        builder.polygon(
+            DrawMode::fill(),
+            &[
+                along(0.0, -0.5 * NOZZLE_WIDTH),
+                along(NOZZLE_LENGTH, -NOZZLE_WIDTH),
+                along(NOZZLE_LENGTH, NOZZLE_WIDTH),
+                along(0.0, 0.5 * NOZZLE_WIDTH),
+            ],
+            NOZZLE_COLOR,
+        )?;
+        let tip = along(NOZZLE_LENGTH + ARROW_LENGTH, 0.0);
+        let head = 0.3 * ARROW_LENGTH;
+        builder.line(&[along(NOZZLE_LENGTH, 0.0), tip], ARROW_WIDTH, ARROW_COLOR)?;
+        builder.line(
+            &[
+                along(NOZZLE_LENGTH + ARROW_LENGTH - head, -head),
+                tip,
+                along(NOZZLE_LENGTH + ARROW_LENGTH - head, head),
+            ],
+            ARROW_WIDTH,
+            ARROW_COLOR,
+        )?;
+    }
+    builder.build(ctx).map(Some)
+}
@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+
+use ggez::graphics::{Color, Rect};
+use ggez::{Context, GameResult};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::chart::History;
+use crate::config::SimulationConfig;
+use crate::events::CollisionEvent;
+use crate::presets::{Layout, PRESETS};
+use crate::theme::{self, THEMES};
+use crate::world::{Particle, World};
+
+const INFECTED_COLOR: Color = Color::new(0.9, 0.15, 0.15, 1.0);
+const RECOVERED_COLOR: Color = Color::new(0.5, 0.5, 0.5, 1.0);
+// the chart's susceptible band, since they keep their own colors
+const SUSCEPTIBLE_COLOR: Color = Color::new(0.3, 0.6, 1.0, 0.8);
+
+// the longest --epidemic runs for if it never dies out, in simulated seconds
+const HEADLESS_LIMIT: f32 = 600.0;
+const HEADLESS_SEED: u64 = 1;
+const HEADLESS_STEP: f32 = 1.0 / 120.0;
+
+// where a particle in an epidemic is at. particles outside one have none
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Health {
+    Susceptible,
+    // with this many seconds to go until it's recovered
+    Infected(f32),
+    Recovered,
+}
+
+impl Health {
+    // a susceptible particle keeps its own color
+    pub fn color(self) -> Option<Color> {
+        match self {
+            Health::Susceptible => None,
+            Health::Infected(_) => Some(INFECTED_COLOR),
+            Health::Recovered => Some(RECOVERED_COLOR),
+        }
+    }
+}
+
+// how many are susceptible, infected and recovered, none if nothing's in an epidemic
+pub fn counts(particles: &[Particle]) -> Option<[usize; 3]> {
+    let mut counts = [0; 3];
+    let mut any = false;
+    for health in particles.iter().filter_map(|p| p.health) {
+        any = true;
+        match health {
+            Health::Susceptible => counts[0] += 1,
+            Health::Infected(_) => counts[1] += 1,
+            Health::Recovered => counts[2] += 1,
+        }
+    }
+    if any {
+        Some(counts)
+    } else {
+        None
+    }
+}
+
+// caught it, with recovery_time to go
+fn infect(particle: &mut Particle, config: &SimulationConfig) {
+    particle.health = Some(Health::Infected(config.recovery_time));
+    particle.color = INFECTED_COLOR;
+}
+
+// one step of an epidemic: every touch between an infected particle and a susceptible one
+// this step, from `collisions`, passes it on with infection_chance, then the infected get
+// `time_elapsed` closer to recovering. the rng comes from the step and the two ids, like a
+// thermostat's
+pub fn spread(
+    particles: &mut [Particle],
+    collisions: &[CollisionEvent],
+    time_elapsed: f32,
+    config: &SimulationConfig,
+    step: u64,
+) {
+    if !particles
+        .iter()
+        .any(|p| matches!(p.health, Some(Health::Infected(_))))
+    {
+        return;
+    }
+
+    let index: BTreeMap<u64, usize> = particles
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.id, i))
+        .collect();
+    // anything caught this step can't pass it on until the next
+    let mut caught = Vec::new();
+    for event in collisions {
+        let (a, b) = match (index.get(&event.a), index.get(&event.b)) {
+            (Some(&a), Some(&b)) => (a, b),
+            _ => continue,
+        };
+        let target = match (particles[a].health, particles[b].health) {
+            (Some(Health::Infected(_)), Some(Health::Susceptible)) => b,
+            (Some(Health::Susceptible), Some(Health::Infected(_))) => a,
+            _ => continue,
+        };
+        if caught.contains(&target) {
+            continue;
+        }
+        let mut rng = StdRng::seed_from_u64(
+            step.wrapping_mul(u64::from(u32::MAX)) ^ (event.a << 32) ^ event.b,
+        );
+        if rng.gen::<f32>() < config.infection_chance {
+            caught.push(target);
+        }
+    }
+
+    for particle in particles.iter_mut() {
+        if let Some(Health::Infected(left)) = particle.health {
+            let left = left - time_elapsed;
+            if left <= 0.0 {
+                particle.health = Some(Health::Recovered);
+                particle.color = RECOVERED_COLOR;
+            } else {
+                particle.health = Some(Health::Infected(left));
+            }
+        }
+    }
+    for i in caught {
+        infect(&mut particles[i], config);
+    }
+}
+
+// `count` of them infected to start with, spread through the list rather than all together
+pub fn seed_infections(particles: &mut [Particle], count: usize, config: &SimulationConfig) {
+    for particle in particles.iter_mut() {
+        particle.health = Some(Health::Susceptible);
+    }
+    let n = particles.len();
+    let count = count.min(n);
+    for k in 0..count {
+        infect(&mut particles[k * n / count], config);
+    }
+}
+
+// the share that ever caught it, out of everyone in the epidemic
+fn attack_rate(counts: [usize; 3]) -> f32 {
+    let total = counts.iter().sum::<usize>();
+    if total == 0 {
+        0.0
+    } else {
+        (counts[1] + counts[2]) as f32 / total as f32
+    }
+}
+
+fn summary(counts: [usize; 3], time: f32) -> String {
+    format!(
+        "epidemic over after {:.1}s: attack rate {:.1}% ({} of {})",
+        time,
+        100.0 * attack_rate(counts),
+        counts[1] + counts[2],
+        counts.iter().sum::<usize>()
+    )
+}
+
+// how the epidemic's going: the counts over time for the chart, and a summary once no one's
+// infected any more
+#[derive(Default)]
+pub struct Epidemic {
+    history: History,
+    time: f32,
+    over: Option<String>,
+    announced: bool,
+}
+
+impl Epidemic {
+    pub fn clear(&mut self) {
+        *self = Epidemic::default();
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32) {
+        let counts = match counts(&world.particles) {
+            Some(counts) if self.over.is_none() => counts,
+            _ => return,
+        };
+        self.time += time_elapsed;
+        self.history
+            .record(counts.iter().map(|&c| c as f32).collect(), time_elapsed);
+        if counts[1] == 0 {
+            self.over = Some(summary(counts, self.time));
+        }
+    }
+
+    // the summary, only the first time it's asked for once it's over
+    pub fn announce(&mut self) -> Option<&str> {
+        if self.announced {
+            return None;
+        }
+        self.announced = self.over.is_some();
+        self.over.as_deref()
+    }
+
+    pub fn hud_line(&self, world: &World) -> Option<String> {
+        let counts = counts(&world.particles)?;
+        Some(match &self.over {
+            Some(summary) => summary.clone(),
+            None => format!(
+                "epidemic: {} susceptible, {} infected, {} recovered",
+                counts[0], counts[1], counts[2]
+            ),
+        })
+    }
+
+    pub fn draw(&self, ctx: &mut Context, pane: Rect) -> GameResult {
+        self.history.draw_stacked(
+            ctx,
+            pane,
+            &[SUSCEPTIBLE_COLOR, INFECTED_COLOR, RECOVERED_COLOR],
+        )
+    }
+}
+
+// the epidemic preset without a window until no one's infected, or HEADLESS_LIMIT seconds.
+// prints the attack rate, so runs with different settings can be compared
+pub fn run_headless(config: &SimulationConfig) -> bool {
+    let theme = &THEMES[theme::find(&config.theme).unwrap_or(0)];
+    let layout = Layout {
+        width: config.window_width,
+        height: config.window_height,
+        config,
+        theme,
+    };
+    let preset = PRESETS
+        .iter()
+        .find(|preset| preset.name == "epidemic")
+        .expect("an epidemic preset");
+    let scene = (preset.build)(&layout, &mut StdRng::seed_from_u64(HEADLESS_SEED));
+    let config = SimulationConfig {
+        gravity: scene.gravity.unwrap_or(config.gravity),
+        restitution: scene.restitution.unwrap_or(config.restitution),
+        log_collisions: false,
+        ..config.clone()
+    };
+
+    let mut world = World::new(layout.width, layout.height);
+    world.load(scene.particles);
+    let mut epidemic = Epidemic::default();
+    let steps = (HEADLESS_LIMIT / HEADLESS_STEP) as usize;
+    for _ in 0..steps {
+        world.events.clear();
+        world.step(HEADLESS_STEP, &config, theme);
+        epidemic.record(&world, HEADLESS_STEP);
+        if let Some(summary) = epidemic.announce() {
+            println!(
+                "infection_chance {}, recovery_time {}, initial_infected {}",
+                config.infection_chance, config.recovery_time, config.initial_infected
+            );
+            println!("{}", summary);
+            return true;
+        }
+    }
+    match counts(&world.particles) {
+        Some(counts) => println!(
+            "still going after {}s: attack rate so far {:.1}%",
+            HEADLESS_LIMIT,
+            100.0 * attack_rate(counts)
+        ),
+        None => println!("no epidemic to run"),
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, touch};
+
+    // four in a row, the first infected, and their ids
+    fn outbreak(config: &SimulationConfig) -> (World, Vec<u64>) {
+        let mut world = World::new(400.0, 400.0);
+        for i in 0..4 {
+            world.add_particle(testing::particle(50.0 + 100.0 * i as f32, 200.0, 10.0));
+        }
+        seed_infections(&mut world.particles, 1, config);
+        let ids = world.particles.iter().map(|p| p.id).collect();
+        (world, ids)
+    }
+
+    #[test]
+    fn only_the_infected_pass_it_on_and_not_the_step_they_caught_it() {
+        let config = SimulationConfig {
+            infection_chance: 1.0,
+            recovery_time: 1.0,
+            ..testing::config()
+        };
+        let (mut world, ids) = outbreak(&config);
+        assert_eq!(counts(&world.particles), Some([3, 1, 0]));
+        let events = [
+            touch(ids[1], ids[2]),
+            touch(ids[0], ids[1]),
+            touch(ids[1], ids[2]),
+        ];
+        spread(&mut world.particles, &events, 0.5, &config, 0);
+        assert_eq!(counts(&world.particles), Some([2, 2, 0]));
+        assert_eq!(world.particles[1].health, Some(Health::Infected(1.0)));
+        assert_eq!(world.particles[1].color, INFECTED_COLOR);
+    }
+
+    #[test]
+    fn the_infected_recover_and_cannot_catch_it_again() {
+        let config = SimulationConfig {
+            infection_chance: 1.0,
+            recovery_time: 1.0,
+            ..testing::config()
+        };
+        let (mut world, ids) = outbreak(&config);
+        spread(&mut world.particles, &[], 1.0, &config, 0);
+        assert_eq!(world.particles[0].health, Some(Health::Recovered));
+        assert_eq!(world.particles[0].color, RECOVERED_COLOR);
+        // touching one that's infected
+        infect(&mut world.particles[1], &config);
+        spread(
+            &mut world.particles,
+            &[touch(ids[0], ids[1])],
+            0.1,
+            &config,
+            1,
+        );
+        assert_eq!(world.particles[0].health, Some(Health::Recovered));
+    }
+
+    #[test]
+    fn with_no_chance_nothing_spreads() {
+        let config = SimulationConfig {
+            infection_chance: 0.0,
+            recovery_time: 1.0,
+            ..testing::config()
+        };
+        let (mut world, ids) = outbreak(&config);
+        spread(
+            &mut world.particles,
+            &[touch(ids[0], ids[1])],
+            0.1,
+            &config,
+            0,
+        );
+        assert_eq!(counts(&world.particles), Some([3, 1, 0]));
+    }
+
+    #[test]
+    fn once_no_one_is_infected_it_is_over_with_the_attack_rate() {
+        let config = SimulationConfig {
+            infection_chance: 1.0,
+            recovery_time: 1.0,
+            ..testing::config()
+        };
+        let (mut world, ids) = outbreak(&config);
+        spread(
+            &mut world.particles,
+            &[touch(ids[0], ids[1])],
+            0.5,
+            &config,
+            0,
+        );
+        let mut epidemic = Epidemic::default();
+        epidemic.record(&world, 0.5);
+        assert!(epidemic.announce().is_none());
+        spread(&mut world.particles, &[], 1.0, &config, 1);
+        epidemic.record(&world, 1.0);
+        // half of them caught it, said once and then kept on the hud
+        let summary = epidemic.announce().unwrap().to_string();
+        assert!(
+            summary.contains("attack rate 50.0% (2 of 4)"),
+            "{}",
+            summary
+        );
+        assert!(epidemic.announce().is_none());
+        assert_eq!(epidemic.hud_line(&world), Some(summary));
+    }
+}
@@ -0,0 +1,72 @@
+use ggez::graphics::Color;
+
+use crate::{Point, Vector};
+
+// two particles touched this step
+#[derive(Clone)]
+pub struct CollisionEvent {
+    // on the surface of `a`, facing `b`
+    pub point: Point,
+    // unit vector from `a` towards `b`
+    pub normal: Vector,
+    // magnitude of the momentum change of either particle
+    pub impulse: f32,
+    // particle ids, not indices, so they stay meaningful after particles are removed
+    pub a: u64,
+    pub b: u64,
+    pub combined_mass: f32,
+    // what it was worked out from and what came of it, `a` first: the restitution, each
+    // one's mass, and their velocities going in and coming out, friction and all
+    pub restitution: f32,
+    pub masses: [f32; 2],
+    pub before: [Vector; 2],
+    pub after: [Vector; 2],
+}
+
+// a particle bounced off one of the walls this step
+pub struct WallEvent {
+    pub id: u64,
+    // on the wall, level with the particle's center
+    pub point: Point,
+    // unit vector out of the wall, into the box
+    pub normal: Vector,
+    pub impulse: f32,
+    // one of the walls that shut everything in, the box's own edges or the container's,
+    // rather than something standing inside
+    pub enclosing: bool,
+    pub mass: f32,
+    pub rad: f32,
+    pub color: Color,
+}
+
+// a particle fell into a void or a black hole this step and is gone
+pub struct ConsumedEvent {
+    pub pos: Point,
+    pub rad: f32,
+    pub color: Color,
+}
+
+// a raindrop hit the floor and went back up to the top
+pub struct SplashEvent {
+    pub pos: Point,
+    pub color: Color,
+}
+
+// what the physics reports each step, so effects can react without living in the solver.
+// cleared at the start of every update
+#[derive(Default)]
+pub struct Events {
+    pub collisions: Vec<CollisionEvent>,
+    pub walls: Vec<WallEvent>,
+    pub consumed: Vec<ConsumedEvent>,
+    pub splashes: Vec<SplashEvent>,
+}
+
+impl Events {
+    pub fn clear(&mut self) {
+        self.collisions.clear();
+        self.walls.clear();
+        self.consumed.clear();
+        self.splashes.clear();
+    }
+}
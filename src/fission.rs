@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+
+use ggez::graphics::Color;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SimulationConfig;
+use crate::world::{Particle, World};
+use crate::{Point, Vector};
+
+const FUEL_COLOR: Color = Color::new(0.35, 0.75, 0.3, 1.0);
+const NEUTRON_COLOR: Color = Color::new(1.0, 0.95, 0.5, 1.0);
+// fuel's heavy enough that a neutron bouncing off it hardly moves it, so the field stays put
+pub const FUEL_MASS: f32 = 1000.0;
+pub const NEUTRON_RAD: f32 = 2.5;
+pub const NEUTRON_MASS: f32 = 0.05;
+// gap between the split fuel's place and the neutrons it lets go, on top of the radii
+const RELEASE_GAP: f32 = 1.0;
+
+// what a particle is in a chain reaction scene. particles outside one have none
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Nuclide {
+    Fuel,
+    Neutron,
+}
+
+impl Nuclide {
+    pub fn color(self) -> Color {
+        match self {
+            Nuclide::Fuel => FUEL_COLOR,
+            Nuclide::Neutron => NEUTRON_COLOR,
+        }
+    }
+}
+
+pub fn make(particle: &mut Particle, nuclide: Nuclide) {
+    particle.nuclide = Some(nuclide);
+    particle.color = nuclide.color();
+    particle.original_color = nuclide.color();
+}
+
+// fuel left and free neutrons, none if nothing's in a chain reaction scene
+pub fn counts(particles: &[Particle]) -> Option<[usize; 2]> {
+    let mut counts = [0; 2];
+    let mut any = false;
+    for nuclide in particles.iter().filter_map(|p| p.nuclide) {
+        any = true;
+        match nuclide {
+            Nuclide::Fuel => counts[0] += 1,
+            Nuclide::Neutron => counts[1] += 1,
+        }
+    }
+    if any {
+        Some(counts)
+    } else {
+        None
+    }
+}
+
+// one step of a chain reaction. every neutron that hit a fuel particle this step, from the
+// collisions after `first_collision`, is absorbed by it, and splits it with fission_chance:
+// the fuel goes, and two or three new neutrons fly out of where it was at neutron_speed, each
+// its own way. otherwise it's just captured and the fuel stays. neutrons reaching the box's
+// edges, from the wall hits after `first_wall`, leak out and are gone. everything goes in and
+// out by id with `remove` and `spawn_at`, so nothing holding on to a particle is left
+// pointing at the wrong one, and spawning stops at max_particles. the rng comes from the
+// step and the id like a thermostat's
+pub fn split(
+    world: &mut World,
+    first_collision: usize,
+    first_wall: usize,
+    config: &SimulationConfig,
+) {
+    if !world.particles.iter().any(|p| p.nuclide.is_some()) {
+        return;
+    }
+
+    let nuclides: BTreeMap<u64, Nuclide> = world
+        .particles
+        .iter()
+        .filter_map(|p| p.nuclide.map(|n| (p.id, n)))
+        .collect();
+    let mut absorbed = Vec::new();
+    let mut split = Vec::new();
+    for event in &world.events.collisions[first_collision..] {
+        let (neutron, fuel) = match (nuclides.get(&event.a), nuclides.get(&event.b)) {
+            (Some(Nuclide::Neutron), Some(Nuclide::Fuel)) => (event.a, event.b),
+            (Some(Nuclide::Fuel), Some(Nuclide::Neutron)) => (event.b, event.a),
+            _ => continue,
+        };
+        if absorbed.contains(&neutron) || split.contains(&fuel) {
+            continue;
+        }
+        let mut rng =
+            StdRng::seed_from_u64(world.step_count.wrapping_mul(u64::from(u32::MAX)) ^ neutron);
+        absorbed.push(neutron);
+        if rng.gen::<f32>() < config.fission_chance {
+            split.push(fuel);
+        }
+    }
+    for event in &world.events.walls[first_wall..] {
+        if event.enclosing
+            && nuclides.get(&event.id) == Some(&Nuclide::Neutron)
+            && !absorbed.contains(&event.id)
+        {
+            absorbed.push(event.id);
+        }
+    }
+    for id in absorbed {
+        world.remove(id);
+    }
+
+    for id in split {
+        let fuel = match world.remove(id) {
+            Some(fuel) => fuel,
+            None => continue,
+        };
+        let mut rng =
+            StdRng::seed_from_u64(world.step_count.wrapping_mul(u64::from(u32::MAX)) ^ !fuel.id);
+        let released = rng.gen_range(2..=3);
+        let start = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+        for k in 0..released {
+            let angle = start + std::f32::consts::PI * 2.0 * k as f32 / released as f32;
+            let (sin, cos) = angle.sin_cos();
+            let distance = fuel.rad - NEUTRON_RAD + RELEASE_GAP;
+            let mut neutron = Particle::new(
+                Point {
+                    x: fuel.pos.x + distance * cos,
+                    y: fuel.pos.y + distance * sin,
+                },
+                Vector {
+                    x: config.neutron_speed * cos,
+                    y: config.neutron_speed * sin,
+                },
+                NEUTRON_RAD,
+                NEUTRON_MASS,
+                NEUTRON_COLOR,
+            );
+            make(&mut neutron, Nuclide::Neutron);
+            world.spawn_at(neutron, config);
+        }
+    }
+}
+
+// how the chain reaction's going: the most neutrons there have been, and a summary once it's
+// died out or hit the particle cap
+#[derive(Default)]
+pub struct ChainReaction {
+    start_fuel: Option<usize>,
+    peak: usize,
+    time: f32,
+    over: Option<String>,
+    announced: bool,
+}
+
+impl ChainReaction {
+    pub fn clear(&mut self) {
+        *self = ChainReaction::default();
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32, config: &SimulationConfig) {
+        if self.over.is_some() {
+            return;
+        }
+        // the last of the neutrons leaving with the last of the fuel leaves nothing at all
+        let [fuel, neutrons] = match counts(&world.particles) {
+            Some(counts) => counts,
+            None if self.start_fuel.is_some() => [0, 0],
+            None => return,
+        };
+        let start = *self.start_fuel.get_or_insert(fuel);
+        self.time += time_elapsed;
+        self.peak = self.peak.max(neutrons);
+        let split = start.saturating_sub(fuel);
+        let ending = if neutrons > 0 && world.particles.len() >= config.max_particles {
+            "hit the particle cap"
+        } else if neutrons == 0 {
+            "died out"
+        } else {
+            return;
+        };
+        self.over = Some(format!(
+            "chain reaction {} after {:.1}s: {} of {} fuel split ({:.0}%), at most {} neutrons",
+            ending,
+            self.time,
+            split,
+            start,
+            100.0 * split as f32 / start.max(1) as f32,
+            self.peak
+        ));
+    }
+
+    // the summary, only the first time it's asked for once it's over
+    pub fn announce(&mut self) -> Option<&str> {
+        if self.announced {
+            return None;
+        }
+        self.announced = self.over.is_some();
+        self.over.as_deref()
+    }
+
+    pub fn hud_line(&self, world: &World) -> Option<String> {
+        if let Some(summary) = &self.over {
+            return Some(summary.clone());
+        }
+        let [fuel, neutrons] = counts(&world.particles)?;
+        Some(format!(
+            "chain reaction: {} free neutrons, {} fuel left",
+            neutrons, fuel
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, STEP};
+
+    #[test]
+    fn never_splitting_the_neutron_is_captured_and_it_dies_out() {
+        let config = SimulationConfig {
+            fission_chance: 0.0,
+            ..testing::config()
+        };
+        let (mut world, config) = testing::preset("chain reaction", &config);
+        let [fuel, _] = counts(&world.particles).unwrap();
+        let mut reaction = ChainReaction::default();
+        testing::run_each(&mut world, 10 * 120, &config, |world| {
+            reaction.record(world, STEP, &config)
+        });
+        assert_eq!(counts(&world.particles), Some([fuel, 0]));
+        assert!(reaction.announce().unwrap().contains("died out"));
+        assert_eq!(reaction.announce(), None);
+    }
+
+    #[test]
+    fn always_splitting_it_sweeps_through_most_of_the_fuel() {
+        let config = SimulationConfig {
+            fission_chance: 1.0,
+            ..testing::config()
+        };
+        let (mut world, config) = testing::preset("chain reaction", &config);
+        let [start, _] = counts(&world.particles).unwrap();
+        let mut reaction = ChainReaction::default();
+        testing::run_each(&mut world, 10 * 120, &config, |world| {
+            reaction.record(world, STEP, &config)
+        });
+        // with nothing left at all there's no reaction to count
+        let [fuel, _] = counts(&world.particles).unwrap_or([0, 0]);
+        assert!(fuel < start / 2, "{} of {} fuel left", fuel, start);
+        assert!(reaction.peak > 10);
+    }
+
+    #[test]
+    fn with_a_cap_it_stops_there_rather_than_spilling_over() {
+        let config = SimulationConfig {
+            fission_chance: 1.0,
+            max_particles: 110,
+            ..testing::config()
+        };
+        let (mut world, config) = testing::preset("chain reaction", &config);
+        let mut reaction = ChainReaction::default();
+        testing::run_each(&mut world, 10 * 120, &config, |world| {
+            reaction.record(world, STEP, &config)
+        });
+        assert!(world.particles.len() <= 110);
+        assert!(reaction.announce().unwrap().contains("particle cap"));
+    }
+}
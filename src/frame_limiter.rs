@@ -0,0 +1,40 @@
+use std::time::{Duration, Instant};
+
+use ggez::timer;
+
+// sleep is only accurate to a millisecond or two, so the last stretch is spent yielding
+const YIELD_MARGIN: Duration = Duration::from_millis(2);
+
+// software cap on the frame rate, for when vsync is off or the display is faster than needed.
+// the physics integrates the real frame time so nothing changes apart from smoothness
+pub struct FrameLimiter {
+    last_frame: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new() -> FrameLimiter {
+        FrameLimiter {
+            last_frame: Instant::now(),
+        }
+    }
+
+    // call once per frame after presenting. `None` (or anything not positive) means uncapped
+    pub fn wait(&mut self, max_fps: Option<f32>) {
+        if let Some(fps) = max_fps.filter(|&fps| fps > 0.0) {
+            let target = Duration::from_secs_f32(1.0 / fps);
+            loop {
+                let elapsed = self.last_frame.elapsed();
+                if elapsed >= target {
+                    break;
+                }
+                let remaining = target - elapsed;
+                if remaining > YIELD_MARGIN {
+                    timer::sleep(remaining - YIELD_MARGIN);
+                } else {
+                    timer::yield_now();
+                }
+            }
+        }
+        self.last_frame = Instant::now();
+    }
+}
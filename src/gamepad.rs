@@ -0,0 +1,173 @@
+use ggez::event::{Axis, GamepadId};
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder};
+use ggez::{Context, GameResult};
+
+use crate::{Point, Vector};
+
+// stick movement smaller than this is drift rather than input
+const DEAD_ZONE: f32 = 0.15;
+// how fast the reticle moves at full stick, logical pixels per second
+const RETICLE_SPEED: f32 = 600.0;
+// launch speed gained per second of holding the spawn button, and the most it can reach
+const CHARGE_RATE: f32 = 800.0;
+const MAX_CHARGE: f32 = 1200.0;
+// length of the aiming line per unit of launch speed
+const AIM_SCALE: f32 = 1.0 / 3.0;
+
+const RETICLE_RADIUS: f32 = 10.0;
+const RETICLE_WIDTH: f32 = 2.0;
+const RETICLE_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.8);
+const CHARGE_COLOR: Color = Color::new(1.0, 0.7, 0.3, 0.9);
+
+// a cursor steered with the left stick, standing in for the mouse. it only shows up once a
+// gamepad is used, and hides again as soon as the mouse moves
+pub struct Reticle {
+    // in screen coordinates
+    pub pos: Point,
+    stick: Vector,
+    // the pad that last did something, none while the mouse is in charge
+    active: Option<GamepadId>,
+    // seconds the spawn button has been held for, while it's held
+    charge: Option<f32>,
+}
+
+impl Reticle {
+    pub fn new(pos: Point) -> Reticle {
+        Reticle {
+            pos,
+            stick: Vector { x: 0.0, y: 0.0 },
+            active: None,
+            charge: None,
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    // any pad input makes the reticle the one in charge
+    pub fn activate(&mut self, id: GamepadId) {
+        self.active = Some(id);
+    }
+
+    pub fn deactivate(&mut self) {
+        self.active = None;
+        self.charge = None;
+        self.stick = Vector { x: 0.0, y: 0.0 };
+    }
+
+    pub fn axis(&mut self, axis: Axis, value: f32, id: GamepadId) {
+        match axis {
+            Axis::LeftStickX => self.stick.x = value,
+            // up on the stick is positive, up on the screen is negative
+            Axis::LeftStickY => self.stick.y = -value,
+            _ => return,
+        }
+        if value.abs() > DEAD_ZONE {
+            self.activate(id);
+        }
+    }
+
+    fn stick(&self) -> Vector {
+        let length = (self.stick.x * self.stick.x + self.stick.y * self.stick.y).sqrt();
+        if length < DEAD_ZONE {
+            Vector { x: 0.0, y: 0.0 }
+        } else {
+            self.stick
+        }
+    }
+
+    // move with the stick, or hold still and charge while the spawn button is down.
+    // gilrs picks up pads plugged in at any time, this only has to notice one going away
+    pub fn update(&mut self, ctx: &Context, dt: f32, width: f32, height: f32) {
+        let id = match self.active {
+            Some(id) => id,
+            None => return,
+        };
+        if !ggez::input::gamepad::gamepad(ctx, id).is_connected() {
+            self.deactivate();
+            return;
+        }
+
+        match &mut self.charge {
+            Some(charge) => *charge += dt,
+            None => {
+                let stick = self.stick();
+                self.pos.x = (self.pos.x + stick.x * RETICLE_SPEED * dt).clamp(0.0, width);
+                self.pos.y = (self.pos.y + stick.y * RETICLE_SPEED * dt).clamp(0.0, height);
+            }
+        }
+    }
+
+    pub fn start_charge(&mut self) {
+        self.charge = Some(0.0);
+    }
+
+    // the velocity to launch with, the way the stick points at the speed the hold charged up.
+    // none if the spawn button wasn't being held
+    pub fn release_charge(&mut self) -> Option<Vector> {
+        self.charge.take()?;
+        Some(self.launch_velocity())
+    }
+
+    fn launch_velocity(&self) -> Vector {
+        let charge = match self.charge {
+            Some(charge) => charge,
+            None => return Vector { x: 0.0, y: 0.0 },
+        };
+        let stick = self.stick();
+        let length = (stick.x * stick.x + stick.y * stick.y).sqrt();
+        if length <= f32::EPSILON {
+            return Vector { x: 0.0, y: 0.0 };
+        }
+        let speed = (charge * CHARGE_RATE).min(MAX_CHARGE);
+        Vector {
+            x: stick.x / length * speed,
+            y: stick.y / length * speed,
+        }
+    }
+
+    // a ring with a cross through it, and while charging a line the way it'll launch
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        if !self.active() {
+            return Ok(());
+        }
+
+        let mut builder = MeshBuilder::new();
+        builder.circle(
+            DrawMode::stroke(RETICLE_WIDTH),
+            self.pos,
+            RETICLE_RADIUS,
+            0.5,
+            RETICLE_COLOR,
+        );
+        for (dx, dy) in [(1.0, 0.0), (0.0, 1.0)].iter() {
+            builder.line(
+                &[
+                    Point {
+                        x: self.pos.x - dx * 1.5 * RETICLE_RADIUS,
+                        y: self.pos.y - dy * 1.5 * RETICLE_RADIUS,
+                    },
+                    Point {
+                        x: self.pos.x + dx * 1.5 * RETICLE_RADIUS,
+                        y: self.pos.y + dy * 1.5 * RETICLE_RADIUS,
+                    },
+                ],
+                1.0,
+                RETICLE_COLOR,
+            )?;
+        }
+
+        let vel = self.launch_velocity();
+        if vel.x != 0.0 || vel.y != 0.0 {
+            let tip = Point {
+                x: self.pos.x + vel.x * AIM_SCALE,
+                y: self.pos.y + vel.y * AIM_SCALE,
+            };
+            builder.line(&[self.pos, tip], RETICLE_WIDTH, CHARGE_COLOR)?;
+        }
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
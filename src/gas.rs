@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+
+use crate::config::SimulationConfig;
+use crate::world::World;
+
+// the gas measured the way a lab would, in the simulation's own units: lengths in pixels,
+// time in seconds, mass in the units particles are given, and boltzmann's constant 1. so a
+// temperature is an energy, mass px^2/s^2, and a pressure in 2d is a force per unit length of
+// wall, mass/s^2. a thermostat at t holds a gas at temperature t^2
+pub struct Reading {
+    // what's free to move, pinned particles are part of the walls
+    pub count: usize,
+    // the mean kinetic energy, which in 2d is the temperature, over the same time as the
+    // pressure so a gas that's cooling or warming still compares like with like
+    pub temperature: f32,
+    // none with no walls to push on, or before there's been any time to watch them
+    pub pressure: Option<f32>,
+    pub area: f32,
+}
+
+impl Reading {
+    // P A / N T, which for an ideal gas is 1. real disks take up room, so a dense gas comes out
+    // over
+    pub fn ratio(&self) -> Option<f32> {
+        let pressure = self.pressure?;
+        let nt = self.count as f32 * self.temperature;
+        if nt <= f32::EPSILON {
+            return None;
+        }
+        Some(pressure * self.area / nt)
+    }
+
+    pub fn hud_line(&self) -> String {
+        let pressure = match self.pressure {
+            Some(pressure) => format!("{:.1}", pressure),
+            None => String::from("-"),
+        };
+        let ratio = match self.ratio() {
+            Some(ratio) => format!("{:.2}", ratio),
+            None => String::from("-"),
+        };
+        format!(
+            "gas: T {:.0}, P {}, A {:.0} px^2, PA/NT {}",
+            self.temperature, pressure, self.area, ratio
+        )
+    }
+
+    pub fn log_line(&self) -> String {
+        let pressure_area = match self.pressure {
+            Some(pressure) => format!("{:.0}", pressure * self.area),
+            None => String::from("-"),
+        };
+        let ratio = match self.ratio() {
+            Some(ratio) => format!("{:.3}", ratio),
+            None => String::from("-"),
+        };
+        format!(
+            "gas: N {}, PA {}, NT {:.0}, PA/NT {}",
+            self.count,
+            pressure_area,
+            self.count as f32 * self.temperature,
+            ratio
+        )
+    }
+}
+
+// the push on the walls over the last gas_window seconds, from the wall events, so the
+// pressure is the momentum they've taken over that time and length. a soft wall's event
+// counts it as coming back out as fast as it went in, so damped ones read a little high
+#[derive(Default)]
+pub struct Gauge {
+    // how long each advance was, the impulse the enclosing walls took over it and the
+    // temperature at the end of it
+    samples: VecDeque<Sample>,
+    since_log: f32,
+}
+
+struct Sample {
+    time: f32,
+    impulse: f32,
+    temperature: f32,
+}
+
+// what's free to move and its mean kinetic energy
+fn temperature(world: &World) -> (usize, f32) {
+    let moving: Vec<_> = world.particles.iter().filter(|p| !p.pinned).collect();
+    if moving.is_empty() {
+        return (0, 0.0);
+    }
+    let energy: f32 = moving
+        .iter()
+        .map(|p| 0.5 * p.mass * p.vel_magnitude().powi(2))
+        .sum();
+    (moving.len(), energy / moving.len() as f32)
+}
+
+impl Gauge {
+    pub fn clear(&mut self) {
+        *self = Gauge::default();
+    }
+
+    // `time_elapsed` after the last record, with the world's events from all of it
+    pub fn record(&mut self, world: &World, time_elapsed: f32, config: &SimulationConfig) {
+        let impulse: f32 = world
+            .events
+            .walls
+            .iter()
+            .filter(|event| event.enclosing)
+            .map(|event| event.impulse)
+            .sum();
+        self.samples.push_back(Sample {
+            time: time_elapsed,
+            impulse,
+            temperature: temperature(world).1,
+        });
+        self.since_log += time_elapsed;
+
+        // always the latest sample, however short the window
+        let mut watched: f32 = self.samples.iter().map(|s| s.time).sum();
+        while self.samples.len() > 1 {
+            let oldest = self.samples[0].time;
+            if watched - oldest < config.gas_window {
+                break;
+            }
+            watched -= oldest;
+            self.samples.pop_front();
+        }
+    }
+
+    // nothing recorded yet, and it's the temperature right now
+    pub fn read(&self, world: &World, config: &SimulationConfig) -> Reading {
+        let (count, now) = temperature(world);
+        let (area, walls) = world.enclosure(config.boundary);
+        let watched: f32 = self.samples.iter().map(|s| s.time).sum();
+        let impulse: f32 = self.samples.iter().map(|s| s.impulse).sum();
+        let (temperature, pressure) = if watched > 0.0 {
+            let temperature: f32 = self.samples.iter().map(|s| s.temperature * s.time).sum();
+            let pressure = if walls > 0.0 {
+                Some(impulse / (watched * walls))
+            } else {
+                None
+            };
+            (temperature / watched, pressure)
+        } else {
+            (now, None)
+        };
+
+        Reading {
+            count,
+            temperature,
+            pressure,
+            area,
+        }
+    }
+
+    // whether a whole window's gone by since this last said so, for the log
+    pub fn log_due(&mut self, config: &SimulationConfig) -> bool {
+        if self.since_log < config.gas_window {
+            return false;
+        }
+        self.since_log = 0.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, STEP};
+    use crate::theme::THEMES;
+    use crate::world::{Boundary, Particle, WallStyle};
+    use crate::Vector;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // sparse enough that the disks hardly take up any room
+    fn gas(count: usize, speed: f32) -> World {
+        let mut world = World::new(800.0, 600.0);
+        let mut rng = StdRng::seed_from_u64(5);
+        for i in 0..count {
+            let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+            let (x, y) = (40.0 + 80.0 * (i % 10) as f32, 30.0 + 60.0 * (i / 10) as f32);
+            world.add_particle(Particle {
+                vel: Vector {
+                    x: speed * angle.cos(),
+                    y: speed * angle.sin(),
+                },
+                mass: 1.0 + (i % 3) as f32,
+                ..testing::particle(x, y, 3.0)
+            });
+        }
+        world
+    }
+
+    #[test]
+    fn pressure_times_area_comes_to_count_times_temperature() {
+        for &(boundary, style) in &[
+            (Boundary::Walls, WallStyle::Hard),
+            (Boundary::Walls, WallStyle::Soft),
+            (Boundary::Channel, WallStyle::Hard),
+            (Boundary::OpenTop, WallStyle::Hard),
+        ] {
+            let config = SimulationConfig {
+                restitution: 1.0,
+                drag: 0.0,
+                boundary,
+                wall_style: style,
+                // undamped, so a soft wall gives back all it's given
+                wall_damping: 0.0,
+                gas_window: 20.0,
+                ..testing::config()
+            };
+            // slow enough that nothing gets far out of an open top before it's back
+            let mut world = gas(100, 200.0);
+            let mut gauge = Gauge::default();
+            for _ in 0..20 {
+                world.events.clear();
+                for _ in 0..60 {
+                    world.step(STEP, &config, &THEMES[0]);
+                }
+                gauge.record(&world, 0.5, &config);
+            }
+            let reading = gauge.read(&world, &config);
+            let ratio = reading.ratio().unwrap();
+            assert!(
+                (ratio - 1.0).abs() < 0.1,
+                "{} {} walls: {}",
+                boundary.name(),
+                if style == WallStyle::Soft {
+                    "soft"
+                } else {
+                    "hard"
+                },
+                reading.log_line()
+            );
+        }
+    }
+
+    #[test]
+    fn with_no_walls_to_push_on_there_is_no_pressure() {
+        let config = SimulationConfig {
+            boundary: Boundary::Wrap,
+            ..testing::config()
+        };
+        let mut gauge = Gauge::default();
+        let world = gas(10, 200.0);
+        gauge.record(&world, 0.5, &config);
+        assert!(gauge.read(&world, &config).pressure.is_none());
+    }
+}
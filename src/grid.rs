@@ -0,0 +1,65 @@
+use ggez::graphics::{self, Color, DrawParam, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+
+use crate::Point;
+
+const MINOR_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.06);
+const MAJOR_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.14);
+
+// every nth line is drawn brighter
+const MAJOR_EVERY: usize = 5;
+
+// reference lines behind the particles, only rebuilt when the spacing or size changes
+#[derive(Default)]
+pub struct Grid {
+    mesh: Option<Mesh>,
+    built_for: (f32, f32, f32),
+}
+
+impl Grid {
+    pub fn draw(&mut self, ctx: &mut Context, spacing: f32, width: f32, height: f32) -> GameResult {
+        let key = (spacing, width, height);
+        if self.mesh.is_none() || self.built_for != key {
+            self.mesh = Some(Self::build(ctx, spacing, width, height)?);
+            self.built_for = key;
+        }
+
+        match &self.mesh {
+            Some(mesh) => graphics::draw(ctx, mesh, DrawParam::default()),
+            None => Ok(()),
+        }
+    }
+
+    fn build(ctx: &mut Context, spacing: f32, width: f32, height: f32) -> GameResult<Mesh> {
+        let spacing = spacing.max(2.0);
+        let mut builder = MeshBuilder::new();
+
+        let color = |i: usize| {
+            if i.is_multiple_of(MAJOR_EVERY) {
+                MAJOR_COLOR
+            } else {
+                MINOR_COLOR
+            }
+        };
+
+        let mut i = 0;
+        while i as f32 * spacing <= width {
+            let x = i as f32 * spacing;
+            builder.line(
+                &[Point { x, y: 0.0 }, Point { x, y: height }],
+                1.0,
+                color(i),
+            )?;
+            i += 1;
+        }
+
+        let mut i = 0;
+        while i as f32 * spacing <= height {
+            let y = i as f32 * spacing;
+            builder.line(&[Point { x: 0.0, y }, Point { x: width, y }], 1.0, color(i))?;
+            i += 1;
+        }
+
+        builder.build(ctx)
+    }
+}
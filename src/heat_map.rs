@@ -0,0 +1,228 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+use crate::broadphase::SpatialGrid;
+use crate::config::SimulationConfig;
+use crate::world::World;
+
+// how often the map's read, in simulated seconds, and how far each reading moves it from what
+// it showed, so a particle darting in and out of a cell doesn't make it flicker
+const UPDATE_TIME: f32 = 0.5;
+const SMOOTHING: f32 = 0.4;
+// cells averaging fewer particles than this have nothing to show and are left clear. ones with
+// fewer than one are fainter for it
+const EMPTY: f32 = 0.05;
+const CELL_ALPHA: f32 = 0.45;
+// from no temperature at all, through the middle, up to heat_map_max and over
+const COLD_COLOR: Color = Color::new(0.15, 0.35, 1.0, 1.0);
+const MIDDLE_COLOR: Color = Color::new(0.85, 0.85, 0.85, 1.0);
+const HOT_COLOR: Color = Color::new(1.0, 0.2, 0.1, 1.0);
+
+// the box in square cells heat_map_cell across, each colored by how hot the particles in it
+// are. a cell's temperature is in the thermostats' terms, the speed a mass 1 particle would
+// have with its particles' mean kinetic energy
+#[derive(Default)]
+pub struct HeatMap {
+    cell_size: f32,
+    cols: usize,
+    // each cell's kinetic energy and particle count, smoothed, in row order
+    cells: Vec<(f32, f32)>,
+    since_update: f32,
+    // what the particles are bucketed into the cells with, apart from the world's so drawing
+    // the map leaves the physics alone
+    grid: Option<SpatialGrid>,
+}
+
+impl HeatMap {
+    pub fn clear(&mut self) {
+        *self = HeatMap::default();
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32, config: &SimulationConfig) {
+        if !config.heat_map {
+            self.cells.clear();
+            return;
+        }
+        self.since_update += time_elapsed;
+        if !self.cells.is_empty() && self.since_update < UPDATE_TIME {
+            return;
+        }
+        self.since_update = 0.0;
+
+        let particles = &world.particles;
+        let grid = self
+            .grid
+            .get_or_insert_with(|| SpatialGrid::new(world.width, world.height));
+        grid.set_bounds(world.width, world.height);
+        grid.rebuild(particles, config.heat_map_cell, (false, false));
+        let mut cols = 0;
+        let mut readings = Vec::with_capacity(self.cells.len());
+        grid.for_each_cell(|col, _, members| {
+            cols = cols.max(col + 1);
+            let energy: f32 = members
+                .iter()
+                .map(|&i| {
+                    let particle = &particles[i];
+                    let speed = particle.vel_magnitude();
+                    0.5 * particle.mass * speed * speed
+                })
+                .sum();
+            readings.push((energy, members.len() as f32));
+        });
+
+        // a new size of cell, or of box, starts over
+        if grid.cell_size() != self.cell_size || readings.len() != self.cells.len() {
+            self.cell_size = grid.cell_size();
+            self.cols = cols;
+            self.cells = readings;
+            return;
+        }
+        for (cell, reading) in self.cells.iter_mut().zip(readings) {
+            cell.0 += (reading.0 - cell.0) * SMOOTHING;
+            cell.1 += (reading.1 - cell.1) * SMOOTHING;
+        }
+    }
+
+    // none for a cell with nothing in it
+    fn temperature(&self, cell: (f32, f32)) -> Option<f32> {
+        let (energy, count) = cell;
+        if count < EMPTY {
+            return None;
+        }
+        Some((2.0 * energy / count).max(0.0).sqrt())
+    }
+
+    // behind everything, blue for cold and red for heat_map_max and up
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        width: f32,
+        height: f32,
+        config: &SimulationConfig,
+    ) -> GameResult {
+        if self.cells.is_empty() || self.cols == 0 {
+            return Ok(());
+        }
+        let mut builder = MeshBuilder::new();
+        let mut any = false;
+        for (i, &cell) in self.cells.iter().enumerate() {
+            let temperature = match self.temperature(cell) {
+                Some(temperature) => temperature,
+                None => continue,
+            };
+            let x = (i % self.cols) as f32 * self.cell_size;
+            let y = (i / self.cols) as f32 * self.cell_size;
+            // the last row and column hang over the edge
+            let rect = Rect::new(
+                x,
+                y,
+                self.cell_size.min(width - x),
+                self.cell_size.min(height - y),
+            );
+            if rect.w <= 0.0 || rect.h <= 0.0 {
+                continue;
+            }
+            let color = Color {
+                a: CELL_ALPHA * cell.1.min(1.0),
+                ..color(temperature / config.heat_map_max.max(f32::EPSILON))
+            };
+            builder.rectangle(DrawMode::fill(), rect, color);
+            any = true;
+        }
+        if !any {
+            return Ok(());
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
+
+// `t` of the way up the scale, 0 cold and 1 hot
+fn color(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let (from, to, t) = if t < 0.5 {
+        (COLD_COLOR, MIDDLE_COLOR, 2.0 * t)
+    } else {
+        (MIDDLE_COLOR, HOT_COLOR, 2.0 * t - 1.0)
+    };
+    Color::new(
+        from.r + (to.r - from.r) * t,
+        from.g + (to.g - from.g) * t,
+        from.b + (to.b - from.b) * t,
+        1.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::world::Particle;
+    use crate::Vector;
+
+    // a fast mass 1 particle in the top left cell and a slow mass 4 one in the next, read once
+    fn mapped(config: &SimulationConfig) -> (World, HeatMap) {
+        let mut world = World::new(400.0, 200.0);
+        let particle = |x: f32, speed: f32, mass: f32| Particle {
+            vel: Vector { x: speed, y: 0.0 },
+            mass,
+            ..testing::particle(x, 50.0, 5.0)
+        };
+        world.add_particle(particle(50.0, 300.0, 1.0));
+        world.add_particle(particle(150.0, 50.0, 4.0));
+        let mut map = HeatMap::default();
+        map.record(&world, 0.0, config);
+        (world, map)
+    }
+
+    #[test]
+    fn hot_cells_cold_cells_and_empty_ones() {
+        let config = SimulationConfig {
+            heat_map: true,
+            heat_map_cell: 100.0,
+            ..testing::config()
+        };
+        let (_, map) = mapped(&config);
+        assert_eq!((map.cols, map.cells.len()), (4, 8));
+        let hot = map.temperature(map.cells[0]).unwrap();
+        let cold = map.temperature(map.cells[1]).unwrap();
+        assert!((hot - 300.0).abs() < 1e-2 && (cold - 100.0).abs() < 1e-2);
+        // nothing in the rest, and nothing drawn for them rather than a NaN
+        assert!(map.cells[2..]
+            .iter()
+            .all(|&cell| map.temperature(cell).is_none()));
+    }
+
+    #[test]
+    fn it_reads_again_only_when_due_and_then_part_way() {
+        let config = SimulationConfig {
+            heat_map: true,
+            heat_map_cell: 100.0,
+            ..testing::config()
+        };
+        let (mut world, mut map) = mapped(&config);
+        let hot = map.temperature(map.cells[0]).unwrap();
+        world.particles[0].vel.x = 100.0;
+        map.record(&world, 0.5 * UPDATE_TIME, &config);
+        assert_eq!(map.temperature(map.cells[0]), Some(hot));
+        map.record(&world, 0.5 * UPDATE_TIME, &config);
+        let cooled = map.temperature(map.cells[0]).unwrap();
+        assert!(cooled < hot && cooled > 100.0, "{}", cooled);
+    }
+
+    #[test]
+    fn a_cell_left_empty_fades_out() {
+        // rather than blanking straight away
+        let config = SimulationConfig {
+            heat_map: true,
+            heat_map_cell: 100.0,
+            ..testing::config()
+        };
+        let (mut world, mut map) = mapped(&config);
+        world.particles[1].pos.x = 350.0;
+        map.record(&world, UPDATE_TIME, &config);
+        assert!(map.cells[1].1 > EMPTY && map.cells[1].1 < 1.0);
+        assert!((map.temperature(map.cells[1]).unwrap() - 100.0).abs() < 1e-2);
+    }
+}
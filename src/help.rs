@@ -0,0 +1,276 @@
+use ggez::event::{Button, KeyCode, KeyMods};
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, Text};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Deserializer};
+
+use crate::actions::KeyMap;
+use crate::Point;
+
+const DIM_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.7);
+const COLUMN_GAP: f32 = 24.0;
+
+// every key the config can name. they go by their KeyCode names, so "Key1", "Space", "F11"
+const NAMED_KEYS: &[KeyCode] = &[
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Escape,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Return,
+    KeyCode::Back,
+    KeyCode::Delete,
+    KeyCode::Insert,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::LBracket,
+    KeyCode::RBracket,
+    KeyCode::Period,
+    KeyCode::Comma,
+    KeyCode::Minus,
+    KeyCode::Equals,
+    KeyCode::Slash,
+    KeyCode::Backslash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::Grave,
+];
+
+// a key plus the modifiers that have to be held with it
+#[derive(Clone, Copy, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub mods: KeyMods,
+}
+
+impl Key {
+    pub const fn plain(code: KeyCode) -> Key {
+        Key {
+            code,
+            mods: KeyMods::NONE,
+        }
+    }
+    pub const fn shift(code: KeyCode) -> Key {
+        Key {
+            code,
+            mods: KeyMods::SHIFT,
+        }
+    }
+    pub const fn ctrl(code: KeyCode) -> Key {
+        Key {
+            code,
+            mods: KeyMods::CTRL,
+        }
+    }
+    pub const fn alt(code: KeyCode) -> Key {
+        Key {
+            code,
+            mods: KeyMods::ALT,
+        }
+    }
+
+    // ctrl, alt and logo have to match exactly so a plain binding doesn't steal ctrl+key.
+    // shift only matters when the binding asks for it
+    pub fn matches(&self, code: KeyCode, mods: KeyMods) -> bool {
+        let strict = KeyMods::CTRL | KeyMods::ALT | KeyMods::LOGO;
+        self.code == code && mods.contains(self.mods) && (mods & strict) == (self.mods & strict)
+    }
+
+    pub fn name(&self) -> String {
+        let code = match self.code {
+            KeyCode::Return => String::from("Enter"),
+            KeyCode::Escape => String::from("Esc"),
+            code => format!("{:?}", code),
+        };
+        let mut name = String::new();
+        for (mods, label) in [
+            (KeyMods::CTRL, "Ctrl+"),
+            (KeyMods::ALT, "Alt+"),
+            (KeyMods::SHIFT, "Shift+"),
+        ]
+        .iter()
+        {
+            if self.mods.contains(*mods) {
+                name.push_str(label);
+            }
+        }
+        name + &code
+    }
+}
+
+// "Ctrl+Shift+R" and the like. modifiers and names don't care about case, and a few keys
+// can go by what's printed on them: "Enter", "Esc", "Backspace", "1", "[" and so on
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Key, String> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let name = parts.pop().unwrap_or_default();
+
+        let mut mods = KeyMods::NONE;
+        for part in parts {
+            mods |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyMods::CTRL,
+                "shift" => KeyMods::SHIFT,
+                "alt" => KeyMods::ALT,
+                "logo" | "super" | "cmd" => KeyMods::LOGO,
+                _ => return Err(format!("unknown modifier `{}` in `{}`", part, text)),
+            };
+        }
+
+        let lower = name.to_lowercase();
+        let alias = match lower.as_str() {
+            "enter" => Some(KeyCode::Return),
+            "esc" => Some(KeyCode::Escape),
+            "backspace" => Some(KeyCode::Back),
+            "." => Some(KeyCode::Period),
+            "," => Some(KeyCode::Comma),
+            "[" => Some(KeyCode::LBracket),
+            "]" => Some(KeyCode::RBracket),
+            _ => None,
+        };
+        let digit = format!("key{}", lower);
+        let code = alias.or_else(|| {
+            NAMED_KEYS.iter().copied().find(|code| {
+                let debug = format!("{:?}", code).to_lowercase();
+                debug == lower || debug == digit
+            })
+        });
+        match code {
+            Some(code) => Ok(Key { code, mods }),
+            None => Err(format!("unknown key `{}`", text)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Key, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// one entry in the gamepad table. the input handling and the help overlay both read the
+// same table, so the overlay can't list a button that doesn't do what it says. `release` is for buttons that do something when they're let
+// go as well, like charging up a launch
+pub struct PadBinding<T: 'static> {
+    pub buttons: &'static [Button],
+    pub description: &'static str,
+    pub press: fn(&mut T, &mut Context),
+    pub release: Option<fn(&mut T, &mut Context)>,
+}
+
+impl<T> PadBinding<T> {
+    pub fn find(
+        bindings: &'static [PadBinding<T>],
+        button: Button,
+    ) -> Option<&'static PadBinding<T>> {
+        bindings
+            .iter()
+            .find(|binding| binding.buttons.contains(&button))
+    }
+
+    fn buttons_label(&self) -> String {
+        let names: Vec<_> = self
+            .buttons
+            .iter()
+            .map(|button| format!("Pad {:?}", button))
+            .collect();
+        names.join(" / ")
+    }
+}
+
+// dims the scene and lists every binding in two columns, centred on the screen,
+// the gamepad's after the keys
+pub fn draw<T>(
+    ctx: &mut Context,
+    keys: &KeyMap,
+    pad_bindings: &[PadBinding<T>],
+    screen_width: f32,
+    screen_height: f32,
+) -> GameResult {
+    let screen = graphics::screen_coordinates(ctx);
+    let dim = Mesh::new_rectangle(ctx, DrawMode::fill(), screen, DIM_COLOR)?;
+    graphics::draw(ctx, &dim, DrawParam::default())?;
+
+    let rows: Vec<(String, &str)> = keys
+        .rows()
+        .into_iter()
+        .chain(
+            pad_bindings
+                .iter()
+                .map(|b| (b.buttons_label(), b.description)),
+        )
+        .collect();
+    let keys: Vec<_> = rows.iter().map(|(keys, _)| keys.as_str()).collect();
+    let descriptions: Vec<_> = rows.iter().map(|(_, description)| *description).collect();
+    let keys = Text::new(keys.join("\n"));
+    let descriptions = Text::new(descriptions.join("\n"));
+
+    let (keys_w, keys_h) = keys.dimensions(ctx);
+    let (descriptions_w, _) = descriptions.dimensions(ctx);
+    let total = keys_w as f32 + COLUMN_GAP + descriptions_w as f32;
+    let x = ((screen_width - total) / 2.0).max(0.0);
+    let y = ((screen_height - keys_h as f32) / 2.0).max(0.0);
+
+    graphics::draw(ctx, &keys, DrawParam::default().dest(Point { x, y }))?;
+    graphics::draw(
+        ctx,
+        &descriptions,
+        DrawParam::default().dest(Point {
+            x: x + keys_w as f32 + COLUMN_GAP,
+            y,
+        }),
+    )
+}
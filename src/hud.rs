@@ -0,0 +1,221 @@
+use std::time::{Duration, Instant};
+
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect, Text};
+use ggez::{Context, GameResult};
+
+use crate::{Point, Vector};
+
+const MARGIN: f32 = 10.0;
+const PADDING: f32 = 4.0;
+const BACKING_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+
+// how long a notice stays up, the last part of it fading out
+const NOTICE_TIME: Duration = Duration::from_millis(1500);
+const NOTICE_FADE: f32 = 0.3;
+
+// a short message in the top middle of the screen, e.g. after a setting changes
+#[derive(Default)]
+pub struct Notice {
+    shown: Option<(String, Instant)>,
+}
+
+impl Notice {
+    pub fn show(&mut self, text: String) {
+        self.shown = Some((text, Instant::now()));
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, screen_width: f32) -> GameResult {
+        let (text, since) = match &self.shown {
+            Some((text, since)) => (text, since.elapsed()),
+            None => return Ok(()),
+        };
+        if since >= NOTICE_TIME {
+            self.shown = None;
+            return Ok(());
+        }
+
+        let left = 1.0 - since.as_secs_f32() / NOTICE_TIME.as_secs_f32();
+        let alpha = (left / NOTICE_FADE).min(1.0);
+
+        let text = Text::new(text.as_str());
+        let (w, h) = text.dimensions(ctx);
+        let (w, h) = (w as f32, h as f32);
+        let dest = Point {
+            x: (screen_width - w) / 2.0,
+            y: MARGIN,
+        };
+        let backing = Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(
+                dest.x - PADDING,
+                dest.y - PADDING,
+                w + 2.0 * PADDING,
+                h + 2.0 * PADDING,
+            ),
+            Color {
+                a: BACKING_COLOR.a * alpha,
+                ..BACKING_COLOR
+            },
+        )?;
+
+        graphics::draw(ctx, &backing, DrawParam::default())?;
+        graphics::draw(
+            ctx,
+            &text,
+            DrawParam::default()
+                .dest(dest)
+                .color(Color::new(1.0, 1.0, 1.0, alpha)),
+        )
+    }
+}
+
+// the gravity dial's circle, and the gravity at its edge
+const GRAVITY_DIAL_RADIUS: f32 = 40.0;
+const GRAVITY_DIAL_MAX: f32 = 100.0;
+// two presses in the dial this close together zero gravity
+const DOUBLE_CLICK: Duration = Duration::from_millis(350);
+const DIAL_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.3);
+const ARROW_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.8);
+
+// which way gravity points, in a circle in the bottom left, and a handle for setting it.
+// dragging in it points gravity from the center to the cursor, as strong as it is far out.
+// it keeps no gravity of its own, whatever it sets goes straight into the config
+#[derive(Default)]
+pub struct GravityDial {
+    dragging: bool,
+    last_press: Option<Instant>,
+}
+
+impl GravityDial {
+    fn center(screen_height: f32) -> Point {
+        Point {
+            x: MARGIN + GRAVITY_DIAL_RADIUS,
+            // clear of the comparison labels along the bottom edge
+            y: screen_height - 4.0 * MARGIN - GRAVITY_DIAL_RADIUS,
+        }
+    }
+
+    pub fn contains(&self, pos: Point, screen_height: f32) -> bool {
+        let center = Self::center(screen_height);
+        let (dx, dy) = (pos.x - center.x, pos.y - center.y);
+        dx * dx + dy * dy <= GRAVITY_DIAL_RADIUS * GRAVITY_DIAL_RADIUS
+    }
+
+    // the gravity for the cursor at `pos`, clamped at the edge of the dial
+    fn gravity_at(pos: Point, screen_height: f32) -> Vector {
+        let center = Self::center(screen_height);
+        let (dx, dy) = (pos.x - center.x, pos.y - center.y);
+        let distance = (dx * dx + dy * dy).sqrt();
+        let scale = GRAVITY_DIAL_MAX / GRAVITY_DIAL_RADIUS;
+        let clamp = if distance > GRAVITY_DIAL_RADIUS {
+            GRAVITY_DIAL_RADIUS / distance
+        } else {
+            1.0
+        };
+        Vector {
+            x: dx * clamp * scale,
+            y: dy * clamp * scale,
+        }
+    }
+
+    // a press inside the dial, the gravity it sets. a double click zeroes it
+    pub fn mouse_down(&mut self, pos: Point, screen_height: f32) -> Vector {
+        let now = Instant::now();
+        let double = self
+            .last_press
+            .is_some_and(|last| now.duration_since(last) < DOUBLE_CLICK);
+        if double {
+            self.last_press = None;
+            self.dragging = false;
+            return Vector { x: 0.0, y: 0.0 };
+        }
+        self.last_press = Some(now);
+        self.dragging = true;
+        Self::gravity_at(pos, screen_height)
+    }
+
+    // the new gravity while a drag is going on
+    pub fn mouse_motion(&self, pos: Point, screen_height: f32) -> Option<Vector> {
+        if self.dragging {
+            Some(Self::gravity_at(pos, screen_height))
+        } else {
+            None
+        }
+    }
+
+    pub fn mouse_up(&mut self) {
+        self.dragging = false;
+    }
+}
+
+// the arrow is as long as the drag that would set it, stopping at the edge
+pub fn draw_gravity(ctx: &mut Context, gravity: Vector, screen_height: f32) -> GameResult {
+    let center = GravityDial::center(screen_height);
+
+    let mut builder = MeshBuilder::new();
+    builder.circle(
+        DrawMode::fill(),
+        center,
+        GRAVITY_DIAL_RADIUS,
+        0.5,
+        BACKING_COLOR,
+    );
+    builder.circle(
+        DrawMode::stroke(1.0),
+        center,
+        GRAVITY_DIAL_RADIUS,
+        0.5,
+        DIAL_COLOR,
+    );
+
+    let magnitude = (gravity.x * gravity.x + gravity.y * gravity.y).sqrt();
+    if magnitude > f32::EPSILON {
+        let length = GRAVITY_DIAL_RADIUS * (magnitude / GRAVITY_DIAL_MAX).min(1.0);
+        let dir = Vector {
+            x: gravity.x / magnitude,
+            y: gravity.y / magnitude,
+        };
+        let tip = Point {
+            x: center.x + dir.x * length,
+            y: center.y + dir.y * length,
+        };
+        builder.line(&[center, tip], 2.0, ARROW_COLOR)?;
+        builder.circle(DrawMode::fill(), tip, 3.0, 0.5, ARROW_COLOR);
+    } else {
+        builder.circle(DrawMode::fill(), center, 2.0, 0.5, ARROW_COLOR);
+    }
+
+    let mesh = builder.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())
+}
+
+// status lines drawn in the top right corner, drawn after everything else so they stay put
+pub fn draw(ctx: &mut Context, lines: &[String], screen_width: f32) -> GameResult {
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let text = Text::new(lines.join("\n"));
+    let (w, h) = text.dimensions(ctx);
+    let (w, h) = (w as f32, h as f32);
+
+    let dest = Point {
+        x: screen_width - MARGIN - w,
+        y: MARGIN,
+    };
+    let backing = Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        Rect::new(
+            dest.x - PADDING,
+            dest.y - PADDING,
+            w + 2.0 * PADDING,
+            h + 2.0 * PADDING,
+        ),
+        BACKING_COLOR,
+    )?;
+
+    graphics::draw(ctx, &backing, DrawParam::default())?;
+    graphics::draw(ctx, &text, DrawParam::default().dest(dest))
+}
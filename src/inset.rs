@@ -0,0 +1,224 @@
+use ggez::graphics::{
+    self, BlendMode, Canvas, Color, DrawMode, DrawParam, Drawable, Image, MeshBuilder, Rect,
+};
+use ggez::{conf::NumSamples, Context, GameResult};
+use serde::Deserialize;
+
+use crate::render::{self, DrawOrder};
+use crate::{Particle, Point, SimulationConfig};
+
+// size on screen in logical pixels, and the gap to the edge of the pane
+const INSET_SIZE: f32 = 220.0;
+const MARGIN: f32 = 10.0;
+
+const BORDER_WIDTH: f32 = 2.0;
+const BORDER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.8);
+const CROSSHAIR_SIZE: f32 = 8.0;
+const CROSSHAIR_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.5);
+
+// which particle the inset is centred on
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InsetTarget {
+    // whichever is fastest right now, jumping between particles as that changes
+    Fastest,
+    // stays on the particle it was following when this was picked,
+    // or the first selected particle if there is one
+    Locked,
+}
+
+impl InsetTarget {
+    pub fn next(self) -> InsetTarget {
+        match self {
+            InsetTarget::Fastest => InsetTarget::Locked,
+            InsetTarget::Locked => InsetTarget::Fastest,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InsetTarget::Fastest => "fastest",
+            InsetTarget::Locked => "locked",
+        }
+    }
+}
+
+// a magnified corner view around one particle. only the particles that can be seen in it
+// are drawn a second time, and into a canvas so nothing spills outside the border
+#[derive(Default)]
+pub struct Inset {
+    // id of the particle being followed
+    followed: Option<u64>,
+    nearby: Vec<Particle>,
+    draw_order: DrawOrder,
+    canvas: Option<Canvas>,
+    built_for: u16,
+}
+
+impl Inset {
+    // ids start again after a reset, so a locked id would land on some other particle
+    pub fn clear(&mut self) {
+        self.followed = None;
+    }
+
+    // pick the particle to follow for this frame
+    pub fn update(&mut self, particles: &[Particle], target: InsetTarget, selected: Option<u64>) {
+        if target == InsetTarget::Locked && selected.is_some() {
+            self.followed = selected;
+        }
+        let still_there = self
+            .followed
+            .is_some_and(|id| particles.iter().any(|p| p.id == id));
+        if target == InsetTarget::Fastest || !still_there {
+            self.followed = particles
+                .iter()
+                .max_by(|a, b| a.vel_magnitude().total_cmp(&b.vel_magnitude()))
+                .map(|p| p.id);
+        }
+    }
+
+    // `particles` in pane coordinates, `pane` is the pane's rect on screen
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        particles: &[Particle],
+        pane: Rect,
+        config: &SimulationConfig,
+        particle_image: Option<&Image>,
+        background: Color,
+    ) -> GameResult {
+        let center = match self
+            .followed
+            .and_then(|id| particles.iter().find(|p| p.id == id))
+        {
+            Some(particle) => particle.pos,
+            None => return Ok(()),
+        };
+
+        let size = INSET_SIZE
+            .min(pane.w - 2.0 * MARGIN)
+            .min(pane.h - 2.0 * MARGIN);
+        if size <= 0.0 {
+            return Ok(());
+        }
+
+        // the bit of the pane the inset shows
+        let zoom = config.inset_zoom.max(1.0);
+        let half = 0.5 * size / zoom;
+        let view = Rect::new(center.x - half, center.y - half, 2.0 * half, 2.0 * half);
+
+        self.nearby.clear();
+        self.nearby.extend(
+            particles
+                .iter()
+                .filter(|p| {
+                    p.pos.x + p.rad >= view.x
+                        && p.pos.x - p.rad <= view.right()
+                        && p.pos.y + p.rad >= view.y
+                        && p.pos.y - p.rad <= view.bottom()
+                })
+                .cloned(),
+        );
+
+        // physical pixels so the border stays sharp on hidpi displays
+        let factor = graphics::window(ctx).get_hidpi_factor() as f32;
+        let pixels = ((size * factor) as u16).max(1);
+        if self.canvas.is_none() || self.built_for != pixels {
+            let mut canvas = Canvas::new(ctx, pixels, pixels, NumSamples::One)?;
+            canvas.set_blend_mode(Some(BlendMode::Replace));
+            self.canvas = Some(canvas);
+            self.built_for = pixels;
+        }
+
+        // pointing the screen coordinates at the view does the zooming
+        let screen = graphics::screen_coordinates(ctx);
+        graphics::set_canvas(ctx, self.canvas.as_ref());
+        graphics::set_screen_coordinates(ctx, view)?;
+        let result = self.draw_view(ctx, config, particle_image, background, zoom);
+        graphics::set_canvas(ctx, None);
+        graphics::set_screen_coordinates(ctx, screen)?;
+        result?;
+
+        let dest = Point {
+            x: pane.right() - MARGIN - size,
+            y: pane.bottom() - MARGIN - size,
+        };
+        if let Some(canvas) = &self.canvas {
+            let scale = size / f32::from(canvas.image().width());
+            graphics::draw(
+                ctx,
+                canvas,
+                DrawParam::default().dest(dest).scale([scale, scale]),
+            )?;
+        }
+
+        let mid = Point {
+            x: dest.x + 0.5 * size,
+            y: dest.y + 0.5 * size,
+        };
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(
+            DrawMode::stroke(BORDER_WIDTH),
+            Rect::new(dest.x, dest.y, size, size),
+            BORDER_COLOR,
+        );
+        builder.line(
+            &[
+                Point {
+                    x: mid.x - CROSSHAIR_SIZE,
+                    y: mid.y,
+                },
+                Point {
+                    x: mid.x + CROSSHAIR_SIZE,
+                    y: mid.y,
+                },
+            ],
+            1.0,
+            CROSSHAIR_COLOR,
+        )?;
+        builder.line(
+            &[
+                Point {
+                    x: mid.x,
+                    y: mid.y - CROSSHAIR_SIZE,
+                },
+                Point {
+                    x: mid.x,
+                    y: mid.y + CROSSHAIR_SIZE,
+                },
+            ],
+            1.0,
+            CROSSHAIR_COLOR,
+        )?;
+        let overlay = builder.build(ctx)?;
+        graphics::draw(ctx, &overlay, DrawParam::default())
+    }
+
+    fn draw_view(
+        &mut self,
+        ctx: &mut Context,
+        config: &SimulationConfig,
+        particle_image: Option<&Image>,
+        background: Color,
+        zoom: f32,
+    ) -> GameResult {
+        graphics::clear(ctx, background);
+
+        let order = self.draw_order.update(&self.nearby);
+        match particle_image {
+            Some(image) => {
+                let batch = render::image_batch(image, &self.nearby, order);
+                graphics::draw(ctx, &batch, DrawParam::default())
+            }
+            None => {
+                // magnified circles need more vertices to stay round
+                let mut config = config.clone();
+                config.circle_quality *= zoom;
+                match render::particle_mesh(ctx, &self.nearby, order, &config)? {
+                    Some(mesh) => graphics::draw(ctx, &mesh, DrawParam::default()),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
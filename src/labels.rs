@@ -0,0 +1,92 @@
+use ggez::graphics::{
+    self, Color, DrawMode, DrawParam, FilterMode, Font, MeshBuilder, Rect, Scale, Text,
+};
+use ggez::{Context, GameResult};
+use serde::Deserialize;
+
+use crate::{Particle, Point};
+
+// above this many particles the labels are skipped entirely, text is too slow to lay out
+pub const LABEL_LIMIT: usize = 200;
+
+const FONT_SCALE: f32 = 12.0;
+const PADDING: f32 = 2.0;
+const BACKING_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.65);
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelField {
+    Off,
+    Id,
+    Mass,
+    Speed,
+    Position,
+}
+
+impl LabelField {
+    pub fn next(self) -> LabelField {
+        match self {
+            LabelField::Off => LabelField::Id,
+            LabelField::Id => LabelField::Mass,
+            LabelField::Mass => LabelField::Speed,
+            LabelField::Speed => LabelField::Position,
+            LabelField::Position => LabelField::Off,
+        }
+    }
+
+    fn format(self, particle: &Particle) -> String {
+        match self {
+            LabelField::Off => String::new(),
+            LabelField::Id => format!("#{}", particle.id),
+            LabelField::Mass => format!("{:.2}", particle.mass),
+            LabelField::Speed => format!("{:.1}", particle.vel_magnitude()),
+            LabelField::Position => format!("{:.0}, {:.0}", particle.pos.x, particle.pos.y),
+        }
+    }
+}
+
+// a small label up and to the right of each particle, on a backing box so it reads on any theme
+pub fn draw(
+    ctx: &mut Context,
+    particles: &[Particle],
+    field: LabelField,
+    font: Font,
+) -> GameResult {
+    if field == LabelField::Off || particles.is_empty() || particles.len() > LABEL_LIMIT {
+        return Ok(());
+    }
+
+    let mut backing = MeshBuilder::new();
+    let mut texts = Vec::with_capacity(particles.len());
+
+    for particle in particles {
+        let mut text = Text::new(field.format(particle));
+        text.set_font(font, Scale::uniform(FONT_SCALE));
+        let (w, h) = text.dimensions(ctx);
+        let (w, h) = (w as f32, h as f32);
+
+        let dest = Point {
+            x: particle.pos.x + particle.rad + PADDING * 2.0,
+            y: particle.pos.y - particle.rad - h * 0.5,
+        };
+        backing.rectangle(
+            DrawMode::fill(),
+            Rect::new(
+                dest.x - PADDING,
+                dest.y - PADDING,
+                w + PADDING * 2.0,
+                h + PADDING * 2.0,
+            ),
+            BACKING_COLOR,
+        );
+        texts.push((text, dest));
+    }
+
+    let mesh = backing.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+    for (text, dest) in &texts {
+        graphics::queue_text(ctx, text, *dest, None);
+    }
+    graphics::draw_queued_text(ctx, DrawParam::default(), None, FilterMode::Linear)
+}
@@ -1,253 +1,3186 @@
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use ggez::event::{self, Axis, Button, EventHandler, GamepadId, KeyCode, KeyMods, MouseButton};
 use ggez::{
-    conf::{WindowMode, WindowSetup},
-    graphics::{self, Color},
-};
-use ggez::{
-    event::{self, EventHandler},
-    graphics::Mesh,
+    conf::{FullscreenType, WindowMode, WindowSetup},
+    graphics::{self, Color, Font, Image, Rect},
 };
-use ggez::{Context, ContextBuilder, GameResult};
-use rand::{prelude::SliceRandom, Rng};
+use ggez::{Context, ContextBuilder, GameError, GameResult};
+use rand::{prelude::SliceRandom, rngs::StdRng, SeedableRng};
+
+mod actions;
+mod belt;
+mod black_holes;
+mod boids;
+mod broadphase;
+mod center_of_mass;
+mod chaos;
+mod chart;
+mod clipboard;
+mod collision_math;
+mod color;
+mod config;
+mod container;
+mod crystal;
+mod demo;
+mod demon;
+mod determinism;
+mod effects;
+mod emitters;
+mod epidemic;
+mod events;
+mod fission;
+mod frame_limiter;
+mod gamepad;
+mod gas;
+mod grid;
+mod heat_map;
+mod help;
+mod hud;
+mod inset;
+mod labels;
+mod maxwell;
+mod menu;
+mod mixing;
+mod motion_blur;
+mod pane;
+mod paths;
+mod piston;
+mod portals;
+mod predators;
+mod presets;
+mod reactions;
+mod render;
+mod scene_file;
+mod shake;
+mod slingshot;
+mod sound;
+mod spawn;
+mod stress;
+mod targets;
+#[cfg(test)]
+mod testing;
+mod theme;
+mod thermostat;
+mod tracer;
+mod ui;
+mod voids;
+mod walls;
+mod weather;
+mod wells;
+mod world;
+mod zones;
+
+use actions::{Action, KeyMap};
+use black_holes::BlackHole;
+use clipboard::Clipboard;
+use color::ColorPolicy;
+use config::{CompareOverrides, SimulationConfig};
+use demo::{Demo, Reel};
+use emitters::Emitter;
+use frame_limiter::FrameLimiter;
+use gamepad::Reticle;
+use help::PadBinding;
+use hud::{GravityDial, Notice};
+use inset::Inset;
+use menu::{MenuEntry, PauseMenu};
+use motion_blur::{ClearMode, SceneCanvas};
+use pane::Pane;
+use presets::{Layout, PRESETS};
+use scene_file::{CopiedParticle, SceneFile};
+use shake::ScreenShake;
+use slingshot::Aim;
+use sound::Sounds;
+use spawn::{SpawnSettings, Tool, Variant};
+use stress::{Stress, Timing, Verdict};
+use theme::{Theme, THEMES};
+use ui::{ControlPanel, Widget};
+use walls::{Block, Pillar, Segment};
+use weather::Weather;
+use wells::Well;
+use world::{Boundary, Hold, Particle, WallStyle};
 
 type Vector = ggez::mint::Vector2<f32>;
 type Point = ggez::mint::Point2<f32>;
 
-// dimensions
-const SCREEN_WIDTH: f32 = 1280.0;
-const SCREEN_HEIGHT: f32 = 720.0;
+// optional overrides for SimulationConfig
+const CONFIG_PATH: &str = "collisions.toml";
+
+// how many particles the spawn button adds
+const SPAWN_BATCH: usize = 50;
+
+// the physics step, in simulated seconds
+const FIXED_STEP: f32 = 1.0 / 120.0;
+// beyond this many steps in one frame the simulation just runs slow instead
+const MAX_STEPS_PER_FRAME: usize = 64;
+// what [ and ] step through
+const TIME_SCALES: &[f32] = &[0.1, 0.25, 0.5, 1.0, 2.0, 4.0];
+
+// how far one press of an arrow key moves gravity, and with shift held
+const GRAVITY_STEP: f32 = 1.0;
+const GRAVITY_COARSE_STEP: f32 = 25.0;
 
-// restitution coefficient
-const RESTITUTION: f32 = 1.0;
+const REPULSION_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.15);
+const ATTRACTION_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.12);
+const SELECTION_FILL: Color = Color::new(1.0, 0.85, 0.3, 0.1);
+const SELECTION_EDGE: Color = Color::new(1.0, 0.85, 0.3, 0.8);
 
-// acceleration
-const ACCELERATION: Vector = Vector { x: -1.0, y: 2.0 };
-const RESISTANCE: Vector = Vector { x: 0.0, y: 0.0 };
+// drags shorter than this don't make a wall, so a stray click leaves nothing behind
+const MIN_WALL_LENGTH: f32 = 4.0;
+const WALL_PREVIEW_COLOR: Color = Color::new(0.8, 0.8, 0.8, 0.5);
 
-// how many particles?
-const NUM_PARTICLES: usize = 40;
+// the line between the two sides of the comparison mode
+const DIVIDER_WIDTH: f32 = 2.0;
+const DIVIDER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.5);
+
+static CONTROLS: &[Widget<GameState>] = &[
+    Widget::Slider {
+        label: "gravity x",
+        min: -20.0,
+        max: 20.0,
+        get: |s| s.config.gravity.x,
+        set: |s, v| s.config.gravity.x = v,
+    },
+    Widget::Slider {
+        label: "gravity y",
+        min: -20.0,
+        max: 20.0,
+        get: |s| s.config.gravity.y,
+        set: |s, v| s.config.gravity.y = v,
+    },
+    Widget::Slider {
+        label: "restitution",
+        min: 0.0,
+        max: 1.0,
+        get: |s| s.config.restitution,
+        set: |s, v| s.config.restitution = v,
+    },
+    Widget::Slider {
+        label: "wall friction",
+        min: 0.0,
+        max: 1.0,
+        get: |s| s.config.wall_friction,
+        set: |s, v| s.config.wall_friction = v,
+    },
+    Widget::Slider {
+        label: "particle friction",
+        min: 0.0,
+        max: 1.0,
+        get: |s| s.config.particle_friction,
+        set: |s, v| s.config.particle_friction = v,
+    },
+    Widget::Checkbox {
+        label: "sleeping",
+        get: |s| s.config.sleeping,
+        set: |s, v| s.config.sleeping = v,
+    },
+    Widget::Slider {
+        label: "drag",
+        min: 0.0,
+        max: 0.01,
+        get: |s| s.config.drag,
+        set: |s, v| s.config.drag = v,
+    },
+    Widget::Slider {
+        label: "wind",
+        min: -200.0,
+        max: 200.0,
+        get: |s| s.config.wind,
+        set: |s, v| s.config.wind = v,
+    },
+    Widget::Slider {
+        label: "cooling rate",
+        min: 0.0,
+        max: 0.1,
+        get: |s| s.config.cooling_rate,
+        set: |s, v| s.config.cooling_rate = v,
+    },
+    Widget::Checkbox {
+        label: "gravity on",
+        get: |s| s.config.gravity_enabled,
+        set: |s, v| s.config.gravity_enabled = v,
+    },
+    Widget::Checkbox {
+        label: "drag on",
+        get: |s| s.config.drag_enabled,
+        set: |s, v| s.config.drag_enabled = v,
+    },
+    Widget::Checkbox {
+        label: "wells on",
+        get: |s| s.config.wells_enabled,
+        set: |s, v| s.config.wells_enabled = v,
+    },
+    Widget::Checkbox {
+        label: "soft walls",
+        get: |s| s.config.wall_style == WallStyle::Soft,
+        set: |s, v| {
+            s.config.wall_style = if v { WallStyle::Soft } else { WallStyle::Hard };
+        },
+    },
+    Widget::Checkbox {
+        label: "conveyor belt",
+        get: |s| s.config.belt,
+        set: |s, v| s.config.belt = v,
+    },
+    Widget::Slider {
+        label: "belt speed",
+        min: -400.0,
+        max: 400.0,
+        get: |s| s.config.belt_speed,
+        set: |s, v| s.config.belt_speed = v,
+    },
+    // these change the emitters already up as well as the next ones placed
+    Widget::Slider {
+        label: "emitter rate",
+        min: 0.0,
+        max: 200.0,
+        get: |s| s.config.emitter_rate,
+        set: |s, v| {
+            s.config.emitter_rate = v;
+            s.tune_emitters(|emitter| emitter.rate = v);
+        },
+    },
+    Widget::Slider {
+        label: "emitter spread",
+        min: 0.0,
+        max: 180.0,
+        get: |s| s.config.emitter_spread,
+        set: |s, v| {
+            s.config.emitter_spread = v;
+            s.tune_emitters(|emitter| emitter.spread = v);
+        },
+    },
+    // the middle of the speeds, keeping how far apart they are
+    Widget::Slider {
+        label: "emitter speed",
+        min: 0.0,
+        max: 1500.0,
+        get: |s| 0.5 * (s.config.emitter_speed_min + s.config.emitter_speed_max),
+        set: |s, v| {
+            let half = 0.5 * (s.config.emitter_speed_max - s.config.emitter_speed_min);
+            s.config.emitter_speed_min = (v - half).max(0.0);
+            s.config.emitter_speed_max = v + half;
+            s.tune_emitters(|emitter| {
+                let half = 0.5 * (emitter.speed_max - emitter.speed_min);
+                emitter.speed_min = (v - half).max(0.0);
+                emitter.speed_max = v + half;
+            });
+        },
+    },
+    Widget::Slider {
+        label: "time scale",
+        min: 0.1,
+        max: 4.0,
+        get: |s| s.config.time_scale,
+        set: |s, v| s.config.time_scale = v,
+    },
+    Widget::Slider {
+        label: "radius min",
+        min: 1.0,
+        max: 50.0,
+        get: |s| s.config.spawn_radius_min,
+        set: |s, v| s.config.spawn_radius_min = v,
+    },
+    Widget::Slider {
+        label: "radius max",
+        min: 1.0,
+        max: 50.0,
+        get: |s| s.config.spawn_radius_max,
+        set: |s, v| s.config.spawn_radius_max = v,
+    },
+    Widget::Slider {
+        label: "mass min",
+        min: 0.1,
+        max: 10.0,
+        get: |s| s.config.spawn_mass_min,
+        set: |s, v| s.config.spawn_mass_min = v,
+    },
+    Widget::Slider {
+        label: "mass max",
+        min: 0.1,
+        max: 10.0,
+        get: |s| s.config.spawn_mass_max,
+        set: |s, v| s.config.spawn_mass_max = v,
+    },
+    Widget::Checkbox {
+        label: "velocity lines",
+        get: |s| s.config.show_velocity_lines,
+        set: |s, v| s.config.show_velocity_lines = v,
+    },
+    Widget::Checkbox {
+        label: "squash and stretch",
+        get: |s| s.config.squash_stretch,
+        set: |s, v| s.config.squash_stretch = v,
+    },
+    Widget::Slider {
+        label: "circle quality",
+        min: 0.25,
+        max: 4.0,
+        get: |s| s.config.circle_quality,
+        set: |s, v| s.config.circle_quality = v,
+    },
+    Widget::Checkbox {
+        label: "glow",
+        get: |s| s.config.glow,
+        set: |s, v| s.config.glow = v,
+    },
+    Widget::Slider {
+        label: "stroke width",
+        min: 0.5,
+        max: 8.0,
+        get: |s| s.config.stroke_width,
+        set: |s, v| s.config.stroke_width = v,
+    },
+    Widget::Slider {
+        label: "fade alpha",
+        min: 0.01,
+        max: 1.0,
+        get: |s| s.config.fade_alpha,
+        set: |s, v| s.config.fade_alpha = v,
+    },
+    Widget::Checkbox {
+        label: "sparks",
+        get: |s| s.config.sparks,
+        set: |s, v| s.config.sparks = v,
+    },
+    Widget::Checkbox {
+        label: "wall flashes",
+        get: |s| s.config.wall_flashes,
+        set: |s, v| s.config.wall_flashes = v,
+    },
+    Widget::Slider {
+        label: "spark threshold",
+        min: 0.0,
+        max: 200.0,
+        get: |s| s.config.spark_threshold,
+        set: |s, v| s.config.spark_threshold = v,
+    },
+    Widget::Checkbox {
+        label: "screen shake",
+        get: |s| s.config.screen_shake,
+        set: |s, v| s.config.screen_shake = v,
+    },
+    Widget::Slider {
+        label: "shake intensity",
+        min: 0.0,
+        max: 3.0,
+        get: |s| s.config.shake_intensity,
+        set: |s, v| s.config.shake_intensity = v,
+    },
+    Widget::Checkbox {
+        label: "sound",
+        get: |s| s.config.sound,
+        set: |s, v| s.config.sound = v,
+    },
+    Widget::Slider {
+        label: "volume",
+        min: 0.0,
+        max: 1.0,
+        get: |s| s.config.sound_volume,
+        set: |s, v| s.config.sound_volume = v,
+    },
+    Widget::Checkbox {
+        label: "plexus",
+        get: |s| s.config.plexus,
+        set: |s, v| s.config.plexus = v,
+    },
+    Widget::Slider {
+        label: "plexus distance",
+        min: 10.0,
+        max: 300.0,
+        get: |s| s.config.plexus_distance,
+        set: |s, v| s.config.plexus_distance = v,
+    },
+    Widget::Checkbox {
+        label: "grid",
+        get: |s| s.config.show_grid,
+        set: |s, v| s.config.show_grid = v,
+    },
+    Widget::Slider {
+        label: "grid spacing",
+        min: 10.0,
+        max: 250.0,
+        get: |s| s.config.grid_spacing,
+        set: |s, v| s.config.grid_spacing = v,
+    },
+    Widget::Checkbox {
+        label: "heat map",
+        get: |s| s.config.heat_map,
+        set: |s, v| s.config.heat_map = v,
+    },
+    Widget::Checkbox {
+        label: "collision math",
+        get: |s| s.config.collision_math,
+        set: |s, v| s.config.collision_math = v,
+    },
+    Widget::Slider {
+        label: "heat map cell",
+        min: 20.0,
+        max: 200.0,
+        get: |s| s.config.heat_map_cell,
+        set: |s, v| s.config.heat_map_cell = v,
+    },
+    Widget::Checkbox {
+        label: "center of mass",
+        get: |s| s.config.show_center_of_mass,
+        set: |s, v| s.config.show_center_of_mass = v,
+    },
+    Widget::Checkbox {
+        label: "center of mass trail",
+        get: |s| s.config.center_of_mass_trail,
+        set: |s, v| s.config.center_of_mass_trail = v,
+    },
+    Widget::Checkbox {
+        label: "gas readouts",
+        get: |s| s.config.show_gas,
+        set: |s, v| s.config.show_gas = v,
+    },
+    Widget::Slider {
+        label: "gas window",
+        min: 0.5,
+        max: 10.0,
+        get: |s| s.config.gas_window,
+        set: |s, v| s.config.gas_window = v,
+    },
+    Widget::Checkbox {
+        label: "maxwell-boltzmann fit",
+        get: |s| s.config.maxwell,
+        set: |s, v| s.config.maxwell = v,
+    },
+    Widget::Checkbox {
+        label: "flocking",
+        get: |s| s.config.boids,
+        set: |s, v| s.set_boids(v),
+    },
+    Widget::Slider {
+        label: "flock radius",
+        min: 10.0,
+        max: 200.0,
+        get: |s| s.config.boid_radius,
+        set: |s, v| s.config.boid_radius = v,
+    },
+    Widget::Slider {
+        label: "flock separation",
+        min: 0.0,
+        max: 2000.0,
+        get: |s| s.config.boid_separation,
+        set: |s, v| s.config.boid_separation = v,
+    },
+    Widget::Slider {
+        label: "flock alignment",
+        min: 0.0,
+        max: 5.0,
+        get: |s| s.config.boid_alignment,
+        set: |s, v| s.config.boid_alignment = v,
+    },
+    Widget::Slider {
+        label: "flock cohesion",
+        min: 0.0,
+        max: 5.0,
+        get: |s| s.config.boid_cohesion,
+        set: |s, v| s.config.boid_cohesion = v,
+    },
+    Widget::Slider {
+        label: "flock speed",
+        min: 0.0,
+        max: 400.0,
+        get: |s| s.config.boid_speed,
+        set: |s, v| s.config.boid_speed = v,
+    },
+    Widget::Checkbox {
+        label: "cursor repulsion",
+        get: |s| s.config.repulsion,
+        set: |s, v| s.config.repulsion = v,
+    },
+    Widget::Slider {
+        label: "repulsion radius",
+        min: 20.0,
+        max: 500.0,
+        get: |s| s.config.repulsion_radius,
+        set: |s, v| s.config.repulsion_radius = v,
+    },
+    Widget::Slider {
+        label: "repulsion strength",
+        min: 100.0,
+        max: 20000.0,
+        get: |s| s.config.repulsion_strength,
+        set: |s, v| s.config.repulsion_strength = v,
+    },
+    Widget::Checkbox {
+        label: "zoomed inset",
+        get: |s| s.config.inset,
+        set: |s, v| s.config.inset = v,
+    },
+    Widget::Slider {
+        label: "inset zoom",
+        min: 1.5,
+        max: 10.0,
+        get: |s| s.config.inset_zoom,
+        set: |s, v| s.config.inset_zoom = v,
+    },
+    Widget::Checkbox {
+        label: "fps counter",
+        get: |s| s.config.show_fps,
+        set: |s, v| s.config.show_fps = v,
+    },
+    Widget::Slider {
+        label: "fps cap (0 = off)",
+        min: 0.0,
+        max: 240.0,
+        get: |s| s.config.max_fps.unwrap_or(0.0),
+        set: |s, v| s.config.max_fps = if v < 1.0 { None } else { Some(v.round()) },
+    },
+    Widget::Checkbox {
+        label: "log collisions",
+        get: |s| s.config.log_collisions,
+        set: |s, v| s.config.log_collisions = v,
+    },
+    Widget::Button {
+        label: "reset",
+        action: |s| s.reset(),
+    },
+    Widget::Button {
+        label: "new seed",
+        action: |s| s.reseed(),
+    },
+    Widget::Button {
+        label: "pause / resume",
+        action: |s| s.paused = !s.paused,
+    },
+    Widget::Button {
+        label: "spawn 50",
+        action: |s| s.spawn_random(SPAWN_BATCH),
+    },
+];
+
+// the gamepad's bindings, listed in the help after the keys
+static PAD_BINDINGS: &[PadBinding<GameState>] = &[
+    PadBinding {
+        buttons: &[Button::South],
+        description: "spawn at the reticle, hold to charge a launch and aim it with the stick",
+        press: |s, _| s.reticle.start_charge(),
+        release: Some(|s, _| s.launch_from_reticle()),
+    },
+    PadBinding {
+        buttons: &[Button::East],
+        description: "explosion at the reticle",
+        press: |s, _| s.explode_at_reticle(),
+        release: None,
+    },
+    PadBinding {
+        buttons: &[Button::LeftTrigger, Button::LeftTrigger2],
+        description: "slow down time",
+        press: |s, _| s.step_time_scale(false),
+        release: None,
+    },
+    PadBinding {
+        buttons: &[Button::RightTrigger, Button::RightTrigger2],
+        description: "speed up time",
+        press: |s, _| s.step_time_scale(true),
+        release: None,
+    },
+    PadBinding {
+        buttons: &[Button::Start],
+        description: "pause/resume",
+        press: |s, _| s.paused = !s.paused,
+        release: None,
+    },
+];
+
+// what esc brings up. quit goes through ggez's event loop like the quit key does, so
+// everything gets dropped and closed properly on the way out
+static MENU: &[MenuEntry<GameState>] = &[
+    MenuEntry {
+        label: "Resume",
+        action: |_, _| {},
+    },
+    MenuEntry {
+        label: "Reset",
+        action: |s, _| s.reset(),
+    },
+    MenuEntry {
+        label: "New seed",
+        action: |s, _| s.reseed(),
+    },
+    MenuEntry {
+        label: "Toggle fullscreen",
+        action: |s, ctx| {
+            if let Err(e) = s.toggle_fullscreen(ctx) {
+                println!("failed to toggle fullscreen: {}", e);
+            }
+        },
+    },
+    MenuEntry {
+        label: "Settings panel",
+        action: |s, _| s.panel.toggle(),
+    },
+    MenuEntry {
+        label: "Quit",
+        action: |_, ctx| event::quit(ctx),
+    },
+];
 
 fn main() -> GameResult {
-    let (mut ctx, mut event_loop) = ContextBuilder::new("collisions", "Tom Thorogood")
-        .window_mode(WindowMode::default().dimensions(SCREEN_WIDTH, SCREEN_HEIGHT))
-        .window_setup(WindowSetup::default().title("Collisions"))
-        .build()?;
-    let mut my_game = GameState::new(&mut ctx);
+    let mut config = SimulationConfig::load(std::path::Path::new(CONFIG_PATH))?;
+    config.apply_args(std::env::args().skip(1))?;
+    if config.check_maxwell {
+        std::process::exit(if maxwell::check(&config) { 0 } else { 1 });
+    }
+    if config.run_crystallize {
+        std::process::exit(if crystal::run_headless(&config) { 0 } else { 1 });
+    }
+    if let Some(path) = &config.chaos_file {
+        std::process::exit(if chaos::run_headless(&config, path) {
+            0
+        } else {
+            1
+        });
+    }
+    if let Some(steps) = config.verify_determinism {
+        std::process::exit(if determinism::run_headless(&config, steps) {
+            0
+        } else {
+            1
+        });
+    }
+    if config.run_stress && config.stress_headless {
+        std::process::exit(if stress::run_headless(&config) { 0 } else { 1 });
+    }
+    if config.run_epidemic {
+        std::process::exit(if epidemic::run_headless(&config) {
+            0
+        } else {
+            1
+        });
+    }
+
+    // winit reads this when it creates the window, so it has to be set before building
+    if let Some(factor) = config.hidpi_factor {
+        std::env::set_var("WINIT_HIDPI_FACTOR", factor.to_string());
+    }
+
+    let mut builder = ContextBuilder::new("collisions", "Tom Thorogood")
+        .window_mode(window_mode(&config))
+        .window_setup(
+            WindowSetup::default()
+                .title("Collisions")
+                .vsync(config.vsync),
+        );
+
+    // an installed binary finds the resources directory next to itself,
+    // but under cargo run that's somewhere in target/ so point at the source tree instead
+    if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+        builder = builder.add_resource_path(std::path::Path::new(&manifest_dir).join("resources"));
+    }
+
+    let (mut ctx, mut event_loop) = builder.build()?;
+    let mut my_game = GameState::new(&mut ctx, config)?;
+    if my_game.config.open_scene {
+        my_game.load_scene();
+    }
+    if my_game.config.walls_file.is_some() {
+        my_game.load_walls();
+    }
+    if my_game.config.run_demo {
+        my_game.demo_reel();
+    }
+    if my_game.config.run_stress {
+        my_game.stress_test();
+    }
     event::run(&mut ctx, &mut event_loop, &mut my_game)
 }
+// ggez hands mouse positions over in logical window pixels. the screen coordinates are
+// kept equal to the logical size so this is the identity today, but anything that reads
+// the mouse should go through here so it stays right if the two ever differ
+fn window_to_world(ctx: &Context, x: f32, y: f32) -> Point {
+    let screen = graphics::screen_coordinates(ctx);
+    let (width, height) = graphics::drawable_size(ctx);
+    if width <= 0.0 || height <= 0.0 {
+        return Point { x, y };
+    }
+    Point {
+        x: screen.x + x * screen.w / width,
+        y: screen.y + y * screen.h / height,
+    }
+}
+
+// what a preset's own walls, wells, pillars or anything else it puts in the box swap in for
+// the ones up now, if anything. they go again with the next preset, ones put up any other way
+// stay
+fn from_preset<T>(items: Option<Vec<T>>, from_preset: &mut bool) -> Option<Vec<T>> {
+    let items = match items {
+        Some(items) => items,
+        None if *from_preset => Vec::new(),
+        None => return None,
+    };
+    *from_preset = !items.is_empty();
+    Some(items)
+}
+
+fn window_mode(config: &SimulationConfig) -> WindowMode {
+    WindowMode::default()
+        .dimensions(config.window_width, config.window_height)
+        .resizable(true)
+}
+
 struct GameState {
-    particles: Vec<Particle>,
+    // the first pane, plus a second one beside it in the comparison mode
+    panes: Vec<Pane>,
+    // physics the second pane runs with instead, when comparing
+    compare: Option<CompareOverrides>,
+    // what every pane was last reset with, so they all start out the same
+    seed: u64,
+    // index into PRESETS of the scene a reset goes back to
+    preset: usize,
+    // the walls, wells, pillars, emitters, zones, voids, gates, blocks, black holes and
+    // portals up now are the ones the preset put there, and its trails and grain settings
+    // are on
+    preset_walls: bool,
+    preset_wells: bool,
+    preset_pillars: bool,
+    preset_emitters: bool,
+    preset_zones: bool,
+    preset_voids: bool,
+    preset_gates: bool,
+    preset_blocks: bool,
+    preset_black_holes: bool,
+    preset_portals: bool,
+    preset_trails: bool,
+    preset_grains: bool,
+    preset_weather: bool,
+    preset_cooling: bool,
+    // the comparison running is the preset's own
+    preset_compare: bool,
+    theme: usize,
+    config: SimulationConfig,
+    paused: bool,
+    panel: ControlPanel<GameState>,
+    particle_image: Option<Image>,
+    // window size in logical pixels, split between the panes
+    width: f32,
+    height: f32,
+    // window size to go back to when leaving fullscreen
+    windowed_size: Option<(f32, f32)>,
+    font: Font,
+    frame_limiter: FrameLimiter,
+    scene_canvas: SceneCanvas,
+    sounds: Sounds,
+    shake: ScreenShake,
+    help_open: bool,
+    menu: PauseMenu<GameState>,
+    // simulated time not yet stepped, always less than FIXED_STEP
+    accumulator: f32,
+    notice: Notice,
+    clipboard: Clipboard,
+    // zoomed view of the first pane
+    inset: Inset,
+    // a particle being placed with the mouse, not in any pane yet
+    aim: Option<Aim>,
+    // playing the presets one after another, see demo::Demo
+    demo: Option<Demo>,
+    // adding particles until the frame time can't keep up, see stress::Stress
+    stress: Option<Stress>,
+    // how long the last update's steps took, for the stress test
+    physics_time: f32,
+    // left edge of the pane a particle was picked up in, while one is held
+    held_left: Option<f32>,
+    // what the next particle spawned with the mouse will be like
+    spawn: SpawnSettings,
+    // the middle button is down, pulling particles towards the cursor
+    attracting: bool,
+    // ids of the selected particles, the same in every pane
+    selection: BTreeSet<u64>,
+    // where a rubber band selection started, in screen coordinates
+    selecting_from: Option<Point>,
+    // what the last delete took out of each pane, for undoing it
+    undo_delete: Option<Vec<Vec<Particle>>>,
+    // what the left button puts down, and where the wall being drawn started
+    tool: Tool,
+    wall_from: Option<Point>,
+    // last mouse position in screen coordinates, none until the mouse first moves
+    cursor: Option<Point>,
+    // the gamepad's cursor
+    reticle: Reticle,
+    gravity_dial: GravityDial,
+    keys: KeyMap,
 }
 
 impl GameState {
-    pub fn new(_ctx: &mut Context) -> GameState {
-        let colors = vec![
-            Color::from_rgb(170, 216, 211),
-            Color::from_rgb(50, 175, 230),
-            Color::from_rgb(0, 173, 181),
-            Color::from_rgb(10, 17, 200),
-            Color::from_rgb(150, 150, 20),
-            Color::from_rgb(0, 90, 45),
-            Color::from_rgb(200, 100, 50),
-        ];
-
-        let mut particles = Vec::new();
-
-        let r = SCREEN_HEIGHT.min(SCREEN_WIDTH) * 0.4;
-
-        let center_x = 0.5 * SCREEN_WIDTH;
-        let center_y = 0.5 * SCREEN_HEIGHT;
-
-        for i in 0..NUM_PARTICLES {
-            let rad = rand::thread_rng().gen_range(7.5..12.5);
-            let mass = rand::thread_rng().gen_range(1.0..1.25);
-            let color = colors
-                .choose(&mut rand::thread_rng())
-                .expect("Some colors in the vec");
-
-            let angle = std::f32::consts::PI * 2.0 * (i as f32) / (NUM_PARTICLES as f32);
-            let x = r * f32::cos(angle) + center_x;
-            let y = r * f32::sin(angle) + center_y;
-
-            particles.push(Particle::new(
-                Point { x, y },
-                Vector {
-                    x: (center_x - x) * -0.2,
-                    y: (center_y - y) * -0.2,
-                },
-                rad,
-                mass,
-                *color,
-            ));
+    pub fn new(ctx: &mut Context, config: SimulationConfig) -> GameResult<GameState> {
+        // Load/create resources here: images, fonts, sounds, etc.
+        let particle_image = match &config.particle_image {
+            Some(path) => Some(Image::new(ctx, path)?),
+            None => None,
+        };
+
+        let (width, height) = (config.window_width, config.window_height);
+        let compare = config.compare.clone();
+        let keys = KeyMap::new(&config.keys);
+        let panes = if compare.is_some() { 2 } else { 1 };
+        let spawn = SpawnSettings::new(&config);
+        // load already checked it
+        let container = config.container().unwrap_or_default();
+
+        let mut state = GameState {
+            panes: (0..panes)
+                .map(|_| {
+                    let mut pane = Pane::new(width, height);
+                    pane.world.container = container.clone();
+                    pane
+                })
+                .collect(),
+            compare,
+            seed: 0,
+            preset: 0,
+            preset_walls: false,
+            preset_wells: false,
+            preset_pillars: false,
+            preset_emitters: false,
+            preset_zones: false,
+            preset_voids: false,
+            preset_gates: false,
+            preset_blocks: false,
+            preset_black_holes: false,
+            preset_portals: false,
+            preset_trails: false,
+            preset_grains: false,
+            preset_weather: false,
+            preset_cooling: false,
+            preset_compare: false,
+            theme: theme::find(&config.theme).unwrap_or_default(),
+            config,
+            paused: false,
+            panel: ControlPanel::new("settings", CONTROLS),
+            particle_image,
+            width,
+            height,
+            windowed_size: None,
+            font: Font::default(),
+            frame_limiter: FrameLimiter::new(),
+            scene_canvas: SceneCanvas::default(),
+            sounds: Sounds::new(ctx),
+            shake: ScreenShake::default(),
+            help_open: false,
+            menu: PauseMenu::new(MENU),
+            accumulator: 0.0,
+            notice: Notice::default(),
+            clipboard: Clipboard::default(),
+            inset: Inset::default(),
+            aim: None,
+            demo: None,
+            stress: None,
+            physics_time: 0.0,
+            held_left: None,
+            spawn,
+            attracting: false,
+            selection: BTreeSet::new(),
+            selecting_from: None,
+            undo_delete: None,
+            tool: Tool::Particles,
+            wall_from: None,
+            cursor: None,
+            reticle: Reticle::new(Point {
+                x: 0.5 * width,
+                y: 0.5 * height,
+            }),
+            gravity_dial: GravityDial::default(),
+            keys,
+        };
+
+        // the window manager may not have given us the size we asked for,
+        // so start from whatever the window really is rather than the config
+        let (width, height) = graphics::drawable_size(ctx);
+        state.fit_window(ctx, width, height)?;
+
+        state.reseed();
+        Ok(state)
+    }
+
+    // paused, or the help is open and configured to pause
+    fn frozen(&self) -> bool {
+        self.paused || self.menu.open || (self.help_open && self.config.help_pauses)
+    }
+
+    pub fn theme(&self) -> &'static Theme {
+        &THEMES[self.theme]
+    }
+
+    // one notch along TIME_SCALES, `up` for faster
+    pub fn step_time_scale(&mut self, up: bool) {
+        let current = self.config.time_scale;
+        let next = if up {
+            TIME_SCALES.iter().copied().find(|&s| s > current + 1e-4)
+        } else {
+            TIME_SCALES
+                .iter()
+                .rev()
+                .copied()
+                .find(|&s| s < current - 1e-4)
+        };
+        if let Some(scale) = next {
+            self.config.time_scale = scale;
         }
+        self.notice
+            .show(format!("time scale {}x", self.config.time_scale));
+    }
 
-        // Load/create resources here: images, fonts, sounds, etc.
-        GameState { particles }
-    }
-
-    fn handle_collisions(&mut self) {
-        let num_particles = self.particles.len();
-
-        // collisions
-        for i in 0..num_particles - 1 {
-            for j in i + 1..num_particles {
-                if self.particles[i].is_colliding(&self.particles[j]) {
-                    println!(
-                        "collision at distance: {}",
-                        self.particles[i].distance(&self.particles[j])
-                    );
-
-                    // u1
-                    let u1_x = self.particles[i].vel.x;
-                    let u1_y = self.particles[i].vel.y;
-
-                    // u2
-                    let u2_x = self.particles[j].vel.x;
-                    let u2_y = self.particles[j].vel.y;
-
-                    // m2 / m1
-                    let m2_div_m1 = self.particles[j].mass / self.particles[i].mass;
-
-                    // v1
-                    self.particles[i].vel.x = ((1.0 - RESTITUTION) / 2.0 * u1_x)
-                        + ((m2_div_m1 + RESTITUTION) / 2.0 * u2_x);
-                    self.particles[i].vel.y = ((1.0 - RESTITUTION) / 2.0 * u1_y)
-                        + ((m2_div_m1 + RESTITUTION) / 2.0 * u2_y);
-
-                    // v2
-                    self.particles[j].vel.x = ((1.0 + RESTITUTION) / 2.0 * u1_x)
-                        + ((m2_div_m1 - RESTITUTION) / 2.0 * u2_x);
-                    self.particles[j].vel.y = ((1.0 + RESTITUTION) / 2.0 * u1_y)
-                        + ((m2_div_m1 - RESTITUTION) / 2.0 * u2_y);
-
-                    let magnitude_1 = self.particles[i].vel_magnitude();
-                    let magnitude_2 = self.particles[j].vel_magnitude();
-
-                    let a = (self.particles[i].vel.x / magnitude_1).abs();
-                    let b = (self.particles[i].vel.y / magnitude_1).abs();
-                    let c = (self.particles[j].vel.x / magnitude_2).abs();
-                    let d = (self.particles[j].vel.y / magnitude_2).abs();
-
-                    let new_color = Color::from_rgb(
-                        ((a * b) * 256.0) as u8,
-                        ((c * d) * 256.0) as u8,
-                        ((d * a) * 256.0) as u8,
-                    );
-
-                    self.particles[i].color = new_color;
-                    self.particles[j].color = new_color;
-                }
+    pub fn next_inset_target(&mut self) {
+        self.config.inset_target = self.config.inset_target.next();
+        self.notice
+            .show(format!("inset: {}", self.config.inset_target.name()));
+    }
+
+    // one step the way a gravity action's arrow points, a big one for the coarse ones
+    pub fn nudge_gravity(&mut self, action: Action) {
+        let (x, y, step) = match action {
+            Action::GravityUp => (0.0, -1.0, GRAVITY_STEP),
+            Action::GravityDown => (0.0, 1.0, GRAVITY_STEP),
+            Action::GravityLeft => (-1.0, 0.0, GRAVITY_STEP),
+            Action::GravityRight => (1.0, 0.0, GRAVITY_STEP),
+            Action::GravityCoarseUp => (0.0, -1.0, GRAVITY_COARSE_STEP),
+            Action::GravityCoarseDown => (0.0, 1.0, GRAVITY_COARSE_STEP),
+            Action::GravityCoarseLeft => (-1.0, 0.0, GRAVITY_COARSE_STEP),
+            Action::GravityCoarseRight => (1.0, 0.0, GRAVITY_COARSE_STEP),
+            _ => return,
+        };
+        self.config.gravity.x += x * step;
+        self.config.gravity.y += y * step;
+        self.show_gravity();
+    }
+
+    fn show_gravity(&mut self) {
+        self.notice.show(format!(
+            "gravity ({:.1}, {:.1})",
+            self.config.gravity.x, self.config.gravity.y
+        ));
+    }
+
+    fn show_forces(&mut self) {
+        self.notice.show(format!("forces: {}", self.forces_label()));
+    }
+
+    // flocking starts out with gravity off so the flocks have the whole box, it can go back on
+    fn set_boids(&mut self, on: bool) {
+        self.config.boids = on;
+        if on {
+            self.config.gravity_enabled = false;
+        }
+        self.show_forces();
+    }
+
+    // the forces acting right now, for the hud
+    fn forces_label(&self) -> String {
+        let forces: Vec<&str> = [
+            ("gravity", self.config.gravity_enabled),
+            ("drag", self.config.drag_enabled && self.config.drag > 0.0),
+            (
+                "wells",
+                self.config.wells_enabled && !self.panes[0].world.wells.is_empty(),
+            ),
+            ("repulsion", self.config.repulsion && self.cursor.is_some()),
+            ("attraction", self.attracting),
+            ("flocking", self.config.boids),
+        ]
+        .iter()
+        .filter(|(_, on)| *on)
+        .map(|(name, _)| *name)
+        .collect();
+        if forces.is_empty() {
+            String::from("none")
+        } else {
+            forces.join(", ")
+        }
+    }
+
+    // the config a pane's physics runs with
+    fn pane_config(&self, pane: usize) -> SimulationConfig {
+        match (&self.compare, pane) {
+            (Some(overrides), 1) => overrides.apply(&self.config),
+            _ => self.config.clone(),
+        }
+    }
+
+    fn particles_mut(&mut self) -> impl Iterator<Item = &mut Particle> {
+        self.panes
+            .iter_mut()
+            .flat_map(|pane| pane.world.particles.iter_mut())
+    }
+
+    pub fn next_theme(&mut self) {
+        self.theme = (self.theme + 1) % THEMES.len();
+
+        let theme = self.theme();
+        if self.config.remap_colors {
+            for particle in self.particles_mut() {
+                particle.color = theme.nearest(particle.color);
+                particle.original_color = theme.nearest(particle.original_color);
             }
         }
     }
-    fn handle_movement(&mut self, time_elapsed: f32) {
-        for particle in &mut self.particles {
-            particle.update(time_elapsed);
+
+    // switching to the original policy puts every particle back to its spawn color
+    pub fn next_color_policy(&mut self) {
+        self.config.color_policy = self.config.color_policy.next();
+        if self.config.color_policy == ColorPolicy::Original {
+            for particle in self.particles_mut() {
+                particle.color = particle.original_color;
+            }
         }
     }
-}
 
-impl EventHandler for GameState {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        let time_elapsed = ggez::timer::delta(ctx).as_secs_f32();
+    // everything back to how the current seed started out, in every pane from the same seed:
+    // particles, effects, events, trails and anything the mouse was doing. startup comes
+    // through here too
+    pub fn reset(&mut self) {
+        for pane in 0..self.panes.len() {
+            self.reset_pane(pane);
+        }
+        // paths drawn with no clear start over too
+        self.scene_canvas.clear();
+        self.forget_scene_state();
+    }
+
+    // everything that refers to particles by id or was part way through with them
+    fn forget_scene_state(&mut self) {
+        self.shake.clear();
+        self.inset.clear();
+        self.aim = None;
+        self.held_left = None;
+        self.selection.clear();
+        self.selecting_from = None;
+        // the ids in it now belong to other particles
+        self.undo_delete = None;
+        self.accumulator = 0.0;
+    }
 
-        self.handle_collisions();
-        self.handle_movement(time_elapsed);
+    // a fresh random scene, with the seed shown so it can be told apart from the last one
+    pub fn reseed(&mut self) {
+        self.seed = rand::random();
+        self.notice.show(format!("seed {}", self.seed));
+        self.reset();
+    }
 
-        Ok(())
+    // back to the scene the other panes started from
+    pub fn reset_pane(&mut self, pane: usize) {
+        let scene = self.build_scene();
+        if let Some(pane) = self.panes.get_mut(pane) {
+            let traced = scene
+                .tracer
+                .and_then(|i| scene.particles.get(i))
+                .map(|p| p.pos);
+            pane.load(scene.particles);
+            // ids are handed out again on load, so it's found by where it is
+            let id = traced.and_then(|pos| pane.world.particle_at(pos));
+            pane.tracer.follow(id);
+            if let Some(index) = scene.chaos {
+                pane.start_chaos(index);
+            }
+        }
     }
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        graphics::clear(ctx, graphics::BLACK);
 
-        let params = graphics::DrawParam::default();
+    // a preset with its own comparison splits the window for it, and the next preset puts
+    // back whatever was running before. true if the panes changed size
+    fn compare_for_preset(&mut self, compare: Option<CompareOverrides>) -> bool {
+        let own = compare.is_some();
+        let compare = match compare {
+            Some(overrides) => Some(overrides),
+            None if self.preset_compare => self.config.compare.clone(),
+            None => return false,
+        };
+        self.preset_compare = own;
+        self.compare = compare;
 
-        for particle in &self.particles {
-            let mesh = particle.mesh(ctx)?;
+        let count = if self.compare.is_some() { 2 } else { 1 };
+        if count == self.panes.len() {
+            return false;
+        }
+        self.panes.truncate(count);
+        while self.panes.len() < count {
+            let world = &self.panes[0].world;
+            let mut pane = Pane::new(world.width, world.height);
+            pane.world.container = world.container.clone();
+            pane.world.segments = world.segments.clone();
+            pane.world.pillars = world.pillars.clone();
+            pane.world.blocks = world.blocks.clone();
+            pane.world.wells = world.wells.clone();
+            pane.world.black_holes = world.black_holes.clone();
+            pane.world.portals = world.portals.clone();
+            pane.world.voids = world.voids.clone();
+            pane.world.emitters = world.emitters.clone();
+            pane.world.zones = world.zones.clone();
+            self.panes.push(pane);
+        }
+        self.set_bounds(self.width, self.height);
+        true
+    }
 
-            graphics::draw(ctx, &mesh, params)?;
+    // the current preset from the current seed. every pane is the same size, so any will do
+    fn build_scene(&self) -> presets::Scene {
+        let layout = Layout {
+            width: self.panes[0].world.width,
+            height: self.panes[0].world.height,
+            config: &self.config,
+            theme: self.theme(),
+        };
+        (PRESETS[self.preset].build)(&layout, &mut StdRng::seed_from_u64(self.seed))
+    }
 
-            let line = graphics::Mesh::new_line(
-                ctx,
-                &[
-                    particle.pos,
-                    Point {
-                        x: particle.pos.x + particle.vel.x,
-                        y: particle.pos.y + particle.vel.y,
-                    },
-                ],
-                2.0,
-                particle.color,
-            )?;
+    // switch to a preset scene, taking on its gravity and restitution
+    pub fn load_preset(&mut self, preset: usize) {
+        if preset >= PRESETS.len() {
+            return;
+        }
+        self.preset = preset;
 
-            graphics::draw(ctx, &line, params)?;
+        let mut scene = self.build_scene();
+        // laid out again for the panes' new size
+        if self.compare_for_preset(scene.compare.take()) {
+            scene = self.build_scene();
+        }
+        if let Some(gravity) = scene.gravity {
+            self.config.gravity = gravity;
+        }
+        if let Some(restitution) = scene.restitution {
+            self.config.restitution = restitution;
+        }
+        if let Some(walls) = from_preset(scene.walls, &mut self.preset_walls) {
+            for pane in &mut self.panes {
+                pane.world.segments = walls.clone();
+            }
+        }
+        if let Some(wells) = from_preset(scene.wells, &mut self.preset_wells) {
+            for pane in &mut self.panes {
+                pane.world.wells = wells.clone();
+            }
+        }
+        if let Some(pillars) = from_preset(scene.pillars, &mut self.preset_pillars) {
+            for pane in &mut self.panes {
+                pane.world.pillars = pillars.clone();
+            }
+        }
+        if let Some(emitters) = from_preset(scene.emitters, &mut self.preset_emitters) {
+            for pane in &mut self.panes {
+                pane.world.emitters = emitters.clone();
+            }
+        }
+        if let Some(zones) = from_preset(scene.zones, &mut self.preset_zones) {
+            for pane in &mut self.panes {
+                pane.world.zones = zones.clone();
+            }
+        }
+        if let Some(voids) = from_preset(scene.voids, &mut self.preset_voids) {
+            for pane in &mut self.panes {
+                pane.world.voids = voids.clone();
+            }
+        }
+        if let Some(gates) = from_preset(scene.gates, &mut self.preset_gates) {
+            for pane in &mut self.panes {
+                pane.world.gates = gates.clone();
+            }
+        }
+        if let Some(blocks) = from_preset(scene.blocks, &mut self.preset_blocks) {
+            for pane in &mut self.panes {
+                pane.world.blocks = blocks.clone();
+            }
+        }
+        if let Some(black_holes) = from_preset(scene.black_holes, &mut self.preset_black_holes) {
+            for pane in &mut self.panes {
+                pane.world.black_holes = black_holes.clone();
+            }
+        }
+        if let Some(portals) = from_preset(scene.portals, &mut self.preset_portals) {
+            for pane in &mut self.panes {
+                pane.world.portals = portals.clone();
+            }
+        }
+        if scene.trails {
+            self.config.clear_mode = ClearMode::None;
+        } else if self.preset_trails {
+            self.config.clear_mode = ClearMode::Hard;
         }
+        self.preset_trails = scene.trails;
+        if let Some(grains) = scene.grains {
+            self.config.wall_friction = grains.friction;
+            self.config.particle_friction = grains.friction;
+            self.config.sleeping = true;
+            self.config.solver_iterations = grains.solver_iterations;
+        } else if self.preset_grains {
+            let defaults = SimulationConfig::default();
+            self.config.wall_friction = defaults.wall_friction;
+            self.config.particle_friction = defaults.particle_friction;
+            self.config.sleeping = defaults.sleeping;
+            self.config.solver_iterations = defaults.solver_iterations;
+        }
+        self.preset_grains = scene.grains.is_some();
+        if let Some(weather) = scene.weather {
+            self.set_weather(weather);
+        } else if self.preset_weather {
+            self.set_weather(Weather::Off);
+        }
+        self.preset_weather = scene.weather.is_some();
+        if let Some(temperature) = scene.cooling {
+            crystal::start(&mut self.config, temperature);
+        } else if self.preset_cooling {
+            crystal::stop(&mut self.config);
+        }
+        self.preset_cooling = scene.cooling.is_some();
+        self.notice
+            .show(format!("{}: {}", preset + 1, PRESETS[preset].name));
+        self.reset();
+    }
 
-        graphics::present(ctx)
+    // the first pane's particles and walls along with the physics they're running under
+    pub fn save_scene(&mut self) {
+        let path = std::path::Path::new(&self.config.scene_file);
+        let pane = &self.panes[0];
+        let scene = SceneFile {
+            budget: pane.game.budget(),
+            launcher: pane.game.launcher(),
+            ..SceneFile::capture(&pane.world, &self.config)
+        };
+        match scene.save(path) {
+            Ok(()) => {
+                println!("saved scene to {}", path.display());
+                self.notice.show(format!("saved {}", path.display()));
+            }
+            Err(e) => {
+                println!("failed to save scene: {}", e);
+                self.notice.show(String::from("failed to save the scene"));
+            }
+        }
     }
-}
 
-struct Particle {
-    pub rad: f32,
-    pub pos: Point,
-    pub mass: f32,
-    pub vel: Vector,
-    color: Color,
-}
+    // every pane gets the same saved particles and walls. a reset afterwards still goes back
+    // to the preset rather than the file
+    pub fn load_scene(&mut self) {
+        let path = std::path::PathBuf::from(&self.config.scene_file);
+        let scene = SceneFile::load(&path).and_then(|scene| {
+            let container = scene
+                .container()
+                .map_err(|e| GameError::ConfigError(format!("{}: {}", path.display(), e)))?;
+            Ok((scene, container))
+        });
+        let (scene, container) = match scene {
+            Ok(scene) => scene,
+            Err(e) => {
+                println!("failed to load scene: {}", e);
+                self.notice
+                    .show(format!("couldn't load {}", path.display()));
+                return;
+            }
+        };
 
-impl Particle {
-    pub fn new(pos: Point, vel: Vector, rad: f32, mass: f32, color: Color) -> Particle {
-        Particle {
-            pos,
-            vel,
-            rad,
-            mass,
-            color,
+        self.config.gravity = scene.gravity;
+        self.config.restitution = scene.restitution;
+        let particles = scene.particles();
+        for pane in &mut self.panes {
+            // before the particles, so they're checked against the new container
+            pane.world.container = container.clone();
+            pane.load(particles.clone());
+            pane.world.segments = scene.walls.clone();
+            pane.world.pillars = scene.pillars.clone();
+            pane.world.blocks = scene.blocks.clone();
+            pane.world.gates = scene.gates.clone();
+            pane.world.wells = scene.wells.clone();
+            pane.world.black_holes = scene.black_holes.clone();
+            pane.world.portals = scene.portals.clone();
+            pane.world.voids = scene.voids.clone();
+            pane.world.emitters = scene.emitters.clone();
+            pane.world.zones = scene.zones.clone();
+            pane.world.targets = scene.targets.clone();
+            pane.game.start(scene.budget, scene.launcher);
         }
+        self.preset_walls = false;
+        self.preset_wells = false;
+        self.preset_pillars = false;
+        self.preset_emitters = false;
+        self.preset_zones = false;
+        self.preset_voids = false;
+        self.preset_gates = false;
+        self.preset_blocks = false;
+        self.preset_black_holes = false;
+        self.preset_portals = false;
+        self.forget_scene_state();
+        self.notice.show(format!("loaded {}", path.display()));
     }
-    pub fn mesh(&self, ctx: &mut Context) -> GameResult<Mesh> {
-        graphics::Mesh::new_circle(
-            ctx,
-            graphics::DrawMode::fill(),
-            self.pos,
-            self.rad,
-            0.05,
-            self.color,
-        )
+
+    // every pane's walls swapped for the ones traced in walls_file, scaled to the box
+    pub fn load_walls(&mut self) {
+        let path = match &self.config.walls_file {
+            Some(path) => std::path::PathBuf::from(path),
+            None => return,
+        };
+        let (width, height) = (self.panes[0].world.width, self.panes[0].world.height);
+        let course = match paths::load(&path, width, height) {
+            Ok(course) => course,
+            Err(e) => {
+                println!("failed to load walls: {}", e);
+                self.notice
+                    .show(format!("couldn't load {}", path.display()));
+                return;
+            }
+        };
+        for warning in &course.warnings {
+            println!("{}: {}", path.display(), warning);
+        }
+        for pane in &mut self.panes {
+            pane.world.segments = course.walls.clone();
+        }
+        self.preset_walls = false;
+        self.notice.show(if course.warnings.is_empty() {
+            format!(
+                "loaded {} walls from {}",
+                course.walls.len(),
+                path.display()
+            )
+        } else {
+            format!(
+                "{} walls from {}, some outside the box",
+                course.walls.len(),
+                path.display()
+            )
+        });
+    }
+
+    // switch the left button to `tool`, or back to spawning if it's already on it
+    pub fn toggle_tool(&mut self, tool: Tool) {
+        self.tool = if self.tool == tool {
+            Tool::Particles
+        } else {
+            tool
+        };
+        self.wall_from = None;
+        self.notice.show(String::from(match self.tool {
+            Tool::Particles => "spawning particles",
+            Tool::Walls => "drawing walls",
+            Tool::Pillars => "placing pillars",
+            Tool::Wells => "placing gravity wells",
+            Tool::BlackHoles => "placing black holes",
+            Tool::Blocks => "placing blocks",
+            Tool::Emitters => "placing emitters",
+        }));
+    }
+
+    // `pos` in screen coordinates as a point in the pane it's over
+    fn pane_point(&self, pos: Point) -> Option<Point> {
+        let pane = self.panes.iter().find(|pane| pane.contains_x(pos.x))?;
+        Some(Point {
+            x: pos.x - pane.left,
+            y: pos.y,
+        })
+    }
+
+    // a well of the configured strength at `pos`, in every pane
+    pub fn add_well(&mut self, pos: Point) {
+        let well = match self.pane_point(pos) {
+            Some(pos) => Well {
+                pos,
+                strength: self.config.well_strength,
+            },
+            None => return,
+        };
+        for pane in &mut self.panes {
+            pane.world.wells.push(well);
+        }
+    }
+
+    // the well under `pos` in screen coordinates, by index so it's the same one in every pane
+    fn well_at(&self, pos: Point) -> Option<usize> {
+        let local = self.pane_point(pos)?;
+        self.panes[0].world.well_at(local)
+    }
+
+    // false if there's no well there
+    pub fn remove_well_at(&mut self, pos: Point) -> bool {
+        let index = match self.well_at(pos) {
+            Some(index) => index,
+            None => return false,
+        };
+        for pane in &mut self.panes {
+            pane.world.wells.remove(index);
+        }
+        true
+    }
+
+    // one notch of the mouse wheel over a well, false if there's no well there
+    pub fn scale_well_at(&mut self, pos: Point, notches: f32) -> bool {
+        let index = match self.well_at(pos) {
+            Some(index) => index,
+            None => return false,
+        };
+        for pane in &mut self.panes {
+            pane.world.wells[index].scale_strength(notches);
+        }
+        self.notice.show(format!(
+            "well strength {:.0}",
+            self.panes[0].world.wells[index].strength
+        ));
+        true
+    }
+
+    // a black hole of black_hole_mass at `pos`, in every pane
+    pub fn add_black_hole(&mut self, pos: Point) {
+        let hole = match self.pane_point(pos) {
+            Some(pos) => BlackHole::new(pos, self.config.black_hole_mass),
+            None => return,
+        };
+        for pane in &mut self.panes {
+            pane.world.black_holes.push(hole.clone());
+        }
+    }
+
+    // false if there's no black hole there. each pane's may have grown differently, so it's
+    // the one under `pos` in the first that goes from all of them
+    pub fn remove_black_hole_at(&mut self, pos: Point) -> bool {
+        let index = match self.pane_point(pos) {
+            Some(local) => self.panes[0].world.black_hole_at(local),
+            None => None,
+        };
+        let index = match index {
+            Some(index) => index,
+            None => return false,
+        };
+        for pane in &mut self.panes {
+            pane.world.black_holes.remove(index);
+        }
+        true
+    }
+
+    // a pillar the size of the next particle at `pos` in screen coordinates,
+    // at the same place in every pane
+    pub fn add_pillar(&mut self, pos: Point) {
+        let left = match self.panes.iter().find(|pane| pane.contains_x(pos.x)) {
+            Some(pane) => pane.left,
+            None => return,
+        };
+        let pillar = Pillar {
+            pos: Point {
+                x: pos.x - left,
+                y: pos.y,
+            },
+            rad: self.spawn.radius,
+        };
+        for pane in &mut self.panes {
+            pane.world.pillars.push(pillar);
+        }
+    }
+
+    // take out the pillar under `pos` in every pane. false if there isn't one
+    pub fn remove_pillar_at(&mut self, pos: Point) -> bool {
+        let index = match self.panes.iter().find(|pane| pane.contains_x(pos.x)) {
+            Some(pane) => pane.world.pillar_at(Point {
+                x: pos.x - pane.left,
+                y: pos.y,
+            }),
+            None => None,
+        };
+        let index = match index {
+            Some(index) => index,
+            None => return false,
+        };
+        for pane in &mut self.panes {
+            pane.world.pillars.remove(index);
+        }
+        true
+    }
+
+    // a block with corners at `from` and `to` in screen coordinates, placed like a wall
+    pub fn add_block(&mut self, from: Point, to: Point) {
+        let pane = match self.panes.iter().find(|pane| pane.contains_x(from.x)) {
+            Some(pane) => pane,
+            None => return,
+        };
+        let (left, width, height) = (pane.left, pane.world.width, pane.world.height);
+        let block = Block::from_corners(
+            Point {
+                x: from.x - left,
+                y: from.y,
+            },
+            Point {
+                x: (to.x - left).clamp(0.0, width),
+                y: to.y.clamp(0.0, height),
+            },
+        );
+        if block.thickness() < MIN_WALL_LENGTH {
+            return;
+        }
+        for pane in &mut self.panes {
+            pane.world.blocks.push(block);
+        }
+    }
+
+    // an emitter at `from` in screen coordinates pointing towards `to`, or straight up for a
+    // click without a drag
+    pub fn add_emitter(&mut self, from: Point, to: Point) {
+        let pos = match self.pane_point(from) {
+            Some(pos) => pos,
+            None => return,
+        };
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let direction = if (dx * dx + dy * dy).sqrt() < MIN_WALL_LENGTH {
+            -90.0
+        } else {
+            dy.atan2(dx).to_degrees()
+        };
+        let emitter = Emitter::new(pos, direction, &self.config);
+        for pane in &mut self.panes {
+            pane.world.emitters.push(emitter);
+        }
+    }
+
+    // false if there's no emitter there
+    pub fn remove_emitter_at(&mut self, pos: Point) -> bool {
+        let index = match self.pane_point(pos) {
+            Some(local) => self.panes[0].world.emitter_at(local),
+            None => None,
+        };
+        let index = match index {
+            Some(index) => index,
+            None => return false,
+        };
+        for pane in &mut self.panes {
+            pane.world.emitters.remove(index);
+        }
+        true
+    }
+
+    // take out the block under `pos` in every pane. false if there isn't one
+    pub fn remove_block_at(&mut self, pos: Point) -> bool {
+        let index = match self.pane_point(pos) {
+            Some(local) => self.panes[0].world.block_at(local),
+            None => None,
+        };
+        let index = match index {
+            Some(index) => index,
+            None => return false,
+        };
+        for pane in &mut self.panes {
+            pane.world.blocks.remove(index);
+        }
+        true
+    }
+
+    // a wall from `from` to `to` in screen coordinates, in every pane at the same place.
+    // the pane it starts in decides where, and the end is kept inside that pane
+    pub fn add_wall(&mut self, from: Point, to: Point) {
+        let pane = match self.panes.iter().find(|pane| pane.contains_x(from.x)) {
+            Some(pane) => pane,
+            None => return,
+        };
+        let (left, width, height) = (pane.left, pane.world.width, pane.world.height);
+        let segment = Segment::new(
+            Point {
+                x: from.x - left,
+                y: from.y,
+            },
+            Point {
+                x: (to.x - left).clamp(0.0, width),
+                y: to.y.clamp(0.0, height),
+            },
+        );
+        if segment.length() < MIN_WALL_LENGTH {
+            return;
+        }
+        for pane in &mut self.panes {
+            pane.world.segments.push(segment);
+        }
+    }
+
+    pub fn remove_last_wall(&mut self) {
+        for pane in &mut self.panes {
+            pane.world.segments.pop();
+        }
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.preset_walls = false;
+        self.preset_wells = false;
+        self.preset_pillars = false;
+        self.preset_emitters = false;
+        self.preset_gates = false;
+        self.preset_blocks = false;
+        self.preset_black_holes = false;
+        for pane in &mut self.panes {
+            pane.world.segments.clear();
+            pane.world.gates.clear();
+            pane.world.pillars.clear();
+            pane.world.blocks.clear();
+            pane.world.wells.clear();
+            pane.world.black_holes.clear();
+            pane.world.emitters.clear();
+        }
+        self.notice.show(String::from(
+            "walls, pillars, blocks, wells, black holes and emitters cleared",
+        ));
+    }
+
+    // the presets one after another from demo_file, or all of them, until esc. pressed again
+    // while it's playing, on to the next
+    pub fn demo_reel(&mut self) {
+        if let Some(demo) = &mut self.demo {
+            demo.skip();
+            return;
+        }
+        let reel = match &self.config.demo_file {
+            Some(path) => match Reel::load(std::path::Path::new(path), &self.config) {
+                Ok(reel) => reel,
+                Err(e) => {
+                    println!("failed to load the demo reel: {}", e);
+                    self.notice.show(format!("couldn't load {}", path));
+                    return;
+                }
+            },
+            None => Reel::every_preset(&self.config),
+        };
+        self.demo = Some(Demo::new(reel, self.config.clone()));
+    }
+
+    // back to the sandbox, leaving the last item playing
+    pub fn stop_demo(&mut self) {
+        if self.demo.take().is_some() {
+            self.notice.show(String::from("demo reel stopped"));
+        }
+    }
+
+    // a box of stress_start particles with a batch more every few seconds until a frame takes
+    // too long, see stress::Stress. pressed again, it starts over
+    pub fn stress_test(&mut self) {
+        let config = Stress::spawn_config(&self.config);
+        let theme = self.theme();
+        for pane in &mut self.panes {
+            pane.load(Vec::new());
+            pane.spawn_random(config.stress_start, &config, theme, self.seed);
+        }
+        self.forget_scene_state();
+        self.paused = false;
+        self.stress = Some(Stress::new(&self.config, false));
+        self.notice.show(String::from("stress test"));
+    }
+
+    // leaving however many particles it got to
+    pub fn stop_stress(&mut self) {
+        if self.stress.take().is_some() {
+            self.notice.show(String::from("stress test stopped"));
+        }
+    }
+
+    // a frame of the stress test once it's drawn, `drawing` of it spent on that. each batch goes
+    // into every pane, so the count is all of them together
+    fn record_stress(&mut self, drawing: f32, time_elapsed: f32) {
+        if self.frozen() {
+            return;
+        }
+        let mut stress = match self.stress.take() {
+            Some(stress) => stress,
+            None => return,
+        };
+        let particles = self
+            .panes
+            .iter()
+            .map(|pane| pane.world.particles.len())
+            .sum();
+        let timing = Timing {
+            physics: self.physics_time,
+            drawing,
+        };
+        let full = stress::full(&self.panes[0].world);
+        match stress.record(timing, time_elapsed, particles, full) {
+            Verdict::Keep => {}
+            Verdict::Add(count) => {
+                let config = Stress::spawn_config(&self.config);
+                let theme = self.theme();
+                // somewhere else in the box than the last batch
+                let seed = self.seed.wrapping_add(particles as u64);
+                let mut added = 0;
+                for pane in &mut self.panes {
+                    let before = pane.world.particles.len();
+                    pane.spawn_random(count, &config, theme, seed);
+                    added += pane.world.particles.len() - before;
+                }
+                stress.added(added);
+            }
+            Verdict::Done(removed) => {
+                let each = removed / self.panes.len();
+                for pane in &mut self.panes {
+                    // by id, so the grid, a hold and anything resting on them keep up. the
+                    // newest first, each off the end
+                    let particles = &pane.world.particles;
+                    let batch: Vec<u64> = particles[particles.len().saturating_sub(each)..]
+                        .iter()
+                        .rev()
+                        .map(|p| p.id)
+                        .collect();
+                    for id in batch {
+                        pane.world.remove(id);
+                    }
+                }
+                self.forget_scene_state();
+                if let Some(summary) = stress.summary() {
+                    println!("{}", summary);
+                }
+                self.notice.show(String::from("stress test done"));
+            }
+        }
+        self.stress = Some(stress);
+    }
+
+    // whatever the demo reel has due next, at the start of a draw. with a crossfade the scene
+    // as it was last drawn goes into the demo's canvas first to fade out over it
+    fn next_demo_item(&mut self, ctx: &mut Context) -> GameResult {
+        let fades = match &self.demo {
+            Some(demo) => demo.fades(),
+            None => return Ok(()),
+        };
+        if fades {
+            let theme = self.theme();
+            if let Some(demo) = &mut self.demo {
+                demo.begin_capture(ctx, theme.background)?;
+            }
+            let (config, image, none) =
+                (&self.config, self.particle_image.as_ref(), BTreeSet::new());
+            let mut result = Ok(());
+            for pane in &mut self.panes {
+                result = pane.draw_scene(ctx, config, image, &none, theme, [0.0, 0.0]);
+                if result.is_err() {
+                    break;
+                }
+            }
+            if let Some(demo) = &mut self.demo {
+                demo.end_capture(ctx);
+            }
+            result?;
+        }
+        if let Some(preset) = self.demo.as_mut().and_then(Demo::next_due) {
+            self.load_demo_item(preset);
+        }
+        Ok(())
+    }
+
+    // a preset as the demo reel plays it: from the config the reel started with, with nothing
+    // left from before, no walls, wells, zones or anything else placed, none of the last
+    // preset's settings and nothing open over it
+    fn load_demo_item(&mut self, preset: usize) {
+        let base = match &self.demo {
+            Some(demo) => demo.base.clone(),
+            None => return,
+        };
+        self.config = base;
+        // the settings they'd have put back went with the config
+        self.preset_trails = false;
+        self.preset_grains = false;
+        self.preset_weather = false;
+        self.preset_cooling = false;
+        self.preset_walls = false;
+        self.preset_wells = false;
+        self.preset_pillars = false;
+        self.preset_emitters = false;
+        self.preset_zones = false;
+        self.preset_voids = false;
+        self.preset_gates = false;
+        self.preset_blocks = false;
+        self.preset_black_holes = false;
+        self.preset_portals = false;
+        let container = self.config.container().unwrap_or_default();
+        for pane in &mut self.panes {
+            let world = &mut pane.world;
+            world.container = container.clone();
+            world.segments.clear();
+            world.gates.clear();
+            world.pillars.clear();
+            world.blocks.clear();
+            world.wells.clear();
+            world.black_holes.clear();
+            world.portals.clear();
+            world.voids.clear();
+            world.emitters.clear();
+            world.zones.clear();
+        }
+        self.help_open = false;
+        if self.panel.visible {
+            self.panel.toggle();
+        }
+        self.paused = false;
+        self.load_preset(preset);
+    }
+
+    // rain or snow along with the drag and wind it comes with, or back to the defaults
+    fn set_weather(&mut self, weather: Weather) {
+        let defaults = SimulationConfig::default();
+        let (drag, wind) = weather.air().unwrap_or((defaults.drag, defaults.wind));
+        self.config.weather = weather;
+        self.config.drag = drag;
+        self.config.wind = wind;
+    }
+
+    pub fn next_weather(&mut self) {
+        self.set_weather(self.config.weather.next());
+        self.notice
+            .show(format!("weather: {}", self.config.weather.name()));
+    }
+
+    pub fn reheat(&mut self) {
+        let temperature = self.config.reheat_temperature;
+        for pane in &mut self.panes {
+            crystal::heat(&mut pane.world, &mut self.config, temperature);
+        }
+        self.notice
+            .show(format!("heated back up to {:.0}", temperature));
+    }
+
+    // every emitter in every pane, the same way
+    fn tune_emitters(&mut self, tune: impl Fn(&mut Emitter)) {
+        for pane in &mut self.panes {
+            pane.world.emitters.iter_mut().for_each(&tune);
+        }
     }
-    pub fn update(&mut self, time_elapsed: f32) {
-        // bound checks
-        // left/right
-        if (self.pos.x - self.rad) < 0.0 {
-            self.vel.x = self.vel.x.abs() * RESTITUTION;
-        } else if (self.pos.x + self.rad) > SCREEN_WIDTH {
-            self.vel.x = self.vel.x.abs() * -RESTITUTION;
+
+    pub fn open_dividers(&mut self) {
+        let mut opened = false;
+        for pane in &mut self.panes {
+            opened |= pane.world.open_dividers();
         }
+        self.notice.show(String::from(if opened {
+            "divider opened"
+        } else {
+            "no divider to open"
+        }));
+    }
 
-        // top/bottom
-        if (self.pos.y - self.rad) < 0.0 {
-            self.vel.y = self.vel.y.abs() * RESTITUTION;
-        } else if (self.pos.y + self.rad) > SCREEN_HEIGHT {
-            self.vel.y = self.vel.y.abs() * -RESTITUTION;
+    // open or close every gate by hand, taking them off the demon
+    pub fn toggle_gates(&mut self) {
+        let open = match self.panes[0].world.gates.first() {
+            Some(gate) => !gate.open,
+            None => {
+                self.notice.show(String::from("no gate to open"));
+                return;
+            }
+        };
+        for pane in &mut self.panes {
+            for gate in &mut pane.world.gates {
+                gate.open = open;
+                gate.demon = false;
+            }
         }
+        self.notice
+            .show(String::from(if open { "gate open" } else { "gate closed" }));
+    }
 
-        self.pos.x += self.vel.x * time_elapsed;
-        self.pos.y += self.vel.y * time_elapsed;
+    // hand every gate to the demon, or take them back closed
+    pub fn toggle_demon(&mut self) {
+        let demon = match self.panes[0].world.gates.first() {
+            Some(gate) => !gate.demon,
+            None => {
+                self.notice
+                    .show(String::from("no gate for a demon to mind"));
+                return;
+            }
+        };
+        for pane in &mut self.panes {
+            for gate in &mut pane.world.gates {
+                gate.demon = demon;
+                gate.open = false;
+            }
+        }
+        self.notice.show(if demon {
+            format!("demon minding the gate ({})", self.config.demon_policy)
+        } else {
+            String::from("demon off, gate closed")
+        });
+    }
 
-        // resistance increases with vel squared
-        let resistance_x = self.vel.x * self.vel.x * RESISTANCE.x;
-        let resistance_y = self.vel.y * self.vel.y * RESISTANCE.y;
+    // the wall being drawn, from where the drag started to the cursor, or the block
+    fn draw_wall_preview(&self, ctx: &mut Context) -> GameResult {
+        let (from, to) = match (self.wall_from, self.cursor) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Ok(()),
+        };
+        if self.tool == Tool::Blocks {
+            let block = Block::from_corners(from, to);
+            let rect = graphics::Rect::new(
+                block.min.x,
+                block.min.y,
+                block.max.x - block.min.x,
+                block.max.y - block.min.y,
+            );
+            let mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                rect,
+                WALL_PREVIEW_COLOR,
+            )?;
+            return graphics::draw(ctx, &mesh, graphics::DrawParam::default());
+        }
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        if (dx * dx + dy * dy).sqrt() < MIN_WALL_LENGTH {
+            return Ok(());
+        }
+        let line = graphics::Mesh::new_line(ctx, &[from, to], 3.0, WALL_PREVIEW_COLOR)?;
+        graphics::draw(ctx, &line, graphics::DrawParam::default())
+    }
 
-        self.vel.x += (ACCELERATION.x - resistance_x) * time_elapsed;
-        self.vel.y += (ACCELERATION.y - resistance_y) * time_elapsed;
+    // the same random particles in every pane
+    pub fn spawn_random(&mut self, count: usize) {
+        let seed = rand::random();
+        let theme = self.theme();
+        for pane in &mut self.panes {
+            pane.spawn_random(count, &self.config, theme, seed);
+        }
     }
-    pub fn is_colliding(&self, other: &Particle) -> bool {
-        self.distance(other) - (self.rad + other.rad) <= 0.5
+
+    // pick up whatever's under the cursor, in every pane so they stay comparable.
+    // false if there's nothing there
+    pub fn grab(&mut self, pos: Point) -> bool {
+        let (left, id) = match self.panes.iter().find(|pane| pane.contains_x(pos.x)) {
+            Some(pane) => {
+                let local = Point {
+                    x: pos.x - pane.left,
+                    y: pos.y,
+                };
+                match pane.world.particle_at(local) {
+                    Some(id) => (pane.left, id),
+                    None => return false,
+                }
+            }
+            None => return false,
+        };
+
+        self.held_left = Some(left);
+        for pane in &mut self.panes {
+            pane.world.hold = Some(Hold {
+                id,
+                target: Point {
+                    x: pos.x - left,
+                    y: pos.y,
+                },
+            });
+        }
+        true
     }
-    pub fn distance(&self, other: &Particle) -> f32 {
-        let dx = self.pos.x - other.pos.x;
-        let dy = self.pos.y - other.pos.y;
-        (dx * dx + dy * dy).sqrt()
+
+    // the particle under the cursor as json on the clipboard, false on empty space. without a
+    // clipboard it goes to the log instead, which is where it's also printed either way
+    pub fn copy_particle_at(&mut self, pos: Point) -> bool {
+        let index = match self.panes.iter().position(|pane| pane.contains_x(pos.x)) {
+            Some(index) => index,
+            None => return false,
+        };
+        let world = &self.panes[index].world;
+        let local = Point {
+            x: pos.x - self.panes[index].left,
+            y: pos.y,
+        };
+        let particle = match world.particle_at(local) {
+            Some(id) => world.particles.iter().find(|p| p.id == id),
+            None => None,
+        };
+        let particle = match particle {
+            Some(particle) => particle,
+            None => return false,
+        };
+
+        let json = CopiedParticle {
+            id: particle.id,
+            restitution: self.pane_config(index).restitution,
+            particle: particle.into(),
+        }
+        .to_json();
+        println!("particle #{}: {}", particle.id, json);
+        let id = particle.id;
+        match self.clipboard.copy(&json) {
+            Ok(()) => self.notice.show(format!("copied particle #{}", id)),
+            Err(e) => {
+                println!("couldn't reach the clipboard: {}", e);
+                self.notice
+                    .show(format!("no clipboard, particle #{} is in the log", id));
+            }
+        }
+        true
     }
-    pub fn vel_magnitude(&self) -> f32 {
-        (self.vel.x * self.vel.x + self.vel.y * self.vel.y).sqrt()
+
+    // delete whatever's under the cursor from every pane, false on empty space
+    pub fn delete_at(&mut self, pos: Point) -> bool {
+        let id = match self.panes.iter().find(|pane| pane.contains_x(pos.x)) {
+            Some(pane) => pane.world.particle_at(Point {
+                x: pos.x - pane.left,
+                y: pos.y,
+            }),
+            None => None,
+        };
+        let id = match id {
+            Some(id) => id,
+            None => return false,
+        };
+
+        self.delete(&[id]);
+        self.notice.show(format!("deleted particle #{}", id));
+        true
+    }
+
+    // take `ids` out of every pane, keeping them so the last delete can be undone
+    fn delete(&mut self, ids: &[u64]) -> usize {
+        let removed: Vec<Vec<Particle>> = self
+            .panes
+            .iter_mut()
+            .map(|pane| {
+                ids.iter()
+                    .filter_map(|&id| pane.remove_particle(id))
+                    .collect()
+            })
+            .collect();
+
+        for id in ids {
+            self.selection.remove(id);
+        }
+        if self.panes.iter().all(|pane| pane.world.hold.is_none()) {
+            self.held_left = None;
+        }
+
+        let count = removed.first().map_or(0, Vec::len);
+        if count > 0 {
+            self.undo_delete = Some(removed);
+        }
+        count
+    }
+
+    // pin whatever's under the cursor, or unpin it, in every pane. false on empty space
+    pub fn pin_at(&mut self, pos: Point) -> bool {
+        let id = match self.panes.iter().find(|pane| pane.contains_x(pos.x)) {
+            Some(pane) => pane.world.particle_at(Point {
+                x: pos.x - pane.left,
+                y: pos.y,
+            }),
+            None => None,
+        };
+        match id {
+            Some(id) => {
+                self.toggle_pinned(&[id]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn pin_selected(&mut self) {
+        let ids: Vec<u64> = self.selected_ids().collect();
+        self.toggle_pinned(&ids);
+    }
+
+    // mark the particle under `pos` to draw its path from here on, or unmark it, in every pane.
+    // false if there's no particle there
+    pub fn mark_tracer_at(&mut self, pos: Point) -> bool {
+        let pane = match self.panes.iter().find(|pane| pane.contains_x(pos.x)) {
+            Some(pane) => pane,
+            None => return false,
+        };
+        let id = match pane.world.particle_at(Point {
+            x: pos.x - pane.left,
+            y: pos.y,
+        }) {
+            Some(id) => id,
+            None => return false,
+        };
+        let mut marked = false;
+        for pane in &mut self.panes {
+            let color = pane.world.particles.iter().find(|p| p.id == id);
+            if let Some(color) = color.map(|p| p.original_color) {
+                marked = pane.tracers.toggle(id, color);
+            }
+        }
+        self.notice.show(format!(
+            "{} particle #{}",
+            if marked { "tracing" } else { "stopped tracing" },
+            id
+        ));
+        true
+    }
+
+    pub fn clear_tracers(&mut self) {
+        for pane in &mut self.panes {
+            pane.tracers.clear_paths();
+        }
+        self.notice.show(String::from("tracer paths cleared"));
+    }
+
+    // the first pane's marked paths
+    pub fn save_tracers(&mut self) {
+        let tracers = &self.panes[0].tracers;
+        if tracers.is_empty() {
+            self.notice
+                .show(String::from("no tracers, alt+click a particle to mark one"));
+            return;
+        }
+        let path = std::path::Path::new(&self.config.tracer_file);
+        match std::fs::write(path, tracers.csv(FIXED_STEP)) {
+            Ok(()) => {
+                println!("saved tracer paths to {}", path.display());
+                self.notice.show(format!("saved {}", path.display()));
+            }
+            Err(e) => {
+                println!("failed to save tracer paths: {}", e);
+                self.notice
+                    .show(String::from("failed to save the tracer paths"));
+            }
+        }
+    }
+
+    // if any of `ids` is loose they all get pinned, otherwise they're all let go
+    fn toggle_pinned(&mut self, ids: &[u64]) {
+        let world = &self.panes[0].world;
+        let pin = world
+            .particles
+            .iter()
+            .any(|p| !p.pinned && ids.contains(&p.id));
+        for pane in &mut self.panes {
+            for &id in ids {
+                pane.world.set_pinned(id, pin);
+            }
+        }
+        if !ids.is_empty() {
+            let verb = if pin { "pinned" } else { "unpinned" };
+            self.notice.show(match ids {
+                [id] => format!("{} particle #{}", verb, id),
+                _ => format!("{} {} particles", verb, ids.len()),
+            });
+        }
+    }
+
+    pub fn delete_selected(&mut self) {
+        let ids: Vec<u64> = self.selected_ids().collect();
+        let count = self.delete(&ids);
+        if count > 0 {
+            self.notice.show(format!("deleted {} particles", count));
+        }
+    }
+
+    // bring back everything the last delete took out, exactly as it was
+    pub fn undo_delete(&mut self) {
+        let removed = match self.undo_delete.take() {
+            Some(removed) => removed,
+            None => return,
+        };
+        let count = removed.first().map_or(0, Vec::len);
+        for (pane, particles) in self.panes.iter_mut().zip(removed) {
+            pane.world.restore(particles);
+        }
+        self.notice.show(format!("restored {} particles", count));
+    }
+
+    // the particles group operations act on
+    pub fn selected_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.selection.iter().copied()
+    }
+
+    // everything whose center is inside the rectangle from `from` to `to`, in whichever pane
+    // `from` is in. ids are the same in every pane so that picks out the same particles
+    pub fn select_rect(&mut self, from: Point, to: Point) {
+        let pane = match self.panes.iter().find(|pane| pane.contains_x(from.x)) {
+            Some(pane) => pane,
+            None => return,
+        };
+        let rect = Rect::new(
+            from.x.min(to.x) - pane.left,
+            from.y.min(to.y),
+            (from.x - to.x).abs(),
+            (from.y - to.y).abs(),
+        );
+        self.selection = pane
+            .world
+            .particles
+            .iter()
+            .filter(|p| rect.contains(p.pos))
+            .map(|p| p.id)
+            .collect();
+    }
+
+    // the rubber band while a selection is being dragged out
+    fn draw_selection_rect(&self, ctx: &mut Context) -> GameResult {
+        let (from, to) = match (self.selecting_from, self.cursor) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Ok(()),
+        };
+        let rect = Rect::new(
+            from.x.min(to.x),
+            from.y.min(to.y),
+            (from.x - to.x).abs().max(1.0),
+            (from.y - to.y).abs().max(1.0),
+        );
+        let mut builder = graphics::MeshBuilder::new();
+        builder.rectangle(graphics::DrawMode::fill(), rect, SELECTION_FILL);
+        builder.rectangle(graphics::DrawMode::stroke(1.0), rect, SELECTION_EDGE);
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+    }
+
+    fn move_grab(&mut self, pos: Point) {
+        let left = match self.held_left {
+            Some(left) => left,
+            None => return,
+        };
+        for pane in &mut self.panes {
+            if let Some(hold) = &mut pane.world.hold {
+                hold.target = Point {
+                    x: pos.x - left,
+                    y: pos.y,
+                };
+            }
+        }
+    }
+
+    fn release_grab(&mut self) {
+        self.held_left = None;
+        for pane in &mut self.panes {
+            pane.world.hold = None;
+        }
+    }
+
+    // a new particle held at the cursor until the button comes back up. a game's launcher
+    // holds it there instead, so there's no dropping it straight into a target
+    pub fn start_aim(&mut self, pos: Point, variant: Variant) {
+        let left = match self.panes.iter().find(|pane| pane.contains_x(pos.x)) {
+            Some(pane) => pane.left,
+            None => return,
+        };
+
+        let particle = self.new_particle(self.panes[0].game.launcher().unwrap_or(Point {
+            x: pos.x - left,
+            y: pos.y,
+        }));
+        let particle = Particle {
+            mass: self.spawn.mass(&self.config, variant),
+            ..particle
+        };
+        self.aim = Some(Aim::new(particle, left, variant));
+    }
+
+    // at rest at `local` in pane coordinates, sized by the spawn settings
+    fn new_particle(&self, local: Point) -> Particle {
+        let color = self
+            .theme()
+            .palette
+            .choose(&mut rand::thread_rng())
+            .expect("Some colors in the palette");
+        Particle::new(
+            local,
+            Vector { x: 0.0, y: 0.0 },
+            self.spawn.radius,
+            self.spawn.mass(&self.config, Variant::Normal),
+            *color,
+        )
+    }
+
+    // one notch of the mouse wheel, positive for bigger
+    pub fn scale_spawn_radius(&mut self, notches: f32) {
+        self.spawn.scale_radius(notches);
+        self.notice.show(format!(
+            "spawn radius {:.1}, mass {:.2}",
+            self.spawn.radius,
+            self.spawn.mass(&self.config, Variant::Normal)
+        ));
+    }
+
+    // the cursor in the coordinates of the pane it's over, none over the panel.
+    // forces from the cursor go to every pane at this point so they stay comparable
+    fn cursor_in_pane(&self) -> Option<Point> {
+        let cursor = self.cursor?;
+        if self.panel.contains(cursor.x, cursor.y) {
+            return None;
+        }
+        let pane = self.panes.iter().find(|pane| pane.contains_x(cursor.x))?;
+        Some(Point {
+            x: cursor.x - pane.left,
+            y: cursor.y,
+        })
+    }
+
+    pub fn explode(&mut self) {
+        if let Some(center) = self.cursor_in_pane() {
+            self.explode_at(center);
+        }
+    }
+
+    pub fn explode_at_reticle(&mut self) {
+        if let Some(center) = self.pane_point(self.reticle.pos) {
+            self.explode_at(center);
+        }
+    }
+
+    // a burst around the cursor, or the middle of the first pane with the cursor elsewhere.
+    // the same seed in every pane, so they all get the same particles
+    pub fn spawn_burst(&mut self) {
+        let center = self.cursor_in_pane().unwrap_or(Point {
+            x: 0.5 * self.panes[0].world.width,
+            y: 0.5 * self.panes[0].world.height,
+        });
+        let seed = rand::random();
+        let theme = self.theme();
+        let mut spawned = 0;
+        for pane in &mut self.panes {
+            spawned = pane.spawn_burst(center, &self.config, theme, seed);
+        }
+
+        let wanted = self.config.burst_count;
+        if spawned == wanted {
+            self.notice.show(format!("burst of {}", spawned));
+        } else if self.panes[0].world.particles.len() >= self.config.max_particles {
+            self.notice.show(format!(
+                "burst cut to {} of {}, max_particles is {}",
+                spawned, wanted, self.config.max_particles
+            ));
+        } else {
+            self.notice
+                .show(format!("burst cut to {} of {}, no room", spawned, wanted));
+        }
+    }
+
+    // the same blast at the same spot in every pane, random directions included
+    fn explode_at(&mut self, center: Point) {
+        let seed = rand::random();
+        for pane in &mut self.panes {
+            pane.explode(center, &self.config, seed);
+        }
+    }
+
+    fn repulsor(&self) -> Option<Point> {
+        self.cursor_in_pane().filter(|_| self.config.repulsion)
+    }
+
+    fn attractor(&self) -> Option<Point> {
+        self.cursor_in_pane().filter(|_| self.attracting)
+    }
+
+    // a faint line from everything being pulled in to where it's being pulled
+    fn draw_attraction(&self, ctx: &mut Context) -> GameResult {
+        let local = match self.attractor() {
+            Some(local) => local,
+            None => return Ok(()),
+        };
+
+        let mut builder = graphics::MeshBuilder::new();
+        let mut any = false;
+        for pane in &self.panes {
+            let center = Point {
+                x: pane.left + local.x,
+                y: local.y,
+            };
+            for particle in pane.snapshot() {
+                let dx = particle.pos.x - local.x;
+                let dy = particle.pos.y - local.y;
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance < 1.0 || distance >= self.config.attraction_radius {
+                    continue;
+                }
+                let from = Point {
+                    x: pane.left + particle.pos.x,
+                    y: particle.pos.y,
+                };
+                builder.line(&[from, center], 1.0, ATTRACTION_COLOR)?;
+                any = true;
+            }
+        }
+        if !any {
+            return Ok(());
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+    }
+
+    // a faint circle around the cursor showing how far the repulsion reaches
+    fn draw_repulsion(&self, ctx: &mut Context) -> GameResult {
+        let cursor = match self.cursor {
+            Some(cursor) if self.repulsor().is_some() => cursor,
+            _ => return Ok(()),
+        };
+        let circle = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::stroke(1.0),
+            cursor,
+            self.config.repulsion_radius,
+            0.5,
+            REPULSION_COLOR,
+        )?;
+        graphics::draw(ctx, &circle, graphics::DrawParam::default())
+    }
+
+    // the size the next mouse spawn will be, at the cursor
+    fn draw_spawn_ghost(&self, ctx: &mut Context) -> GameResult {
+        let cursor = match self.cursor {
+            Some(cursor)
+                if self.aim.is_none()
+                    && self.held_left.is_none()
+                    && self.tool != Tool::Walls
+                    && self.tool != Tool::Blocks
+                    && self.tool != Tool::Emitters =>
+            {
+                cursor
+            }
+            _ => return Ok(()),
+        };
+        if self.panel.contains(cursor.x, cursor.y)
+            || self.gravity_dial.contains(cursor, self.height)
+        {
+            return Ok(());
+        }
+        let variant = Variant::from_mods(ggez::input::keyboard::active_mods(ctx));
+        spawn::draw_ghost(ctx, cursor, self.spawn.radius, variant)
+    }
+
+    // let go of the aimed particle, at the same spot in every pane so they stay comparable
+    pub fn launch_aim(&mut self) {
+        let particle = match self.aim.take() {
+            Some(aim) => aim.launch(),
+            None => return,
+        };
+        // a game's launches are limited, and there are none once it's over
+        if !self.panes[0].game.can_launch() {
+            self.notice.show(String::from("no launches left"));
+            return;
+        }
+        for pane in &mut self.panes {
+            pane.game.launch();
+        }
+        self.spawn_everywhere(particle);
+    }
+
+    // let go of the spawn button, firing a particle from the reticle
+    pub fn launch_from_reticle(&mut self) {
+        let vel = match self.reticle.release_charge() {
+            Some(vel) => vel,
+            None => return,
+        };
+        let local = match self.pane_point(self.reticle.pos) {
+            Some(local) => local,
+            None => return,
+        };
+        let particle = Particle {
+            vel,
+            ..self.new_particle(local)
+        };
+        self.spawn_everywhere(particle);
+    }
+
+    // `particle` at its pane coordinates in every pane
+    fn spawn_everywhere(&mut self, particle: Particle) {
+        for pane in &mut self.panes {
+            if !pane.world.spawn_at(particle.clone(), &self.config) {
+                println!(
+                    "no room to spawn at ({:.0}, {:.0})",
+                    particle.pos.x, particle.pos.y
+                );
+            }
+        }
+    }
+
+    // the window changed size: split it between the panes
+    pub fn set_bounds(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+
+        let share = width / self.panes.len() as f32;
+        for (i, pane) in self.panes.iter_mut().enumerate() {
+            pane.left = share * i as f32;
+            pane.world.set_bounds(share, height);
+        }
+    }
+
+    // sizes here are logical pixels. mapping the screen to the logical size keeps the
+    // simulation the same size on 1x and 2x displays, ggez sizes the pixel viewport itself
+    pub fn fit_window(&mut self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        graphics::set_screen_coordinates(ctx, Rect::new(0.0, 0.0, width, height))?;
+        self.set_bounds(width, height);
+        Ok(())
+    }
+
+    // borderless fullscreen and back. the actual bounds change arrives through resize_event,
+    // where anything left outside the restored window is clamped back inside it
+    pub fn toggle_fullscreen(&mut self, ctx: &mut Context) -> GameResult {
+        let mode = window_mode(&self.config);
+
+        match self.windowed_size.take() {
+            Some((width, height)) => graphics::set_mode(
+                ctx,
+                mode.dimensions(width, height)
+                    .fullscreen_type(FullscreenType::Windowed),
+            ),
+            None => {
+                self.windowed_size = Some(graphics::drawable_size(ctx));
+                graphics::set_mode(ctx, mode.fullscreen_type(FullscreenType::Desktop))
+            }
+        }
+    }
+
+    // `steps` fixed steps in every pane, and everything that reacts to them.
+    // stepping while paused goes through here too, so it's the same as a live step
+    fn run_steps(&mut self, ctx: &mut Context, steps: usize) -> GameResult {
+        // every pane steps by the same amount so they stay comparable
+        let theme = self.theme();
+        for i in 0..self.panes.len() {
+            let config = self.pane_config(i);
+            self.panes[i].advance(steps, FIXED_STEP, &config, theme);
+        }
+        let time_elapsed = steps as f32 * FIXED_STEP;
+        crystal::cool(&mut self.config, time_elapsed);
+
+        // slow enough to follow, the last collision, or the selected particle's
+        if steps > 0 {
+            let selected = self.selected_ids().next();
+            let pane = &mut self.panes[0];
+            pane.collision_math
+                .follow(&pane.world, selected, self.paused, &self.config);
+        }
+
+        if let Some(summary) = self.panes[0].epidemic.announce() {
+            println!("{}", summary);
+        }
+        // a chain reaction that's over stops there, to look at what it left
+        if let Some(summary) = self.panes[0].chain_reaction.announce() {
+            println!("{}", summary);
+            self.paused = true;
+        }
+        let pane = &mut self.panes[0];
+        if let Some(summary) = pane.game.announce(&pane.world) {
+            println!("{}", summary);
+        }
+
+        if self.config.show_gas {
+            let pane = &mut self.panes[0];
+            if pane.gauge.log_due(&self.config) {
+                println!("{}", pane.gauge.read(&pane.world, &self.config).log_line());
+            }
+        }
+
+        if self.config.screen_shake {
+            for pane in &self.panes {
+                for event in &pane.world.events.collisions {
+                    self.shake.add(event.impulse, self.config.shake_threshold);
+                }
+            }
+        }
+        let events: Vec<_> = self.panes.iter().map(|pane| &pane.world.events).collect();
+        self.sounds.update(ctx, &events, time_elapsed, &self.config)
+    }
+
+    fn hud_lines(&self, ctx: &Context) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        match self.tool {
+            Tool::Particles => {}
+            Tool::Walls => lines.push(String::from("drawing walls (W to stop)")),
+            Tool::Pillars => lines.push(String::from("placing pillars (K to stop)")),
+            Tool::Wells => lines.push(String::from("placing gravity wells (A to stop)")),
+            Tool::BlackHoles => lines.push(String::from("placing black holes (Shift+H to stop)")),
+            Tool::Blocks => lines.push(String::from("placing blocks (J to stop)")),
+            Tool::Emitters => lines.push(String::from("placing emitters (V to stop)")),
+        }
+
+        if !self.selection.is_empty() {
+            lines.push(format!("selected: {}", self.selection.len()));
+        }
+
+        if self.paused {
+            lines.push(format!("PAUSED at step {}", self.panes[0].world.step_count));
+        }
+
+        lines.push(format!(
+            "gravity: ({:.1}, {:.1}){}",
+            self.config.gravity.x,
+            self.config.gravity.y,
+            if self.config.gravity_enabled {
+                ""
+            } else {
+                " off"
+            }
+        ));
+        lines.push(format!("forces: {}", self.forces_label()));
+
+        if self.config.sleeping {
+            let particles = &self.panes[0].world.particles;
+            let asleep = particles.iter().filter(|p| p.asleep).count();
+            lines.push(format!("asleep: {} of {}", asleep, particles.len()));
+        }
+        if self.config.weather != Weather::Off {
+            lines.push(format!(
+                "weather: {}, wind {:.0} px/s",
+                self.config.weather.name(),
+                self.config.wind
+            ));
+        }
+        let voids = &self.panes[0].world.voids;
+        if !voids.is_empty() {
+            let counts: Vec<String> = voids.iter().map(|v| v.consumed.to_string()).collect();
+            lines.push(format!("lost down the voids: {}", counts.join(", ")));
+        }
+        let holes = &self.panes[0].world.black_holes;
+        if !holes.is_empty() {
+            let counts: Vec<String> = holes.iter().map(|h| h.consumed.to_string()).collect();
+            lines.push(format!("swallowed by black holes: {}", counts.join(", ")));
+        }
+        if self.config.boundary == Boundary::Channel {
+            let flows: Vec<String> = self
+                .panes
+                .iter()
+                .map(|pane| format!("{:.1}", pane.world.flow()))
+                .collect();
+            lines.push(format!("flow: {} px/s", flows.join(", ")));
+        }
+
+        if let Some(readout) = PRESETS[self.preset].readout {
+            lines.push(readout(&self.panes[0].world, &self.config));
+        }
+        lines.extend(self.panes[0].tracer.hud_line());
+        if self.config.show_gas {
+            let pane = &self.panes[0];
+            lines.push(pane.gauge.read(&pane.world, &self.config).hud_line());
+        }
+        if self.config.maxwell {
+            let pane = &self.panes[0];
+            lines.push(pane.maxwell.hud_line(&pane.world, &self.config));
+        }
+        lines.extend(self.panes[0].epidemic.hud_line(&self.panes[0].world));
+        lines.extend(self.panes[0].populations.hud_line(&self.panes[0].world));
+        lines.extend(self.panes[0].mixing.hud_line(&self.panes[0].world));
+        lines.extend(self.panes[0].reactions.hud_line(&self.panes[0].world));
+        lines.extend(self.panes[0].chain_reaction.hud_line(&self.panes[0].world));
+        lines.extend(crystal::hud_line(&self.panes[0].world, &self.config));
+        lines.extend(demon::hud_line(&self.panes[0].world, &self.config));
+        lines.extend(self.panes[0].chaos.as_ref().map(|chaos| chaos.hud_line()));
+        lines.extend(self.panes[0].game.hud_line(&self.panes[0].world));
+        if let Some(stress) = &self.stress {
+            let particles = self
+                .panes
+                .iter()
+                .map(|pane| pane.world.particles.len())
+                .sum();
+            lines.push(stress.hud_line(particles));
+        }
+
+        if self.config.show_fps {
+            let cap = match self.config.max_fps {
+                Some(fps) if fps > 0.0 => format!("cap {:.0}", fps),
+                _ if self.config.vsync => String::from("vsync"),
+                _ => String::from("uncapped"),
+            };
+            lines.push(format!("fps: {:.1} ({})", ggez::timer::fps(ctx), cap));
+        }
+
+        if self.config.show_center_of_mass {
+            lines.push(
+                match center_of_mass::compute(&self.panes[0].world.particles) {
+                    Some((_, vel)) => {
+                        format!("center of mass velocity: ({:.2}, {:.2})", vel.x, vel.y)
+                    }
+                    None => String::from("center of mass velocity: -"),
+                },
+            );
+        }
+
+        lines
+    }
+
+    // a line between the panes and what each one is running with at the top
+    fn draw_comparison(&self, ctx: &mut Context) -> GameResult {
+        let overrides = match &self.compare {
+            Some(overrides) if self.panes.len() > 1 => overrides,
+            _ => return Ok(()),
+        };
+
+        let x = self.panes[1].left;
+        let divider = graphics::Mesh::new_line(
+            ctx,
+            &[Point { x, y: 0.0 }, Point { x, y: self.height }],
+            DIVIDER_WIDTH,
+            DIVIDER_COLOR,
+        )?;
+        graphics::draw(ctx, &divider, graphics::DrawParam::default())?;
+
+        for (i, pane) in self.panes.iter().enumerate() {
+            let side = if i == 0 { "A" } else { "B" };
+            let energy = pane.world.kinetic_energy() / pane.start_energy.max(f32::EPSILON);
+            let text = graphics::Text::new(format!(
+                "{}: {}, kinetic energy {:.0}% of the start",
+                side,
+                overrides.describe(&self.pane_config(i)),
+                100.0 * energy
+            ));
+            let dest = Point {
+                x: pane.left + 10.0,
+                y: self.height - 10.0 - text.height(ctx) as f32,
+            };
+            graphics::draw(ctx, &text, graphics::DrawParam::default().dest(dest))?;
+        }
+        Ok(())
+    }
+
+    // everything a key can do. which keys do what is up to the KeyMap
+    fn perform(&mut self, action: Action, ctx: &mut Context) {
+        match action {
+            Action::ToggleHelp => self.help_open = !self.help_open,
+            Action::Reseed => self.reseed(),
+            Action::ResetLeft => self.reset_pane(0),
+            Action::ResetRight => self.reset_pane(1),
+            Action::Reset => self.reset(),
+            Action::Preset1 => self.load_preset(0),
+            Action::Preset2 => self.load_preset(1),
+            Action::Preset3 => self.load_preset(2),
+            Action::Preset4 => self.load_preset(3),
+            Action::Preset5 => self.load_preset(4),
+            Action::Preset6 => self.load_preset(5),
+            Action::Preset7 => self.load_preset(6),
+            Action::Preset8 => self.load_preset(7),
+            Action::Preset9 => self.load_preset(8),
+            Action::Preset10 => self.load_preset(9),
+            Action::NextPreset => self.load_preset((self.preset + 1) % PRESETS.len()),
+            Action::PreviousPreset => {
+                self.load_preset((self.preset + PRESETS.len() - 1) % PRESETS.len())
+            }
+            Action::Pause => self.paused = !self.paused,
+            Action::Step => {
+                if self.paused {
+                    if let Err(e) = self.run_steps(ctx, 1) {
+                        println!("failed to step: {}", e);
+                    }
+                }
+            }
+            Action::SlowDown => self.step_time_scale(false),
+            Action::SpeedUp => self.step_time_scale(true),
+            Action::GravityCoarseUp
+            | Action::GravityCoarseDown
+            | Action::GravityCoarseLeft
+            | Action::GravityCoarseRight
+            | Action::GravityUp
+            | Action::GravityDown
+            | Action::GravityLeft
+            | Action::GravityRight => self.nudge_gravity(action),
+            Action::ToggleGravity => {
+                self.config.gravity_enabled = !self.config.gravity_enabled;
+                self.show_forces();
+            }
+            Action::FlipGravity => {
+                self.config.gravity.x = -self.config.gravity.x;
+                self.config.gravity.y = -self.config.gravity.y;
+                self.show_gravity();
+            }
+            Action::ToggleDrag => {
+                self.config.drag_enabled = !self.config.drag_enabled;
+                self.show_forces();
+            }
+            Action::NextBoundary => {
+                self.config.boundary = self.config.boundary.next();
+                self.notice
+                    .show(format!("edges: {}", self.config.boundary.name()));
+            }
+            Action::ToggleWells => {
+                self.config.wells_enabled = !self.config.wells_enabled;
+                self.show_forces();
+            }
+            Action::ToggleRepulsion => self.config.repulsion = !self.config.repulsion,
+            // only held, while a left drag picks out the rectangle
+            Action::Select => {}
+            Action::DeleteSelected => self.delete_selected(),
+            Action::PinSelected => self.pin_selected(),
+            Action::UndoDelete => self.undo_delete(),
+            Action::ClearTracers => self.clear_tracers(),
+            Action::SaveTracers => self.save_tracers(),
+            Action::RemoveLastWall => self.remove_last_wall(),
+            Action::ClearObstacles => self.clear_obstacles(),
+            Action::OpenDividers => self.open_dividers(),
+            Action::ToggleGate => self.toggle_gates(),
+            Action::ToggleDemon => self.toggle_demon(),
+            Action::NextWeather => self.next_weather(),
+            Action::Reheat => self.reheat(),
+            Action::WallTool => self.toggle_tool(Tool::Walls),
+            Action::PillarTool => self.toggle_tool(Tool::Pillars),
+            Action::WellTool => self.toggle_tool(Tool::Wells),
+            Action::BlackHoleTool => self.toggle_tool(Tool::BlackHoles),
+            Action::BlockTool => self.toggle_tool(Tool::Blocks),
+            Action::EmitterTool => self.toggle_tool(Tool::Emitters),
+            Action::SaveScene => self.save_scene(),
+            Action::LoadScene => self.load_scene(),
+            Action::TogglePanel => self.panel.toggle(),
+            Action::NextTheme => self.next_theme(),
+            Action::NextColorPolicy => self.next_color_policy(),
+            Action::ToggleGrid => self.config.show_grid = !self.config.show_grid,
+            Action::ToggleHeatMap => self.config.heat_map = !self.config.heat_map,
+            Action::ToggleCollisionMath => self.config.collision_math = !self.config.collision_math,
+            Action::ToggleSquash => self.config.squash_stretch = !self.config.squash_stretch,
+            Action::ToggleGlow => self.config.glow = !self.config.glow,
+            Action::NextParticleStyle => {
+                self.config.particle_style = self.config.particle_style.next()
+            }
+            Action::Explode => self.explode(),
+            Action::Burst => self.spawn_burst(),
+            Action::TogglePlexus => self.config.plexus = !self.config.plexus,
+            Action::NextLabels => self.config.labels = self.config.labels.next(),
+            Action::ToggleCenterOfMass => {
+                self.config.show_center_of_mass = !self.config.show_center_of_mass
+            }
+            Action::ToggleGas => self.config.show_gas = !self.config.show_gas,
+            Action::ToggleMaxwell => self.config.maxwell = !self.config.maxwell,
+            Action::ToggleBoids => self.set_boids(!self.config.boids),
+            Action::ToggleBoidRadius => {
+                self.config.show_boid_radius = !self.config.show_boid_radius
+            }
+            Action::NextInsetTarget => self.next_inset_target(),
+            Action::ToggleInset => self.config.inset = !self.config.inset,
+            Action::NextClearMode => self.config.clear_mode = self.config.clear_mode.next(),
+            Action::ToggleSound => self.config.sound = !self.config.sound,
+            Action::ToggleFps => self.config.show_fps = !self.config.show_fps,
+            Action::ToggleFullscreen => {
+                if let Err(e) = self.toggle_fullscreen(ctx) {
+                    println!("failed to toggle fullscreen: {}", e);
+                }
+            }
+            Action::DemoReel => self.demo_reel(),
+            Action::StressTest => self.stress_test(),
+            Action::Menu => self.menu.show(),
+            Action::Quit => event::quit(ctx),
+        }
+    }
+}
+
+impl EventHandler for GameState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        // the reticle moves while paused too, like the mouse
+        let dt = ggez::timer::delta(ctx).as_secs_f32();
+        self.reticle.update(ctx, dt, self.width, self.height);
+        if !self.menu.open {
+            if let Some(demo) = &mut self.demo {
+                demo.advance(dt);
+            }
+        }
+
+        // nothing is added to the accumulator while frozen, so resuming
+        // carries on from the same fraction of a step rather than catching up
+        if self.frozen() {
+            return Ok(());
+        }
+
+        // the physics always moves in steps of FIXED_STEP, so a faster time scale means more
+        // steps rather than bigger ones. whatever doesn't make a whole step waits for next frame
+        let frame_time = ggez::timer::delta(ctx).as_secs_f32() * self.config.time_scale.max(0.0);
+        self.accumulator += frame_time;
+        let mut steps = (self.accumulator / FIXED_STEP) as usize;
+        self.accumulator -= steps as f32 * FIXED_STEP;
+        if steps > MAX_STEPS_PER_FRAME {
+            // too far behind to catch up, drop the rest rather than spiral
+            steps = MAX_STEPS_PER_FRAME;
+            self.accumulator = 0.0;
+        }
+
+        let (repulsor, attractor) = (self.repulsor(), self.attractor());
+        for pane in &mut self.panes {
+            pane.world.repulsor = repulsor;
+            pane.world.attractor = attractor;
+        }
+
+        let started = Instant::now();
+        let result = self.run_steps(ctx, steps);
+        self.physics_time = started.elapsed().as_secs_f32();
+        result
+    }
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let started = Instant::now();
+        self.next_demo_item(ctx)?;
+        self.scene_canvas.begin(
+            ctx,
+            self.config.clear_mode,
+            self.theme().background,
+            self.config.fade_alpha,
+            self.frozen(),
+        )?;
+
+        let shake = if self.config.screen_shake {
+            let dt = ggez::timer::delta(ctx).as_secs_f32();
+            self.shake.update(dt, self.config.shake_intensity);
+            self.shake.offset()
+        } else {
+            [0.0, 0.0]
+        };
+
+        // drawn part way between the last two steps, so slow motion still moves every frame.
+        // paused shows exactly where the last step left things, so stepping shows each one
+        let alpha = if self.paused {
+            1.0
+        } else {
+            (self.accumulator / FIXED_STEP).clamp(0.0, 1.0)
+        };
+        let theme = self.theme();
+        for pane in &mut self.panes {
+            pane.interpolate(alpha);
+            pane.draw_scene(
+                ctx,
+                &self.config,
+                self.particle_image.as_ref(),
+                &self.selection,
+                theme,
+                shake,
+            )?;
+        }
+
+        // text and panels go on top of the blurred scene so they never smear
+        self.scene_canvas.finish(ctx)?;
+
+        // labels move with their particles, the hud and panel stay put
+        for pane in &self.panes {
+            pane.draw_labels(ctx, &self.config, self.font, shake)?;
+        }
+        if let Some(demo) = &mut self.demo {
+            demo.draw_fade(ctx, ggez::timer::delta(ctx).as_secs_f32())?;
+        }
+
+        if let Some(aim) = &self.aim {
+            aim.draw(ctx)?;
+        }
+        self.draw_spawn_ghost(ctx)?;
+        self.draw_repulsion(ctx)?;
+        self.draw_selection_rect(ctx)?;
+        self.draw_wall_preview(ctx)?;
+        self.draw_attraction(ctx)?;
+        self.reticle.draw(ctx)?;
+        self.draw_comparison(ctx)?;
+        if self.config.inset {
+            let pane = &self.panes[0];
+            let selected = self.selected_ids().next();
+            self.inset
+                .update(pane.snapshot(), self.config.inset_target, selected);
+            let rect = Rect::new(pane.left, 0.0, pane.world.width, pane.world.height);
+            self.inset.draw(
+                ctx,
+                pane.snapshot(),
+                rect,
+                &self.config,
+                self.particle_image.as_ref(),
+                self.theme().background,
+            )?;
+        }
+        let pane = &self.panes[0];
+        let rect = Rect::new(pane.left, 0.0, pane.world.width, pane.world.height);
+        if self.config.maxwell {
+            pane.maxwell.draw(ctx, rect)?;
+        }
+        pane.epidemic.draw(ctx, rect)?;
+        pane.populations.draw(ctx, rect)?;
+        pane.mixing.draw(ctx, rect)?;
+        pane.reactions.draw(ctx, rect)?;
+        if let Some(chaos) = &pane.chaos {
+            chaos.draw(ctx, rect)?;
+        }
+        hud::draw(ctx, &self.hud_lines(ctx), self.width)?;
+        hud::draw_gravity(ctx, self.config.active_gravity(), self.height)?;
+        self.notice.draw(ctx, self.width)?;
+        if let Some(demo) = &self.demo {
+            demo.draw_caption(ctx, self.font, self.width, self.height)?;
+        }
+        let pane = &self.panes[0];
+        let again = self.keys.keys_for(Action::LoadScene).unwrap_or_default();
+        pane.game
+            .draw_results(ctx, &pane.world, &again, self.width, self.height)?;
+        self.panel.draw(ctx, self)?;
+
+        if self.help_open {
+            help::draw(ctx, &self.keys, PAD_BINDINGS, self.width, self.height)?;
+        }
+        self.menu.draw(ctx, self.width, self.height)?;
+
+        // the stress test leaves out waiting on the screen, so vsync doesn't count
+        let drawing = started.elapsed().as_secs_f32();
+        graphics::present(ctx)?;
+        self.frame_limiter.wait(self.config.max_fps);
+        self.record_stress(drawing, ggez::timer::delta(ctx).as_secs_f32());
+        Ok(())
+    }
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        let pos = window_to_world(ctx, x, y);
+        let mods = ggez::input::keyboard::active_mods(ctx);
+        let variant = Variant::from_mods(mods);
+        self.cursor = Some(pos);
+        // the menu takes every click while it's up
+        if self.menu.open {
+            if button == MouseButton::Left {
+                if let Some(entry) = self.menu.mouse_down(pos, self.width, self.height) {
+                    (entry.action)(self, ctx);
+                }
+            }
+            return;
+        }
+        if button == MouseButton::Left {
+            if self.panel.contains(pos.x, pos.y) {
+                if let Some(edit) = self.panel.mouse_down(pos.x, pos.y) {
+                    edit.apply(self);
+                }
+            } else if self.gravity_dial.contains(pos, self.height) {
+                self.config.gravity = self.gravity_dial.mouse_down(pos, self.height);
+                self.show_gravity();
+            } else if matches!(self.tool, Tool::Walls | Tool::Blocks | Tool::Emitters) {
+                self.wall_from = Some(pos);
+            } else if self.tool == Tool::Pillars {
+                self.add_pillar(pos);
+            } else if self.tool == Tool::Wells {
+                self.add_well(pos);
+            } else if self.tool == Tool::BlackHoles {
+                self.add_black_hole(pos);
+            } else if self.keys.held(ctx, Action::Select) {
+                self.selecting_from = Some(pos);
+            } else if mods.contains(KeyMods::ALT) && self.mark_tracer_at(pos) {
+                // alt on empty space spawns as usual
+            } else if mods.contains(KeyMods::CTRL) && self.copy_particle_at(pos) {
+                // ctrl on empty space still spawns a light particle
+            } else if !self.grab(pos) {
+                // with a selection, clicking empty space clears it rather than spawning
+                if self.selection.is_empty() {
+                    self.start_aim(pos, variant);
+                } else {
+                    self.selection.clear();
+                }
+            }
+        } else if button == MouseButton::Right {
+            // cancelling an aim takes priority over deleting, then particles, pillars, black
+            // holes, wells, emitters and blocks in the order they're drawn, top first
+            if self.aim.take().is_none()
+                && !self.delete_at(pos)
+                && !self.remove_pillar_at(pos)
+                && !self.remove_black_hole_at(pos)
+                && !self.remove_well_at(pos)
+                && !self.remove_emitter_at(pos)
+            {
+                self.remove_block_at(pos);
+            }
+        } else if button == MouseButton::Middle {
+            // on a particle it pins it, on empty space it pulls things in
+            if !self.pin_at(pos) {
+                self.attracting = true;
+            }
+        }
+    }
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if button == MouseButton::Left {
+            let pos = window_to_world(ctx, x, y);
+            if let Some(from) = self.selecting_from.take() {
+                self.select_rect(from, pos);
+            }
+            if let Some(from) = self.wall_from.take() {
+                match self.tool {
+                    Tool::Blocks => self.add_block(from, pos),
+                    Tool::Emitters => self.add_emitter(from, pos),
+                    _ => self.add_wall(from, pos),
+                }
+            }
+            self.panel.mouse_up();
+            self.gravity_dial.mouse_up();
+            self.launch_aim();
+            self.release_grab();
+        } else if button == MouseButton::Middle {
+            self.attracting = false;
+        }
+    }
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        let pos = window_to_world(ctx, x, y);
+        self.cursor = Some(pos);
+        // the mouse takes over from the gamepad
+        self.reticle.deactivate();
+        if self.menu.open {
+            self.menu.mouse_motion(pos, self.width, self.height);
+            return;
+        }
+        if let Some(gravity) = self.gravity_dial.mouse_motion(pos, self.height) {
+            self.config.gravity = gravity;
+            self.show_gravity();
+            return;
+        }
+        if let Some(aim) = &mut self.aim {
+            aim.cursor = pos;
+        }
+        self.move_grab(pos);
+        if let Some(edit) = self.panel.mouse_motion(pos.x, pos.y) {
+            edit.apply(self);
+        }
+    }
+    // over a well the wheel sets its strength, anywhere else the size of the next spawn
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) {
+        if y == 0.0 || self.menu.open {
+            return;
+        }
+        let on_well = match self.cursor {
+            Some(cursor) => self.scale_well_at(cursor, y.signum()),
+            None => false,
+        };
+        if !on_well {
+            self.scale_spawn_radius(y.signum());
+        }
+    }
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, button: Button, id: GamepadId) {
+        self.reticle.activate(id);
+        if let Some(binding) = PadBinding::find(PAD_BINDINGS, button) {
+            (binding.press)(self, ctx);
+        }
+    }
+    fn gamepad_button_up_event(&mut self, ctx: &mut Context, button: Button, _id: GamepadId) {
+        if let Some(release) = PadBinding::find(PAD_BINDINGS, button).and_then(|b| b.release) {
+            release(self, ctx);
+        }
+    }
+    fn gamepad_axis_event(&mut self, _ctx: &mut Context, axis: Axis, value: f32, id: GamepadId) {
+        self.reticle.axis(axis, value, id);
+    }
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        if let Err(e) = self.fit_window(ctx, width, height) {
+            println!("failed to resize: {}", e);
+        }
+    }
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        // the menu has the keyboard to itself, esc the same as resume
+        if self.menu.open {
+            match keycode {
+                KeyCode::Escape if !repeat => self.menu.close(),
+                KeyCode::Up => self.menu.move_selection(false),
+                KeyCode::Down => self.menu.move_selection(true),
+                KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space if !repeat => {
+                    let entry = self.menu.choose();
+                    (entry.action)(self, ctx);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        // a held key only goes again for what's meant to be held, esc included
+        let action = self.keys.press(keycode, keymods, repeat);
+        if repeat && action.is_none() {
+            return;
+        }
+
+        // with the help open, esc closes it instead of quitting
+        let closes_help = keycode == KeyCode::Escape || action == Some(Action::ToggleHelp);
+        if self.help_open && closes_help {
+            self.help_open = false;
+            return;
+        }
+
+        // esc drops a particle being aimed rather than quitting
+        if self.aim.is_some() && keycode == KeyCode::Escape {
+            self.aim = None;
+            return;
+        }
+
+        // and stops the demo reel or the stress test rather than opening the menu
+        if self.demo.is_some() && keycode == KeyCode::Escape {
+            self.stop_demo();
+            return;
+        }
+        if self.stress.is_some() && keycode == KeyCode::Escape {
+            self.stop_stress();
+            return;
+        }
+
+        if let Some(action) = action {
+            self.perform(action, ctx);
+        }
     }
 }
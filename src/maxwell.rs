@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::config::SimulationConfig;
+use crate::presets::{Layout, PRESETS};
+use crate::theme::{self, THEMES};
+use crate::world::{WallStyle, World};
+use crate::{Point, Vector};
+
+// how often every particle's speed goes in once the gas has settled, in simulated seconds.
+// far enough apart that most have hit something between one and the next
+const SAMPLE_TIME: f32 = 0.25;
+// the newest ones kept, a couple of minutes of a few hundred particles
+const MAX_SAMPLES: usize = 100_000;
+// a fit at least this close passes, see Fit
+pub const GOOD_FIT: f32 = 0.05;
+
+const BINS: usize = 40;
+// the chart reaches this many times the typical speed of the lightest particle
+const TOP_SPEED: f32 = 3.7;
+const CURVE_POINTS: usize = 100;
+const CHART_WIDTH: f32 = 320.0;
+const CHART_HEIGHT: f32 = 140.0;
+const MARGIN: f32 = 10.0;
+const BACKING_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+const BAR_COLOR: Color = Color::new(0.3, 0.6, 1.0, 0.8);
+const CURVE_COLOR: Color = Color::new(1.0, 0.85, 0.3, 1.0);
+const CURVE_WIDTH: f32 = 2.0;
+
+// the check --check-maxwell runs: the dense gas settling for maxwell_settle seconds, then this
+// long of sampling
+const CHECK_TIME: f32 = 60.0;
+const CHECK_SEED: u64 = 1;
+const CHECK_STEP: f32 = 1.0 / 120.0;
+
+// how well the speeds match the 2d maxwell-boltzmann distribution at the temperature they
+// come to. in 2d the kinetic energies go exponential with mean T whatever the mass, which is
+// the same as every mass's speeds going maxwell-boltzmann, so the fit's on those:
+// the kolmogorov-smirnov distance, the furthest the share of samples below any energy gets
+// from the share there should be. 0 is perfect, a shuffle that never mixes x and y stays
+// well over GOOD_FIT
+pub struct Fit {
+    // the mean kinetic energy, see gas::Reading for the units
+    pub temperature: f32,
+    pub distance: f32,
+    pub samples: usize,
+    // the measured and expected share of speeds per unit speed, across the chart
+    pub histogram: Vec<f32>,
+    pub curve: Vec<f32>,
+}
+
+// speed and mass pairs
+pub fn fit(samples: &[(f32, f32)], masses: &[f32]) -> Option<Fit> {
+    if samples.is_empty() || masses.is_empty() {
+        return None;
+    }
+    let mut energies: Vec<f32> = samples
+        .iter()
+        .map(|&(speed, mass)| 0.5 * mass * speed * speed)
+        .collect();
+    let n = energies.len() as f32;
+    let temperature = energies.iter().sum::<f32>() / n;
+    if temperature <= f32::EPSILON {
+        return None;
+    }
+
+    energies.sort_by(f32::total_cmp);
+    let mut distance: f32 = 0.0;
+    for (i, energy) in energies.iter().enumerate() {
+        let expected = 1.0 - (-energy / temperature).exp();
+        distance = distance
+            .max((i as f32 + 1.0) / n - expected)
+            .max(expected - i as f32 / n);
+    }
+
+    let lightest = masses.iter().copied().fold(f32::INFINITY, f32::min);
+    let top_speed = TOP_SPEED * (temperature / lightest.max(f32::EPSILON)).sqrt();
+    let width = top_speed / BINS as f32;
+    let mut histogram = vec![0.0; BINS];
+    for &(speed, _) in samples {
+        let bin = (speed / width) as usize;
+        if bin < BINS {
+            histogram[bin] += 1.0 / (n * width);
+        }
+    }
+    // the particles there are now stand in for the mix of masses
+    let curve = (0..=CURVE_POINTS)
+        .map(|i| {
+            let speed = top_speed * i as f32 / CURVE_POINTS as f32;
+            let total: f32 = masses
+                .iter()
+                .map(|m| m * speed / temperature * (-m * speed * speed / (2.0 * temperature)).exp())
+                .sum();
+            total / masses.len() as f32
+        })
+        .collect();
+
+    Some(Fit {
+        temperature,
+        distance,
+        samples: samples.len(),
+        histogram,
+        curve,
+    })
+}
+
+// why the distribution wouldn't be maxwell-boltzmann, if it wouldn't: anything pulling,
+// slowing, heating or losing energy
+pub fn unsuitable(world: &World, config: &SimulationConfig) -> Option<&'static str> {
+    let gravity = config.active_gravity();
+    if gravity.x != 0.0 || gravity.y != 0.0 {
+        Some("needs no gravity")
+    } else if config.active_drag() != 0.0 {
+        Some("needs no drag")
+    } else if config.restitution != 1.0 {
+        Some("needs restitution 1")
+    } else if config.wall_style == WallStyle::Soft && config.wall_damping != 0.0 {
+        Some("needs undamped walls")
+    } else if config.thermostats.any() {
+        Some("needs no thermostats")
+    } else if config.wells_enabled && !world.wells.is_empty() {
+        Some("needs no wells")
+    } else if !world.zones.is_empty() {
+        Some("needs no zones")
+    } else {
+        None
+    }
+}
+
+// the speeds since the gas last settled, and how they fit
+#[derive(Default)]
+pub struct Maxwell {
+    // simulated seconds it's been left alone for
+    settling: f32,
+    since_sample: f32,
+    samples: VecDeque<(f32, f32)>,
+    fit: Option<Fit>,
+}
+
+impl Maxwell {
+    pub fn clear(&mut self) {
+        *self = Maxwell::default();
+    }
+
+    // `time_elapsed` after the last record. anything that makes the distribution not apply
+    // starts the settling again
+    pub fn record(&mut self, world: &World, time_elapsed: f32, config: &SimulationConfig) {
+        if unsuitable(world, config).is_some() {
+            self.clear();
+            return;
+        }
+        self.settling += time_elapsed;
+        if self.settling < config.maxwell_settle {
+            return;
+        }
+        self.since_sample += time_elapsed;
+        if self.since_sample < SAMPLE_TIME {
+            return;
+        }
+        self.since_sample -= SAMPLE_TIME;
+
+        let moving = world.particles.iter().filter(|p| !p.pinned);
+        self.samples
+            .extend(moving.clone().map(|p| (p.vel_magnitude(), p.mass)));
+        while self.samples.len() > MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        let masses: Vec<f32> = moving.map(|p| p.mass).collect();
+        self.fit = fit(self.samples.make_contiguous(), &masses);
+    }
+
+    pub fn fit(&self) -> Option<&Fit> {
+        self.fit.as_ref()
+    }
+
+    pub fn hud_line(&self, world: &World, config: &SimulationConfig) -> String {
+        if let Some(reason) = unsuitable(world, config) {
+            return format!("maxwell-boltzmann: {}", reason);
+        }
+        match &self.fit {
+            Some(fit) => format!(
+                "maxwell-boltzmann: T {:.0}, distance {:.3} over {} speeds ({})",
+                fit.temperature,
+                fit.distance,
+                fit.samples,
+                if fit.distance <= GOOD_FIT {
+                    "fits"
+                } else {
+                    "doesn't fit"
+                }
+            ),
+            None => format!(
+                "maxwell-boltzmann: settling, {:.0}s to go",
+                (config.maxwell_settle - self.settling).max(0.0)
+            ),
+        }
+    }
+
+    // the histogram of speeds with the distribution over it, along the bottom of `pane`
+    pub fn draw(&self, ctx: &mut Context, pane: Rect) -> GameResult {
+        let fit = match &self.fit {
+            Some(fit) => fit,
+            None => return Ok(()),
+        };
+        let chart = Rect::new(
+            pane.x + 0.5 * (pane.w - CHART_WIDTH),
+            pane.y + pane.h - MARGIN - CHART_HEIGHT,
+            CHART_WIDTH,
+            CHART_HEIGHT,
+        );
+        let tallest = fit
+            .histogram
+            .iter()
+            .chain(&fit.curve)
+            .copied()
+            .fold(f32::EPSILON, f32::max);
+        let height = |density: f32| chart.h * density / tallest;
+
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(DrawMode::fill(), chart, BACKING_COLOR);
+        let bar = chart.w / BINS as f32;
+        for (i, density) in fit.histogram.iter().enumerate() {
+            let h = height(*density);
+            if h > 0.0 {
+                builder.rectangle(
+                    DrawMode::fill(),
+                    Rect::new(chart.x + bar * i as f32, chart.y + chart.h - h, bar, h),
+                    BAR_COLOR,
+                );
+            }
+        }
+        let curve: Vec<Point> = fit
+            .curve
+            .iter()
+            .enumerate()
+            .map(|(i, density)| Point {
+                x: chart.x + chart.w * i as f32 / CURVE_POINTS as f32,
+                y: chart.y + chart.h - height(*density),
+            })
+            .collect();
+        builder.line(&curve, CURVE_WIDTH, CURVE_COLOR)?;
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
+
+// the dense gas preset in a box with nothing to stop it settling, run for maxwell_settle then
+// CHECK_TIME seconds. prints the fit, and whether it passed for the exit code
+pub fn check(config: &SimulationConfig) -> bool {
+    let config = SimulationConfig {
+        gravity: Vector { x: 0.0, y: 0.0 },
+        drag: 0.0,
+        restitution: 1.0,
+        wall_style: WallStyle::Hard,
+        thermostats: Default::default(),
+        log_collisions: false,
+        ..config.clone()
+    };
+    let theme = &THEMES[theme::find(&config.theme).unwrap_or(0)];
+    let layout = Layout {
+        width: config.window_width,
+        height: config.window_height,
+        config: &config,
+        theme,
+    };
+    let preset = PRESETS
+        .iter()
+        .find(|preset| preset.name == "dense gas")
+        .expect("a dense gas preset");
+    let scene = (preset.build)(&layout, &mut StdRng::seed_from_u64(CHECK_SEED));
+
+    let mut world = World::new(layout.width, layout.height);
+    world.load(scene.particles);
+    let mut maxwell = Maxwell::default();
+    let steps = ((config.maxwell_settle + CHECK_TIME) / CHECK_STEP).ceil() as usize;
+    for _ in 0..steps {
+        world.events.clear();
+        world.step(CHECK_STEP, &config, theme);
+        maxwell.record(&world, CHECK_STEP, &config);
+    }
+
+    match maxwell.fit() {
+        Some(fit) => {
+            let passed = fit.distance <= GOOD_FIT;
+            println!(
+                "maxwell-boltzmann: T {:.0}, distance {:.4} over {} speeds, {} (at most {})",
+                fit.temperature,
+                fit.distance,
+                fit.samples,
+                if passed { "passed" } else { "failed" },
+                GOOD_FIT
+            );
+            passed
+        }
+        None => {
+            println!("maxwell-boltzmann: nothing to fit");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, STEP};
+
+    #[test]
+    fn dense_gas_settles_into_maxwell_boltzmann() {
+        // every speed between 150 and 300
+        let config = SimulationConfig {
+            maxwell_settle: 20.0,
+            ..testing::config()
+        };
+        let (mut world, config) = testing::preset("dense gas", &config);
+
+        // it starts out nothing like it. swapping whole velocities, as resolving them per axis
+        // did for equal masses, would keep it that way
+        let start: Vec<(f32, f32)> = world
+            .particles
+            .iter()
+            .map(|p| (p.vel_magnitude(), p.mass))
+            .collect();
+        let masses: Vec<f32> = world.particles.iter().map(|p| p.mass).collect();
+        let before = fit(&start, &masses).unwrap();
+        assert!(before.distance > 3.0 * GOOD_FIT, "{}", before.distance);
+
+        let mut maxwell = Maxwell::default();
+        testing::run_each(&mut world, 4800, &config, |world| {
+            maxwell.record(world, STEP, &config)
+        });
+        let after = maxwell.fit().unwrap();
+        assert!(after.distance < GOOD_FIT, "{}", after.distance);
+        // nothing lost or gained on the way
+        assert!((after.temperature / before.temperature - 1.0).abs() < 0.01);
+        // and the histogram's a share per unit speed, like the curve. all one mass here
+        let top_speed = TOP_SPEED * (after.temperature / masses[0]).sqrt();
+        let width = top_speed / BINS as f32;
+        let total: f32 = after.histogram.iter().map(|h| h * width).sum();
+        assert!(total > 0.99 && total <= 1.0 + 1e-4, "{}", total);
+    }
+
+    #[test]
+    fn gravity_means_a_different_distribution_altogether() {
+        let config = SimulationConfig {
+            maxwell_settle: 0.0,
+            ..testing::config()
+        };
+        let (world, config) = testing::preset("dense gas", &config);
+        let mut maxwell = Maxwell::default();
+        maxwell.record(&world, SAMPLE_TIME, &config);
+        assert!(maxwell.fit().is_some());
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 100.0 },
+            ..config
+        };
+        maxwell.record(&world, SAMPLE_TIME, &config);
+        assert!(maxwell.fit().is_none());
+    }
+}
@@ -0,0 +1,135 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect, Text};
+use ggez::{Context, GameResult};
+
+use crate::Point;
+
+const ENTRY_WIDTH: f32 = 240.0;
+const ENTRY_HEIGHT: f32 = 32.0;
+const ENTRY_GAP: f32 = 8.0;
+
+const DIM_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+const ENTRY_COLOR: Color = Color::new(0.3, 0.3, 0.35, 1.0);
+const SELECTED_COLOR: Color = Color::new(0.0, 0.68, 0.71, 1.0);
+
+// one line of the menu. the menu closes before the action runs, so resume is a no-op
+pub struct MenuEntry<T: 'static> {
+    pub label: &'static str,
+    pub action: fn(&mut T, &mut Context),
+}
+
+// a column of entries in the middle of the screen, picked with the arrows and enter
+// or the mouse. the scene stays frozen behind it while it's open
+pub struct PauseMenu<T: 'static> {
+    entries: &'static [MenuEntry<T>],
+    pub open: bool,
+    selected: usize,
+}
+
+impl<T> PauseMenu<T> {
+    pub fn new(entries: &'static [MenuEntry<T>]) -> PauseMenu<T> {
+        PauseMenu {
+            entries,
+            open: false,
+            selected: 0,
+        }
+    }
+
+    // always starts on the first entry, so enter straight away resumes
+    pub fn show(&mut self) {
+        self.open = true;
+        self.selected = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    // wraps around at either end
+    pub fn move_selection(&mut self, down: bool) {
+        let count = self.entries.len();
+        self.selected = if down {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    // the highlighted entry, closing the menu for it to run
+    pub fn choose(&mut self) -> &'static MenuEntry<T> {
+        self.close();
+        &self.entries[self.selected]
+    }
+
+    fn entry_rect(&self, i: usize, screen_width: f32, screen_height: f32) -> Rect {
+        let total = self.entries.len() as f32 * (ENTRY_HEIGHT + ENTRY_GAP) - ENTRY_GAP;
+        Rect::new(
+            (screen_width - ENTRY_WIDTH) / 2.0,
+            (screen_height - total) / 2.0 + i as f32 * (ENTRY_HEIGHT + ENTRY_GAP),
+            ENTRY_WIDTH,
+            ENTRY_HEIGHT,
+        )
+    }
+
+    fn entry_at(&self, pos: Point, screen_width: f32, screen_height: f32) -> Option<usize> {
+        (0..self.entries.len()).find(|&i| {
+            self.entry_rect(i, screen_width, screen_height)
+                .contains(pos)
+        })
+    }
+
+    // hovering an entry highlights it, like the arrows would
+    pub fn mouse_motion(&mut self, pos: Point, screen_width: f32, screen_height: f32) {
+        if let Some(i) = self.entry_at(pos, screen_width, screen_height) {
+            self.selected = i;
+        }
+    }
+
+    // the entry clicked on, if any. clicks anywhere else do nothing and the menu stays up
+    pub fn mouse_down(
+        &mut self,
+        pos: Point,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> Option<&'static MenuEntry<T>> {
+        self.selected = self.entry_at(pos, screen_width, screen_height)?;
+        Some(self.choose())
+    }
+
+    pub fn draw(&self, ctx: &mut Context, screen_width: f32, screen_height: f32) -> GameResult {
+        if !self.open {
+            return Ok(());
+        }
+
+        let screen = graphics::screen_coordinates(ctx);
+        let dim = Mesh::new_rectangle(ctx, DrawMode::fill(), screen, DIM_COLOR)?;
+        graphics::draw(ctx, &dim, DrawParam::default())?;
+
+        let mut builder = MeshBuilder::new();
+        for i in 0..self.entries.len() {
+            let color = if i == self.selected {
+                SELECTED_COLOR
+            } else {
+                ENTRY_COLOR
+            };
+            builder.rectangle(
+                DrawMode::fill(),
+                self.entry_rect(i, screen_width, screen_height),
+                color,
+            );
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let rect = self.entry_rect(i, screen_width, screen_height);
+            let text = Text::new(entry.label);
+            let (w, h) = text.dimensions(ctx);
+            let dest = Point {
+                x: rect.x + (rect.w - w as f32) / 2.0,
+                y: rect.y + (rect.h - h as f32) / 2.0,
+            };
+            graphics::draw(ctx, &text, DrawParam::default().dest(dest))?;
+        }
+        Ok(())
+    }
+}
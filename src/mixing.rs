@@ -0,0 +1,180 @@
+use ggez::graphics::{Color, Rect};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::chart::History;
+use crate::world::{Particle, World};
+
+const HOT_COLOR: Color = Color::new(0.95, 0.25, 0.2, 1.0);
+const COLD_COLOR: Color = Color::new(0.25, 0.45, 1.0, 1.0);
+// the entropy is worked out over a grid this many cells across and down
+const CELLS: usize = 8;
+
+// which of the two gases a particle in a two-gas scene belongs to. particles outside one
+// have none
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Gas {
+    // fast, starting on the left
+    Hot,
+    // slow, starting on the right
+    Cold,
+}
+
+impl Gas {
+    pub fn color(self) -> Color {
+        match self {
+            Gas::Hot => HOT_COLOR,
+            Gas::Cold => COLD_COLOR,
+        }
+    }
+}
+
+pub fn make(particle: &mut Particle, gas: Gas) {
+    particle.gas = Some(gas);
+    particle.color = gas.color();
+    particle.original_color = gas.color();
+}
+
+// how many of each gas are in the left half of the box, none if nothing's in a two-gas scene
+pub fn left_counts(world: &World) -> Option<[usize; 2]> {
+    let mut counts = [0; 2];
+    let mut any = false;
+    for particle in &world.particles {
+        let gas = match particle.gas {
+            Some(gas) => gas,
+            None => continue,
+        };
+        any = true;
+        if particle.pos.x < 0.5 * world.width {
+            match gas {
+                Gas::Hot => counts[0] += 1,
+                Gas::Cold => counts[1] += 1,
+            }
+        }
+    }
+    if any {
+        Some(counts)
+    } else {
+        None
+    }
+}
+
+// the share of the left half that's the cold gas, 0 while the divider's up and about a half
+// once they've mixed
+pub fn mixed_share(world: &World) -> Option<f32> {
+    let [hot, cold] = left_counts(world)?;
+    Some(cold as f32 / (hot + cold).max(1) as f32)
+}
+
+// the mixing entropy of a CELLS by CELLS grid over the box, per particle and over its most,
+// ln 2, so 0 with the two apart and 1 with every cell half and half
+pub fn entropy(world: &World) -> Option<f32> {
+    let mut cells = [[0usize; 2]; CELLS * CELLS];
+    let mut total = 0;
+    for particle in &world.particles {
+        let gas = match particle.gas {
+            Some(gas) => gas,
+            None => continue,
+        };
+        let column = ((particle.pos.x / world.width * CELLS as f32) as usize).min(CELLS - 1);
+        let row = ((particle.pos.y / world.height * CELLS as f32) as usize).min(CELLS - 1);
+        cells[row * CELLS + column][(gas == Gas::Cold) as usize] += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return None;
+    }
+    let mut sum = 0.0;
+    for cell in &cells {
+        let n = (cell[0] + cell[1]) as f32;
+        for &count in cell {
+            if count > 0 {
+                let p = count as f32 / n;
+                sum -= count as f32 * p.ln();
+            }
+        }
+    }
+    Some(sum / (total as f32 * 2f32.ln()))
+}
+
+// how the left half's made up over time, for the chart
+#[derive(Default)]
+pub struct Mixing {
+    history: History,
+}
+
+impl Mixing {
+    pub fn clear(&mut self) {
+        *self = Mixing::default();
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32) {
+        if let Some(counts) = left_counts(world) {
+            self.history
+                .record(counts.iter().map(|&c| c as f32).collect(), time_elapsed);
+        }
+    }
+
+    pub fn hud_line(&self, world: &World) -> Option<String> {
+        let share = mixed_share(world)?;
+        let entropy = entropy(world)?;
+        Some(format!(
+            "mixing: left half {:.0}% blue, entropy {:.2}",
+            100.0 * share,
+            entropy
+        ))
+    }
+
+    pub fn draw(&self, ctx: &mut Context, pane: Rect) -> GameResult {
+        self.history
+            .draw_stacked(ctx, pane, &[HOT_COLOR, COLD_COLOR])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    // eight of each down the two sides
+    fn apart() -> World {
+        let mut world = World::new(800.0, 800.0);
+        for i in 0..8 {
+            let y = 50.0 + 100.0 * i as f32;
+            for &(x, gas) in &[(50.0, Gas::Hot), (750.0, Gas::Cold)] {
+                let mut particle = testing::particle(x, y, 4.0);
+                make(&mut particle, gas);
+                world.add_particle(particle);
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn nothing_to_measure_without_two_gases() {
+        assert_eq!(mixed_share(&World::new(800.0, 800.0)), None);
+    }
+
+    #[test]
+    fn kept_apart_nothing_is_mixed() {
+        let world = apart();
+        assert_eq!(left_counts(&world), Some([8, 0]));
+        assert_eq!(mixed_share(&world), Some(0.0));
+        assert!(entropy(&world).unwrap().abs() < 1e-6);
+    }
+
+    #[test]
+    fn one_of_each_in_every_cell_is_all_mixed() {
+        // down the left, so it's half and half there
+        let mut world = apart();
+        for particle in world.particles.iter_mut() {
+            if particle.gas == Some(Gas::Cold) {
+                particle.pos.x = 60.0;
+            }
+        }
+        assert_eq!(mixed_share(&world), Some(0.5));
+        assert!((entropy(&world).unwrap() - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,107 @@
+use ggez::graphics::{self, BlendMode, Canvas, Color, DrawMode, DrawParam, Drawable, Mesh};
+use ggez::{conf::NumSamples, Context, GameResult};
+use serde::Deserialize;
+
+// how the scene is wiped between frames
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClearMode {
+    // solid background every frame
+    Hard,
+    // a translucent background over the last frame, fast particles leave streaks
+    Fade,
+    // never wiped, everything leaves a permanent trail
+    None,
+}
+
+impl ClearMode {
+    pub fn next(self) -> ClearMode {
+        match self {
+            ClearMode::Hard => ClearMode::Fade,
+            ClearMode::Fade => ClearMode::None,
+            ClearMode::None => ClearMode::Hard,
+        }
+    }
+}
+
+// the window's back buffer isn't guaranteed to keep its contents after present,
+// so anything but a hard clear draws the scene into a canvas that lives between frames
+#[derive(Default)]
+pub struct SceneCanvas {
+    canvas: Option<Canvas>,
+    built_for: (u16, u16),
+    active: bool,
+}
+
+impl SceneCanvas {
+    // start drawing the scene. overlays drawn after `finish` are never smeared
+    pub fn begin(
+        &mut self,
+        ctx: &mut Context,
+        mode: ClearMode,
+        background: Color,
+        fade_alpha: f32,
+        paused: bool,
+    ) -> GameResult {
+        if mode == ClearMode::Hard {
+            // drop the canvas so switching back later starts from a clean slate
+            self.canvas = None;
+            self.active = false;
+            graphics::clear(ctx, background);
+            return Ok(());
+        }
+
+        // canvas in physical pixels so it stays sharp on hidpi displays
+        let (width, height) = graphics::drawable_size(ctx);
+        let factor = graphics::window(ctx).get_hidpi_factor() as f32;
+        let size = ((width * factor) as u16, (height * factor) as u16);
+        if self.canvas.is_none() || self.built_for != size {
+            let mut canvas = Canvas::new(ctx, size.0.max(1), size.1.max(1), NumSamples::One)?;
+            canvas.set_blend_mode(Some(BlendMode::Replace));
+            graphics::set_canvas(ctx, Some(&canvas));
+            graphics::clear(ctx, background);
+            self.canvas = Some(canvas);
+            self.built_for = size;
+        }
+
+        graphics::set_canvas(ctx, self.canvas.as_ref());
+        self.active = true;
+
+        // while paused the same frame is redrawn over itself, fading would wipe it out
+        if mode == ClearMode::Fade && !paused {
+            let screen = graphics::screen_coordinates(ctx);
+            let fade = Color {
+                a: fade_alpha.clamp(0.0, 1.0),
+                ..background
+            };
+            let rect = Mesh::new_rectangle(ctx, DrawMode::fill(), screen, fade)?;
+            graphics::draw(ctx, &rect, DrawParam::default())?;
+        }
+        Ok(())
+    }
+
+    // start again from the background next frame, whatever's built up on the canvas
+    pub fn clear(&mut self) {
+        self.canvas = None;
+    }
+
+    // copy the scene to the screen and go back to drawing there directly
+    pub fn finish(&mut self, ctx: &mut Context) -> GameResult {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+        graphics::set_canvas(ctx, None);
+
+        if let Some(canvas) = &self.canvas {
+            let screen = graphics::screen_coordinates(ctx);
+            let image = canvas.image();
+            let param = DrawParam::default().dest([screen.x, screen.y]).scale([
+                screen.w / f32::from(image.width()),
+                screen.h / f32::from(image.height()),
+            ]);
+            graphics::draw(ctx, canvas, param)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::BTreeSet;
+
+use ggez::graphics::{self, BlendMode, Color, DrawMode, DrawParam, Font, Image, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::belt;
+use crate::black_holes;
+use crate::boids;
+use crate::broadphase::SpatialGrid;
+use crate::center_of_mass::CenterOfMass;
+use crate::chaos::Chaos;
+use crate::collision_math::CollisionMath;
+use crate::config::SimulationConfig;
+use crate::container;
+use crate::demon;
+use crate::effects::Effects;
+use crate::emitters;
+use crate::epidemic::Epidemic;
+use crate::fission::ChainReaction;
+use crate::gas::Gauge;
+use crate::grid::Grid;
+use crate::heat_map::HeatMap;
+use crate::labels;
+use crate::maxwell::Maxwell;
+use crate::mixing::Mixing;
+use crate::piston;
+use crate::portals;
+use crate::predators::Populations;
+use crate::reactions::Reactions;
+use crate::render::{self, DrawOrder};
+use crate::targets::{self, Game};
+use crate::theme::Theme;
+use crate::thermostat;
+use crate::tracer::{Tracer, Tracers};
+use crate::voids;
+use crate::walls;
+use crate::wells;
+use crate::world::{Boundary, Particle, World};
+use crate::zones;
+use crate::Point;
+
+const RING_WIDTH: f32 = 2.0;
+const HOLD_RING_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.9);
+const SELECTED_RING_COLOR: Color = Color::new(1.0, 0.85, 0.3, 0.9);
+const PIN_RADIUS: f32 = 3.0;
+const PIN_COLOR: Color = Color::new(0.9, 0.2, 0.2, 1.0);
+
+// a world plus everything drawn for it. normally one fills the window,
+// the comparison mode puts a second one beside it
+pub struct Pane {
+    pub world: World,
+    pub effects: Effects,
+    pub center_of_mass: CenterOfMass,
+    pub tracer: Tracer,
+    pub tracers: Tracers,
+    pub gauge: Gauge,
+    pub maxwell: Maxwell,
+    pub epidemic: Epidemic,
+    pub populations: Populations,
+    pub mixing: Mixing,
+    pub reactions: Reactions,
+    pub chain_reaction: ChainReaction,
+    // a nudged copy of the world run alongside it, for the chaos preset
+    pub chaos: Option<Chaos>,
+    pub heat_map: HeatMap,
+    pub collision_math: CollisionMath,
+    pub game: Game,
+    draw_order: DrawOrder,
+    grid: Grid,
+    // the plexus lines' own broad phase, the world's is left to the physics
+    plexus: SpatialGrid,
+    // the particles as drawn, part way between the last two steps
+    snapshot: Vec<Particle>,
+    // left edge in screen coordinates
+    pub left: f32,
+    // the kinetic energy it was loaded with, for the comparison
+    pub start_energy: f32,
+}
+
+impl Pane {
+    pub fn new(width: f32, height: f32) -> Pane {
+        Pane {
+            world: World::new(width, height),
+            effects: Effects::default(),
+            center_of_mass: CenterOfMass::default(),
+            tracer: Tracer::default(),
+            tracers: Tracers::default(),
+            gauge: Gauge::default(),
+            maxwell: Maxwell::default(),
+            epidemic: Epidemic::default(),
+            populations: Populations::default(),
+            mixing: Mixing::default(),
+            reactions: Reactions::default(),
+            chain_reaction: ChainReaction::default(),
+            chaos: None,
+            heat_map: HeatMap::default(),
+            collision_math: CollisionMath::default(),
+            game: Game::default(),
+            draw_order: DrawOrder::default(),
+            grid: Grid::default(),
+            plexus: SpatialGrid::new(width, height),
+            snapshot: Vec::new(),
+            left: 0.0,
+            start_energy: 0.0,
+        }
+    }
+
+    // panes loaded with the same particles start out identical
+    pub fn load(&mut self, particles: Vec<Particle>) {
+        self.world.load(particles);
+        self.start_energy = self.world.kinetic_energy();
+        self.effects.clear();
+        self.center_of_mass.clear();
+        self.tracer.clear();
+        self.tracers.clear();
+        self.gauge.clear();
+        self.maxwell.clear();
+        self.epidemic.clear();
+        self.populations.clear();
+        self.mixing.clear();
+        self.reactions.clear();
+        self.chain_reaction.clear();
+        self.chaos = None;
+        self.heat_map.clear();
+        self.collision_math.clear();
+        // unlike the walls, the targets go with the game
+        self.world.targets.clear();
+        self.game.clear();
+    }
+
+    // run a copy of the world as it's just been loaded alongside it, with the particle at
+    // `index` nudged, see chaos::Chaos
+    pub fn start_chaos(&mut self, index: usize) {
+        self.chaos = Some(Chaos::new(&self.world, index));
+    }
+
+    pub fn spawn_random(
+        &mut self,
+        count: usize,
+        config: &SimulationConfig,
+        theme: &Theme,
+        seed: u64,
+    ) {
+        self.world
+            .spawn_random(count, config, theme, &mut StdRng::seed_from_u64(seed));
+    }
+
+    pub fn spawn_burst(
+        &mut self,
+        center: Point,
+        config: &SimulationConfig,
+        theme: &Theme,
+        seed: u64,
+    ) -> usize {
+        self.world.spawn_burst(
+            center,
+            config.burst_count,
+            config,
+            theme,
+            &mut StdRng::seed_from_u64(seed),
+        )
+    }
+
+    // delete a particle, leaving a puff where it was
+    pub fn remove_particle(&mut self, id: u64) -> Option<Particle> {
+        let particle = self.world.remove(id)?;
+        self.effects
+            .spawn_puff(particle.pos, particle.rad, particle.color);
+        Some(particle)
+    }
+
+    pub fn explode(&mut self, center: Point, config: &SimulationConfig, seed: u64) {
+        self.world
+            .explode(center, config, &mut StdRng::seed_from_u64(seed));
+        self.effects.spawn_blast(center, config.blast_radius);
+    }
+
+    // whether `x` in screen coordinates falls in this pane
+    pub fn contains_x(&self, x: f32) -> bool {
+        x >= self.left && x < self.left + self.world.width
+    }
+
+    // run `steps` fixed steps of `step_time`, then let the effects react to all of them at once
+    pub fn advance(
+        &mut self,
+        steps: usize,
+        step_time: f32,
+        config: &SimulationConfig,
+        theme: &Theme,
+    ) {
+        self.world.events.clear();
+        for _ in 0..steps {
+            self.world.step(step_time, config, theme);
+        }
+        let time_elapsed = steps as f32 * step_time;
+
+        if config.sparks {
+            self.effects
+                .spawn_sparks(&self.world.events.collisions, config.spark_threshold);
+        }
+        if config.wall_flashes {
+            self.effects.spawn_wall_flashes(&self.world.events.walls);
+        }
+        self.effects.spawn_shrinks(&self.world.events.consumed);
+        self.effects.spawn_splashes(&self.world.events.splashes);
+        self.effects.update(time_elapsed);
+
+        if steps > 0 {
+            self.center_of_mass.record(&self.world.particles);
+            self.tracer.record(&self.world, time_elapsed);
+            self.tracers.record(&self.world);
+            if config.show_gas {
+                self.gauge.record(&self.world, time_elapsed, config);
+            }
+            if config.maxwell {
+                self.maxwell.record(&self.world, time_elapsed, config);
+            }
+            self.epidemic.record(&self.world, time_elapsed);
+            self.populations.record(&self.world, time_elapsed);
+            self.mixing.record(&self.world, time_elapsed);
+            self.reactions.record(&self.world, time_elapsed);
+            self.chain_reaction
+                .record(&self.world, time_elapsed, config);
+            if let Some(chaos) = &mut self.chaos {
+                chaos.advance(&self.world, steps, step_time, config, theme);
+            }
+            self.heat_map.record(&self.world, time_elapsed, config);
+            self.game.record(&self.world, time_elapsed, config);
+        }
+    }
+
+    // `alpha` of the way from where the last step started to where it ended, for the next draw
+    pub fn interpolate(&mut self, alpha: f32) {
+        self.snapshot.clear();
+        self.snapshot
+            .extend(self.world.particles.iter().map(|particle| {
+                let mut particle = particle.clone();
+                particle.pos.x =
+                    particle.prev_pos.x + (particle.pos.x - particle.prev_pos.x) * alpha;
+                particle.pos.y =
+                    particle.prev_pos.y + (particle.pos.y - particle.prev_pos.y) * alpha;
+                particle
+            }));
+        if let Some(chaos) = &mut self.chaos {
+            chaos.interpolate(alpha);
+        }
+    }
+
+    // the particles as last drawn, in pane coordinates
+    pub fn snapshot(&self) -> &[Particle] {
+        &self.snapshot
+    }
+
+    // everything but the text, moved to the pane's place on screen plus `shake`
+    pub fn draw_scene(
+        &mut self,
+        ctx: &mut Context,
+        config: &SimulationConfig,
+        particle_image: Option<&Image>,
+        selected: &BTreeSet<u64>,
+        theme: &Theme,
+        shake: [f32; 2],
+    ) -> GameResult {
+        self.push_offset(ctx, shake)?;
+        let result = self.draw_scene_inner(ctx, config, particle_image, selected, theme);
+        graphics::pop_transform(ctx);
+        graphics::apply_transformations(ctx)?;
+        result
+    }
+
+    fn draw_scene_inner(
+        &mut self,
+        ctx: &mut Context,
+        config: &SimulationConfig,
+        particle_image: Option<&Image>,
+        selected: &BTreeSet<u64>,
+        theme: &Theme,
+    ) -> GameResult {
+        let world = &self.world;
+        let particles = &self.snapshot;
+
+        if config.show_grid {
+            self.grid
+                .draw(ctx, config.grid_spacing, world.width, world.height)?;
+        }
+
+        let params = DrawParam::default();
+
+        self.heat_map.draw(ctx, world.width, world.height, config)?;
+        if let Some(container) = &world.container {
+            let mesh = container::mesh(ctx, container)?;
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = zones::mesh(ctx, &world.zones)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = voids::mesh(ctx, &world.voids)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = targets::mesh(ctx, &world.targets, self.game.launcher())? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = walls::block_mesh(ctx, &world.blocks, theme.obstacle)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        // a channel has no right side for a piston to move
+        let right = if config.boundary.wraps_x() {
+            world.width
+        } else {
+            world.right()
+        };
+        if config.belt && config.boundary != Boundary::Wrap {
+            let mesh = belt::mesh(ctx, world.belt_offset, right, world.height)?;
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if !config.boundary.wraps_x() {
+            if let Some(mesh) = piston::mesh(ctx, &world.piston, config, world.height)? {
+                graphics::draw(ctx, &mesh, params)?;
+            }
+        }
+        if config.boundary != Boundary::Wrap {
+            if let Some(mesh) = thermostat::mesh(ctx, config, right, world.height)? {
+                graphics::draw(ctx, &mesh, params)?;
+            }
+        }
+        if let Some(mesh) = walls::mesh(ctx, &world.segments, &world.pillars)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = demon::mesh(ctx, &world.gates)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = wells::mesh(ctx, &world.wells, config)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = black_holes::mesh(ctx, &world.black_holes)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = portals::mesh(ctx, &world.portals)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        if let Some(mesh) = emitters::mesh(ctx, &world.emitters)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+
+        // behind the particles, the tracers themselves included
+        if let Some(mesh) = self.tracers.mesh(ctx, world.width, world.height)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+        let tracer = &self.tracer;
+        if let Some(traced) = particles.iter().find(|p| Some(p.id) == tracer.id()) {
+            if let Some(mesh) = tracer.mesh(ctx, traced.original_color)? {
+                graphics::draw(ctx, &mesh, params)?;
+            }
+        }
+
+        if config.boids && config.show_boid_radius {
+            if let Some(mesh) = boids::mesh(ctx, particles, config)? {
+                graphics::draw(ctx, &mesh, params)?;
+            }
+        }
+
+        if config.plexus {
+            let distance = config.plexus_distance;
+            self.plexus.set_bounds(world.width, world.height);
+            self.plexus.rebuild(particles, distance, (false, false));
+            if let Some(mesh) = render::plexus_mesh(ctx, particles, &self.plexus, distance)? {
+                graphics::draw(ctx, &mesh, params)?;
+            }
+        }
+
+        let order = self.draw_order.update(particles);
+
+        if config.glow {
+            if let Some(mesh) = render::glow_mesh(ctx, particles, order, config)? {
+                graphics::set_blend_mode(ctx, BlendMode::Add)?;
+                let result = graphics::draw(ctx, &mesh, params);
+                graphics::set_blend_mode(ctx, BlendMode::Alpha)?;
+                result?;
+            }
+        }
+
+        match particle_image {
+            Some(image) => {
+                let batch = render::image_batch(image, particles, order);
+                graphics::draw(ctx, &batch, params)?;
+            }
+            None => {
+                if let Some(mesh) = render::particle_mesh(ctx, particles, order, config)? {
+                    graphics::draw(ctx, &mesh, params)?;
+                }
+            }
+        }
+
+        if let Some(chaos) = &mut self.chaos {
+            chaos.draw_shadow(ctx, config)?;
+        }
+
+        if let Some(mesh) = self.effects.mesh(ctx)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+
+        if let Some(mesh) = self.highlight_mesh(ctx, selected)? {
+            graphics::draw(ctx, &mesh, params)?;
+        }
+
+        if config.show_velocity_lines {
+            if let Some(mesh) = render::velocity_mesh(ctx, particles)? {
+                graphics::draw(ctx, &mesh, params)?;
+            }
+        }
+
+        if config.show_center_of_mass {
+            self.center_of_mass
+                .draw(ctx, particles, config.center_of_mass_trail)?;
+        }
+
+        Ok(())
+    }
+
+    // rings around the selected particles and the one being dragged,
+    // and a pin stuck in the middle of the pinned ones
+    fn highlight_mesh(
+        &self,
+        ctx: &mut Context,
+        selected: &BTreeSet<u64>,
+    ) -> GameResult<Option<Mesh>> {
+        let held = self.world.hold.as_ref().map(|hold| hold.id);
+        let any_pinned = self.snapshot.iter().any(|p| p.pinned);
+        if held.is_none() && selected.is_empty() && !any_pinned {
+            return Ok(None);
+        }
+
+        let mut builder = MeshBuilder::new();
+        let mut any = false;
+        for particle in &self.snapshot {
+            if particle.pinned {
+                builder.circle(DrawMode::fill(), particle.pos, PIN_RADIUS, 0.1, PIN_COLOR);
+                any = true;
+            }
+            let color = if held == Some(particle.id) {
+                HOLD_RING_COLOR
+            } else if selected.contains(&particle.id) {
+                SELECTED_RING_COLOR
+            } else {
+                continue;
+            };
+            builder.circle(
+                DrawMode::stroke(RING_WIDTH),
+                particle.pos,
+                particle.rad + RING_WIDTH,
+                0.1,
+                color,
+            );
+            any = true;
+        }
+        if any {
+            builder.build(ctx).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // labels, the zones' included, go on after the motion blur copy so they never smear, but
+    // still move with the pane
+    pub fn draw_labels(
+        &self,
+        ctx: &mut Context,
+        config: &SimulationConfig,
+        font: Font,
+        shake: [f32; 2],
+    ) -> GameResult {
+        self.push_offset(ctx, shake)?;
+        let result = zones::draw_labels(ctx, &self.world.zones, font)
+            .and_then(|_| labels::draw(ctx, &self.snapshot, config.labels, font))
+            .and_then(|_| {
+                if config.collision_math {
+                    let (width, height) = (self.world.right(), self.world.height);
+                    self.collision_math.draw(ctx, font, width, height)
+                } else {
+                    Ok(())
+                }
+            });
+        graphics::pop_transform(ctx);
+        graphics::apply_transformations(ctx)?;
+        result
+    }
+
+    // ggez doesn't combine pushed transforms, so the pane offset and shake go in together
+    fn push_offset(&self, ctx: &mut Context, shake: [f32; 2]) -> GameResult {
+        let offset = DrawParam::default().dest([self.left + shake[0], shake[1]]);
+        graphics::push_transform(ctx, Some(offset.to_matrix()));
+        graphics::apply_transformations(ctx)
+    }
+}
@@ -0,0 +1,439 @@
+use std::{fs, path::Path};
+
+use crate::walls::Segment;
+use crate::Point;
+
+// endpoints closer than this once scaled are taken to be the same point, so lines drawn
+// separately to meet at a corner share it exactly and nothing slips between them
+const JOIN_TOLERANCE: f32 = 0.5;
+
+// walls traced out in a file rather than written out segment by segment. either a .svg, of
+// which only absolute <line>, <rect>, <polyline> and <path> (M, L, H, V and Z) are read, or
+// anything else as one polyline a line of points:
+//
+//     # a ramp, in a drawing 1280 wide and 720 high
+//     size 1280 720
+//     100 200, 400 300, 400 320
+//
+// either way they're scaled to fit the box, if the file says how big the drawing is
+pub struct Course {
+    pub walls: Vec<Segment>,
+    // bits of the drawing that end up outside the box, which still load
+    pub warnings: Vec<String>,
+}
+
+// a polyline, and where in the file it came from for the messages
+struct Polyline {
+    source: String,
+    points: Vec<Point>,
+}
+
+#[derive(Default)]
+struct Drawing {
+    // the top left corner and size the drawing was made at
+    view: Option<(Point, f32, f32)>,
+    polylines: Vec<Polyline>,
+}
+
+pub fn load(path: &Path, width: f32, height: f32) -> Result<Course, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    let drawing = if is_svg {
+        parse_svg(&text)
+    } else {
+        parse_polylines(&text)
+    };
+    drawing
+        .map(|drawing| fit(drawing, width, height))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn parse_polylines(text: &str) -> Result<Drawing, String> {
+    let mut drawing = Drawing::default();
+    for (i, line) in text.lines().enumerate() {
+        let source = format!("line {}", i + 1);
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(size) = line.strip_prefix("size") {
+            let (width, height) = pair(size).ok_or_else(|| {
+                format!(
+                    "{}: `size` needs a width and height, e.g. size 1280 720",
+                    source
+                )
+            })?;
+            drawing.view = Some((Point { x: 0.0, y: 0.0 }, width, height));
+            continue;
+        }
+        let points = line
+            .split(',')
+            .map(|point| {
+                pair(point)
+                    .map(|(x, y)| Point { x, y })
+                    .ok_or_else(|| format!("{}: `{}` isn't an x y point", source, point.trim()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if points.len() < 2 {
+            return Err(format!("{}: a polyline needs at least two points", source));
+        }
+        drawing.polylines.push(Polyline { source, points });
+    }
+    Ok(drawing)
+}
+
+// two numbers split by whitespace
+fn pair(text: &str) -> Option<(f32, f32)> {
+    let mut numbers = text.split_whitespace().map(str::parse::<f32>);
+    match (numbers.next(), numbers.next(), numbers.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) if x.is_finite() && y.is_finite() => Some((x, y)),
+        _ => None,
+    }
+}
+
+fn parse_svg(text: &str) -> Result<Drawing, String> {
+    let mut drawing = Drawing::default();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let offset = text.len() - rest.len() + start;
+        let source_line = text[..offset].matches('\n').count() + 1;
+        rest = &rest[start + 1..];
+        // comments can have anything in them, tags included
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let end = rest
+            .find('>')
+            .ok_or_else(|| format!("line {}: a tag that never ends", source_line))?;
+        let tag = rest[..end].trim_end_matches('/');
+        rest = &rest[end + 1..];
+
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let (name, attributes) = tag.split_at(name_end);
+        let source = format!("<{}> on line {}", name, source_line);
+        let attributes = match name {
+            "svg" | "line" | "rect" | "polyline" | "polygon" | "path" => attributes_of(attributes)
+                .ok_or_else(|| format!("{}: couldn't read its attributes", source))?,
+            // closing tags, groups, the xml declaration and so on
+            _ => continue,
+        };
+        let get = |key: &str| {
+            attributes
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, value)| *value)
+        };
+        let number = |key: &str| {
+            let value = get(key).ok_or_else(|| format!("{}: no `{}`", source, key))?;
+            value
+                .trim()
+                .trim_end_matches("px")
+                .parse::<f32>()
+                .map_err(|_| format!("{}: `{}` isn't a number", source, value))
+        };
+        if name != "svg" && get("transform").is_some() {
+            return Err(format!("{}: transforms aren't supported", source));
+        }
+
+        let points = match name {
+            "svg" => {
+                drawing.view = match get("viewBox") {
+                    Some(view_box) => {
+                        let numbers = numbers(view_box)
+                            .filter(|numbers| numbers.len() == 4)
+                            .ok_or_else(|| format!("{}: `viewBox` needs 4 numbers", source))?;
+                        let min = Point {
+                            x: numbers[0],
+                            y: numbers[1],
+                        };
+                        Some((min, numbers[2], numbers[3]))
+                    }
+                    None => match (number("width"), number("height")) {
+                        (Ok(width), Ok(height)) => Some((Point { x: 0.0, y: 0.0 }, width, height)),
+                        _ => None,
+                    },
+                };
+                continue;
+            }
+            "line" => vec![
+                Point {
+                    x: number("x1")?,
+                    y: number("y1")?,
+                },
+                Point {
+                    x: number("x2")?,
+                    y: number("y2")?,
+                },
+            ],
+            "rect" => {
+                let (x, y) = (number("x").unwrap_or(0.0), number("y").unwrap_or(0.0));
+                let (w, h) = (number("width")?, number("height")?);
+                let corner = |x, y| Point { x, y };
+                vec![
+                    corner(x, y),
+                    corner(x + w, y),
+                    corner(x + w, y + h),
+                    corner(x, y + h),
+                    corner(x, y),
+                ]
+            }
+            "polyline" | "polygon" => {
+                let numbers = get("points")
+                    .and_then(numbers)
+                    .filter(|numbers| numbers.len().is_multiple_of(2))
+                    .ok_or_else(|| format!("{}: `points` needs pairs of numbers", source))?;
+                let mut points: Vec<Point> = numbers
+                    .chunks(2)
+                    .map(|xy| Point { x: xy[0], y: xy[1] })
+                    .collect();
+                if name == "polygon" && !points.is_empty() {
+                    points.push(points[0]);
+                }
+                points
+            }
+            _ => {
+                let d = get("d").ok_or_else(|| format!("{}: no `d`", source))?;
+                for points in trace(d).map_err(|e| format!("{}: {}", source, e))? {
+                    drawing.polylines.push(Polyline {
+                        source: source.clone(),
+                        points,
+                    });
+                }
+                continue;
+            }
+        };
+        drawing.polylines.push(Polyline { source, points });
+    }
+    Ok(drawing)
+}
+
+// name="value" pairs, in either kind of quotes
+fn attributes_of(mut text: &str) -> Option<Vec<(&str, &str)>> {
+    let mut attributes = Vec::new();
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Some(attributes);
+        }
+        let equals = text.find('=')?;
+        let key = text[..equals].trim();
+        let value = text[equals + 1..].trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let close = value[1..].find(quote)?;
+        attributes.push((key, &value[1..=close]));
+        text = &value[close + 2..];
+    }
+}
+
+// every number in `text`, however they're split up: "10,20", "10 20" and "10-20" are all two
+fn numbers(text: &str) -> Option<Vec<f32>> {
+    let mut numbers = Vec::new();
+    let mut current = String::new();
+    let mut finish = |current: &mut String| -> Option<()> {
+        if !current.is_empty() {
+            numbers.push(current.parse::<f32>().ok()?);
+            current.clear();
+        }
+        Some(())
+    };
+    for c in text.chars() {
+        let exponent = current.ends_with(['e', 'E']);
+        match c {
+            '-' | '+' if !exponent => {
+                finish(&mut current)?;
+                current.push(c);
+            }
+            '.' if current.contains('.') && !current.contains(['e', 'E']) => {
+                finish(&mut current)?;
+                current.push(c);
+            }
+            '0'..='9' | '.' | 'e' | 'E' | '-' | '+' => current.push(c),
+            c if c.is_whitespace() || c == ',' => finish(&mut current)?,
+            _ => return None,
+        }
+    }
+    finish(&mut current)?;
+    Some(numbers)
+}
+
+// the polylines a path's `d` draws, one for each M
+fn trace(d: &str) -> Result<Vec<Vec<Point>>, String> {
+    let mut polylines: Vec<Vec<Point>> = Vec::new();
+    let mut at = Point { x: 0.0, y: 0.0 };
+    let mut rest = d.trim();
+    while let Some(command) = rest.chars().next() {
+        if !command.is_ascii_alphabetic() {
+            return Err(format!("expected a command, found `{}`", command));
+        }
+        let args_end = rest[1..]
+            .find(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E')
+            .map_or(rest.len(), |i| i + 1);
+        let args = numbers(&rest[1..args_end])
+            .ok_or_else(|| format!("`{}` has something other than numbers after it", command))?;
+        rest = rest[args_end..].trim_start();
+
+        let pairs = |args: &[f32]| -> Result<Vec<Point>, String> {
+            if args.is_empty() || !args.len().is_multiple_of(2) {
+                return Err(format!("`{}` needs pairs of numbers", command));
+            }
+            Ok(args
+                .chunks(2)
+                .map(|xy| Point { x: xy[0], y: xy[1] })
+                .collect())
+        };
+        match command {
+            'M' => {
+                let points = pairs(&args)?;
+                at = *points.last().expect("checked there's a pair");
+                // any more pairs after the first are lines on from it
+                polylines.push(points);
+            }
+            'L' | 'H' | 'V' => {
+                let current = polylines
+                    .last_mut()
+                    .ok_or_else(|| format!("`{}` before any M", command))?;
+                let points = match command {
+                    'L' => pairs(&args)?,
+                    _ if args.is_empty() => return Err(format!("`{}` needs a number", command)),
+                    'H' => args.iter().map(|&x| Point { x, y: at.y }).collect(),
+                    _ => args.iter().map(|&y| Point { x: at.x, y }).collect(),
+                };
+                for point in points {
+                    current.push(point);
+                    at = point;
+                }
+            }
+            'Z' => {
+                let current = polylines
+                    .last_mut()
+                    .ok_or_else(|| String::from("`Z` before any M"))?;
+                let start = current[0];
+                current.push(start);
+                // the next one carries on from where this one closed
+                polylines.push(vec![start]);
+                at = start;
+            }
+            c if c.is_ascii_lowercase() => {
+                return Err(format!(
+                    "`{}` is relative, only absolute coordinates are supported",
+                    c
+                ))
+            }
+            c => return Err(format!("`{}` isn't supported, only M, L, H, V and Z", c)),
+        }
+    }
+    polylines.retain(|points| points.len() >= 2);
+    Ok(polylines)
+}
+
+// scaled the same both ways to fit the box and centered in it, if the drawing has a size
+fn fit(drawing: Drawing, width: f32, height: f32) -> Course {
+    let (scale, offset) = match drawing.view {
+        Some((min, w, h)) if w > 0.0 && h > 0.0 => {
+            let scale = (width / w).min(height / h);
+            let offset = Point {
+                x: 0.5 * (width - w * scale) - min.x * scale,
+                y: 0.5 * (height - h * scale) - min.y * scale,
+            };
+            (scale, offset)
+        }
+        _ => (1.0, Point { x: 0.0, y: 0.0 }),
+    };
+
+    let mut course = Course {
+        walls: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let mut ends: Vec<Point> = Vec::new();
+    for polyline in drawing.polylines {
+        let mut outside = false;
+        let mut last: Option<Point> = None;
+        for point in polyline.points {
+            let point = Point {
+                x: offset.x + point.x * scale,
+                y: offset.y + point.y * scale,
+            };
+            let near = |end: &&Point| {
+                let (dx, dy) = (end.x - point.x, end.y - point.y);
+                dx * dx + dy * dy <= JOIN_TOLERANCE * JOIN_TOLERANCE
+            };
+            let point = match ends.iter().find(near) {
+                Some(end) => *end,
+                None => {
+                    ends.push(point);
+                    point
+                }
+            };
+            outside |= !(0.0..=width).contains(&point.x) || !(0.0..=height).contains(&point.y);
+            // a repeated point is no wall at all
+            if let Some(last) = last.filter(|last| last.x != point.x || last.y != point.y) {
+                course.walls.push(Segment::new(last, point));
+            }
+            last = Some(point);
+        }
+        if outside {
+            course
+                .warnings
+                .push(format!("{} goes outside the box", polyline.source));
+        }
+    }
+    course
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_course_fits_the_box() {
+        let drawing = parse_polylines(include_str!("../scenes/course.path")).unwrap();
+        let course = fit(drawing, 1280.0, 720.0);
+        assert!(course.warnings.is_empty(), "{:?}", course.warnings);
+        assert!(course.walls.iter().all(|wall| wall.length() > 0.0));
+
+        // half the size, it's all half the size
+        let drawing = parse_polylines(include_str!("../scenes/course.path")).unwrap();
+        let half = fit(drawing, 640.0, 360.0);
+        assert_eq!(half.walls.len(), course.walls.len());
+        assert!((half.walls[0].a.x - 0.5 * course.walls[0].a.x).abs() < 1e-3);
+
+        let error = parse_polylines("size 100 100\n10 10, 20 20\n10 10, twenty 20\n");
+        assert_eq!(
+            error.err().unwrap(),
+            "line 3: `twenty 20` isn't an x y point"
+        );
+    }
+
+    #[test]
+    fn svg_lines_rects_and_paths() {
+        let svg = r#"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 200 100">
+  <!-- a floor, a box and a ramp up to a ledge -->
+  <line x1="0" y1="100" x2="200" y2="100"/>
+  <rect x="10" y="10" width="20" height="20" />
+  <path d="M 50,80 L100-40 H150 V 50 Z"/>
+</svg>"#;
+        let course = fit(parse_svg(svg).unwrap(), 400.0, 200.0);
+        // 1 for the line, 4 round the rect and 4 round the closed path
+        assert_eq!(course.walls.len(), 9);
+        // the ramp goes up out of the top, and the floor's twice as long
+        assert_eq!(
+            course.warnings,
+            vec!["<path> on line 6 goes outside the box"]
+        );
+        assert_eq!(course.walls[0].length(), 400.0);
+        // the rect's corner is where it started
+        assert_eq!(course.walls[4].b.x, course.walls[1].a.x);
+
+        let relative = svg.replace("H150", "h50");
+        let error = parse_svg(&relative).err().unwrap();
+        assert!(
+            error.starts_with("<path> on line 6: `h` is relative"),
+            "{}",
+            error
+        );
+    }
+}
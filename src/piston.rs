@@ -0,0 +1,107 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use serde::Deserialize;
+
+use crate::config::SimulationConfig;
+
+const PISTON_WIDTH: f32 = 8.0;
+const PISTON_COLOR: Color = Color::new(0.8, 0.8, 0.8, 1.0);
+
+// how the right side of the box moves
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PistonMode {
+    // stays at the edge of the box
+    Off,
+    // in and out smoothly, once every piston_period seconds
+    Oscillate,
+    // at piston_speed until it reaches a limit, then back the other way
+    Sweep,
+}
+
+// the right side of the box as a wall that can move, between piston_min and piston_max of
+// the box's width. anything bouncing off it does so in the wall's own frame, so pushing in
+// speeds the particles up and pulling out slows them down
+pub struct Piston {
+    pub x: f32,
+    // positive is outwards
+    pub vel: f32,
+    time: f32,
+    inwards: bool,
+    // false until it's moved once, so switching it on doesn't count the jump to its limits
+    // as a speed
+    running: bool,
+}
+
+impl Piston {
+    // at the edge of a box `width` wide, where every mode starts from
+    pub fn new(width: f32) -> Piston {
+        Piston {
+            x: width,
+            vel: 0.0,
+            time: 0.0,
+            inwards: true,
+            running: false,
+        }
+    }
+
+    pub fn update(&mut self, time_elapsed: f32, config: &SimulationConfig, width: f32) {
+        let low = config.piston_min.clamp(0.0, 1.0) * width;
+        let high = config.piston_max.clamp(0.0, 1.0) * width;
+        let (low, high) = (low.min(high), low.max(high));
+        let start = self.x;
+
+        match config.piston {
+            PistonMode::Off => {
+                self.x = width;
+                self.vel = 0.0;
+                self.running = false;
+                return;
+            }
+            PistonMode::Oscillate => {
+                self.time += time_elapsed;
+                let phase = std::f32::consts::PI * 2.0 * self.time / config.piston_period.max(0.1);
+                self.x = low + (high - low) * (0.5 + 0.5 * phase.cos());
+            }
+            PistonMode::Sweep => {
+                // back to the limits first, in case they or the box just changed
+                let x = start.clamp(low, high);
+                let step = config.piston_speed.abs() * time_elapsed;
+                self.x = if self.inwards { x - step } else { x + step };
+                if self.x <= low {
+                    self.x = low;
+                    self.inwards = false;
+                } else if self.x >= high {
+                    self.x = high;
+                    self.inwards = true;
+                }
+            }
+        }
+
+        self.vel = if self.running && time_elapsed > 0.0 {
+            (self.x - start) / time_elapsed
+        } else {
+            0.0
+        };
+        self.running = true;
+    }
+}
+
+// a bar down the right side at the piston, nothing while it's off
+pub fn mesh(
+    ctx: &mut Context,
+    piston: &Piston,
+    config: &SimulationConfig,
+    height: f32,
+) -> GameResult<Option<Mesh>> {
+    if config.piston == PistonMode::Off {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    builder.rectangle(
+        DrawMode::fill(),
+        Rect::new(piston.x, 0.0, PISTON_WIDTH, height),
+        PISTON_COLOR,
+    );
+    builder.build(ctx).map(Some)
+}
@@ -0,0 +1,243 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::world::Particle;
+use crate::{Point, Vector};
+
+// how far outside the exit ring a particle comes out, beyond just clearing it
+const EXIT_GAP: f32 = 1.0;
+// the most times a particle coming out on top of another is moved further out, by its radius
+// each time, before it's left for the collisions to sort out
+const EXIT_NUDGES: usize = 8;
+
+const RING_WIDTH: f32 = 3.0;
+const MARK_LENGTH: f32 = 0.4;
+// one per pair, round again after the last
+const PORTAL_COLORS: &[Color] = &[
+    Color::new(1.0, 0.6, 0.1, 0.9),
+    Color::new(0.2, 0.6, 1.0, 0.9),
+    Color::new(0.7, 1.0, 0.3, 0.9),
+    Color::new(1.0, 0.3, 0.8, 0.9),
+];
+
+// one end of a portal. `angle` is which way it faces, in degrees clockwise from the right
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Mouth {
+    pub pos: Point,
+    pub rad: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+
+impl Mouth {
+    // where the move from `from` to `to` first goes into the ring, if it does. a move that
+    // starts inside doesn't count, so anything just let out can't fall straight back in
+    fn entry(&self, from: Point, to: Point) -> Option<Point> {
+        let (fx, fy) = (from.x - self.pos.x, from.y - self.pos.y);
+        let (mx, my) = (to.x - from.x, to.y - from.y);
+        let c = fx * fx + fy * fy - self.rad * self.rad;
+        if c <= 0.0 {
+            return None;
+        }
+        let a = mx * mx + my * my;
+        if a <= f32::EPSILON {
+            return None;
+        }
+        let b = fx * mx + fy * my;
+        let discriminant = b * b - a * c;
+        if b >= 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let t = (-b - discriminant.sqrt()) / a;
+        if t > 1.0 {
+            return None;
+        }
+        Some(Point {
+            x: from.x + mx * t,
+            y: from.y + my * t,
+        })
+    }
+}
+
+// two rings joined up. whatever goes into one comes out of the other at the same speed, as
+// if it had gone straight through, turned by however much more the exit faces than the entry
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Portal {
+    pub a: Mouth,
+    pub b: Mouth,
+}
+
+// where a particle came out of a portal, and which way to keep moving it if that turns out
+// to be on top of something
+pub struct Exit {
+    pub pos: Point,
+    pub vel: Vector,
+    pub normal: Vector,
+}
+
+impl Portal {
+    // if `particle` went into either end this step, where it comes out of the other
+    pub fn pass(&self, particle: &Particle) -> Option<Exit> {
+        let (from, to) = (particle.prev_pos, particle.pos);
+        let (entry, mouth, exit) = match self.a.entry(from, to) {
+            Some(entry) => (entry, &self.a, &self.b),
+            None => (self.b.entry(from, to)?, &self.b, &self.a),
+        };
+
+        let turn = (exit.angle - mouth.angle).to_radians();
+        let rotate = |v: Vector| Vector {
+            x: v.x * turn.cos() - v.y * turn.sin(),
+            y: v.x * turn.sin() + v.y * turn.cos(),
+        };
+        // straight through: in on one side, out of the far side of the other
+        let offset = rotate(Vector {
+            x: (mouth.pos.x - entry.x) / mouth.rad.max(f32::EPSILON),
+            y: (mouth.pos.y - entry.y) / mouth.rad.max(f32::EPSILON),
+        });
+        let distance = exit.rad + particle.rad + EXIT_GAP;
+        Some(Exit {
+            pos: Point {
+                x: exit.pos.x + offset.x * distance,
+                y: exit.pos.y + offset.y * distance,
+            },
+            vel: rotate(particle.vel),
+            normal: offset,
+        })
+    }
+}
+
+impl Exit {
+    // step out along the normal while `blocked`, up to EXIT_NUDGES times
+    pub fn clear_of(mut self, rad: f32, blocked: impl Fn(Point) -> bool) -> Exit {
+        for _ in 0..EXIT_NUDGES {
+            if !blocked(self.pos) {
+                break;
+            }
+            self.pos.x += self.normal.x * rad;
+            self.pos.y += self.normal.y * rad;
+        }
+        self
+    }
+}
+
+// a ring for each end with a tick showing which way it faces, each pair in its own color
+pub fn mesh(ctx: &mut Context, portals: &[Portal]) -> GameResult<Option<Mesh>> {
+    if portals.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for (i, portal) in portals.iter().enumerate() {
+        let color = PORTAL_COLORS[i % PORTAL_COLORS.len()];
+        for mouth in &[portal.a, portal.b] {
+            builder.circle(
+                DrawMode::stroke(RING_WIDTH),
+                mouth.pos,
+                mouth.rad,
+                0.5,
+                color,
+            );
+            let (sin, cos) = mouth.angle.to_radians().sin_cos();
+            let inner = mouth.rad * (1.0 - MARK_LENGTH);
+            builder.line(
+                &[
+                    Point {
+                        x: mouth.pos.x + cos * inner,
+                        y: mouth.pos.y + sin * inner,
+                    },
+                    Point {
+                        x: mouth.pos.x + cos * mouth.rad,
+                        y: mouth.pos.y + sin * mouth.rad,
+                    },
+                ],
+                RING_WIDTH,
+                color,
+            )?;
+        }
+    }
+    builder.build(ctx).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portal() -> Portal {
+        Portal {
+            a: Mouth {
+                pos: Point { x: 100.0, y: 100.0 },
+                rad: 20.0,
+                angle: 0.0,
+            },
+            b: Mouth {
+                pos: Point { x: 500.0, y: 300.0 },
+                rad: 20.0,
+                angle: 90.0,
+            },
+        }
+    }
+
+    fn moving(from: Point, to: Point, vel: Vector) -> Particle {
+        let mut particle = Particle::new(to, vel, 10.0, 1.0, Color::new(1.0, 1.0, 1.0, 1.0));
+        particle.prev_pos = from;
+        particle
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn comes_out_the_far_side_turned() {
+        let particle = moving(
+            Point { x: 60.0, y: 100.0 },
+            Point { x: 85.0, y: 100.0 },
+            Vector { x: 300.0, y: 0.0 },
+        );
+        let exit = portal().pass(&particle).expect("Went in");
+        assert!(close(exit.pos.x, 500.0) && close(exit.pos.y, 331.0));
+        assert!(close(exit.vel.x, 0.0) && close(exit.vel.y, 300.0));
+    }
+
+    #[test]
+    fn works_both_ways() {
+        // straight down into b, out of a heading right
+        let particle = moving(
+            Point { x: 500.0, y: 260.0 },
+            Point { x: 500.0, y: 290.0 },
+            Vector { x: 0.0, y: 200.0 },
+        );
+        let exit = portal().pass(&particle).expect("Went in");
+        assert!(close(exit.pos.x, 131.0) && close(exit.pos.y, 100.0));
+        assert!(close(exit.vel.x, 200.0) && close(exit.vel.y, 0.0));
+    }
+
+    #[test]
+    fn fast_particles_cant_skip_a_ring() {
+        let particle = moving(
+            Point { x: 0.0, y: 100.0 },
+            Point { x: 200.0, y: 100.0 },
+            Vector { x: 24000.0, y: 0.0 },
+        );
+        assert!(portal().pass(&particle).is_some());
+    }
+
+    #[test]
+    fn leaving_doesnt_go_back_in() {
+        // already out of b's far side and moving away, which is where it's let out
+        let particle = moving(
+            Point { x: 500.0, y: 331.0 },
+            Point { x: 500.0, y: 333.5 },
+            Vector { x: 0.0, y: 300.0 },
+        );
+        assert!(portal().pass(&particle).is_none());
+
+        // starting inside a ring doesn't count either
+        let particle = moving(
+            Point { x: 100.0, y: 100.0 },
+            Point { x: 110.0, y: 100.0 },
+            Vector { x: 300.0, y: 0.0 },
+        );
+        assert!(portal().pass(&particle).is_none());
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+
+use ggez::graphics::{Color, Rect};
+use ggez::{Context, GameResult};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::chart::History;
+use crate::config::SimulationConfig;
+use crate::world::{Particle, World};
+use crate::{Point, Vector};
+
+const PREY_COLOR: Color = Color::new(0.35, 0.8, 0.35, 1.0);
+const PREDATOR_COLOR: Color = Color::new(1.0, 0.45, 0.1, 1.0);
+// a hungry predator's size. a well fed one grows, up to about half as big again just before
+// it splits
+pub const PREDATOR_RAD: f32 = 9.0;
+// gap between a parent and its young, on top of both radii
+const BIRTH_GAP: f32 = 1.0;
+// the young set off at a speed of their own rather than the parent's. the fast prey are the
+// ones that get caught, so copying the survivors would slow everything down to a crawl
+pub const BIRTH_SPEED_MIN: f32 = 60.0;
+pub const BIRTH_SPEED_MAX: f32 = 120.0;
+
+// what a particle is in a predator-prey scene. particles outside one have none
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Species {
+    Prey,
+    // with this many seconds of energy left before it starves
+    Predator(f32),
+}
+
+impl Species {
+    pub fn color(self) -> Color {
+        match self {
+            Species::Prey => PREY_COLOR,
+            Species::Predator(_) => PREDATOR_COLOR,
+        }
+    }
+}
+
+pub fn make_prey(particle: &mut Particle) {
+    particle.species = Some(Species::Prey);
+    particle.color = PREY_COLOR;
+    particle.original_color = PREY_COLOR;
+}
+
+// starting out with predator_energy, at its hungry size
+pub fn make_predator(particle: &mut Particle, config: &SimulationConfig) {
+    particle.color = PREDATOR_COLOR;
+    particle.original_color = PREDATOR_COLOR;
+    feed(particle, config.predator_energy, config);
+}
+
+// `energy` left, and sized to match: the area grows with the energy from predator_energy to
+// twice that
+fn feed(particle: &mut Particle, energy: f32, config: &SimulationConfig) {
+    particle.species = Some(Species::Predator(energy));
+    let scale = (energy / config.predator_energy.max(f32::EPSILON)).clamp(1.0, 2.0);
+    particle.rad = PREDATOR_RAD * scale.sqrt();
+    particle.mass = config.mass_for_radius(particle.rad);
+}
+
+// how many prey and predators there are, none if nothing's in a predator-prey scene
+pub fn counts(particles: &[Particle]) -> Option<[usize; 2]> {
+    let mut counts = [0; 2];
+    let mut any = false;
+    for species in particles.iter().filter_map(|p| p.species) {
+        any = true;
+        match species {
+            Species::Prey => counts[0] += 1,
+            Species::Predator(_) => counts[1] += 1,
+        }
+    }
+    if any {
+        Some(counts)
+    } else {
+        None
+    }
+}
+
+// one step of a predator-prey scene. every prey a predator touched this step, from the
+// collisions after `first_collision`, is eaten and worth predator_meal seconds of energy to
+// it. predators burn through `time_elapsed` of theirs and starve at none, or split in two at
+// twice predator_energy. prey breed at prey_birth_rate each a second, while there are fewer
+// than prey_cap. everything goes in and out by id with `remove` and `spawn_at`, so nothing
+// holding on to a particle is left pointing at the wrong one, and the rng comes from the step
+// and the id like a thermostat's
+pub fn live(
+    world: &mut World,
+    first_collision: usize,
+    time_elapsed: f32,
+    config: &SimulationConfig,
+) {
+    if !world.particles.iter().any(|p| p.species.is_some()) {
+        return;
+    }
+
+    let species: BTreeMap<u64, Species> = world
+        .particles
+        .iter()
+        .filter_map(|p| p.species.map(|s| (p.id, s)))
+        .collect();
+    let mut eaten = Vec::new();
+    let mut meals: BTreeMap<u64, usize> = BTreeMap::new();
+    for event in &world.events.collisions[first_collision..] {
+        let (predator, prey) = match (species.get(&event.a), species.get(&event.b)) {
+            (Some(Species::Predator(_)), Some(Species::Prey)) => (event.a, event.b),
+            (Some(Species::Prey), Some(Species::Predator(_))) => (event.b, event.a),
+            _ => continue,
+        };
+        if !eaten.contains(&prey) {
+            eaten.push(prey);
+            *meals.entry(predator).or_insert(0) += 1;
+        }
+    }
+    for id in eaten {
+        world.remove(id);
+    }
+
+    let mut starved = Vec::new();
+    let mut young = Vec::new();
+    for particle in &mut world.particles {
+        let energy = match particle.species {
+            Some(Species::Predator(energy)) => energy,
+            _ => continue,
+        };
+        let meals = meals.get(&particle.id).copied().unwrap_or(0);
+        let energy = energy - time_elapsed + config.predator_meal * meals as f32;
+        if energy <= 0.0 {
+            starved.push(particle.id);
+        } else if energy >= 2.0 * config.predator_energy {
+            feed(particle, 0.5 * energy, config);
+            young.push(particle.clone());
+        } else {
+            feed(particle, energy, config);
+        }
+    }
+    for id in starved {
+        world.remove(id);
+    }
+
+    let chance = config.prey_birth_rate * time_elapsed;
+    let mut prey = world
+        .particles
+        .iter()
+        .filter(|p| p.species == Some(Species::Prey))
+        .count();
+    for particle in &world.particles {
+        if prey >= config.prey_cap {
+            break;
+        }
+        if particle.species != Some(Species::Prey) {
+            continue;
+        }
+        let mut rng =
+            StdRng::seed_from_u64(world.step_count.wrapping_mul(u64::from(u32::MAX)) ^ particle.id);
+        if rng.gen::<f32>() < chance {
+            young.push(particle.clone());
+            prey += 1;
+        }
+    }
+
+    for parent in young {
+        let mut rng =
+            StdRng::seed_from_u64(world.step_count.wrapping_mul(u64::from(u32::MAX)) ^ !parent.id);
+        let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+        let (sin, cos) = angle.sin_cos();
+        let distance = 2.0 * parent.rad + BIRTH_GAP;
+        let speed = rng.gen_range(BIRTH_SPEED_MIN..BIRTH_SPEED_MAX);
+        let mut child = Particle::new(
+            Point {
+                x: parent.pos.x + distance * cos,
+                y: parent.pos.y + distance * sin,
+            },
+            Vector {
+                x: speed * cos,
+                y: speed * sin,
+            },
+            parent.rad,
+            parent.mass,
+            parent.color,
+        );
+        child.species = parent.species;
+        world.spawn_at(child, config);
+    }
+}
+
+// the two populations over time, for the chart
+#[derive(Default)]
+pub struct Populations {
+    history: History,
+}
+
+impl Populations {
+    pub fn clear(&mut self) {
+        *self = Populations::default();
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32) {
+        if let Some(counts) = counts(&world.particles) {
+            self.history
+                .record(counts.iter().map(|&c| c as f32).collect(), time_elapsed);
+        }
+    }
+
+    pub fn hud_line(&self, world: &World) -> Option<String> {
+        let counts = counts(&world.particles)?;
+        let outcome = match counts {
+            [0, _] => ", the prey are all eaten",
+            [_, 0] => ", the predators have starved",
+            _ => "",
+        };
+        Some(format!(
+            "predator-prey: {} prey, {} predators{}",
+            counts[0], counts[1], outcome
+        ))
+    }
+
+    pub fn draw(&self, ctx: &mut Context, pane: Rect) -> GameResult {
+        self.history
+            .draw_stacked(ctx, pane, &[PREY_COLOR, PREDATOR_COLOR])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, touch};
+
+    fn predator(x: f32, config: &SimulationConfig) -> Particle {
+        let mut predator = testing::particle(x, 200.0, 4.0);
+        make_predator(&mut predator, config);
+        predator
+    }
+
+    fn prey(x: f32) -> Particle {
+        let mut prey = testing::particle(x, 200.0, 4.0);
+        make_prey(&mut prey);
+        prey
+    }
+
+    // a predator and a prey it's touching, and their ids
+    fn meal(config: &SimulationConfig) -> (World, u64, u64) {
+        let mut world = World::new(400.0, 400.0);
+        world.add_particle(predator(100.0, config));
+        world.add_particle(prey(100.0 + PREDATOR_RAD + 4.0));
+        let (predator, prey) = (world.particles[0].id, world.particles[1].id);
+        world.events.collisions.push(touch(prey, predator));
+        (world, predator, prey)
+    }
+
+    #[test]
+    fn a_predator_eats_the_prey_it_touches_and_grows() {
+        let config = SimulationConfig {
+            predator_energy: 2.0,
+            predator_meal: 1.5,
+            prey_birth_rate: 0.0,
+            ..testing::config()
+        };
+        let (mut world, _, prey) = meal(&config);
+        world.add_particle(self::prey(300.0));
+        live(&mut world, 0, 0.5, &config);
+        // the one it touched goes, and it's that much better off and bigger for it
+        assert_eq!(counts(&world.particles), Some([1, 1]));
+        assert!(world.particles.iter().all(|p| p.id != prey));
+        assert_eq!(world.particles[0].species, Some(Species::Predator(3.0)));
+        assert!(world.particles[0].rad > PREDATOR_RAD);
+    }
+
+    #[test]
+    fn a_predator_splits_at_twice_its_energy() {
+        let config = SimulationConfig {
+            predator_energy: 2.0,
+            predator_meal: 1.5,
+            prey_birth_rate: 0.0,
+            ..testing::config()
+        };
+        let (mut world, predator, _) = meal(&config);
+        world.particles[0].species = Some(Species::Predator(3.0));
+        live(&mut world, 0, 0.5, &config);
+        // 4 is twice predator_energy, so two hungry ones with half each
+        assert_eq!(counts(&world.particles), Some([0, 2]));
+        assert!(world.particles.iter().any(|p| p.id == predator));
+        for predator in world.particles.iter() {
+            assert_eq!(predator.species, Some(Species::Predator(2.0)));
+            assert_eq!(predator.rad, PREDATOR_RAD);
+        }
+    }
+
+    #[test]
+    fn predators_starve_with_nothing_to_eat() {
+        let config = SimulationConfig {
+            predator_energy: 2.0,
+            predator_meal: 1.5,
+            prey_birth_rate: 0.0,
+            ..testing::config()
+        };
+        let mut world = World::new(400.0, 400.0);
+        world.add_particle(predator(100.0, &config));
+        live(&mut world, 0, 1.0, &config);
+        assert_eq!(world.particles[0].species, Some(Species::Predator(1.0)));
+        live(&mut world, 0, 1.0, &config);
+        assert!(world.particles.is_empty());
+    }
+
+    #[test]
+    fn prey_breed_up_to_the_cap_and_no_further() {
+        let config = SimulationConfig {
+            predator_energy: 2.0,
+            predator_meal: 1.5,
+            prey_birth_rate: 1.0,
+            prey_cap: 5,
+            ..testing::config()
+        };
+        let mut world = World::new(400.0, 400.0);
+        world.add_particle(prey(200.0));
+        testing::run(&mut world, 400, &config);
+        assert_eq!(counts(&world.particles), Some([5, 0]));
+    }
+}
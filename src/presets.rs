@@ -0,0 +1,2302 @@
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng};
+
+use crate::black_holes::BlackHole;
+use crate::config::{CompareOverrides, SimulationConfig};
+use crate::demon::Gate;
+use crate::emitters::Emitter;
+use crate::epidemic;
+use crate::fission::{self, Nuclide, FUEL_MASS, NEUTRON_MASS, NEUTRON_RAD};
+use crate::mixing::{self, Gas};
+use crate::portals::Portal;
+use crate::predators::{self, BIRTH_SPEED_MAX, BIRTH_SPEED_MIN, PREDATOR_RAD};
+use crate::reactions::{self, Reagent};
+use crate::theme::Theme;
+use crate::voids::{Shape, Void};
+use crate::walls::{Block, Pillar, Segment};
+use crate::weather::Weather;
+use crate::wells::Well;
+use crate::world::{Particle, World};
+use crate::zones::Zone;
+use crate::{Point, Vector};
+
+// what a preset lays out in a box of the given size. gravity, restitution and walls, when
+// set, replace the current ones as the preset loads, a plain reset leaves them alone
+pub struct Scene {
+    pub particles: Vec<Particle>,
+    pub gravity: Option<Vector>,
+    pub restitution: Option<f32>,
+    pub walls: Option<Vec<Segment>>,
+    // the index of a particle to follow and chart the walk of, see tracer::Tracer
+    pub tracer: Option<usize>,
+    // like walls, these replace the current wells, pillars, emitters, zones and voids as the
+    // preset loads
+    pub wells: Option<Vec<Well>>,
+    pub pillars: Option<Vec<Pillar>>,
+    pub emitters: Option<Vec<Emitter>>,
+    pub zones: Option<Vec<Zone>>,
+    pub voids: Option<Vec<Void>>,
+    // and the gates, see demon::Gate
+    pub gates: Option<Vec<Gate>>,
+    // the same for blocks, black holes and portals
+    pub blocks: Option<Vec<Block>>,
+    pub black_holes: Option<Vec<BlackHole>>,
+    pub portals: Option<Vec<Portal>>,
+    // never clear the screen, so everything draws its path. back to a hard clear with the
+    // next preset
+    pub trails: bool,
+    // friction, sleeping and solver passes for piles of grains, back to the defaults with the
+    // next preset
+    pub grains: Option<Grains>,
+    // rain or snow with its own drag and wind, off again with the next preset
+    pub weather: Option<Weather>,
+    // run side by side with these overrides on the right, back to how it was with the next
+    // preset
+    pub compare: Option<CompareOverrides>,
+    // every wall held at this temperature and cooling, with the particles pulling together,
+    // see crystal::start. ordinary walls again with the next preset
+    pub cooling: Option<f32>,
+    // the index of a particle to nudge in a copy of the scene run alongside it, drawn over
+    // it with how far the two drift apart, see chaos::Chaos
+    pub chaos: Option<usize>,
+}
+
+// what a scene of grains runs with, on top of sleeping
+#[derive(Clone, Copy)]
+pub struct Grains {
+    // both the walls' and between particles
+    pub friction: f32,
+    pub solver_iterations: usize,
+}
+
+// `rng` comes from the reset seed. the fixed arrangements don't use it for anything but
+// colors, so the same preset in the same size box always starts the same
+pub struct Preset {
+    pub name: &'static str,
+    pub build: fn(&Layout, &mut StdRng) -> Scene,
+    // a line for the hud about how the scene's getting on
+    pub readout: Option<fn(&World, &SimulationConfig) -> String>,
+}
+
+pub struct Layout<'a> {
+    pub width: f32,
+    pub height: f32,
+    pub config: &'a SimulationConfig,
+    pub theme: &'a Theme,
+}
+
+impl Layout<'_> {
+    fn color(&self, rng: &mut StdRng) -> ggez::graphics::Color {
+        *self
+            .theme
+            .palette
+            .choose(rng)
+            .expect("Some colors in the palette")
+    }
+
+    // a particle with the usual density for its size
+    fn particle(&self, rng: &mut StdRng, x: f32, y: f32, vel: Vector, rad: f32) -> Particle {
+        let mass = self.config.mass_for_radius(rad);
+        Particle::new(Point { x, y }, vel, rad, mass, self.color(rng))
+    }
+}
+
+// loaded with the number keys, 1 first
+pub static PRESETS: &[Preset] = &[
+    Preset {
+        name: "ring",
+        build: ring,
+        readout: None,
+    },
+    Preset {
+        name: "head-on",
+        build: head_on,
+        readout: None,
+    },
+    Preset {
+        name: "heavy vs light",
+        build: heavy_vs_light,
+        readout: None,
+    },
+    Preset {
+        name: "newton's cradle",
+        build: cradle,
+        readout: None,
+    },
+    Preset {
+        name: "billiards",
+        build: billiards,
+        readout: None,
+    },
+    Preset {
+        name: "dense gas",
+        build: gas,
+        readout: None,
+    },
+    Preset {
+        name: "rain",
+        build: rain,
+        readout: None,
+    },
+    Preset {
+        name: "brownian motion",
+        build: brownian,
+        readout: None,
+    },
+    Preset {
+        name: "clusters",
+        build: clusters,
+        readout: None,
+    },
+    Preset {
+        name: "hourglass",
+        build: hourglass,
+        readout: Some(through_the_neck),
+    },
+    Preset {
+        name: "epidemic",
+        build: epidemic,
+        readout: None,
+    },
+    Preset {
+        name: "predator-prey",
+        build: predator_prey,
+        readout: None,
+    },
+    Preset {
+        name: "orbits",
+        build: orbits,
+        readout: None,
+    },
+    Preset {
+        name: "galton board",
+        build: galton,
+        readout: Some(galton_bins),
+    },
+    Preset {
+        name: "two gases",
+        build: two_gases,
+        readout: None,
+    },
+    Preset {
+        name: "granular pile",
+        build: granular,
+        readout: Some(repose),
+    },
+    Preset {
+        name: "fountain",
+        build: fountain,
+        readout: Some(in_the_air),
+    },
+    Preset {
+        name: "snow",
+        build: snow,
+        readout: None,
+    },
+    Preset {
+        name: "elastic vs inelastic",
+        build: elastic_vs_inelastic,
+        readout: None,
+    },
+    Preset {
+        name: "crystallization",
+        build: crystallization,
+        readout: None,
+    },
+    Preset {
+        name: "reactions",
+        build: reactions,
+        readout: None,
+    },
+    Preset {
+        name: "chain reaction",
+        build: chain_reaction,
+        readout: None,
+    },
+    Preset {
+        name: "chaos",
+        build: chaos,
+        readout: None,
+    },
+    Preset {
+        name: "maxwell's demon",
+        build: maxwells_demon,
+        readout: None,
+    },
+];
+
+const STILL: Vector = Vector { x: 0.0, y: 0.0 };
+const NO_GRAVITY: Option<Vector> = Some(Vector { x: 0.0, y: 0.0 });
+const TRACER_COLOR: ggez::graphics::Color = ggez::graphics::Color::new(1.0, 0.85, 0.3, 1.0);
+// gap left between particles placed side by side, so they don't start out touching
+const GAP: f32 = 1.0;
+
+// the original scene: config.num_particles on a circle, each heading away from the center
+fn ring(layout: &Layout, rng: &mut StdRng) -> Scene {
+    let config = layout.config;
+    let num_particles = config.num_particles;
+
+    let r = layout.height.min(layout.width) * 0.4;
+
+    let center_x = 0.5 * layout.width;
+    let center_y = 0.5 * layout.height;
+
+    let mut particles = Vec::with_capacity(num_particles);
+    for i in 0..num_particles {
+        let rad = config.spawn_radius(rng);
+        let mass = config.spawn_mass(rng);
+        let color = layout.color(rng);
+
+        let angle = std::f32::consts::PI * 2.0 * (i as f32) / (num_particles as f32);
+        let x = r * f32::cos(angle) + center_x;
+        let y = r * f32::sin(angle) + center_y;
+
+        particles.push(Particle::new(
+            Point { x, y },
+            Vector {
+                x: (center_x - x) * -0.2,
+                y: (center_y - y) * -0.2,
+            },
+            rad,
+            mass,
+            color,
+        ));
+    }
+
+    Scene {
+        particles,
+        gravity: None,
+        restitution: None,
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+fn head_on(layout: &Layout, rng: &mut StdRng) -> Scene {
+    let y = 0.5 * layout.height;
+    Scene {
+        particles: vec![
+            layout.particle(
+                rng,
+                0.25 * layout.width,
+                y,
+                Vector { x: 200.0, y: 0.0 },
+                20.0,
+            ),
+            layout.particle(
+                rng,
+                0.75 * layout.width,
+                y,
+                Vector { x: -200.0, y: 0.0 },
+                20.0,
+            ),
+        ],
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// ten times the mass, coming at each other at the same speed
+fn heavy_vs_light(layout: &Layout, rng: &mut StdRng) -> Scene {
+    let y = 0.5 * layout.height;
+    let mut heavy = layout.particle(
+        rng,
+        0.25 * layout.width,
+        y,
+        Vector { x: 150.0, y: 0.0 },
+        30.0,
+    );
+    let mut light = layout.particle(
+        rng,
+        0.75 * layout.width,
+        y,
+        Vector { x: -150.0, y: 0.0 },
+        12.0,
+    );
+    light.mass = 1.0;
+    heavy.mass = 10.0;
+    Scene {
+        particles: vec![heavy, light],
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// a row of equal particles at rest, and one more rolling into the end of it. the contacts
+// are resolved along the row in order within the one step, so the last one goes out alone
+fn cradle(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 5;
+    const RAD: f32 = 20.0;
+
+    let y = 0.5 * layout.height;
+    // exactly touching, so the hit goes down the row in one go
+    let pitch = 2.0 * RAD;
+    let start = 0.5 * layout.width - 0.5 * pitch * (COUNT - 1) as f32;
+
+    let mut particles: Vec<_> = (0..COUNT)
+        .map(|i| layout.particle(rng, start + pitch * i as f32, y, STILL, RAD))
+        .collect();
+    particles.push(layout.particle(
+        rng,
+        start - 6.0 * pitch,
+        y,
+        Vector { x: 300.0, y: 0.0 },
+        RAD,
+    ));
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// fifteen balls racked in a triangle pointing at the cue ball, on a table twice as long as
+// it's wide. with rack_jitter each ball is nudged a little off its spot, never far enough to
+// touch another, so every seed breaks differently
+fn billiards(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const ROWS: usize = 5;
+    const RAD: f32 = 12.0;
+    const JITTER: f32 = 0.5 * GAP;
+
+    let length = (1.6 * layout.height).min(0.9 * layout.width);
+    let (left, top) = (
+        0.5 * (layout.width - length),
+        0.5 * layout.height - 0.25 * length,
+    );
+    let corner = |x: f32, y: f32| Point {
+        x: left + x * length,
+        y: top + y * length,
+    };
+    let cushions = [
+        corner(0.0, 0.0),
+        corner(1.0, 0.0),
+        corner(1.0, 0.5),
+        corner(0.0, 0.5),
+    ];
+    let walls = (0..4)
+        .map(|i| Segment::new(cushions[i], cushions[(i + 1) % 4]))
+        .collect();
+
+    let y = 0.5 * layout.height;
+    let pitch = 2.0 * RAD + GAP;
+    // rows of a close packed triangle are sqrt(3)/2 of the pitch apart
+    let row_pitch = pitch * 3f32.sqrt() * 0.5;
+    let apex = left + 0.7 * length;
+
+    let mut particles = Vec::new();
+    for row in 0..ROWS {
+        let x = apex + row_pitch * row as f32;
+        for k in 0..=row {
+            let offset = (k as f32 - 0.5 * row as f32) * pitch;
+            let (dx, dy) = if layout.config.rack_jitter {
+                let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                let r = rng.gen_range(0.0..JITTER);
+                (r * angle.cos(), r * angle.sin())
+            } else {
+                (0.0, 0.0)
+            };
+            particles.push(layout.particle(rng, x + dx, y + offset + dy, STILL, RAD));
+        }
+    }
+    let cue = Vector { x: 600.0, y: 0.0 };
+    particles.push(layout.particle(rng, left + 0.25 * length, y, cue, RAD));
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(0.95),
+        walls: Some(walls),
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// 200 small fast particles spread over a grid, nothing slows them down
+fn gas(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 200;
+    const RAD: f32 = 4.0;
+
+    let particles = grid_positions(layout, COUNT, RAD)
+        .into_iter()
+        .map(|pos| {
+            let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+            let speed = rng.gen_range(150.0..300.0);
+            let vel = Vector {
+                x: speed * angle.cos(),
+                y: speed * angle.sin(),
+            };
+            layout.particle(rng, pos.x, pos.y, vel, RAD)
+        })
+        .collect();
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// rows of particles at rest along the top, falling under strong gravity, and splashing back
+// up to the top again once they hit the floor
+fn rain(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COLUMNS: usize = 20;
+    const ROWS: usize = 6;
+    const RAD: f32 = 6.0;
+
+    let dx = layout.width / COLUMNS as f32;
+    let dy = (2.0 * RAD + GAP) * 2.0;
+    let mut particles = Vec::with_capacity(COLUMNS * ROWS);
+    for row in 0..ROWS {
+        // every other row half a column over, so they don't land in stacks
+        let shift = if row % 2 == 0 { 0.25 } else { 0.75 };
+        for column in 0..COLUMNS {
+            let x = dx * (column as f32 + shift);
+            let y = RAD + GAP + dy * row as f32;
+            particles.push(layout.particle(rng, x, y, STILL, RAD));
+        }
+    }
+
+    Scene {
+        particles,
+        gravity: Some(Vector { x: 0.0, y: 400.0 }),
+        restitution: Some(0.6),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: Some(Weather::Rain),
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// one big slow particle in the middle, jostled by lots of small fast ones, and followed so
+// the hud can show its walk. it takes a crowd this thick for the walk to come out random
+// rather than the big one just drifting off in a line
+fn brownian(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 2000;
+    const RAD: f32 = 4.0;
+    const BIG_RAD: f32 = 30.0;
+
+    let center = Point {
+        x: 0.5 * layout.width,
+        y: 0.5 * layout.height,
+    };
+    // a color the palette won't have, so it stands out in any theme
+    let big = Particle::new(
+        center,
+        STILL,
+        BIG_RAD,
+        layout.config.mass_for_radius(BIG_RAD),
+        TRACER_COLOR,
+    );
+    let mut particles = vec![big];
+    let count = COUNT.min(layout.config.max_particles.saturating_sub(1));
+
+    for pos in grid_positions(layout, count * 2, RAD) {
+        if particles.len() > count {
+            break;
+        }
+        let (dx, dy) = (pos.x - center.x, pos.y - center.y);
+        if (dx * dx + dy * dy).sqrt() < BIG_RAD + RAD + GAP {
+            continue;
+        }
+        let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+        let speed = rng.gen_range(100.0..250.0);
+        let vel = Vector {
+            x: speed * angle.cos(),
+            y: speed * angle.sin(),
+        };
+        particles.push(layout.particle(rng, pos.x, pos.y, vel, RAD));
+    }
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: Some(0),
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// two hexagonal clumps flying into each other
+fn clusters(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const RINGS: usize = 3;
+    const RAD: f32 = 8.0;
+
+    let y = 0.5 * layout.height;
+    let mut particles = Vec::new();
+    for (cx, vx) in [(0.25 * layout.width, 150.0), (0.75 * layout.width, -150.0)] {
+        for pos in hexagon(Point { x: cx, y }, RINGS, 2.0 * RAD + GAP) {
+            particles.push(layout.particle(rng, pos.x, pos.y, Vector { x: vx, y: 0.0 }, RAD));
+        }
+    }
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// two chambers at 45 degrees meeting at a neck hourglass_neck wide in the middle, the top one
+// full of grains that trickle down through it. flipping gravity turns it over
+fn hourglass(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 300;
+
+    let walls = hourglass_walls(layout);
+    let rad = layout.config.spawn_radius_min;
+    let (cx, cy) = (0.5 * layout.width, 0.5 * layout.height);
+    let half_neck = 0.5 * layout.config.hourglass_neck;
+    // how far a particle's center has to keep from a 45 degree wall
+    let clearance = rad * 2f32.sqrt() + GAP;
+    let pitch = 2.0 * rad + GAP;
+    let count = COUNT.min(layout.config.max_particles);
+
+    // rows up from just above the neck, staggered like a stack of oranges
+    let mut particles = Vec::with_capacity(count);
+    let mut row = 0;
+    let mut y = cy - 3.0 * rad;
+    while particles.len() < count && y >= rad + GAP {
+        let half_width = half_neck + (cy - y) - clearance;
+        let shift = if row % 2 == 0 { 0.0 } else { 0.5 * pitch };
+        let mut x = cx - half_width + shift;
+        while x <= cx + half_width && particles.len() < count {
+            particles.push(layout.particle(rng, x, y, STILL, rad));
+            x += pitch;
+        }
+        y -= pitch * 3f32.sqrt() * 0.5;
+        row += 1;
+    }
+
+    Scene {
+        particles,
+        gravity: Some(Vector { x: 0.0, y: 400.0 }),
+        restitution: Some(0.2),
+        walls: Some(walls),
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// from each side of the neck up to the top of the box and down to the bottom, so with the
+// top and bottom of the box that's both chambers closed off
+fn hourglass_walls(layout: &Layout) -> Vec<Segment> {
+    let (cx, cy) = (0.5 * layout.width, 0.5 * layout.height);
+    let half_neck = 0.5 * layout.config.hourglass_neck;
+    let mut walls = Vec::with_capacity(4);
+    for side in &[-1.0, 1.0] {
+        let neck = Point {
+            x: cx + side * half_neck,
+            y: cy,
+        };
+        let reach = side * (half_neck + cy);
+        walls.push(Segment::new(
+            neck,
+            Point {
+                x: cx + reach,
+                y: 0.0,
+            },
+        ));
+        walls.push(Segment::new(
+            neck,
+            Point {
+                x: cx + reach,
+                y: layout.height,
+            },
+        ));
+    }
+    walls
+}
+
+// everything that's made it through to whichever half it's falling towards
+fn through_the_neck(world: &World, config: &SimulationConfig) -> String {
+    let neck = 0.5 * world.height;
+    let falling = config.gravity.y >= 0.0;
+    let through = world
+        .particles
+        .iter()
+        .filter(|p| (p.pos.y > neck) == falling)
+        .count();
+    format!("through the neck: {}", through)
+}
+
+// a crowd milling about with initial_infected of them ill, passing it on as they bump into
+// each other, see epidemic::spread
+fn epidemic(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 400;
+    const RAD: f32 = 5.0;
+
+    let mut particles: Vec<Particle> = grid_positions(layout, COUNT, RAD)
+        .into_iter()
+        .map(|pos| {
+            let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+            let speed = rng.gen_range(60.0..120.0);
+            let vel = Vector {
+                x: speed * angle.cos(),
+                y: speed * angle.sin(),
+            };
+            layout.particle(rng, pos.x, pos.y, vel, RAD)
+        })
+        .collect();
+    epidemic::seed_infections(
+        &mut particles,
+        layout.config.initial_infected,
+        layout.config,
+    );
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// a crowd of prey with a few predators spread among them, see predators::live
+fn predator_prey(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 160;
+    // one in this many starts out a predator
+    const PREDATOR_EVERY: usize = 20;
+    const PREY_RAD: f32 = 4.0;
+
+    let particles = grid_positions(layout, COUNT, PREDATOR_RAD)
+        .into_iter()
+        .enumerate()
+        .map(|(i, pos)| {
+            let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+            let speed = rng.gen_range(BIRTH_SPEED_MIN..BIRTH_SPEED_MAX);
+            let vel = Vector {
+                x: speed * angle.cos(),
+                y: speed * angle.sin(),
+            };
+            let mut particle = layout.particle(rng, pos.x, pos.y, vel, PREY_RAD);
+            if i % PREDATOR_EVERY == PREDATOR_EVERY / 2 {
+                predators::make_predator(&mut particle, layout.config);
+            } else {
+                predators::make_prey(&mut particle);
+            }
+            particle
+        })
+        .collect();
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// a heavy planet pinned in the middle with a well on it, a dozen moons going round it in
+// circles and a couple on ellipses further out, clear of them. the speeds come from the
+// well's own pull at each one, whatever well_falloff is, see wells::Well::accel
+fn orbits(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const MOONS: usize = 12;
+    const PLANET_RAD: f32 = 24.0;
+    const MOON_RAD: f32 = 4.0;
+    // the circles go from just off the planet out to this share of the way to the edge
+    const CIRCLES_OUT_TO: f32 = 0.6;
+    // each ellipse starts this share of the way out, as close in as it gets, and swings out
+    // to the edge
+    const ELLIPSES: [f32; 2] = [0.68, 0.8];
+    // turned this far round from one moon to the next, so none start side by side
+    const GOLDEN_ANGLE: f32 = 2.399_963;
+
+    let center = Point {
+        x: 0.5 * layout.width,
+        y: 0.5 * layout.height,
+    };
+    let well = Well {
+        pos: center,
+        strength: layout.config.well_strength,
+    };
+    let edge = 0.47 * layout.width.min(layout.height);
+    // `radius` out at `angle`, going anticlockwise at `share` of the speed for a circle there
+    let launch = |radius: f32, angle: f32, share: f32| {
+        let (sin, cos) = angle.sin_cos();
+        let pos = Point {
+            x: center.x + radius * cos,
+            y: center.y + radius * sin,
+        };
+        let accel = well.accel(pos, layout.config);
+        let speed = share * ((accel.x * accel.x + accel.y * accel.y).sqrt() * radius).sqrt();
+        let vel = Vector {
+            x: speed * sin,
+            y: -speed * cos,
+        };
+        (pos, vel)
+    };
+
+    let mut particles = vec![Particle {
+        pinned: true,
+        ..layout.particle(rng, center.x, center.y, STILL, PLANET_RAD)
+    }];
+    let (inner, outer) = (PLANET_RAD + 40.0, CIRCLES_OUT_TO * edge);
+    for i in 0..MOONS {
+        let radius = inner + (outer - inner) * i as f32 / (MOONS - 1) as f32;
+        let (pos, vel) = launch(radius, GOLDEN_ANGLE * i as f32, 1.0);
+        particles.push(layout.particle(rng, pos.x, pos.y, vel, MOON_RAD));
+    }
+    // for a pull going with 1 / distance^2, a moon going sqrt(2 q / (1 + q)) times as fast as
+    // a circle at its closest gets q times as far out
+    for (k, &share) in ELLIPSES.iter().enumerate() {
+        let radius = share * edge;
+        let q = edge / radius;
+        let angle = std::f32::consts::PI * k as f32;
+        let (pos, vel) = launch(radius, angle, (2.0 * q / (1.0 + q)).sqrt());
+        particles.push(layout.particle(rng, pos.x, pos.y, vel, MOON_RAD));
+    }
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: Some(vec![well]),
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: true,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// where everything on a galton board goes, worked out from the box and the ball size so the
+// readout finds the same bins the preset built
+struct Galton {
+    center: f32,
+    // the balls are spawn_radius_min, and pegs this far apart leave two balls' width between
+    // neighbours, so nothing can wedge between them
+    ball: f32,
+    spacing: f32,
+    rows: usize,
+    // the first row of pegs, and the last
+    top: f32,
+    bottom: f32,
+    // where the dividers start, a peg's spacing under the last row
+    bins_top: f32,
+}
+
+impl Galton {
+    const PEG_SHARE: f32 = 0.5;
+    const MAX_ROWS: usize = 12;
+    // of the balls' speed a second, over the pegs
+    const DRAG: f32 = 3.0;
+
+    fn new(width: f32, height: f32, config: &SimulationConfig) -> Galton {
+        let ball = config.spawn_radius_min.max(1.0);
+        let spacing = 2.0 * Galton::PEG_SHARE * ball + 4.0 * ball;
+        // room over the top for the emitter
+        let top = (0.1 * height).max(3.0 * spacing);
+        let row_height = spacing * 0.75f32.sqrt();
+        let rows = (((0.5 * height - top) / row_height) as usize).clamp(2, Galton::MAX_ROWS);
+        let bottom = top + (rows - 1) as f32 * row_height;
+        Galton {
+            center: 0.5 * width,
+            ball,
+            spacing,
+            rows,
+            top,
+            bottom,
+            bins_top: bottom + spacing,
+        }
+    }
+
+    // one under every gap along the last row, and one past either end of it
+    fn bins(&self) -> usize {
+        self.rows + 1
+    }
+
+    // the left edge of bin `i`, and of the one past the last for the right edge
+    fn divider(&self, i: usize) -> f32 {
+        self.center + (i as f32 - 0.5 * self.bins() as f32) * self.spacing
+    }
+
+    // what's settled in each bin
+    fn counts(&self, world: &World) -> Vec<usize> {
+        let mut counts = vec![0; self.bins()];
+        for p in world.particles.iter().filter(|p| p.pos.y > self.bins_top) {
+            let i = ((p.pos.x - self.divider(0)) / self.spacing).floor();
+            if i >= 0.0 && (i as usize) < counts.len() {
+                counts[i as usize] += 1;
+            }
+        }
+        counts
+    }
+}
+
+// balls dropped one at a time onto a triangle of pegs, bouncing left or right off each row
+// into bins along the bottom, where they pile up into a binomial distribution. the air over
+// the pegs is thick, so a ball comes off each one about as slow as it went on, like in a real
+// board. in free air they pick up speed row by row and run off down the outside
+fn galton(layout: &Layout, _rng: &mut StdRng) -> Scene {
+    const BALLS: usize = 150;
+
+    let board = Galton::new(layout.width, layout.height, layout.config);
+    let s = board.spacing;
+    let row_height = (board.bottom - board.top) / (board.rows - 1) as f32;
+    let mut pillars = Vec::new();
+    for row in 0..board.rows {
+        for j in 0..=row {
+            pillars.push(Pillar {
+                pos: Point {
+                    x: board.center + (j as f32 - 0.5 * row as f32) * s,
+                    y: board.top + row as f32 * row_height,
+                },
+                rad: Galton::PEG_SHARE * board.ball,
+            });
+        }
+    }
+
+    // the outer dividers go up to the last row and on up the sides of the triangle, a peg's
+    // spacing out from it, so nothing bounces off round the outside
+    let (left, right) = (board.divider(0), board.divider(board.bins()));
+    let mut walls = vec![
+        Segment::new(
+            Point {
+                x: board.center - s,
+                y: board.top,
+            },
+            Point {
+                x: left,
+                y: board.bottom,
+            },
+        ),
+        Segment::new(
+            Point {
+                x: board.center + s,
+                y: board.top,
+            },
+            Point {
+                x: right,
+                y: board.bottom,
+            },
+        ),
+    ];
+    for i in 0..=board.bins() {
+        let x = board.divider(i);
+        let from = if i == 0 || i == board.bins() {
+            board.bottom
+        } else {
+            board.bins_top
+        };
+        walls.push(Segment::new(
+            Point { x, y: from },
+            Point {
+                x,
+                y: layout.height,
+            },
+        ));
+    }
+
+    let emitter = Emitter {
+        spread: 10.0,
+        rate: 3.0,
+        speed_min: 10.0,
+        speed_max: 20.0,
+        rad_min: board.ball,
+        rad_max: board.ball,
+        max_total: Some(BALLS),
+        ..Emitter::new(
+            Point {
+                x: board.center,
+                y: board.top - 2.0 * s,
+            },
+            90.0,
+            layout.config,
+        )
+    };
+
+    let thick_air = Zone {
+        min: Point {
+            x: left - s,
+            y: board.top - s,
+        },
+        max: Point {
+            x: right + s,
+            y: board.bins_top,
+        },
+        gravity: 1.0,
+        drag: Galton::DRAG,
+        accel: Vector { x: 0.0, y: 0.0 },
+    };
+
+    Scene {
+        particles: Vec::new(),
+        gravity: Some(Vector { x: 0.0, y: 300.0 }),
+        restitution: Some(0.5),
+        walls: Some(walls),
+        tracer: None,
+        wells: None,
+        pillars: Some(pillars),
+        emitters: Some(vec![emitter]),
+        zones: Some(vec![thick_air]),
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// how many are in each bin, and as many as a binomial would put there
+fn galton_bins(world: &World, config: &SimulationConfig) -> String {
+    let board = Galton::new(world.width, world.height, config);
+    let counts = board.counts(world);
+    let total: usize = counts.iter().sum();
+    let n = board.rows as i32;
+    let mut expected = Vec::new();
+    let mut ways = 1.0f64;
+    for k in 0..=n {
+        expected.push(format!("{:.0}", total as f64 * ways / 2f64.powi(n)));
+        ways = ways * f64::from(n - k) / f64::from(k + 1);
+    }
+    let counts: Vec<String> = counts.iter().map(|c| c.to_string()).collect();
+    format!(
+        "bins: {} (binomial {})",
+        counts.join(" "),
+        expected.join(" ")
+    )
+}
+
+// a hot gas on the left and a cold one on the right, kept apart by a divider down the middle
+// until the open dividers key takes it out. the hud follows how mixed they are
+fn two_gases(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 150;
+    const RAD: f32 = 4.0;
+
+    let half = Vector {
+        x: 0.5 * layout.width - GAP,
+        y: layout.height,
+    };
+    let hot = Spawn {
+        corner: Point { x: 0.0, y: 0.0 },
+        size: half,
+        count: COUNT,
+        rad: RAD,
+        speed_min: 250.0,
+        speed_max: 350.0,
+    };
+    let cold = Spawn {
+        corner: Point {
+            x: 0.5 * layout.width + GAP,
+            y: 0.0,
+        },
+        speed_min: 50.0,
+        speed_max: 100.0,
+        ..hot
+    };
+    let mut particles = Vec::with_capacity(2 * COUNT);
+    for (spawn, gas) in [(hot, Gas::Hot), (cold, Gas::Cold)] {
+        for mut particle in spawn.particles(layout, rng) {
+            mixing::make(&mut particle, gas);
+            particles.push(particle);
+        }
+    }
+
+    let divider = Segment {
+        divider: true,
+        ..Segment::new(
+            Point {
+                x: 0.5 * layout.width,
+                y: 0.0,
+            },
+            Point {
+                x: 0.5 * layout.width,
+                y: layout.height,
+            },
+        )
+    };
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: Some(vec![divider]),
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// grains poured from the top onto the middle of the floor, where friction and a dead bounce
+// heap them up rather than letting them spread flat. they go to sleep once they've settled,
+// so the heap holds still rather than jittering
+fn granular(layout: &Layout, _rng: &mut StdRng) -> Scene {
+    const GRAINS: usize = 400;
+    const RAD: f32 = 4.0;
+
+    let emitter = Emitter {
+        spread: 20.0,
+        rate: 40.0,
+        speed_min: 100.0,
+        speed_max: 150.0,
+        rad_min: RAD,
+        rad_max: RAD,
+        max_total: Some(GRAINS.min(layout.config.max_particles)),
+        ..Emitter::new(
+            Point {
+                x: 0.5 * layout.width,
+                y: 4.0 * RAD,
+            },
+            90.0,
+            layout.config,
+        )
+    };
+
+    Scene {
+        particles: Vec::new(),
+        gravity: Some(Vector { x: 0.0, y: 600.0 }),
+        restitution: Some(0.3),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: Some(vec![emitter]),
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: Some(Grains {
+            friction: 0.6,
+            solver_iterations: 8,
+        }),
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// the heap's slope, from the top of it down to where it runs out on either side
+fn repose(world: &World, _config: &SimulationConfig) -> String {
+    match heap_slope(world) {
+        Some(degrees) => format!("angle of repose: {:.0} degrees", degrees),
+        None => String::from("angle of repose: no heap yet"),
+    }
+}
+
+fn heap_slope(world: &World) -> Option<f32> {
+    let resting: Vec<&Particle> = world
+        .particles
+        .iter()
+        .filter(|p| p.vel_magnitude() < 50.0 && p.pos.y > 0.5 * world.height)
+        .collect();
+    if resting.len() < 20 {
+        return None;
+    }
+    let top = resting.iter().map(|p| p.pos.y).fold(f32::MAX, f32::min);
+    let left = resting.iter().map(|p| p.pos.x).fold(f32::MAX, f32::min);
+    let right = resting.iter().map(|p| p.pos.x).fold(f32::MIN, f32::max);
+    let floor = world.height - resting[0].rad;
+    let half_base = (0.5 * (right - left)).max(f32::EPSILON);
+    Some((floor - top).atan2(half_base).to_degrees())
+}
+
+// a nozzle in the middle of the floor line spraying straight up, with everything falling back
+// below the line lost down a void, so as many go as come and it plays steadily. the spray's
+// fast enough to top out most of the way up the box, and fast enough that one a step can
+// leave the nozzle without landing on the last
+fn fountain(layout: &Layout, _rng: &mut StdRng) -> Scene {
+    const RAD: f32 = 2.5;
+    const GRAVITY: f32 = 450.0;
+    // the floor line this far up from the bottom, and the tops of the arcs this much of the
+    // way up from there
+    const FLOOR: f32 = 40.0;
+    const HEIGHT_SHARE: f32 = 0.8;
+
+    let floor = layout.height - FLOOR;
+    let speed = (2.0 * GRAVITY * HEIGHT_SHARE * floor).sqrt();
+    let emitter = Emitter {
+        spread: 12.0,
+        rate: 110.0,
+        speed_min: 0.97 * speed,
+        speed_max: speed,
+        rad_min: RAD,
+        rad_max: RAD,
+        ..Emitter::new(
+            Point {
+                x: 0.5 * layout.width,
+                y: floor,
+            },
+            270.0,
+            layout.config,
+        )
+    };
+    let drain = Void {
+        shape: Shape::Rect {
+            min: Point { x: 0.0, y: floor },
+            max: Point {
+                x: layout.width,
+                y: layout.height,
+            },
+        },
+        consumed: 0,
+    };
+
+    Scene {
+        particles: Vec::new(),
+        gravity: Some(Vector { x: 0.0, y: GRAVITY }),
+        restitution: None,
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: Some(vec![emitter]),
+        zones: None,
+        voids: Some(vec![drain]),
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+fn in_the_air(world: &World, _config: &SimulationConfig) -> String {
+    format!("fountain: {} in the air", world.particles.len())
+}
+
+// flakes spread over the top half drifting down on the wind, onto a pair of ramps they
+// pile up on. grain friction and sleeping hold the piles up, and whatever settles on the floor
+// goes back to the top
+fn snow(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const FLAKES: usize = 200;
+    const RAD: f32 = 4.0;
+    // how far the ramps drop over their length, gentle enough for the friction to hold
+    const SLOPE: f32 = 0.2;
+
+    let particles = grid_in(
+        Point { x: 0.0, y: 0.0 },
+        Vector {
+            x: layout.width,
+            y: 0.5 * layout.height,
+        },
+        FLAKES,
+        RAD,
+    )
+    .into_iter()
+    .map(|pos| layout.particle(rng, pos.x, pos.y, STILL, RAD))
+    .collect();
+    let ramp = |from: f32, to: f32, y: f32| {
+        let drop = SLOPE * (to - from).abs();
+        Segment::new(
+            Point {
+                x: from * layout.width,
+                y: y * layout.height,
+            },
+            Point {
+                x: to * layout.width,
+                y: y * layout.height + drop * layout.width,
+            },
+        )
+    };
+
+    Scene {
+        particles,
+        gravity: Some(Vector { x: 0.0, y: 200.0 }),
+        restitution: Some(0.1),
+        walls: Some(vec![ramp(0.15, 0.45, 0.55), ramp(0.9, 0.6, 0.7)]),
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: Some(Grains {
+            friction: 0.6,
+            solver_iterations: 4,
+        }),
+        weather: Some(Weather::Snow),
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// the same gas side by side, bouncing elastically on the left and losing half its speed
+// square to each hit on the right, which is soon a lot slower
+fn elastic_vs_inelastic(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 120;
+    const RAD: f32 = 8.0;
+    const SPEED: f32 = 200.0;
+
+    let particles = grid_in(
+        Point { x: 0.0, y: 0.0 },
+        Vector {
+            x: layout.width,
+            y: layout.height,
+        },
+        COUNT,
+        RAD,
+    )
+    .into_iter()
+    .map(|pos| {
+        let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+        let vel = Vector {
+            x: SPEED * angle.cos(),
+            y: SPEED * angle.sin(),
+        };
+        layout.particle(rng, pos.x, pos.y, vel, RAD)
+    })
+    .collect();
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: Some(CompareOverrides {
+            restitution: Some(0.5),
+            ..CompareOverrides::default()
+        }),
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// a few hundred particles that pull on each other, starting hot and cooling off through the
+// walls, see crystal::start. as it cools they condense into drops and then lock into
+// hexagonally packed rafts
+fn crystallization(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 300;
+    const RAD: f32 = 5.0;
+
+    let temperature = layout.config.reheat_temperature;
+    let scale = temperature / layout.config.mass_for_radius(RAD).sqrt();
+    let particles = grid_in(
+        Point { x: 0.0, y: 0.0 },
+        Vector {
+            x: layout.width,
+            y: layout.height,
+        },
+        COUNT.min(layout.config.max_particles),
+        RAD,
+    )
+    .into_iter()
+    .map(|pos| {
+        let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+        // a 2d gas's speeds go as a rayleigh distribution
+        let speed = scale * (-2.0 * (1.0 - rng.gen::<f32>()).ln()).sqrt();
+        let vel = Vector {
+            x: speed * angle.cos(),
+            y: speed * angle.sin(),
+        };
+        layout.particle(rng, pos.x, pos.y, vel, RAD)
+    })
+    .collect();
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: Some(temperature),
+        chaos: None,
+    }
+}
+
+// as many As as Bs, mixed together in a gas, reacting into Cs when they hit hard enough, see
+// reactions::react
+fn reactions(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 300;
+    const RAD: f32 = 5.0;
+
+    let spawn = Spawn {
+        corner: Point { x: 0.0, y: 0.0 },
+        size: Vector {
+            x: layout.width,
+            y: layout.height,
+        },
+        count: COUNT.min(layout.config.max_particles),
+        rad: RAD,
+        speed_min: 100.0,
+        speed_max: 300.0,
+    };
+    let mut particles = spawn.particles(layout, rng);
+    particles.shuffle(rng);
+    for (i, particle) in particles.iter_mut().enumerate() {
+        let reagent = if i % 2 == 0 { Reagent::A } else { Reagent::B };
+        reactions::make(particle, reagent);
+    }
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// a field of fuel sitting still in the middle and a single neutron fired into it from the
+// left, see fission::split
+fn chain_reaction(layout: &Layout, _rng: &mut StdRng) -> Scene {
+    const FUEL: usize = 400;
+    const RAD: f32 = 5.0;
+
+    let corner = Point {
+        x: 0.2 * layout.width,
+        y: 0.1 * layout.height,
+    };
+    let size = Vector {
+        x: 0.6 * layout.width,
+        y: 0.8 * layout.height,
+    };
+    let mut particles: Vec<Particle> = grid_in(
+        corner,
+        size,
+        FUEL.min(layout.config.max_particles.saturating_sub(1)),
+        RAD,
+    )
+    .into_iter()
+    .map(|pos| {
+        let mut fuel = Particle::new(pos, STILL, RAD, FUEL_MASS, Nuclide::Fuel.color());
+        fission::make(&mut fuel, Nuclide::Fuel);
+        fuel
+    })
+    .collect();
+    // lined up with the row through the middle, so it can't slip through between two
+    let row = particles
+        .iter()
+        .map(|fuel| fuel.pos.y)
+        .min_by(|a, b| {
+            let middle = 0.5 * layout.height;
+            (a - middle).abs().total_cmp(&(b - middle).abs())
+        })
+        .unwrap_or(0.5 * layout.height);
+    let mut neutron = Particle::new(
+        Point {
+            x: 0.5 * corner.x,
+            y: row,
+        },
+        Vector {
+            x: layout.config.neutron_speed,
+            y: 0.0,
+        },
+        NEUTRON_RAD,
+        NEUTRON_MASS,
+        Nuclide::Neutron.color(),
+    );
+    fission::make(&mut neutron, Nuclide::Neutron);
+    particles.push(neutron);
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// a few dozen particles bouncing round an empty box with nothing lost, and a copy of it with
+// the first particle nudged by chaos::PERTURBATION run alongside. the two look the same for a
+// few seconds, then the difference shows up and spreads until they've nothing in common
+fn chaos(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 40;
+    const RAD: f32 = 12.0;
+
+    let spawn = Spawn {
+        corner: Point { x: 0.0, y: 0.0 },
+        size: Vector {
+            x: layout.width,
+            y: layout.height,
+        },
+        count: COUNT.min(layout.config.max_particles),
+        rad: RAD,
+        speed_min: 150.0,
+        speed_max: 250.0,
+    };
+
+    Scene {
+        particles: spawn.particles(layout, rng),
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: None,
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: None,
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: Some(0),
+    }
+}
+
+// the same gas either side of a wall down the middle, with a gate halfway up it minded by the
+// demon, see demon::operate. it lets the fast ones through to the right and the slow ones to
+// the left, so the right heats up and the left cools down
+fn maxwells_demon(layout: &Layout, rng: &mut StdRng) -> Scene {
+    const COUNT: usize = 150;
+    const RAD: f32 = 5.0;
+    // the gap's height, a few particles across
+    const OPENING: f32 = 16.0 * RAD;
+
+    let x = 0.5 * layout.width;
+    let left = Spawn {
+        corner: Point { x: 0.0, y: 0.0 },
+        size: Vector {
+            x: x - GAP,
+            y: layout.height,
+        },
+        count: COUNT,
+        rad: RAD,
+        speed_min: 50.0,
+        speed_max: 350.0,
+    };
+    let right = Spawn {
+        corner: Point { x: x + GAP, y: 0.0 },
+        ..left
+    };
+    let mut particles = left.particles(layout, rng);
+    particles.extend(right.particles(layout, rng));
+
+    let (top, bottom) = (
+        0.5 * (layout.height - OPENING),
+        0.5 * (layout.height + OPENING),
+    );
+    let walls = vec![
+        Segment::new(Point { x, y: 0.0 }, Point { x, y: top }),
+        Segment::new(
+            Point { x, y: bottom },
+            Point {
+                x,
+                y: layout.height,
+            },
+        ),
+    ];
+    let gate = Gate {
+        x,
+        top,
+        bottom,
+        open: false,
+        demon: true,
+    };
+
+    Scene {
+        particles,
+        gravity: NO_GRAVITY,
+        restitution: Some(1.0),
+        walls: Some(walls),
+        tracer: None,
+        wells: None,
+        pillars: None,
+        emitters: None,
+        zones: None,
+        voids: None,
+        gates: Some(vec![gate]),
+        blocks: None,
+        black_holes: None,
+        portals: None,
+        trails: false,
+        grains: None,
+        weather: None,
+        compare: None,
+        cooling: None,
+        chaos: None,
+    }
+}
+
+// one kind of particle for a preset that has more than one, each with its own speeds, spread
+// on a grid over the `size` rectangle with its top left at `corner` and heading every which
+// way
+#[derive(Clone, Copy)]
+struct Spawn {
+    corner: Point,
+    size: Vector,
+    count: usize,
+    rad: f32,
+    speed_min: f32,
+    speed_max: f32,
+}
+
+impl Spawn {
+    fn particles(&self, layout: &Layout, rng: &mut StdRng) -> Vec<Particle> {
+        grid_in(self.corner, self.size, self.count, self.rad)
+            .into_iter()
+            .map(|pos| {
+                let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                let speed = rng.gen_range(self.speed_min..self.speed_max);
+                let vel = Vector {
+                    x: speed * angle.cos(),
+                    y: speed * angle.sin(),
+                };
+                layout.particle(rng, pos.x, pos.y, vel, self.rad)
+            })
+            .collect()
+    }
+}
+
+// `count` spots spread evenly over the box on a square grid, at least `rad` from the walls
+fn grid_positions(layout: &Layout, count: usize, rad: f32) -> Vec<Point> {
+    let corner = Point { x: 0.0, y: 0.0 };
+    let size = Vector {
+        x: layout.width,
+        y: layout.height,
+    };
+    grid_in(corner, size, count, rad)
+}
+
+// the same over the `size` rectangle with its top left at `corner`
+fn grid_in(corner: Point, size: Vector, count: usize, rad: f32) -> Vec<Point> {
+    let aspect = size.x / size.y.max(1.0);
+    let columns = ((count as f32 * aspect).sqrt().ceil() as usize).max(1);
+    let rows = count.div_ceil(columns);
+    let dx = (size.x - 2.0 * rad) / columns as f32;
+    let dy = (size.y - 2.0 * rad) / rows as f32;
+
+    (0..count)
+        .map(|i| Point {
+            x: corner.x + rad + dx * ((i % columns) as f32 + 0.5),
+            y: corner.y + rad + dy * ((i / columns) as f32 + 0.5),
+        })
+        .collect()
+}
+
+// a center point and `rings` rings of a hexagonal lattice around it
+fn hexagon(center: Point, rings: usize, pitch: f32) -> Vec<Point> {
+    let rings = rings as i32;
+    let mut points = Vec::new();
+    for q in -rings..=rings {
+        for r in (-rings).max(-q - rings)..=rings.min(-q + rings) {
+            points.push(Point {
+                x: center.x + pitch * (q as f32 + 0.5 * r as f32),
+                y: center.y + pitch * 3f32.sqrt() * 0.5 * r as f32,
+            });
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::THEMES;
+    use rand::SeedableRng;
+
+    #[test]
+    fn hourglass_trickles_through_the_neck() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 1280.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let scene = hourglass(&layout, &mut StdRng::seed_from_u64(1));
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.segments = scene.walls.unwrap();
+        let count = scene.particles.len();
+        assert!(count >= 250, "only {} fit", count);
+        assert!(scene
+            .particles
+            .iter()
+            .all(|p| p.pos.y < 0.5 * layout.height));
+        world.load(scene.particles);
+
+        for _ in 0..600 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+        let through = world
+            .particles
+            .iter()
+            .filter(|p| p.pos.y > 0.5 * layout.height)
+            .count();
+        assert!(through > 0 && through < count, "{} through", through);
+        // nothing gets out round the sides of either chamber
+        let (cx, cy) = (0.5 * layout.width, 0.5 * layout.height);
+        for p in &world.particles {
+            let half_width = 0.5 * config.hourglass_neck + (p.pos.y - cy).abs();
+            assert!((p.pos.x - cx).abs() < half_width, "escaped at {:?}", p.pos);
+        }
+    }
+
+    #[test]
+    fn cradle_sends_one_ball_out_the_far_end() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 1280.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let scene = cradle(&layout, &mut StdRng::seed_from_u64(1));
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            drag: 0.0,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.load(scene.particles);
+        let speed = world.particles[5].vel.x;
+
+        // long enough to reach the row, not for the last one to reach the wall
+        for _ in 0..240 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+        // the striker and the three in the middle are left still
+        let last = &world.particles[4];
+        assert!((last.vel.x - speed).abs() < 0.01 * speed, "{}", last.vel.x);
+        assert!(last.vel.y.abs() < 0.01 * speed);
+        for i in (0..4).chain(5..6) {
+            let p = &world.particles[i];
+            let moving = (p.vel.x * p.vel.x + p.vel.y * p.vel.y).sqrt();
+            assert!(moving < 0.01 * speed, "{} still going at {}", i, moving);
+        }
+    }
+
+    #[test]
+    fn billiards_breaks_cleanly() {
+        let config = SimulationConfig {
+            rack_jitter: false,
+            ..SimulationConfig::default()
+        };
+        let layout = Layout {
+            width: 1280.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        // without the jitter the seed only picks the colors
+        let scene = billiards(&layout, &mut StdRng::seed_from_u64(1));
+        let other = billiards(&layout, &mut StdRng::seed_from_u64(2));
+        assert!(scene
+            .particles
+            .iter()
+            .zip(&other.particles)
+            .all(|(a, b)| a.pos.x == b.pos.x && a.pos.y == b.pos.y));
+
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            drag: 0.0,
+            rack_jitter: false,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.segments = scene.walls.unwrap();
+        world.load(scene.particles);
+        let momentum = |world: &World| {
+            world.particles.iter().fold((0.0, 0.0), |(x, y), p| {
+                (x + p.mass * p.vel.x, y + p.mass * p.vel.y)
+            })
+        };
+        let (start, _) = momentum(&world);
+        let cue = world.particles.last().unwrap().vel.x;
+
+        let overlap = |world: &World| {
+            let mut deepest: f32 = 0.0;
+            for (i, a) in world.particles.iter().enumerate() {
+                for b in &world.particles[i + 1..] {
+                    let gap = ((a.pos.x - b.pos.x).powi(2) + (a.pos.y - b.pos.y).powi(2)).sqrt();
+                    deepest = deepest.max(a.rad + b.rad - gap);
+                }
+            }
+            deepest
+        };
+        let (mut deepest, mut cushioned) = (0.0f32, false);
+        for _ in 0..240 {
+            world.events.clear();
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            // until something reaches a cushion only the balls push on each other
+            cushioned |= !world.events.walls.is_empty();
+            if !cushioned {
+                let (x, y) = momentum(&world);
+                assert!((x - start).abs() < 1e-3 * start && y.abs() < 1e-3 * start);
+            }
+            deepest = deepest.max(overlap(&world));
+        }
+        // never further in than the cue goes in a step, and all sorted out once they've spread
+        assert!(deepest < cue / 120.0, "overlapped by {}", deepest);
+        assert!(
+            overlap(&world) < 0.5,
+            "still overlapping by {}",
+            overlap(&world)
+        );
+        // the rack's broken up, most of it moving
+        let moving = world
+            .particles
+            .iter()
+            .filter(|p| (p.vel.x * p.vel.x + p.vel.y * p.vel.y).sqrt() > 20.0)
+            .count();
+        assert!(moving >= 10, "only {} moving", moving);
+        let spread = world.particles.iter().map(|p| p.pos.y).fold(0.0, f32::max)
+            - world
+                .particles
+                .iter()
+                .map(|p| p.pos.y)
+                .fold(f32::MAX, f32::min);
+        assert!(spread > 200.0, "spread over {}", spread);
+    }
+
+    #[test]
+    fn orbits_hold_for_minutes() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 1280.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let scene = orbits(&layout, &mut StdRng::seed_from_u64(1));
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.wells = scene.wells.unwrap();
+        world.load(scene.particles);
+        let center = world.particles[0].pos;
+        let distance =
+            |p: &Particle| ((p.pos.x - center.x).powi(2) + (p.pos.y - center.y).powi(2)).sqrt();
+        let start: Vec<f32> = world.particles.iter().map(distance).collect();
+        let edge = 0.47 * layout.height;
+
+        // three minutes
+        let (mut closest, mut furthest) = (vec![f32::MAX; start.len()], vec![0.0f32; start.len()]);
+        for _ in 0..180 * 120 {
+            world.events.clear();
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            for (i, p) in world.particles.iter().enumerate() {
+                closest[i] = closest[i].min(distance(p));
+                furthest[i] = furthest[i].max(distance(p));
+            }
+        }
+        assert!(world.events.collisions.is_empty());
+        // the circles stay circles, wobbling a little with the step but not spiralling out, and
+        // the ellipses swing out to about the edge and back
+        for i in 1..=12 {
+            assert!(
+                furthest[i] - closest[i] < 0.03 * start[i],
+                "moon {} went from {} to {}",
+                i,
+                closest[i],
+                furthest[i]
+            );
+        }
+        for i in 13..15 {
+            assert!((closest[i] - start[i]).abs() < 0.02 * start[i]);
+            assert!(
+                (furthest[i] - edge).abs() < 0.1 * edge,
+                "{} out",
+                furthest[i]
+            );
+        }
+    }
+
+    #[test]
+    fn galton_board_piles_up_a_bell() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 1280.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let scene = galton(&layout, &mut StdRng::seed_from_u64(1));
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.segments = scene.walls.unwrap();
+        world.pillars = scene.pillars.unwrap();
+        world.emitters = scene.emitters.unwrap();
+        world.zones = scene.zones.unwrap();
+        for _ in 0..70 * 120 {
+            world.events.clear();
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+
+        // every ball ends up in a bin, spread about the middle one like a binomial would
+        let board = Galton::new(layout.width, layout.height, &config);
+        let counts = board.counts(&world);
+        let total: usize = counts.iter().sum();
+        assert_eq!(total, 150, "{:?}", counts);
+        let mean = counts
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| i as f32 * c as f32)
+            .sum::<f32>()
+            / total as f32;
+        let spread = (counts
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| (i as f32 - mean).powi(2) * c as f32)
+            .sum::<f32>()
+            / total as f32)
+            .sqrt();
+        let rows = board.rows as f32;
+        assert!((mean - 0.5 * rows).abs() < 0.5, "{:?}", counts);
+        assert!(
+            (spread / (0.5 * rows.sqrt()) - 1.0).abs() < 0.35,
+            "{:?} spread {}",
+            counts,
+            spread
+        );
+        assert!(counts[0] + counts[board.rows] < 10, "{:?}", counts);
+    }
+
+    #[test]
+    fn two_gases_mix_once_the_divider_is_out() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 800.0,
+            height: 600.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let scene = two_gases(&layout, &mut StdRng::seed_from_u64(1));
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.segments = scene.walls.unwrap();
+        world.load(scene.particles);
+        let run = |world: &mut World, seconds: usize| {
+            for _ in 0..seconds * 120 {
+                world.events.clear();
+                world.step(1.0 / 120.0, &config, &THEMES[0]);
+            }
+        };
+
+        // nothing gets past the divider
+        run(&mut world, 5);
+        assert_eq!(mixing::mixed_share(&world), Some(0.0));
+        assert!(mixing::entropy(&world).unwrap() < 0.05);
+
+        // and with it gone they spread through each other
+        assert!(world.open_dividers());
+        assert!(world.segments.is_empty() && !world.open_dividers());
+        run(&mut world, 30);
+        let share = mixing::mixed_share(&world).unwrap();
+        let entropy = mixing::entropy(&world).unwrap();
+        assert!(share > 0.35 && entropy > 0.7, "{} {}", share, entropy);
+    }
+
+    #[test]
+    fn granular_pile_heaps_up_and_goes_to_sleep() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 1280.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let scene = granular(&layout, &mut StdRng::seed_from_u64(1));
+        let grains = scene.grains.unwrap();
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            wall_friction: grains.friction,
+            particle_friction: grains.friction,
+            sleeping: true,
+            solver_iterations: grains.solver_iterations,
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.emitters = scene.emitters.unwrap();
+        for _ in 0..40 * 120 {
+            world.events.clear();
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+
+        // poured out, settled down and held still
+        assert_eq!(world.particles.len(), 400);
+        let asleep = world.particles.iter().filter(|p| p.asleep).count();
+        assert!(asleep > 360, "{} asleep", asleep);
+        let before: Vec<Point> = world.particles.iter().map(|p| p.pos).collect();
+        for _ in 0..120 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+        for (p, b) in world.particles.iter().zip(&before) {
+            assert!((p.pos.x - b.x).abs() < 1.0 && (p.pos.y - b.y).abs() < 1.0);
+        }
+        // in a heap rather than spread flat over the floor
+        let slope = heap_slope(&world).unwrap();
+        assert!(slope > 20.0 && slope < 60.0, "{} degrees", slope);
+    }
+
+    #[test]
+    fn fountain_plays_steadily() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 1280.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let scene = fountain(&layout, &mut StdRng::seed_from_u64(1));
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(layout.width, layout.height);
+        world.emitters = scene.emitters.unwrap();
+        world.voids = scene.voids.unwrap();
+        let mut counts = Vec::new();
+        for step in 0..30 * 120 {
+            world.events.clear();
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            if step % 120 == 0 {
+                counts.push(world.particles.len());
+            }
+        }
+        // once the first ones are back down, as many go as come
+        let settled = &counts[10..];
+        let (low, high) = (settled.iter().min().unwrap(), settled.iter().max().unwrap());
+        assert!(*low >= 300 && *high <= 500, "counts {:?}", counts);
+        assert!(world.particles.iter().all(|p| p.pos.y < layout.height));
+    }
+
+    #[test]
+    fn inelastic_side_runs_down_and_elastic_side_keeps_going() {
+        let config = SimulationConfig::default();
+        let layout = Layout {
+            width: 640.0,
+            height: 720.0,
+            config: &config,
+            theme: &THEMES[0],
+        };
+        let build = || elastic_vs_inelastic(&layout, &mut StdRng::seed_from_u64(1));
+        let scene = build();
+        let config = SimulationConfig {
+            gravity: scene.gravity.unwrap(),
+            restitution: scene.restitution.unwrap(),
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let sides = [config.clone(), scene.compare.unwrap().apply(&config)];
+        assert_eq!(sides[1].restitution, 0.5);
+
+        let worlds: Vec<World> = sides
+            .iter()
+            .map(|config| {
+                let mut world = World::new(layout.width, layout.height);
+                world.load(build().particles);
+                for _ in 0..5 * 120 {
+                    world.events.clear();
+                    world.step(1.0 / 120.0, config, &THEMES[0]);
+                }
+                world
+            })
+            .collect();
+        // the same seed, so the same gas on both sides to start with
+        let mut fresh = World::new(layout.width, layout.height);
+        fresh.load(build().particles);
+        let start = fresh.kinetic_energy();
+        let kept: Vec<f32> = worlds.iter().map(|w| w.kinetic_energy() / start).collect();
+        assert!((kept[0] - 1.0).abs() < 0.02, "{:?}", kept);
+        assert!(kept[1] < 0.3, "{:?}", kept);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use ggez::graphics::{Color, Rect};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::chart::History;
+use crate::config::SimulationConfig;
+use crate::world::{Particle, World};
+use crate::{Point, Vector};
+
+const A_COLOR: Color = Color::new(0.9, 0.2, 0.2, 1.0);
+const B_COLOR: Color = Color::new(0.25, 0.45, 1.0, 1.0);
+const C_COLOR: Color = Color::new(0.65, 0.3, 0.85, 1.0);
+
+// which chemical a particle is in a reaction scene. particles outside one have none
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reagent {
+    A,
+    B,
+    // what an A and a B make, and inert
+    C,
+}
+
+impl Reagent {
+    pub fn color(self) -> Color {
+        match self {
+            Reagent::A => A_COLOR,
+            Reagent::B => B_COLOR,
+            Reagent::C => C_COLOR,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Reagent::A => "A",
+            Reagent::B => "B",
+            Reagent::C => "C",
+        }
+    }
+}
+
+pub fn make(particle: &mut Particle, reagent: Reagent) {
+    particle.reagent = Some(reagent);
+    particle.color = reagent.color();
+    particle.original_color = reagent.color();
+}
+
+// how many As, Bs and Cs there are, none if nothing's in a reaction scene
+pub fn counts(particles: &[Particle]) -> Option<[usize; 3]> {
+    let mut counts = [0; 3];
+    let mut any = false;
+    for reagent in particles.iter().filter_map(|p| p.reagent) {
+        any = true;
+        match reagent {
+            Reagent::A => counts[0] += 1,
+            Reagent::B => counts[1] += 1,
+            Reagent::C => counts[2] += 1,
+        }
+    }
+    if any {
+        Some(counts)
+    } else {
+        None
+    }
+}
+
+// one step of a reaction scene. every A and B that hit each other this step, from the
+// collisions after `first_collision`, closing at activation_speed or faster, react: both go
+// and reaction_products Cs come out carrying their mass and momentum between them. one comes
+// out where their center of mass was, two where the A and the B were. a C is as big as the
+// two were put together, or as each of them on average for two, and goes in at the nearest
+// free spot by `spawn_at`, the A and B out by id with `remove`, so nothing's left overlapping
+// or pointing at the wrong particle
+pub fn react(world: &mut World, first_collision: usize, config: &SimulationConfig) {
+    if !world.particles.iter().any(|p| p.reagent.is_some()) {
+        return;
+    }
+
+    let reagents: BTreeMap<u64, (Reagent, f32)> = world
+        .particles
+        .iter()
+        .filter_map(|p| p.reagent.map(|r| (p.id, (r, p.mass))))
+        .collect();
+    let mut reacting: Vec<(u64, u64)> = Vec::new();
+    for event in &world.events.collisions[first_collision..] {
+        let (a, b) = match (reagents.get(&event.a), reagents.get(&event.b)) {
+            (Some((Reagent::A, _)), Some((Reagent::B, _))) => (event.a, event.b),
+            (Some((Reagent::B, _)), Some((Reagent::A, _))) => (event.b, event.a),
+            _ => continue,
+        };
+        // the impulse took (1 + restitution) of the closing speed between them
+        let inverse_masses: f32 = [a, b]
+            .iter()
+            .map(|id| 1.0 / reagents[id].1.max(f32::EPSILON))
+            .sum();
+        let closing = event.impulse * inverse_masses / (1.0 + config.restitution.max(0.0));
+        if closing < config.activation_speed {
+            continue;
+        }
+        if reacting
+            .iter()
+            .any(|&(x, y)| [x, y].contains(&a) || [x, y].contains(&b))
+        {
+            continue;
+        }
+        reacting.push((a, b));
+    }
+
+    for (a, b) in reacting {
+        let (a, b) = match (world.remove(a), world.remove(b)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => continue,
+        };
+        let mass = a.mass + b.mass;
+        let vel = Vector {
+            x: (a.vel.x * a.mass + b.vel.x * b.mass) / mass,
+            y: (a.vel.y * a.mass + b.vel.y * b.mass) / mass,
+        };
+        let area = a.rad * a.rad + b.rad * b.rad;
+        let positions = if config.reaction_products >= 2 {
+            vec![a.pos, b.pos]
+        } else {
+            vec![Point {
+                x: (a.pos.x * a.mass + b.pos.x * b.mass) / mass,
+                y: (a.pos.y * a.mass + b.pos.y * b.mass) / mass,
+            }]
+        };
+        let share = positions.len() as f32;
+        for pos in positions {
+            let mut product = Particle::new(pos, vel, (area / share).sqrt(), mass / share, C_COLOR);
+            make(&mut product, Reagent::C);
+            world.spawn_at(product, config);
+        }
+    }
+}
+
+// the three counts over time, for the chart
+#[derive(Default)]
+pub struct Reactions {
+    history: History,
+}
+
+impl Reactions {
+    pub fn clear(&mut self) {
+        *self = Reactions::default();
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32) {
+        if let Some(counts) = counts(&world.particles) {
+            self.history
+                .record(counts.iter().map(|&c| c as f32).collect(), time_elapsed);
+        }
+    }
+
+    pub fn hud_line(&self, world: &World) -> Option<String> {
+        let counts = counts(&world.particles)?;
+        let names = [Reagent::A, Reagent::B, Reagent::C];
+        let parts: Vec<String> = names
+            .iter()
+            .zip(counts.iter())
+            .map(|(reagent, count)| format!("{} {}", count, reagent.name()))
+            .collect();
+        Some(format!("reaction: {}", parts.join(", ")))
+    }
+
+    pub fn draw(&self, ctx: &mut Context, pane: Rect) -> GameResult {
+        self.history
+            .draw_stacked(ctx, pane, &[A_COLOR, B_COLOR, C_COLOR])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn reagent(x: f32, vel: f32, reagent: Reagent) -> Particle {
+        let mut particle = Particle {
+            vel: Vector { x: vel, y: 0.0 },
+            ..testing::particle(x, 200.0, 5.0)
+        };
+        make(&mut particle, reagent);
+        particle
+    }
+
+    // an A and a B heading at each other, run until they've met
+    fn meet(a: f32, b: f32, config: &SimulationConfig) -> World {
+        let mut world = World::new(400.0, 400.0);
+        world.add_particle(reagent(150.0, a, Reagent::A));
+        world.add_particle(reagent(250.0, -b, Reagent::B));
+        testing::run(&mut world, 240, config);
+        world
+    }
+
+    #[test]
+    fn too_slow_and_they_just_bounce() {
+        let config = SimulationConfig {
+            activation_speed: 100.0,
+            reaction_products: 1,
+            ..testing::config()
+        };
+        let world = meet(40.0, 40.0, &config);
+        assert_eq!(counts(&world.particles), Some([1, 1, 0]));
+    }
+
+    #[test]
+    fn fast_enough_they_make_one_c_where_they_met() {
+        // closing at 160, with both their mass and no momentum
+        let config = SimulationConfig {
+            activation_speed: 100.0,
+            reaction_products: 1,
+            ..testing::config()
+        };
+        let world = meet(80.0, 80.0, &config);
+        assert_eq!(counts(&world.particles), Some([0, 0, 1]));
+        let c = &world.particles[0];
+        assert_eq!(c.mass, 2.0);
+        assert!((c.rad - 50f32.sqrt()).abs() < 1e-4);
+        assert!(c.vel.x.abs() < 1e-4 && (c.pos.x - 200.0).abs() < 5.0);
+        assert_eq!(c.color, C_COLOR);
+    }
+
+    #[test]
+    fn two_products_share_the_mass_and_momentum_without_overlapping() {
+        let config = SimulationConfig {
+            activation_speed: 100.0,
+            reaction_products: 2,
+            ..testing::config()
+        };
+        let world = meet(120.0, 40.0, &config);
+        assert_eq!(counts(&world.particles), Some([0, 0, 2]));
+        let momentum: f32 = world.particles.iter().map(|p| p.mass * p.vel.x).sum();
+        assert!((momentum - 80.0).abs() < 1e-3, "{}", momentum);
+        for (i, c) in world.particles.iter().enumerate() {
+            assert_eq!(c.mass, 1.0);
+            for other in &world.particles[i + 1..] {
+                assert!(c.distance(other) >= c.rad + other.rad - 1e-3);
+            }
+        }
+    }
+}
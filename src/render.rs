@@ -0,0 +1,313 @@
+use ggez::graphics::{
+    spritebatch::SpriteBatch, Color, DrawMode, DrawParam, Image, Mesh, MeshBuilder,
+};
+use ggez::{Context, GameResult};
+use serde::Deserialize;
+
+use crate::{broadphase::SpatialGrid, Particle, Point, SimulationConfig, Vector};
+
+// flattening tolerance as a fraction of the radius, divided by circle_quality
+const TOLERANCE_PER_RADIUS: f32 = 0.02;
+const MIN_TOLERANCE: f32 = 0.01;
+const MAX_TOLERANCE: f32 = 0.5;
+
+// squash and stretch: the aspect ratio grows with speed until MAX_STRETCH
+const STRETCH_SPEED: f32 = 400.0;
+const MAX_STRETCH: f32 = 1.8;
+
+// glow grows from MIN to MAX as speed approaches GLOW_SPEED
+const GLOW_SPEED: f32 = 400.0;
+const GLOW_SCALE_MIN: f32 = 1.6;
+const GLOW_SCALE_MAX: f32 = 3.0;
+const GLOW_ALPHA_MIN: f32 = 0.08;
+const GLOW_ALPHA_MAX: f32 = 0.3;
+
+// outline width never goes past this fraction of the radius, so tiny particles stay readable
+const MAX_STROKE_PER_RADIUS: f32 = 0.35;
+// in the fill-and-stroke style the inside is drawn at this fraction of the opacity
+const BOTH_FILL_ALPHA: f32 = 0.35;
+
+// opacity of a plexus line between touching particles, fading to nothing at the max distance
+const PLEXUS_ALPHA: f32 = 0.6;
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParticleStyle {
+    Fill,
+    Stroke,
+    // a faint fill inside a full strength outline
+    Both,
+}
+
+impl ParticleStyle {
+    pub fn next(self) -> ParticleStyle {
+        match self {
+            ParticleStyle::Fill => ParticleStyle::Stroke,
+            ParticleStyle::Stroke => ParticleStyle::Both,
+            ParticleStyle::Both => ParticleStyle::Fill,
+        }
+    }
+}
+
+// indices into the particle list, biggest first so small particles are drawn on top.
+// the physics order is left alone, and the sort only reruns when a radius or the set of
+// particles changes
+#[derive(Default)]
+pub struct DrawOrder {
+    order: Vec<usize>,
+    // (id, radius bits) the order was sorted for, in particle order
+    sorted_for: Vec<(u64, u32)>,
+}
+
+impl DrawOrder {
+    pub fn update(&mut self, particles: &[Particle]) -> &[usize] {
+        let unchanged = self.sorted_for.len() == particles.len()
+            && self
+                .sorted_for
+                .iter()
+                .zip(particles)
+                .all(|(&(id, rad), p)| id == p.id && rad == p.rad.to_bits());
+
+        if !unchanged {
+            self.sorted_for.clear();
+            self.sorted_for
+                .extend(particles.iter().map(|p| (p.id, p.rad.to_bits())));
+
+            self.order.clear();
+            self.order.extend(0..particles.len());
+            // stable, so equal radii keep their physics order and don't flicker
+            self.order
+                .sort_by(|&a, &b| particles[b].rad.total_cmp(&particles[a].rad));
+        }
+
+        &self.order
+    }
+}
+
+// all the particles go into a single mesh, so drawing them is one call however many there are
+pub fn particle_mesh(
+    ctx: &mut Context,
+    particles: &[Particle],
+    order: &[usize],
+    config: &SimulationConfig,
+) -> GameResult<Option<Mesh>> {
+    batch(
+        ctx,
+        particles,
+        order,
+        config,
+        config.particle_style,
+        |particle| (particle.rad, particle.color),
+    )
+}
+
+// a bigger, faint copy of every particle for the additive glow pass.
+// faster particles glow wider and brighter so the bloom doubles as a motion indicator
+pub fn glow_mesh(
+    ctx: &mut Context,
+    particles: &[Particle],
+    order: &[usize],
+    config: &SimulationConfig,
+) -> GameResult<Option<Mesh>> {
+    batch(
+        ctx,
+        particles,
+        order,
+        config,
+        ParticleStyle::Fill,
+        |particle| {
+            let t = (particle.vel_magnitude() / GLOW_SPEED).min(1.0);
+            let rad = particle.rad * (GLOW_SCALE_MIN + (GLOW_SCALE_MAX - GLOW_SCALE_MIN) * t);
+            let alpha = GLOW_ALPHA_MIN + (GLOW_ALPHA_MAX - GLOW_ALPHA_MIN) * t;
+            let mut color = particle.color;
+            color.a = alpha;
+            (rad, color)
+        },
+    )
+}
+
+fn batch(
+    ctx: &mut Context,
+    particles: &[Particle],
+    order: &[usize],
+    config: &SimulationConfig,
+    particle_style: ParticleStyle,
+    style: impl Fn(&Particle) -> (f32, Color),
+) -> GameResult<Option<Mesh>> {
+    if order.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = MeshBuilder::new();
+    for particle in order.iter().map(|&i| &particles[i]) {
+        let (rad, color) = style(particle);
+        let aspect = if config.squash_stretch {
+            stretch_factor(particle.vel_magnitude())
+        } else {
+            1.0
+        };
+
+        let stroke_width = config
+            .stroke_width
+            .min(rad * MAX_STROKE_PER_RADIUS)
+            .max(0.5);
+        let passes = match particle_style {
+            ParticleStyle::Fill => [Some((DrawMode::fill(), 0.0, color)), None],
+            ParticleStyle::Stroke => [
+                None,
+                Some((DrawMode::stroke(stroke_width), stroke_width, color)),
+            ],
+            ParticleStyle::Both => {
+                let faint = Color {
+                    a: color.a * BOTH_FILL_ALPHA,
+                    ..color
+                };
+                [
+                    Some((DrawMode::fill(), 0.0, faint)),
+                    Some((DrawMode::stroke(stroke_width), stroke_width, color)),
+                ]
+            }
+        };
+
+        // strokes are centred on the outline, so pull them in by half the width
+        // to keep the outer edge where the fill would be
+        for (mode, width, color) in passes.iter().flatten().copied() {
+            let inset = width * 0.5;
+            if aspect <= 1.0 + f32::EPSILON {
+                let tolerance = tolerance(rad, config.circle_quality);
+                builder.circle(mode, particle.pos, rad - inset, tolerance, color);
+            } else {
+                // keep the area the same so fast particles don't look bigger
+                let major = rad * aspect.sqrt();
+                let minor = rad / aspect.sqrt();
+                let angle = particle.vel.y.atan2(particle.vel.x);
+                let tolerance = tolerance(major, config.circle_quality);
+                let points =
+                    ellipse_points(particle.pos, major - inset, minor - inset, angle, tolerance);
+                builder.polygon(mode, &points, color)?;
+            }
+        }
+    }
+
+    builder.build(ctx).map(Some)
+}
+
+// the image scaled to each particle's diameter and tinted with its color
+pub fn image_batch(image: &Image, particles: &[Particle], order: &[usize]) -> SpriteBatch {
+    let mut batch = SpriteBatch::new(image.clone());
+    let (w, h) = (image.width() as f32, image.height() as f32);
+
+    for particle in order.iter().map(|&i| &particles[i]) {
+        let diameter = particle.rad * 2.0;
+        batch.add(
+            DrawParam::default()
+                .dest(particle.pos)
+                .offset(Point { x: 0.5, y: 0.5 })
+                .scale(Vector {
+                    x: diameter / w,
+                    y: diameter / h,
+                })
+                .color(particle.color),
+        );
+    }
+
+    batch
+}
+
+// a line between every pair of particles closer than `distance`, fading out with distance.
+// `grid` has to be built from `particles` already
+pub fn plexus_mesh(
+    ctx: &mut Context,
+    particles: &[Particle],
+    grid: &SpatialGrid,
+    distance: f32,
+) -> GameResult<Option<Mesh>> {
+    let mut builder = MeshBuilder::new();
+    let mut result = Ok(());
+    let mut empty = true;
+
+    grid.for_each_pair(particles, distance, |i, j| {
+        let (a, b) = (&particles[i], &particles[j]);
+        let d = a.distance(b);
+        if d >= distance || d < 0.5 || result.is_err() {
+            return;
+        }
+
+        let mut color = a.color;
+        color.a = PLEXUS_ALPHA * (1.0 - d / distance);
+        result = builder.line(&[a.pos, b.pos], 1.0, color).map(|_| ());
+        empty = false;
+    });
+    result?;
+
+    if empty {
+        Ok(None)
+    } else {
+        builder.build(ctx).map(Some)
+    }
+}
+
+pub fn velocity_mesh(ctx: &mut Context, particles: &[Particle]) -> GameResult<Option<Mesh>> {
+    let mut builder = MeshBuilder::new();
+    let mut empty = true;
+
+    for particle in particles {
+        // a zero length line can't be tessellated
+        if particle.vel_magnitude() < 0.5 {
+            continue;
+        }
+        builder.line(
+            &[
+                particle.pos,
+                Point {
+                    x: particle.pos.x + particle.vel.x,
+                    y: particle.pos.y + particle.vel.y,
+                },
+            ],
+            2.0,
+            particle.color,
+        )?;
+        empty = false;
+    }
+
+    if empty {
+        Ok(None)
+    } else {
+        builder.build(ctx).map(Some)
+    }
+}
+
+// 1.0 at rest, easing up to MAX_STRETCH
+fn stretch_factor(speed: f32) -> f32 {
+    let t = (speed / STRETCH_SPEED).min(1.0);
+    1.0 + (MAX_STRETCH - 1.0) * t * (2.0 - t)
+}
+
+// scaling with the radius keeps the number of segments per pixel of outline similar,
+// instead of a fixed tolerance wasting vertices on small particles and faceting big ones.
+// never more than half the radius, so tiny circles still get a sane polygon
+fn tolerance(rad: f32, quality: f32) -> f32 {
+    let tolerance =
+        (rad * TOLERANCE_PER_RADIUS / quality.max(0.01)).clamp(MIN_TOLERANCE, MAX_TOLERANCE);
+    tolerance.min(rad * 0.5).max(f32::EPSILON)
+}
+
+// outline of an ellipse rotated by `angle`, clockwise in screen space
+fn ellipse_points(center: Point, major: f32, minor: f32, angle: f32, tolerance: f32) -> Vec<Point> {
+    // same flattening rule lyon uses for circles, from the largest radius
+    let segments = (std::f32::consts::PI / (1.0 - tolerance / major).max(-1.0).acos())
+        .ceil()
+        .max(8.0) as usize;
+
+    let (sin, cos) = angle.sin_cos();
+    (0..segments)
+        .map(|i| {
+            let t = std::f32::consts::PI * 2.0 * i as f32 / segments as f32;
+            let (x, y) = (major * t.cos(), minor * t.sin());
+            Point {
+                x: center.x + x * cos - y * sin,
+                y: center.y + x * sin + y * cos,
+            }
+        })
+        .collect()
+}
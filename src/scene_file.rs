@@ -0,0 +1,245 @@
+use std::{fs, path::Path};
+
+use ggez::graphics::Color;
+use ggez::{GameError, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::black_holes::BlackHole;
+use crate::config::SimulationConfig;
+use crate::container::Container;
+use crate::demon::Gate;
+use crate::emitters::Emitter;
+use crate::epidemic::Health;
+use crate::fission::Nuclide;
+use crate::mixing::Gas;
+use crate::portals::Portal;
+use crate::predators::Species;
+use crate::reactions::Reagent;
+use crate::targets::Target;
+use crate::voids::Void;
+use crate::walls::{Block, Pillar, Segment};
+use crate::wells::Well;
+use crate::world::{Particle, World};
+use crate::zones::Zone;
+use crate::{Point, Vector};
+
+// a scene as saved to disk: enough to put the same particles, walls, gates, pillars, blocks,
+// wells, black holes, portals, voids, emitters, zones and targets back, along with the physics
+// they were set up for
+#[derive(Serialize, Deserialize)]
+pub struct SceneFile {
+    // toml wants plain values ahead of tables, so these come first, and nothing's written
+    // empty, an empty list is a plain value
+    pub restitution: f32,
+    pub gravity: Vector,
+    // with targets, how many slingshot launches there are to hit them with, see targets::Game
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<usize>,
+    // where they're all launched from, wherever the click was, if not just anywhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launcher: Option<Point>,
+    // the container's corners, if there is one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub container: Vec<Point>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub particles: Vec<SavedParticle>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub walls: Vec<Segment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pillars: Vec<Pillar>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<Block>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gates: Vec<Gate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wells: Vec<Well>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub black_holes: Vec<BlackHole>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub portals: Vec<Portal>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub voids: Vec<Void>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emitters: Vec<Emitter>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<Zone>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<Target>,
+}
+
+// ids are handed out again on load, and collision colors are forgotten
+#[derive(Serialize, Deserialize)]
+pub struct SavedParticle {
+    pub rad: f32,
+    pub mass: f32,
+    pub color: [f32; 4],
+    pub pos: Point,
+    pub vel: Vector,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<Health>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species: Option<Species>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas: Option<Gas>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reagent: Option<Reagent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nuclide: Option<Nuclide>,
+}
+
+impl SceneFile {
+    // the budget and launcher are the game's rather than the world's, for whoever's saving to
+    // fill in
+    pub fn capture(world: &World, config: &SimulationConfig) -> SceneFile {
+        SceneFile {
+            restitution: config.restitution,
+            gravity: config.gravity,
+            budget: None,
+            launcher: None,
+            container: world
+                .container
+                .as_ref()
+                .map_or_else(Vec::new, |c| c.vertices().to_vec()),
+            particles: world.particles.iter().map(SavedParticle::from).collect(),
+            walls: world.segments.clone(),
+            pillars: world.pillars.clone(),
+            blocks: world.blocks.clone(),
+            gates: world.gates.clone(),
+            wells: world.wells.clone(),
+            black_holes: world.black_holes.clone(),
+            portals: world.portals.clone(),
+            voids: world.voids.clone(),
+            emitters: world.emitters.clone(),
+            zones: world.zones.clone(),
+            targets: world.targets.clone(),
+        }
+    }
+
+    pub fn container(&self) -> Result<Option<Container>, String> {
+        if self.container.is_empty() {
+            Ok(None)
+        } else {
+            Container::new(self.container.clone()).map(Some)
+        }
+    }
+
+    pub fn particles(&self) -> Vec<Particle> {
+        self.particles
+            .iter()
+            .map(|p| {
+                let color = Color::from(p.color);
+                Particle {
+                    pinned: p.pinned,
+                    health: p.health,
+                    species: p.species,
+                    gas: p.gas,
+                    reagent: p.reagent,
+                    nuclide: p.nuclide,
+                    color: p
+                        .health
+                        .and_then(Health::color)
+                        .or_else(|| p.species.map(Species::color))
+                        .or_else(|| p.gas.map(Gas::color))
+                        .or_else(|| p.reagent.map(Reagent::color))
+                        .or_else(|| p.nuclide.map(Nuclide::color))
+                        .unwrap_or(color),
+                    ..Particle::new(p.pos, p.vel, p.rad, p.mass, color)
+                }
+            })
+            .collect()
+    }
+
+    // toml, unless the file name ends in .json
+    pub fn save(&self, path: &Path) -> GameResult {
+        let text = if is_json(path) {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+        } else {
+            toml::to_string(self).map_err(|e| e.to_string())
+        };
+        let text =
+            text.map_err(|e| GameError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> GameResult<SceneFile> {
+        let text = fs::read_to_string(path)?;
+        let scene = if is_json(path) {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&text).map_err(|e| e.to_string())
+        };
+        scene.map_err(|e| GameError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+}
+
+impl From<&Particle> for SavedParticle {
+    fn from(p: &Particle) -> SavedParticle {
+        SavedParticle {
+            pos: p.pos,
+            vel: p.vel,
+            rad: p.rad,
+            mass: p.mass,
+            color: p.original_color.into(),
+            pinned: p.pinned,
+            health: p.health,
+            species: p.species,
+            gas: p.gas,
+            reagent: p.reagent,
+            nuclide: p.nuclide,
+        }
+    }
+}
+
+// one particle as copied to the clipboard. the saved fields plus a couple more that help when
+// debugging, which loading ignores, so it drops straight into the particles of a .json scene
+#[derive(Serialize)]
+pub struct CopiedParticle {
+    pub id: u64,
+    // the box's, particles don't have their own
+    pub restitution: f32,
+    #[serde(flatten)]
+    pub particle: SavedParticle,
+}
+
+impl CopiedParticle {
+    pub fn to_json(&self) -> String {
+        // only plain numbers in here, so this can't fail
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_scenes_load() {
+        let pinball: SceneFile = toml::from_str(include_str!("../scenes/pinball.toml")).unwrap();
+        assert!(!pinball.particles.is_empty());
+        assert!(pinball.walls.iter().all(|wall| wall.length() > 0.0));
+        assert!(pinball.container().unwrap().is_none());
+
+        let breakout: SceneFile = toml::from_str(include_str!("../scenes/breakout.toml")).unwrap();
+        assert!(breakout.walls.iter().all(|wall| wall.strength.is_some()));
+        assert_eq!(breakout.emitters.len(), 1);
+
+        // each level has something to aim at and something to aim with
+        for level in &[
+            include_str!("../scenes/level1.toml"),
+            include_str!("../scenes/level2.toml"),
+            include_str!("../scenes/level3.toml"),
+        ] {
+            let level: SceneFile = toml::from_str(level).unwrap();
+            assert!(!level.targets.is_empty() && level.targets.iter().all(|t| !t.hit));
+            assert!(level.budget.is_some_and(|budget| budget > 0));
+            assert!(level.launcher.is_some());
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use rand::Rng;
+
+// offset at full trauma with an intensity of 1
+const MAX_OFFSET: f32 = 12.0;
+// trauma lost per second, so the hardest shake is over in under half a second
+const DECAY: f32 = 2.5;
+// trauma added per unit of impulse above the threshold
+const TRAUMA_PER_IMPULSE: f32 = 1.0 / 300.0;
+
+// shakes the whole scene after heavy impacts. hits add "trauma" which decays over time,
+// so overlapping shakes build on each other but never beyond the maximum
+#[derive(Default)]
+pub struct ScreenShake {
+    trauma: f32,
+    // picked once a frame so everything pushed that frame moves together
+    offset: [f32; 2],
+}
+
+impl ScreenShake {
+    pub fn add(&mut self, impulse: f32, threshold: f32) {
+        if impulse >= threshold {
+            let amount = 0.3 + (impulse - threshold) * TRAUMA_PER_IMPULSE;
+            self.trauma = (self.trauma + amount).min(1.0);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.trauma = 0.0;
+        self.offset = [0.0, 0.0];
+    }
+
+    // once per drawn frame, in real time so a shake still fades out while paused
+    pub fn update(&mut self, dt: f32, intensity: f32) {
+        self.trauma = (self.trauma - DECAY * dt).max(0.0);
+
+        let mut rng = rand::thread_rng();
+        // squared so small hits barely move the screen
+        let magnitude = MAX_OFFSET * intensity.max(0.0) * self.trauma * self.trauma;
+        self.offset = [
+            magnitude * rng.gen_range(-1.0..=1.0),
+            magnitude * rng.gen_range(-1.0..=1.0),
+        ];
+    }
+
+    // added to the draw transform of the scene, never to the particles themselves
+    pub fn offset(&self) -> [f32; 2] {
+        self.offset
+    }
+}
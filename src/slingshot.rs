@@ -0,0 +1,138 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Text};
+use ggez::{Context, GameResult};
+
+use crate::spawn::{self, Variant};
+use crate::{Particle, Point, Vector};
+
+// launch speed per pixel of drag, and the most a drag can give
+const LAUNCH_SCALE: f32 = 3.0;
+const MAX_LAUNCH_SPEED: f32 = 1200.0;
+// a press that moves less than this still spawns at rest
+const DEAD_ZONE: f32 = 4.0;
+
+const GHOST_ALPHA: f32 = 0.4;
+const ARROW_WIDTH: f32 = 2.0;
+const ARROW_HEAD: f32 = 10.0;
+const ARROW_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.8);
+
+// a particle held at the press position while a drag sets how it's launched.
+// pull back from it like a slingshot, it flies the opposite way to the drag
+pub struct Aim {
+    // in the coordinates of the pane it's going into, left edge at `left` on screen
+    pub particle: Particle,
+    pub left: f32,
+    // cursor in screen coordinates
+    pub cursor: Point,
+    variant: Variant,
+}
+
+impl Aim {
+    pub fn new(particle: Particle, left: f32, variant: Variant) -> Aim {
+        let cursor = Point {
+            x: particle.pos.x + left,
+            y: particle.pos.y,
+        };
+        Aim {
+            particle,
+            left,
+            cursor,
+            variant,
+        }
+    }
+
+    fn anchor(&self) -> Point {
+        Point {
+            x: self.particle.pos.x + self.left,
+            y: self.particle.pos.y,
+        }
+    }
+
+    pub fn velocity(&self) -> Vector {
+        let anchor = self.anchor();
+        let pull = Vector {
+            x: anchor.x - self.cursor.x,
+            y: anchor.y - self.cursor.y,
+        };
+        let length = (pull.x * pull.x + pull.y * pull.y).sqrt();
+        if length < DEAD_ZONE {
+            return Vector { x: 0.0, y: 0.0 };
+        }
+
+        let speed = (length * LAUNCH_SCALE).min(MAX_LAUNCH_SPEED);
+        Vector {
+            x: pull.x / length * speed,
+            y: pull.y / length * speed,
+        }
+    }
+
+    // the particle ready to go into its pane
+    pub fn launch(self) -> Particle {
+        let vel = self.velocity();
+        let mut particle = self.particle;
+        particle.vel = vel;
+        particle
+    }
+
+    // the ghost, an arrow the way it'll go and the speed it'll go at, in screen coordinates
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let anchor = self.anchor();
+        let vel = self.velocity();
+        let speed = (vel.x * vel.x + vel.y * vel.y).sqrt();
+
+        let mut builder = MeshBuilder::new();
+        let ghost = Color {
+            a: GHOST_ALPHA,
+            ..self.particle.color
+        };
+        builder.circle(DrawMode::fill(), anchor, self.particle.rad, 0.1, ghost);
+
+        if speed > 0.0 {
+            // as long as the drag, so the arrow ends where the cursor would be mirrored
+            let length = speed / LAUNCH_SCALE;
+            let dir = Vector {
+                x: vel.x / speed,
+                y: vel.y / speed,
+            };
+            let tip = Point {
+                x: anchor.x + dir.x * length,
+                y: anchor.y + dir.y * length,
+            };
+            builder.line(&[anchor, tip], ARROW_WIDTH, ARROW_COLOR)?;
+
+            let back = Point {
+                x: tip.x - dir.x * ARROW_HEAD,
+                y: tip.y - dir.y * ARROW_HEAD,
+            };
+            let side = Vector {
+                x: -dir.y * ARROW_HEAD * 0.5,
+                y: dir.x * ARROW_HEAD * 0.5,
+            };
+            builder.polygon(
+                DrawMode::fill(),
+                &[
+                    tip,
+                    Point {
+                        x: back.x + side.x,
+                        y: back.y + side.y,
+                    },
+                    Point {
+                        x: back.x - side.x,
+                        y: back.y - side.y,
+                    },
+                ],
+                ARROW_COLOR,
+            )?;
+        }
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+        spawn::draw_ghost(ctx, anchor, self.particle.rad, self.variant)?;
+
+        let readout = Text::new(format!("{:.0} px/s", speed));
+        let dest = Point {
+            x: self.cursor.x + 12.0,
+            y: self.cursor.y + 12.0,
+        };
+        graphics::draw(ctx, &readout, DrawParam::default().dest(dest))
+    }
+}
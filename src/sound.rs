@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use ggez::audio::{SoundData, SoundSource, Source};
+use ggez::{Context, GameResult};
+
+use crate::config::SimulationConfig;
+use crate::events::Events;
+
+const CLICK_PATH: &str = "/click.wav";
+const THUD_PATH: &str = "/thud.wav";
+
+// never more than this many sounds at once
+const MAX_VOICES: usize = 12;
+// the same pair (or a particle and the walls) stays quiet for this long after making a sound,
+// so a resting stack doesn't buzz
+const COOLDOWN: f32 = 0.15;
+// impulse that plays at full volume
+const FULL_VOLUME_IMPULSE: f32 = 150.0;
+
+// key for a particle against the walls in the cooldown table
+const WALL: u64 = u64::MAX;
+
+// plays a click for collision events and a duller thud for wall bounces.
+// a missing sample just means silence for that kind of hit
+pub struct Sounds {
+    click: Option<SoundData>,
+    thud: Option<SoundData>,
+    voices: Vec<Source>,
+    // seconds since each pair last made a sound
+    cooldowns: HashMap<(u64, u64), f32>,
+}
+
+impl Sounds {
+    pub fn new(ctx: &mut Context) -> Sounds {
+        let load = |ctx: &mut Context, path| match SoundData::new(ctx, path) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                println!("couldn't load {}: {}", path, e);
+                None
+            }
+        };
+        Sounds {
+            click: load(ctx, CLICK_PATH),
+            thud: load(ctx, THUD_PATH),
+            voices: Vec::new(),
+            cooldowns: HashMap::new(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &mut Context,
+        events: &[&Events],
+        dt: f32,
+        config: &SimulationConfig,
+    ) -> GameResult {
+        for since in self.cooldowns.values_mut() {
+            *since += dt;
+        }
+        self.cooldowns.retain(|_, since| *since < COOLDOWN);
+        self.voices.retain(|voice| voice.playing());
+
+        if !config.sound {
+            return Ok(());
+        }
+
+        // side by side worlds share ids, so their identical hits share a cooldown too
+        for events in events {
+            for event in &events.collisions {
+                let pair = (event.a.min(event.b), event.a.max(event.b));
+                self.play(ctx, false, pair, event.impulse, event.combined_mass, config)?;
+            }
+            if config.wall_sound {
+                for event in &events.walls {
+                    self.play(
+                        ctx,
+                        true,
+                        (event.id, WALL),
+                        event.impulse,
+                        event.mass,
+                        config,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn play(
+        &mut self,
+        ctx: &mut Context,
+        wall: bool,
+        key: (u64, u64),
+        impulse: f32,
+        mass: f32,
+        config: &SimulationConfig,
+    ) -> GameResult {
+        if impulse < config.sound_threshold
+            || self.voices.len() >= MAX_VOICES
+            || self.cooldowns.contains_key(&key)
+        {
+            return Ok(());
+        }
+        let data = match if wall { &self.thud } else { &self.click } {
+            Some(data) => data.clone(),
+            None => return Ok(()),
+        };
+
+        let mut source = Source::from_data(ctx, data)?;
+        source.set_volume(config.sound_volume * (impulse / FULL_VOLUME_IMPULSE).min(1.0));
+        source.set_pitch(pitch(mass));
+        source.play()?;
+
+        self.voices.push(source);
+        self.cooldowns.insert(key, 0.0);
+        Ok(())
+    }
+}
+
+// heavier hits sound lower. a pair of default particles weighs about 2
+fn pitch(mass: f32) -> f32 {
+    (1.0 - 0.2 * (mass.max(0.01) / 2.0).ln()).clamp(0.6, 1.5)
+}
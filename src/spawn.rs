@@ -0,0 +1,103 @@
+use ggez::event::KeyMods;
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh};
+use ggez::{Context, GameResult};
+
+use crate::{Point, SimulationConfig};
+
+// the mouse wheel scales the spawn radius by this per notch, within the limits
+const RADIUS_STEP: f32 = 1.1;
+const RADIUS_MIN: f32 = 2.0;
+const RADIUS_MAX: f32 = 100.0;
+
+const GHOST_WIDTH: f32 = 1.5;
+const NORMAL_TINT: Color = Color::new(1.0, 1.0, 1.0, 0.35);
+const HEAVY_TINT: Color = Color::new(1.0, 0.55, 0.2, 0.8);
+const LIGHT_TINT: Color = Color::new(0.4, 0.85, 1.0, 0.8);
+
+// picked by the modifiers held when the mouse goes down
+#[derive(Clone, Copy, PartialEq)]
+pub enum Variant {
+    Normal,
+    // shift, heavy_mass_factor times the mass
+    Heavy,
+    // ctrl, light_mass_factor times the mass
+    Light,
+}
+
+impl Variant {
+    pub fn from_mods(mods: KeyMods) -> Variant {
+        if mods.contains(KeyMods::SHIFT) {
+            Variant::Heavy
+        } else if mods.contains(KeyMods::CTRL) {
+            Variant::Light
+        } else {
+            Variant::Normal
+        }
+    }
+
+    fn tint(self) -> Color {
+        match self {
+            Variant::Normal => NORMAL_TINT,
+            Variant::Heavy => HEAVY_TINT,
+            Variant::Light => LIGHT_TINT,
+        }
+    }
+}
+
+// what the left mouse button puts down on empty space
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tool {
+    Particles,
+    // drag out a wall
+    Walls,
+    // click for a pillar the size of the next particle
+    Pillars,
+    // click for a gravity well
+    Wells,
+    // click for a black hole
+    BlackHoles,
+    // drag out a solid rectangle
+    Blocks,
+    // drag the way an emitter should point
+    Emitters,
+}
+
+// everything that decides what the mouse spawns next
+pub struct SpawnSettings {
+    pub radius: f32,
+}
+
+impl SpawnSettings {
+    pub fn new(config: &SimulationConfig) -> SpawnSettings {
+        SpawnSettings {
+            radius: config.default_radius(),
+        }
+    }
+
+    // one notch of the mouse wheel per unit, positive for bigger
+    pub fn scale_radius(&mut self, notches: f32) {
+        self.radius = (self.radius * RADIUS_STEP.powf(notches)).clamp(RADIUS_MIN, RADIUS_MAX);
+    }
+
+    pub fn mass(&self, config: &SimulationConfig, variant: Variant) -> f32 {
+        let factor = match variant {
+            Variant::Normal => 1.0,
+            Variant::Heavy => config.heavy_mass_factor,
+            Variant::Light => config.light_mass_factor,
+        };
+        config.mass_for_radius(self.radius) * factor
+    }
+}
+
+// the outline of what's about to be spawned, tinted by variant
+pub fn draw_ghost(ctx: &mut Context, pos: Point, radius: f32, variant: Variant) -> GameResult {
+    let ghost = Mesh::new_circle(
+        ctx,
+        DrawMode::stroke(GHOST_WIDTH),
+        pos,
+        radius,
+        0.1,
+        variant.tint(),
+    )?;
+    graphics::draw(ctx, &ghost, DrawParam::default())
+}
@@ -0,0 +1,420 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::config::SimulationConfig;
+use crate::theme::{self, Theme, THEMES};
+use crate::world::World;
+
+// how long each count gets before the next batch goes in, and how long the frame time has to
+// stay over stress_budget for it to count as not keeping up rather than a hitch
+const BATCH_TIME: f32 = 3.0;
+const SUSTAINED_TIME: f32 = 1.5;
+// the rolling average is over this many frames
+const ROLLING_FRAMES: usize = 30;
+// with this much of the box covered, more particles only crowd it, so it stops there
+const FULL: f32 = 0.5;
+// without a window a frame is this much simulated time, in steps as long as the window's
+const HEADLESS_FRAME: f32 = 1.0 / 60.0;
+const HEADLESS_STEP: f32 = 1.0 / 120.0;
+const HEADLESS_SEED: u64 = 1;
+
+// what a frame spent its time on, in seconds. without a window there's only the physics
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Timing {
+    pub physics: f32,
+    pub drawing: f32,
+}
+
+impl Timing {
+    fn frame(&self) -> f32 {
+        self.physics + self.drawing
+    }
+
+    fn mean(timings: impl Iterator<Item = Timing>) -> Timing {
+        let (mut total, mut frames) = (Timing::default(), 0);
+        for timing in timings {
+            total.physics += timing.physics;
+            total.drawing += timing.drawing;
+            frames += 1;
+        }
+        let frames = frames.max(1) as f32;
+        Timing {
+            physics: total.physics / frames,
+            drawing: total.drawing / frames,
+        }
+    }
+
+    fn describe(&self, headless: bool) -> String {
+        if headless {
+            format!("{:.1}ms of physics a frame", 1000.0 * self.physics)
+        } else {
+            format!(
+                "{:.1}ms a frame ({:.1}ms physics, {:.1}ms drawing)",
+                1000.0 * self.frame(),
+                1000.0 * self.physics,
+                1000.0 * self.drawing
+            )
+        }
+    }
+}
+
+// what to do after a frame
+#[derive(PartialEq, Debug)]
+pub enum Verdict {
+    Keep,
+    // add a batch of this many, and say how many went in with `added`
+    Add(usize),
+    // it's over, take off the last batch of this many
+    Done(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Ending {
+    // the frame time stayed over the budget
+    SlowedDown,
+    // the box filled up first
+    Full,
+}
+
+// how it went: the most particles it kept up with and what a frame took with them, and what
+// it took with the batch that was too many
+#[derive(Debug)]
+struct Report {
+    particles: usize,
+    timing: Option<Timing>,
+    ending: Ending,
+    over: (usize, Timing),
+}
+
+// adds a batch of stress_batch particles every BATCH_TIME while the rolling average frame
+// time stays under stress_budget, and once it's been over for SUSTAINED_TIME takes the last
+// batch back off and reports the count before it. windowed a frame's time is what the physics
+// and drawing take, leaving out waiting for the screen, so vsync doesn't count
+pub struct Stress {
+    batch: usize,
+    budget: f32,
+    headless: bool,
+    recent: VecDeque<Timing>,
+    // every frame since the last batch, for what a frame took at that count
+    this_batch: Vec<Timing>,
+    since_batch: f32,
+    over_for: f32,
+    last_added: usize,
+    // the last count that kept up for a whole BATCH_TIME, and what a frame took
+    kept_up: Option<(usize, Timing)>,
+    report: Option<Report>,
+}
+
+impl Stress {
+    pub fn new(config: &SimulationConfig, headless: bool) -> Stress {
+        Stress {
+            batch: config.stress_batch.max(1),
+            budget: config.stress_budget / 1000.0,
+            headless,
+            recent: VecDeque::with_capacity(ROLLING_FRAMES),
+            this_batch: Vec::new(),
+            since_batch: 0.0,
+            over_for: 0.0,
+            last_added: 0,
+            kept_up: None,
+            report: None,
+        }
+    }
+
+    // the config particles are spawned with: all the same stress_radius, and as many as it
+    // takes
+    pub fn spawn_config(config: &SimulationConfig) -> SimulationConfig {
+        SimulationConfig {
+            spawn_radius_min: config.stress_radius,
+            spawn_radius_max: config.stress_radius,
+            max_particles: usize::MAX,
+            ..config.clone()
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.report.is_some()
+    }
+
+    // how many of the last batch went in
+    pub fn added(&mut self, count: usize) {
+        self.last_added = count;
+    }
+
+    fn rolling(&self) -> Timing {
+        Timing::mean(self.recent.iter().copied())
+    }
+
+    // a frame `time_elapsed` after the last with `particles` in the box, `full` once they
+    // cover FULL of it
+    pub fn record(
+        &mut self,
+        timing: Timing,
+        time_elapsed: f32,
+        particles: usize,
+        full: bool,
+    ) -> Verdict {
+        if self.done() {
+            return Verdict::Keep;
+        }
+        if self.recent.len() == ROLLING_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(timing);
+        self.this_batch.push(timing);
+        self.since_batch += time_elapsed;
+        if self.rolling().frame() > self.budget {
+            self.over_for += time_elapsed;
+        } else {
+            self.over_for = 0.0;
+        }
+
+        if self.over_for >= SUSTAINED_TIME {
+            let removed = if self.kept_up.is_some() {
+                self.last_added
+            } else {
+                0
+            };
+            self.report = Some(Report {
+                particles: particles - removed,
+                timing: self.kept_up.map(|(_, timing)| timing),
+                ending: Ending::SlowedDown,
+                over: (particles, Timing::mean(self.this_batch.drain(..))),
+            });
+            return Verdict::Done(removed);
+        }
+        if self.since_batch < BATCH_TIME {
+            return Verdict::Keep;
+        }
+        let timing = Timing::mean(self.this_batch.drain(..));
+        self.kept_up = Some((particles, timing));
+        if full {
+            self.report = Some(Report {
+                particles,
+                timing: Some(timing),
+                ending: Ending::Full,
+                over: (particles, timing),
+            });
+            return Verdict::Done(0);
+        }
+        // the new count's timed from scratch, without the frames from before it went in
+        self.since_batch = 0.0;
+        self.over_for = 0.0;
+        self.recent.clear();
+        Verdict::Add(self.batch)
+    }
+
+    pub fn summary(&self) -> Option<String> {
+        let report = self.report.as_ref()?;
+        let budget = 1000.0 * self.budget;
+        let (over_particles, over_timing) = report.over;
+        let over = over_timing
+            .describe(self.headless)
+            .replacen(" a frame", "", 1);
+        let mode = if self.headless { " (headless)" } else { "" };
+        Some(match (report.ending, report.timing) {
+            (Ending::Full, Some(timing)) => format!(
+                "stress test{}: the box filled up at {} particles, still at {}",
+                mode,
+                report.particles,
+                timing.describe(self.headless)
+            ),
+            (Ending::SlowedDown, Some(timing)) => format!(
+                "stress test{}: kept up with {} particles at {}, over {:.1}ms with {} at {}",
+                mode,
+                report.particles,
+                timing.describe(self.headless),
+                budget,
+                over_particles,
+                over
+            ),
+            _ => format!(
+                "stress test{}: over {:.1}ms from the start, with {} particles at {}",
+                mode, budget, report.particles, over
+            ),
+        })
+    }
+
+    pub fn hud_line(&self, particles: usize) -> String {
+        if let Some(summary) = self.summary() {
+            return summary;
+        }
+        format!(
+            "stress test: {} particles at {} of {:.1}ms",
+            particles,
+            self.rolling().describe(self.headless),
+            1000.0 * self.budget
+        )
+    }
+}
+
+// whether the particles cover FULL of the box
+pub fn full(world: &World) -> bool {
+    let area: f32 = world
+        .particles
+        .iter()
+        .map(|p| std::f32::consts::PI * p.rad * p.rad)
+        .sum();
+    area >= FULL * world.right() * world.height
+}
+
+fn spawn(
+    world: &mut World,
+    count: usize,
+    config: &SimulationConfig,
+    theme: &Theme,
+    rng: &mut StdRng,
+) -> usize {
+    let before = world.particles.len();
+    world.spawn_random(count, config, theme, rng);
+    world.particles.len() - before
+}
+
+// the stress test without a window, timing only the physics of each HEADLESS_FRAME. prints
+// how it went, and false if it couldn't keep up even with stress_start
+pub fn run_headless(config: &SimulationConfig) -> bool {
+    let theme = &THEMES[theme::find(&config.theme).unwrap_or(0)];
+    let config = SimulationConfig {
+        log_collisions: false,
+        ..Stress::spawn_config(config)
+    };
+    let mut rng = StdRng::seed_from_u64(HEADLESS_SEED);
+    let mut world = World::new(config.window_width, config.window_height);
+    spawn(&mut world, config.stress_start, &config, theme, &mut rng);
+
+    let mut stress = Stress::new(&config, true);
+    let steps = (HEADLESS_FRAME / HEADLESS_STEP).round() as usize;
+    loop {
+        let started = Instant::now();
+        for _ in 0..steps {
+            world.events.clear();
+            world.step(HEADLESS_STEP, &config, theme);
+        }
+        let timing = Timing {
+            physics: started.elapsed().as_secs_f32(),
+            drawing: 0.0,
+        };
+        let particles = world.particles.len();
+        match stress.record(timing, HEADLESS_FRAME, particles, full(&world)) {
+            Verdict::Keep => {}
+            Verdict::Add(count) => {
+                let added = spawn(&mut world, count, &config, theme, &mut rng);
+                stress.added(added);
+            }
+            Verdict::Done(removed) => {
+                world.particles.truncate(particles - removed);
+                break;
+            }
+        }
+    }
+    println!("{}", stress.summary().unwrap_or_default());
+    stress.kept_up.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    // a frame that takes a millisecond for every `per_ms` particles, half of it physics
+    fn run(stress: &mut Stress, particles: &mut usize, per_ms: usize, full_at: usize) -> usize {
+        for _ in 0..100_000 {
+            let timing = Timing {
+                physics: 0.0005 * (*particles / per_ms) as f32,
+                drawing: 0.0005 * (*particles / per_ms) as f32,
+            };
+            match stress.record(timing, 1.0 / 60.0, *particles, *particles >= full_at) {
+                Verdict::Keep => {}
+                Verdict::Add(count) => {
+                    *particles += count;
+                    stress.added(count);
+                }
+                Verdict::Done(removed) => return removed,
+            }
+        }
+        panic!("it never stopped");
+    }
+
+    #[test]
+    fn batches_go_in_until_the_frame_time_stays_over_the_budget() {
+        // a millisecond for every 100, so 1750 is over and 1500 was the last count under
+        let config = SimulationConfig {
+            stress_start: 500,
+            stress_batch: 250,
+            stress_budget: 16.6,
+            ..testing::config()
+        };
+        let mut stress = Stress::new(&config, false);
+        let mut particles = config.stress_start;
+        let removed = run(&mut stress, &mut particles, 100, usize::MAX);
+        assert_eq!((particles, removed), (1750, 250));
+        let summary = stress.summary().unwrap();
+        assert!(
+            summary.starts_with(
+                "stress test: kept up with 1500 particles at 15.0ms a frame (7.5ms physics, \
+                 7.5ms drawing), over 16.6ms with 1750"
+            ),
+            "{}",
+            summary
+        );
+        assert_eq!(stress.hud_line(particles - removed), summary);
+        // nothing more once it's over
+        assert_eq!(
+            stress.record(Timing::default(), 1.0, 0, false),
+            Verdict::Keep
+        );
+    }
+
+    #[test]
+    fn a_hitch_shorter_than_sustained_time_does_not_stop_it() {
+        let config = SimulationConfig {
+            stress_start: 500,
+            stress_batch: 250,
+            stress_budget: 16.6,
+            ..testing::config()
+        };
+        let mut stress = Stress::new(&config, true);
+        let slow = Timing {
+            physics: 0.1,
+            drawing: 0.0,
+        };
+        for _ in 0..60 {
+            assert_eq!(stress.record(slow, 1.0 / 60.0, 500, false), Verdict::Keep);
+        }
+        assert!(!stress.done());
+    }
+
+    #[test]
+    fn too_slow_from_the_start_takes_nothing_off() {
+        let config = SimulationConfig {
+            stress_start: 500,
+            stress_batch: 250,
+            stress_budget: 16.6,
+            ..testing::config()
+        };
+        let mut stress = Stress::new(&config, true);
+        let mut particles = config.stress_start;
+        assert_eq!(run(&mut stress, &mut particles, 10, usize::MAX), 0);
+        assert!(stress.summary().unwrap().contains("from the start"));
+    }
+
+    #[test]
+    fn a_box_that_fills_up_first_stops_there_keeping_everything() {
+        let config = SimulationConfig {
+            stress_start: 500,
+            stress_batch: 250,
+            stress_budget: 16.6,
+            ..testing::config()
+        };
+        let mut stress = Stress::new(&config, true);
+        let mut particles = config.stress_start;
+        assert_eq!(run(&mut stress, &mut particles, 1000, 1000), 0);
+        assert_eq!(particles, 1000);
+        assert!(stress
+            .summary()
+            .unwrap()
+            .starts_with("stress test (headless): the box filled up at 1000 particles"));
+    }
+}
@@ -0,0 +1,428 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Text};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SimulationConfig;
+use crate::world::{Particle, World};
+use crate::Point;
+
+const RING_WIDTH: f32 = 2.0;
+const PASS_COLOR: Color = Color::new(1.0, 0.8, 0.2, 0.8);
+const REST_COLOR: Color = Color::new(0.35, 0.7, 1.0, 0.8);
+const HIT_COLOR: Color = Color::new(0.3, 0.9, 0.4, 0.8);
+const FILL_ALPHA: f32 = 0.15;
+const HIT_FILL_ALPHA: f32 = 0.35;
+const LAUNCHER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.5);
+const LAUNCHER_RAD: f32 = 14.0;
+const DIM_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+// points for each launch left over when the last target's hit
+const LAUNCH_BONUS: u32 = 50;
+// with every launch used, how long the last one gets to do something before it's game over,
+// if the box doesn't come to rest first
+const GIVE_UP_TIME: f32 = 10.0;
+
+fn hundred() -> u32 {
+    100
+}
+
+// a circle in a game scene, scoring `points` the first time any particle that's free to move
+// has its center inside. with `rest`, only once the particle's come to rest in there, so
+// passing through isn't enough
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Target {
+    pub pos: Point,
+    pub rad: f32,
+    #[serde(default = "hundred")]
+    pub points: u32,
+    #[serde(default)]
+    pub rest: bool,
+    // scored already, a saved scene starts over with none of them
+    #[serde(skip)]
+    pub hit: bool,
+}
+
+impl Target {
+    fn contains(&self, particle: &Particle) -> bool {
+        let (dx, dy) = (particle.pos.x - self.pos.x, particle.pos.y - self.pos.y);
+        dx * dx + dy * dy < self.rad * self.rad
+    }
+}
+
+// one step of the targets, after everything's moved and been timed for rest
+pub fn score(world: &mut World, config: &SimulationConfig) {
+    let particles = &world.particles;
+    for target in world.targets.iter_mut().filter(|target| !target.hit) {
+        target.hit = particles
+            .iter()
+            .any(|p| !p.pinned && target.contains(p) && (!target.rest || p.at_rest(config)));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    // every target hit
+    Won,
+    // every launch used without
+    Lost,
+}
+
+// a scene with targets and a budget of slingshot launches to hit them all with. the targets
+// score as the world steps, this keeps count of the launches and says when it's over
+#[derive(Default)]
+pub struct Game {
+    // how many launches the scene allows, none outside a game
+    budget: Option<usize>,
+    // where in the box they're launched from, if it's not wherever the click is
+    launcher: Option<Point>,
+    launched: usize,
+    since_launch: f32,
+    outcome: Option<Outcome>,
+    announced: bool,
+}
+
+impl Game {
+    pub fn clear(&mut self) {
+        *self = Game::default();
+    }
+
+    pub fn start(&mut self, budget: Option<usize>, launcher: Option<Point>) {
+        *self = Game {
+            budget,
+            launcher,
+            ..Game::default()
+        };
+    }
+
+    pub fn budget(&self) -> Option<usize> {
+        self.budget
+    }
+
+    pub fn launcher(&self) -> Option<Point> {
+        self.launcher
+    }
+
+    // outside a game, always
+    pub fn can_launch(&self) -> bool {
+        match self.budget {
+            Some(budget) => self.outcome.is_none() && self.launched < budget,
+            None => true,
+        }
+    }
+
+    pub fn launch(&mut self) {
+        if self.budget.is_some() && self.can_launch() {
+            self.launched += 1;
+            self.since_launch = 0.0;
+        }
+    }
+
+    fn left(&self) -> usize {
+        self.budget
+            .map_or(0, |budget| budget.saturating_sub(self.launched))
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32, config: &SimulationConfig) {
+        let budget = match self.budget {
+            Some(budget) if self.outcome.is_none() && !world.targets.is_empty() => budget,
+            _ => return,
+        };
+        self.since_launch += time_elapsed;
+        if world.targets.iter().all(|target| target.hit) {
+            self.outcome = Some(Outcome::Won);
+        } else if self.launched >= budget
+            && (self.since_launch >= GIVE_UP_TIME
+                || world
+                    .particles
+                    .iter()
+                    .all(|p| p.pinned || p.at_rest(config)))
+        {
+            self.outcome = Some(Outcome::Lost);
+        }
+    }
+
+    // the targets' points, and the bonus for what's left over once they're all hit
+    pub fn score(&self, world: &World) -> u32 {
+        let points: u32 = world
+            .targets
+            .iter()
+            .filter(|target| target.hit)
+            .map(|target| target.points)
+            .sum();
+        points + self.bonus()
+    }
+
+    fn bonus(&self) -> u32 {
+        match self.outcome {
+            Some(Outcome::Won) => LAUNCH_BONUS * self.left() as u32,
+            _ => 0,
+        }
+    }
+
+    fn summary(&self, world: &World) -> Option<String> {
+        let outcome = self.outcome?;
+        let hit = world.targets.iter().filter(|target| target.hit).count();
+        Some(match outcome {
+            Outcome::Won => format!(
+                "level cleared with {} launches to spare: {} points",
+                self.left(),
+                self.score(world)
+            ),
+            Outcome::Lost => format!(
+                "out of launches with {} of {} targets hit: {} points",
+                hit,
+                world.targets.len(),
+                self.score(world)
+            ),
+        })
+    }
+
+    // the summary, only the first time it's asked for once it's over
+    pub fn announce(&mut self, world: &World) -> Option<String> {
+        if self.announced {
+            return None;
+        }
+        self.announced = self.outcome.is_some();
+        self.summary(world)
+    }
+
+    pub fn hud_line(&self, world: &World) -> Option<String> {
+        if self.budget.is_none() || world.targets.is_empty() {
+            return None;
+        }
+        if let Some(summary) = self.summary(world) {
+            return Some(summary);
+        }
+        let hit = world.targets.iter().filter(|target| target.hit).count();
+        Some(format!(
+            "targets: {} of {} hit, {} points, {} of {} launches left",
+            hit,
+            world.targets.len(),
+            self.score(world),
+            self.left(),
+            self.budget.unwrap_or(0)
+        ))
+    }
+
+    // once it's over, the box dimmed and how it went in the middle. `again` is the keys that
+    // load the level again
+    pub fn draw_results(
+        &self,
+        ctx: &mut Context,
+        world: &World,
+        again: &str,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> GameResult {
+        let outcome = match self.outcome {
+            Some(outcome) => outcome,
+            None => return Ok(()),
+        };
+        let screen = graphics::screen_coordinates(ctx);
+        let dim = Mesh::new_rectangle(ctx, DrawMode::fill(), screen, DIM_COLOR)?;
+        graphics::draw(ctx, &dim, DrawParam::default())?;
+
+        let hit = world.targets.iter().filter(|target| target.hit).count();
+        let mut lines = vec![
+            String::from(match outcome {
+                Outcome::Won => "level cleared",
+                Outcome::Lost => "out of launches",
+            }),
+            String::new(),
+            format!("{} of {} targets hit", hit, world.targets.len()),
+            format!(
+                "{} of {} launches used",
+                self.launched,
+                self.budget.unwrap_or(0)
+            ),
+        ];
+        if self.bonus() > 0 {
+            lines.push(format!("{} bonus for the launches left", self.bonus()));
+        }
+        lines.push(format!("score: {}", self.score(world)));
+        if !again.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("{} to play it again", again));
+        }
+        let text = Text::new(lines.join("\n"));
+        let (w, h) = text.dimensions(ctx);
+        let dest = Point {
+            x: ((screen_width - w as f32) / 2.0).max(0.0),
+            y: ((screen_height - h as f32) / 2.0).max(0.0),
+        };
+        graphics::draw(ctx, &text, DrawParam::default().dest(dest))
+    }
+}
+
+// a ring round each target, yellow ones scored by passing through and blue ones by coming to
+// rest, filled in green once they're hit, and a fainter one where the launcher is
+pub fn mesh(
+    ctx: &mut Context,
+    targets: &[Target],
+    launcher: Option<Point>,
+) -> GameResult<Option<Mesh>> {
+    if targets.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    if let Some(launcher) = launcher {
+        builder.circle(
+            DrawMode::stroke(RING_WIDTH),
+            launcher,
+            LAUNCHER_RAD,
+            0.5,
+            LAUNCHER_COLOR,
+        );
+    }
+    for target in targets {
+        let (color, fill) = match (target.hit, target.rest) {
+            (true, _) => (HIT_COLOR, HIT_FILL_ALPHA),
+            (false, true) => (REST_COLOR, FILL_ALPHA),
+            (false, false) => (PASS_COLOR, FILL_ALPHA),
+        };
+        builder.circle(
+            DrawMode::fill(),
+            target.pos,
+            target.rad,
+            0.5,
+            Color { a: fill, ..color },
+        );
+        builder.circle(
+            DrawMode::stroke(RING_WIDTH),
+            target.pos,
+            target.rad,
+            0.5,
+            color,
+        );
+    }
+    builder.build(ctx).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, STEP};
+    use crate::Vector;
+
+    fn target(x: f32, y: f32, rest: bool) -> Target {
+        Target {
+            pos: Point { x, y },
+            rad: 30.0,
+            points: 100,
+            rest,
+            hit: false,
+        }
+    }
+
+    // a target to pass through halfway down and one to rest in on the floor below it, and a
+    // game of `budget` launches with one of them dropped in at `x`
+    fn drop(x: f32, budget: usize) -> (World, Game) {
+        let mut world = World::new(400.0, 400.0);
+        world.targets = vec![target(100.0, 200.0, false), target(100.0, 385.0, true)];
+        let mut game = Game::default();
+        game.start(Some(budget), None);
+        game.launch();
+        world.add_particle(testing::particle(x, 50.0, 10.0));
+        (world, game)
+    }
+
+    fn hit(world: &World) -> Vec<bool> {
+        world.targets.iter().map(|target| target.hit).collect()
+    }
+
+    #[test]
+    fn passing_through_scores_but_not_where_it_has_to_come_to_rest() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 400.0 },
+            restitution: 0.3,
+            ..testing::config()
+        };
+        let (mut world, mut game) = drop(100.0, 2);
+        testing::run_each(&mut world, 162, &config, |world| {
+            game.record(world, STEP, &config)
+        });
+        // through the first, but still moving in the second
+        assert_eq!(hit(&world), [true, false]);
+        assert!(world.particles[0].pos.y > 360.0);
+        assert_eq!(game.outcome, None);
+        assert_eq!(
+            game.hud_line(&world).unwrap(),
+            "targets: 1 of 2 hit, 100 points, 1 of 2 launches left"
+        );
+    }
+
+    #[test]
+    fn coming_to_rest_scores_and_clears_the_level_with_a_bonus() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 400.0 },
+            restitution: 0.3,
+            ..testing::config()
+        };
+        let (mut world, mut game) = drop(100.0, 2);
+        testing::run_each(&mut world, 522, &config, |world| {
+            game.record(world, STEP, &config)
+        });
+        assert_eq!(hit(&world), [true, true]);
+        assert_eq!(game.outcome, Some(Outcome::Won));
+        // both targets, and LAUNCH_BONUS for the launch to spare
+        assert_eq!(game.score(&world), 250);
+        assert!(!game.can_launch());
+    }
+
+    #[test]
+    fn the_launches_run_out_at_the_budget() {
+        let mut game = Game::default();
+        game.start(Some(2), None);
+        assert!(game.can_launch());
+        game.launch();
+        assert!(game.can_launch());
+        game.launch();
+        assert!(!game.can_launch());
+        // one more doesn't count
+        game.launch();
+        assert_eq!(game.launched, 2);
+    }
+
+    #[test]
+    fn out_of_launches_it_is_over_once_the_last_one_is_at_rest() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 400.0 },
+            restitution: 0.3,
+            ..testing::config()
+        };
+        // dropped beside them both
+        let (mut world, mut game) = drop(300.0, 1);
+        testing::run_each(&mut world, 120, &config, |world| {
+            game.record(world, STEP, &config)
+        });
+        assert_eq!(game.outcome, None);
+        assert_eq!(game.announce(&world), None);
+        testing::run_each(&mut world, 360, &config, |world| {
+            game.record(world, STEP, &config)
+        });
+        assert_eq!(game.outcome, Some(Outcome::Lost));
+        assert_eq!(game.score(&world), 0);
+
+        // the results, said once and then kept on the hud
+        let results = "out of launches with 0 of 2 targets hit: 0 points";
+        assert_eq!(game.announce(&world).as_deref(), Some(results));
+        assert_eq!(game.announce(&world), None);
+        assert_eq!(game.hud_line(&world).as_deref(), Some(results));
+        // and nothing more moves it on
+        world
+            .targets
+            .iter_mut()
+            .for_each(|target| target.hit = true);
+        game.record(&world, STEP, &config);
+        assert_eq!(game.outcome, Some(Outcome::Lost));
+    }
+
+    #[test]
+    fn outside_a_game_it_never_gets_in_the_way() {
+        let (world, mut game) = drop(100.0, 1);
+        game.start(None, None);
+        game.launch();
+        assert!(game.can_launch());
+        assert!(game.hud_line(&world).is_none());
+    }
+}
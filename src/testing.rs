@@ -0,0 +1,93 @@
+// what the tests build their scenes from, so each module's only say what's particular to them
+
+use ggez::graphics::Color;
+
+use crate::config::SimulationConfig;
+use crate::determinism;
+use crate::events::CollisionEvent;
+use crate::presets::{Layout, PRESETS};
+use crate::theme::THEMES;
+use crate::world::{Particle, World};
+use crate::{Point, Vector};
+
+pub const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+pub const STEP: f32 = 1.0 / 120.0;
+
+// a white one of unit mass sitting still at x, y. anything else goes in with struct update
+pub fn particle(x: f32, y: f32, rad: f32) -> Particle {
+    Particle::new(Point { x, y }, Vector { x: 0.0, y: 0.0 }, rad, 1.0, WHITE)
+}
+
+// no gravity and nothing logged, so only what a test sets going moves
+pub fn config() -> SimulationConfig {
+    SimulationConfig {
+        gravity: Vector { x: 0.0, y: 0.0 },
+        log_collisions: false,
+        ..SimulationConfig::default()
+    }
+}
+
+// `a` and `b` touching along x with unit masses, for whatever only cares who touched whom
+pub fn touch(a: u64, b: u64) -> CollisionEvent {
+    CollisionEvent {
+        point: Point { x: 0.0, y: 0.0 },
+        normal: Vector { x: 1.0, y: 0.0 },
+        impulse: 1.0,
+        a,
+        b,
+        combined_mass: 2.0,
+        restitution: 1.0,
+        masses: [1.0, 1.0],
+        before: [Vector { x: 0.0, y: 0.0 }; 2],
+        after: [Vector { x: 0.0, y: 0.0 }; 2],
+    }
+}
+
+// a box of them heading every which way at 200, so they keep running into each other
+pub fn scattered() -> World {
+    let mut world = World::new(400.0, 400.0);
+    for i in 0..30 {
+        let angle = 2.4 * i as f32;
+        let (x, y) = (40.0 + 60.0 * (i % 6) as f32, 40.0 + 60.0 * (i / 6) as f32);
+        world.add_particle(Particle {
+            vel: Vector {
+                x: 200.0 * angle.cos(),
+                y: 200.0 * angle.sin(),
+            },
+            ..particle(x, y, 10.0)
+        });
+    }
+    world
+}
+
+// the named preset as the window loads it into an 800 by 600 box, and `config` with the
+// scene's own settings on top
+pub fn preset(name: &str, config: &SimulationConfig) -> (World, SimulationConfig) {
+    let layout = Layout {
+        width: 800.0,
+        height: 600.0,
+        config,
+        theme: &THEMES[0],
+    };
+    let preset = PRESETS.iter().find(|preset| preset.name == name).unwrap();
+    determinism::build(preset, &layout)
+}
+
+// `steps` of STEP, each with the last one's events cleared as a frame would
+pub fn run(world: &mut World, steps: usize, config: &SimulationConfig) {
+    run_each(world, steps, config, |_| {});
+}
+
+// the same, with `each` looking at the world after every step
+pub fn run_each(
+    world: &mut World,
+    steps: usize,
+    config: &SimulationConfig,
+    mut each: impl FnMut(&World),
+) {
+    for _ in 0..steps {
+        world.events.clear();
+        world.step(STEP, config, &THEMES[0]);
+        each(world);
+    }
+}
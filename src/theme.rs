@@ -0,0 +1,88 @@
+use ggez::graphics::Color;
+
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    // fill for solid obstacles, picked to stand apart from the particles
+    pub obstacle: Color,
+    pub palette: &'static [Color],
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0)
+}
+
+pub static THEMES: [Theme; 4] = [
+    Theme {
+        name: "classic",
+        background: rgb(0, 0, 0),
+        obstacle: rgb(70, 80, 100),
+        palette: &[
+            rgb(170, 216, 211),
+            rgb(50, 175, 230),
+            rgb(0, 173, 181),
+            rgb(10, 17, 200),
+            rgb(150, 150, 20),
+            rgb(0, 90, 45),
+            rgb(200, 100, 50),
+        ],
+    },
+    Theme {
+        name: "pastel",
+        background: rgb(40, 42, 54),
+        obstacle: rgb(98, 114, 164),
+        palette: &[
+            rgb(255, 179, 186),
+            rgb(255, 223, 186),
+            rgb(255, 255, 186),
+            rgb(186, 255, 201),
+            rgb(186, 225, 255),
+            rgb(220, 198, 255),
+        ],
+    },
+    Theme {
+        name: "high-contrast",
+        background: rgb(0, 0, 0),
+        obstacle: rgb(128, 128, 128),
+        palette: &[
+            rgb(255, 255, 255),
+            rgb(255, 0, 0),
+            rgb(0, 255, 0),
+            rgb(0, 128, 255),
+            rgb(255, 255, 0),
+            rgb(255, 0, 255),
+            rgb(0, 255, 255),
+        ],
+    },
+    Theme {
+        name: "monochrome",
+        background: rgb(18, 18, 18),
+        obstacle: rgb(55, 55, 55),
+        palette: &[
+            rgb(90, 90, 90),
+            rgb(130, 130, 130),
+            rgb(170, 170, 170),
+            rgb(210, 210, 210),
+            rgb(250, 250, 250),
+        ],
+    },
+];
+
+pub fn find(name: &str) -> Option<usize> {
+    THEMES.iter().position(|theme| theme.name == name)
+}
+
+impl Theme {
+    // closest palette entry by squared rgb distance
+    pub fn nearest(&self, color: Color) -> Color {
+        let distance = |c: &Color| {
+            let (dr, dg, db) = (c.r - color.r, c.g - color.g, c.b - color.b);
+            dr * dr + dg * dg + db * db
+        };
+        *self
+            .palette
+            .iter()
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+            .expect("Some colors in the palette")
+    }
+}
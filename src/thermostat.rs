@@ -0,0 +1,135 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::config::SimulationConfig;
+use crate::world::Boundary;
+
+const BAR_WIDTH: f32 = 4.0;
+const COLD_COLOR: Color = Color::new(0.2, 0.5, 1.0, 0.9);
+const HOT_COLOR: Color = Color::new(1.0, 0.3, 0.1, 0.9);
+
+// the walls of the box that are held at a temperature, each one's given as the speed a
+// particle of mass 1 would typically leave it at. heavier ones leave slower, lighter faster,
+// so they all come away with the same energy on average. leave a wall out for an ordinary one
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thermostats {
+    pub left: Option<f32>,
+    pub right: Option<f32>,
+    pub top: Option<f32>,
+    pub bottom: Option<f32>,
+}
+
+impl Thermostats {
+    // the wall that pushes back along `normal`, pointing into the box
+    pub fn facing(&self, normal_x: f32, normal_y: f32) -> Option<f32> {
+        if normal_x > 0.0 {
+            self.left
+        } else if normal_x < 0.0 {
+            self.right
+        } else if normal_y > 0.0 {
+            self.top
+        } else if normal_y < 0.0 {
+            self.bottom
+        } else {
+            None
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.walls().iter().any(Option::is_some)
+    }
+
+    pub fn walls(&self) -> [Option<f32>; 4] {
+        [self.left, self.right, self.top, self.bottom]
+    }
+
+    // the temperatures of the walls that have one
+    pub fn temperatures_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+        self.left
+            .iter_mut()
+            .chain(self.right.iter_mut())
+            .chain(self.top.iter_mut())
+            .chain(self.bottom.iter_mut())
+    }
+}
+
+// the speed away from a wall at `temperature` for something that hit it at `speed`. the wall
+// picks a speed the way a gas at that temperature would throw one, and the particle goes
+// thermostat_mix of the way there from its own. never negative, so nothing's sent back in
+pub fn rescale(
+    speed: f32,
+    temperature: f32,
+    mass: f32,
+    config: &SimulationConfig,
+    rng: &mut impl Rng,
+) -> f32 {
+    let scale = temperature.abs() / mass.max(f32::EPSILON).sqrt();
+    // leaving a wall in a 2d gas, the speed square to it goes as a rayleigh distribution
+    let thrown = scale * (-2.0 * (1.0 - rng.gen::<f32>()).ln()).sqrt();
+    let mix = config.thermostat_mix.clamp(0.0, 1.0);
+    (speed.abs() + (thrown - speed.abs()) * mix).max(0.0)
+}
+
+// a bar along each wall with a thermostat, from blue for the coldest to red for the hottest
+pub fn mesh(
+    ctx: &mut Context,
+    config: &SimulationConfig,
+    right: f32,
+    height: f32,
+) -> GameResult<Option<Mesh>> {
+    let thermostats = &config.thermostats;
+    if !thermostats.any() {
+        return Ok(None);
+    }
+    let set: Vec<f32> = thermostats.walls().iter().flatten().copied().collect();
+    let coldest = set.iter().copied().fold(f32::INFINITY, f32::min);
+    let hottest = set.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let color = |temperature: f32| {
+        let t = if hottest > coldest {
+            (temperature - coldest) / (hottest - coldest)
+        } else {
+            0.5
+        };
+        Color::new(
+            COLD_COLOR.r + (HOT_COLOR.r - COLD_COLOR.r) * t,
+            COLD_COLOR.g + (HOT_COLOR.g - COLD_COLOR.g) * t,
+            COLD_COLOR.b + (HOT_COLOR.b - COLD_COLOR.b) * t,
+            COLD_COLOR.a,
+        )
+    };
+
+    // only the walls there are
+    let sides = !config.boundary.wraps_x();
+    let top = config.boundary != Boundary::OpenTop;
+    let mut builder = MeshBuilder::new();
+    let bars = [
+        (
+            thermostats.left.filter(|_| sides),
+            Rect::new(0.0, 0.0, BAR_WIDTH, height),
+        ),
+        (
+            thermostats.right.filter(|_| sides),
+            Rect::new(right - BAR_WIDTH, 0.0, BAR_WIDTH, height),
+        ),
+        (
+            thermostats.top.filter(|_| top),
+            Rect::new(0.0, 0.0, right, BAR_WIDTH),
+        ),
+        (
+            thermostats.bottom,
+            Rect::new(0.0, height - BAR_WIDTH, right, BAR_WIDTH),
+        ),
+    ];
+    if bars.iter().all(|(temperature, _)| temperature.is_none()) {
+        return Ok(None);
+    }
+    for (temperature, rect) in &bars {
+        if let Some(temperature) = temperature {
+            builder.rectangle(DrawMode::fill(), *rect, color(*temperature));
+        }
+    }
+    builder.build(ctx).map(Some)
+}
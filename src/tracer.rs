@@ -0,0 +1,382 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use ggez::graphics::{Color, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+
+use crate::world::World;
+use crate::Point;
+
+// how often the walk is sampled, in simulated seconds
+const SAMPLE_TIME: f32 = 0.1;
+// about ten minutes of walk for the mean squared displacement, and two of path drawn, which
+// is a good while longer than anything else's trail
+const MAX_SAMPLES: usize = 6000;
+const PATH_LENGTH: usize = 1200;
+const PATH_WIDTH: f32 = 1.5;
+// the lags the hud shows the mean squared displacement at, in seconds. each one twice the
+// last, so for a random walk each reading should come out about twice the last too
+const LAGS: [f32; 3] = [2.0, 4.0, 8.0];
+// a marked particle's place is taken every PATH_STEPS steps to start with. once a path has
+// MAX_PATH_POINTS, every other one goes and the gap doubles, so however long it runs for it
+// never takes up more than that
+const PATH_STEPS: u64 = 2;
+const MAX_PATH_POINTS: usize = 2000;
+const MARKED_ALPHA: f32 = 0.8;
+
+// one particle followed around: the path it's taken recently, drawn behind everything, and
+// its whole walk since it was picked for the mean squared displacement
+#[derive(Default)]
+pub struct Tracer {
+    id: Option<u64>,
+    // where it is as drawn, broken wherever it went across a seam
+    path: VecDeque<Option<Point>>,
+    // where it would be if the box went on forever, so a walk across a seam isn't a jump
+    walk: Vec<Point>,
+    // where it was at the last record, as drawn and unwrapped
+    last: Option<(Point, Point)>,
+    since_sample: f32,
+}
+
+impl Tracer {
+    pub fn follow(&mut self, id: Option<u64>) {
+        self.clear();
+        self.id = id;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Tracer::default();
+    }
+
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    // `time_elapsed` after the last record
+    pub fn record(&mut self, world: &World, time_elapsed: f32) {
+        let id = match self.id {
+            Some(id) => id,
+            None => return,
+        };
+        let pos = match world.particles.iter().find(|p| p.id == id) {
+            Some(particle) => particle.pos,
+            None => {
+                // gone, nothing more to follow
+                self.id = None;
+                return;
+            }
+        };
+
+        let (last, unwrapped) = match self.last {
+            Some(last) => last,
+            None => {
+                // where the walk starts from
+                self.walk.push(pos);
+                self.last = Some((pos, pos));
+                self.push_path(Some(pos));
+                return;
+            }
+        };
+        let (mut dx, mut dy) = (pos.x - last.x, pos.y - last.y);
+        // nothing moves half the box in a frame, so that's it coming in the other side
+        if dx.abs() > 0.5 * world.width || dy.abs() > 0.5 * world.height {
+            self.push_path(None);
+        }
+        dx -= world.width * (dx / world.width).round();
+        dy -= world.height * (dy / world.height).round();
+        let unwrapped = Point {
+            x: unwrapped.x + dx,
+            y: unwrapped.y + dy,
+        };
+        self.last = Some((pos, unwrapped));
+        self.push_path(Some(pos));
+
+        self.since_sample += time_elapsed;
+        // a little slack so a frame of exactly SAMPLE_TIME doesn't miss by rounding
+        while self.since_sample >= SAMPLE_TIME - 1e-6 {
+            self.since_sample -= SAMPLE_TIME;
+            if self.walk.len() < MAX_SAMPLES {
+                self.walk.push(unwrapped);
+            }
+        }
+    }
+
+    fn push_path(&mut self, point: Option<Point>) {
+        if self.path.len() == PATH_LENGTH {
+            self.path.pop_front();
+        }
+        self.path.push_back(point);
+    }
+
+    // the mean of the squared distance it's gone over every stretch `lag` seconds long in
+    // its walk, none until the walk's that long
+    pub fn msd(&self, lag: f32) -> Option<f32> {
+        let steps = (lag / SAMPLE_TIME).round() as usize;
+        if steps == 0 || self.walk.len() <= steps {
+            return None;
+        }
+        let pairs = self.walk.len() - steps;
+        let total: f32 = (0..pairs)
+            .map(|i| {
+                let (a, b) = (self.walk[i], self.walk[i + steps]);
+                (b.x - a.x).powi(2) + (b.y - a.y).powi(2)
+            })
+            .sum();
+        Some(total / pairs as f32)
+    }
+
+    // the mean squared displacement at each of LAGS, and the diffusion coefficient from the
+    // longest one there's enough walk for, which in 2d is msd / 4 t
+    pub fn hud_line(&self) -> Option<String> {
+        self.id?;
+        let readings: Vec<(f32, f32)> = LAGS
+            .iter()
+            .filter_map(|&lag| self.msd(lag).map(|msd| (lag, msd)))
+            .collect();
+        let (lag, msd) = match readings.last() {
+            Some(&last) => last,
+            None => return Some(String::from("tracer msd: -")),
+        };
+        let shown: Vec<String> = readings
+            .iter()
+            .map(|(lag, msd)| format!("{}s {:.0}", lag, msd))
+            .collect();
+        Some(format!(
+            "tracer msd: {} px^2, D ~ {:.1} px^2/s",
+            shown.join(", "),
+            msd / (4.0 * lag)
+        ))
+    }
+
+    pub fn mesh(&self, ctx: &mut Context, color: Color) -> GameResult<Option<Mesh>> {
+        let color = Color { a: 0.6, ..color };
+        let mut builder = MeshBuilder::new();
+        let mut any = false;
+        let mut run: Vec<Point> = Vec::new();
+        for point in self.path.iter().chain(std::iter::once(&None)) {
+            match point {
+                Some(point) => run.push(*point),
+                None => {
+                    if run.len() >= 2 {
+                        builder.line(&run, PATH_WIDTH, color)?;
+                        any = true;
+                    }
+                    run.clear();
+                }
+            }
+        }
+        if any {
+            builder.build(ctx).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+// one marked particle's whole path since it was marked
+struct MarkedPath {
+    id: u64,
+    // what it's drawn in once the particle's gone
+    color: Color,
+    // the step each point was taken at and where the particle was, oldest first
+    points: Vec<(u64, Point)>,
+    // steps between points, doubling each time the path's thinned out
+    every: u64,
+}
+
+impl MarkedPath {
+    fn record(&mut self, world: &World) {
+        // stepped back to before the last point, so what came after it hasn't happened
+        let step = world.step_count;
+        if self.points.last().is_some_and(|&(last, _)| last > step) {
+            self.points.retain(|&(taken, _)| taken <= step);
+        }
+        let due = self
+            .points
+            .last()
+            .is_none_or(|&(last, _)| step >= last + self.every);
+        if !due {
+            return;
+        }
+        let particle = match world.particles.iter().find(|p| p.id == self.id) {
+            Some(particle) => particle,
+            // gone, so its path's left as it was
+            None => return,
+        };
+        self.color = particle.original_color;
+        self.points.push((step, particle.pos));
+        if self.points.len() >= MAX_PATH_POINTS {
+            self.points = self.points.iter().step_by(2).copied().collect();
+            self.every *= 2;
+        }
+    }
+}
+
+// particles marked by hand, each drawing the whole path it's taken since, in its own color.
+// unlike a trail it never fades, it just gets coarser once it's long
+#[derive(Default)]
+pub struct Tracers {
+    paths: Vec<MarkedPath>,
+}
+
+impl Tracers {
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn marked(&self, id: u64) -> bool {
+        self.paths.iter().any(|path| path.id == id)
+    }
+
+    // mark `id` from here on, or unmark it and drop its path if it already was
+    pub fn toggle(&mut self, id: u64, color: Color) -> bool {
+        if self.marked(id) {
+            self.paths.retain(|path| path.id != id);
+            return false;
+        }
+        self.paths.push(MarkedPath {
+            id,
+            color,
+            points: Vec::new(),
+            every: PATH_STEPS,
+        });
+        true
+    }
+
+    // every path starts again from where its particle is now
+    pub fn clear_paths(&mut self) {
+        for path in &mut self.paths {
+            path.points.clear();
+            path.every = PATH_STEPS;
+        }
+    }
+
+    pub fn record(&mut self, world: &World) {
+        for path in &mut self.paths {
+            path.record(world);
+        }
+    }
+
+    // `id,step,time,x,y` with a line for every point of every path, `step_time` apart
+    pub fn csv(&self, step_time: f32) -> String {
+        let mut csv = String::from("id,step,time,x,y\n");
+        for path in &self.paths {
+            for &(step, pos) in &path.points {
+                let time = step as f32 * step_time;
+                // writing to a string can't fail
+                let _ = writeln!(csv, "{},{},{:.4},{},{}", path.id, step, time, pos.x, pos.y);
+            }
+        }
+        csv
+    }
+
+    // broken wherever one went across a seam of a wrapping box
+    pub fn mesh(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult<Option<Mesh>> {
+        let mut builder = MeshBuilder::new();
+        let mut any = false;
+        for path in &self.paths {
+            let color = Color {
+                a: MARKED_ALPHA,
+                ..path.color
+            };
+            let mut run: Vec<Point> = Vec::new();
+            for &(_, point) in &path.points {
+                let jumped = run.last().is_some_and(|last: &Point| {
+                    (point.x - last.x).abs() > 0.5 * width
+                        || (point.y - last.y).abs() > 0.5 * height
+                });
+                if jumped {
+                    if run.len() >= 2 {
+                        builder.line(&run, PATH_WIDTH, color)?;
+                        any = true;
+                    }
+                    run.clear();
+                }
+                run.push(point);
+            }
+            if run.len() >= 2 {
+                builder.line(&run, PATH_WIDTH, color)?;
+                any = true;
+            }
+        }
+        if any {
+            builder.build(ctx).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn walks_across_the_seam_without_jumping() {
+        let mut world = World::new(100.0, 100.0);
+        world.add_particle(testing::particle(95.0, 50.0, 2.0));
+        let mut tracer = Tracer::default();
+        tracer.follow(Some(world.particles[0].id));
+
+        // steadily right at 10 a sample, coming in on the left after the first
+        for i in 0..60 {
+            world.particles[0].pos.x = (95.0 + 10.0 * i as f32) % 100.0;
+            tracer.record(&world, SAMPLE_TIME);
+        }
+        // straight line, so it goes as the square of the time rather than the time
+        let (one, two) = (tracer.msd(1.0).unwrap(), tracer.msd(2.0).unwrap());
+        assert!((one - 100.0 * 100.0).abs() < 1.0, "{}", one);
+        assert!((two - 4.0 * one).abs() < 4.0, "{}", two);
+        // the path breaks wherever it came back in
+        assert_eq!(tracer.path.iter().filter(|p| p.is_none()).count(), 6);
+    }
+
+    #[test]
+    fn marked_paths_stay_bounded_and_go_back_with_the_steps() {
+        let mut world = World::new(1000.0, 1000.0);
+        for x in &[100.0, 200.0] {
+            world.add_particle(testing::particle(*x, 500.0, 2.0));
+        }
+        let mut tracers = Tracers::default();
+        assert!(tracers.toggle(0, Color::new(1.0, 0.0, 0.0, 1.0)));
+        assert!(tracers.toggle(1, Color::new(0.0, 0.0, 1.0, 1.0)));
+
+        // far longer than fits, it's thinned out rather than growing
+        for step in 0..100_000 {
+            world.step_count = step;
+            world.particles[0].pos.x = 100.0 + (step % 500) as f32;
+            tracers.record(&world);
+        }
+        for path in &tracers.paths {
+            assert!(path.points.len() < MAX_PATH_POINTS);
+            assert!(path.points.len() > MAX_PATH_POINTS / 4);
+            assert_eq!(path.points[0].0, 0);
+        }
+
+        // stepping back drops what came after
+        world.step_count = 50_000;
+        tracers.record(&world);
+        assert!(tracers.paths[0]
+            .points
+            .iter()
+            .all(|&(step, _)| step <= 50_000));
+
+        let csv = tracers.csv(0.5);
+        assert!(csv.starts_with("id,step,time,x,y\n0,0,0.0000,100,500\n"));
+        assert_eq!(
+            csv.lines().count(),
+            1 + tracers.paths.iter().map(|p| p.points.len()).sum::<usize>()
+        );
+
+        // unmarking drops its path, clearing keeps the marks but starts them again
+        assert!(!tracers.toggle(1, Color::new(0.0, 0.0, 1.0, 1.0)));
+        tracers.clear_paths();
+        assert!(tracers.marked(0) && !tracers.marked(1));
+        assert!(tracers.paths[0].points.is_empty());
+    }
+}
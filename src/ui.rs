@@ -0,0 +1,236 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Rect, Text};
+use ggez::{Context, GameResult};
+
+use crate::Point;
+
+const PANEL_WIDTH: f32 = 280.0;
+const ROW_HEIGHT: f32 = 22.0;
+const PADDING: f32 = 6.0;
+const SLIDER_WIDTH: f32 = 110.0;
+
+const PANEL_COLOR: Color = Color::new(0.1, 0.1, 0.12, 0.85);
+const WIDGET_COLOR: Color = Color::new(0.3, 0.3, 0.35, 1.0);
+const ACCENT_COLOR: Color = Color::new(0.0, 0.68, 0.71, 1.0);
+
+// a control bound to some field of `T`; plain fn pointers so the table can be a static
+pub enum Widget<T: 'static> {
+    Slider {
+        label: &'static str,
+        min: f32,
+        max: f32,
+        get: fn(&T) -> f32,
+        set: fn(&mut T, f32),
+    },
+    Checkbox {
+        label: &'static str,
+        get: fn(&T) -> bool,
+        set: fn(&mut T, bool),
+    },
+    Button {
+        label: &'static str,
+        action: fn(&mut T),
+    },
+}
+
+// an interaction with a widget, applied to the target after the panel is done with it
+pub struct Edit<T: 'static> {
+    widget: &'static Widget<T>,
+    fraction: f32,
+}
+
+impl<T> Edit<T> {
+    pub fn apply(&self, target: &mut T) {
+        match self.widget {
+            Widget::Slider { min, max, set, .. } => set(target, min + (max - min) * self.fraction),
+            Widget::Checkbox { get, set, .. } => {
+                let value = get(target);
+                set(target, !value);
+            }
+            Widget::Button { action, .. } => action(target),
+        }
+    }
+}
+
+pub struct ControlPanel<T: 'static> {
+    title: &'static str,
+    widgets: &'static [Widget<T>],
+    origin: Point,
+    pub visible: bool,
+    pub collapsed: bool,
+    dragging: Option<usize>,
+}
+
+impl<T> ControlPanel<T> {
+    pub fn new(title: &'static str, widgets: &'static [Widget<T>]) -> ControlPanel<T> {
+        ControlPanel {
+            title,
+            widgets,
+            origin: Point { x: 10.0, y: 10.0 },
+            visible: false,
+            collapsed: false,
+            dragging: None,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.dragging = None;
+    }
+
+    fn title_rect(&self) -> Rect {
+        Rect::new(self.origin.x, self.origin.y, PANEL_WIDTH, ROW_HEIGHT)
+    }
+    fn row_rect(&self, i: usize) -> Rect {
+        Rect::new(
+            self.origin.x,
+            self.origin.y + ROW_HEIGHT * (i + 1) as f32,
+            PANEL_WIDTH,
+            ROW_HEIGHT,
+        )
+    }
+    fn bounds(&self) -> Rect {
+        let rows = if self.collapsed {
+            0
+        } else {
+            self.widgets.len()
+        };
+        Rect::new(
+            self.origin.x,
+            self.origin.y,
+            PANEL_WIDTH,
+            ROW_HEIGHT * (rows + 1) as f32 + PADDING,
+        )
+    }
+    fn slider_track(row: Rect) -> Rect {
+        Rect::new(
+            row.right() - SLIDER_WIDTH - PADDING,
+            row.y + ROW_HEIGHT * 0.35,
+            SLIDER_WIDTH,
+            ROW_HEIGHT * 0.3,
+        )
+    }
+    fn slider_fraction(&self, i: usize, x: f32) -> f32 {
+        let track = Self::slider_track(self.row_rect(i));
+        ((x - track.x) / track.w).clamp(0.0, 1.0)
+    }
+
+    // whether the panel is covering this point, so clicks there shouldn't reach the scene
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        self.visible && self.bounds().contains(Point { x, y })
+    }
+
+    pub fn mouse_down(&mut self, x: f32, y: f32) -> Option<Edit<T>> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        if self.title_rect().contains(Point { x, y }) {
+            self.collapsed = !self.collapsed;
+            return None;
+        }
+        if self.collapsed {
+            return None;
+        }
+
+        let i = (0..self.widgets.len()).find(|&i| self.row_rect(i).contains(Point { x, y }))?;
+        let widget = &self.widgets[i];
+        if let Widget::Slider { .. } = widget {
+            self.dragging = Some(i);
+        }
+        Some(Edit {
+            widget,
+            fraction: self.slider_fraction(i, x),
+        })
+    }
+
+    pub fn mouse_motion(&mut self, x: f32, _y: f32) -> Option<Edit<T>> {
+        let i = self.dragging?;
+        Some(Edit {
+            widget: &self.widgets[i],
+            fraction: self.slider_fraction(i, x),
+        })
+    }
+
+    pub fn mouse_up(&mut self) {
+        self.dragging = None;
+    }
+
+    pub fn draw(&self, ctx: &mut Context, target: &T) -> GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(DrawMode::fill(), self.bounds(), PANEL_COLOR);
+        builder.rectangle(DrawMode::fill(), self.title_rect(), WIDGET_COLOR);
+
+        let mut labels = Vec::new();
+        let arrow = if self.collapsed { "+" } else { "-" };
+        labels.push((format!("{} {}", arrow, self.title), self.title_rect()));
+
+        if !self.collapsed {
+            for (i, widget) in self.widgets.iter().enumerate() {
+                let row = self.row_rect(i);
+                match widget {
+                    Widget::Slider {
+                        label,
+                        min,
+                        max,
+                        get,
+                        ..
+                    } => {
+                        let value = get(target);
+                        let track = Self::slider_track(row);
+                        let fraction = ((value - min) / (max - min)).clamp(0.0, 1.0);
+                        builder.rectangle(DrawMode::fill(), track, WIDGET_COLOR);
+                        builder.rectangle(
+                            DrawMode::fill(),
+                            Rect::new(track.x, track.y, track.w * fraction, track.h),
+                            ACCENT_COLOR,
+                        );
+                        labels.push((format!("{}: {:.2}", label, value), row));
+                    }
+                    Widget::Checkbox { label, get, .. } => {
+                        let size = ROW_HEIGHT * 0.6;
+                        let bx = Rect::new(
+                            row.right() - size - PADDING,
+                            row.y + (ROW_HEIGHT - size) / 2.0,
+                            size,
+                            size,
+                        );
+                        let color = if get(target) {
+                            ACCENT_COLOR
+                        } else {
+                            WIDGET_COLOR
+                        };
+                        builder.rectangle(DrawMode::fill(), bx, color);
+                        labels.push((label.to_string(), row));
+                    }
+                    Widget::Button { label, .. } => {
+                        let button = Rect::new(
+                            row.x + PADDING,
+                            row.y + 2.0,
+                            row.w - 2.0 * PADDING,
+                            row.h - 4.0,
+                        );
+                        builder.rectangle(DrawMode::fill(), button, WIDGET_COLOR);
+                        labels.push((format!("[{}]", label), row));
+                    }
+                }
+            }
+        }
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        for (label, row) in labels {
+            let text = Text::new(label);
+            let dest = Point {
+                x: row.x + PADDING,
+                y: row.y + (ROW_HEIGHT - text.height(ctx) as f32) / 2.0,
+            };
+            graphics::draw(ctx, &text, DrawParam::default().dest(dest))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{Point, Vector};
+
+const VOID_FILL: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+const HATCH_COLOR: Color = Color::new(0.5, 0.15, 0.15, 0.8);
+const HATCH_SPACING: f32 = 12.0;
+const HATCH_WIDTH: f32 = 1.5;
+
+// a rectangle given by its corners, or a circle
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Shape {
+    Rect { min: Point, max: Point },
+    Circle { pos: Point, rad: f32 },
+}
+
+// a hole in the box. anything whose center gets inside is gone, and counted
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Void {
+    #[serde(flatten)]
+    pub shape: Shape,
+    // since the last reset, not saved
+    #[serde(skip)]
+    pub consumed: usize,
+}
+
+impl Void {
+    pub fn contains(&self, p: Point) -> bool {
+        match self.shape {
+            Shape::Rect { min, max } => {
+                (min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y)
+            }
+            Shape::Circle { pos, rad } => {
+                let (dx, dy) = (p.x - pos.x, p.y - pos.y);
+                dx * dx + dy * dy <= rad * rad
+            }
+        }
+    }
+
+    // where the diagonal line through the points with x - y = `k` crosses the void, if it does
+    fn hatch(&self, k: f32) -> Option<[Point; 2]> {
+        match self.shape {
+            Shape::Rect { min, max } => {
+                let from = min.x.max(min.y + k);
+                let to = max.x.min(max.y + k);
+                if from >= to {
+                    return None;
+                }
+                Some([
+                    Point {
+                        x: from,
+                        y: from - k,
+                    },
+                    Point { x: to, y: to - k },
+                ])
+            }
+            Shape::Circle { pos, rad } => {
+                let half = std::f32::consts::FRAC_1_SQRT_2;
+                let normal = Vector { x: half, y: -half };
+                let d = k * half - (pos.x * normal.x + pos.y * normal.y);
+                if d.abs() >= rad {
+                    return None;
+                }
+                let foot = Point {
+                    x: pos.x + d * normal.x,
+                    y: pos.y + d * normal.y,
+                };
+                let h = (rad * rad - d * d).sqrt() * half;
+                Some([
+                    Point {
+                        x: foot.x - h,
+                        y: foot.y - h,
+                    },
+                    Point {
+                        x: foot.x + h,
+                        y: foot.y + h,
+                    },
+                ])
+            }
+        }
+    }
+
+    // the diagonal lines that could cross it
+    fn hatch_range(&self) -> (f32, f32) {
+        let (min, max) = match self.shape {
+            Shape::Rect { min, max } => (min, max),
+            Shape::Circle { pos, rad } => (
+                Point {
+                    x: pos.x - rad,
+                    y: pos.y - rad,
+                },
+                Point {
+                    x: pos.x + rad,
+                    y: pos.y + rad,
+                },
+            ),
+        };
+        (min.x - max.y, max.x - min.y)
+    }
+}
+
+// each void darkened with red hatching across it
+pub fn mesh(ctx: &mut Context, voids: &[Void]) -> GameResult<Option<Mesh>> {
+    if voids.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for void in voids {
+        match void.shape {
+            Shape::Rect { min, max } => {
+                let rect = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+                builder.rectangle(DrawMode::fill(), rect, VOID_FILL);
+            }
+            Shape::Circle { pos, rad } => {
+                builder.circle(DrawMode::fill(), pos, rad, 0.5, VOID_FILL);
+            }
+        }
+        // lined up across the box so neighbouring voids hatch alike
+        let (low, high) = void.hatch_range();
+        let mut k = (low / HATCH_SPACING).ceil() * HATCH_SPACING;
+        while k < high {
+            if let Some(line) = void.hatch(k) {
+                builder.line(&line, HATCH_WIDTH, HATCH_COLOR)?;
+            }
+            k += HATCH_SPACING;
+        }
+    }
+    builder.build(ctx).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Voids {
+        voids: Vec<Void>,
+    }
+
+    #[test]
+    fn either_shape_from_toml() {
+        let text = "[[voids]]\nmin = [10.0, 20.0]\nmax = [30.0, 40.0]\n\n\
+                    [[voids]]\npos = [100.0, 100.0]\nrad = 15.0\n";
+        let voids = toml::from_str::<Voids>(text).unwrap().voids;
+        assert!(matches!(voids[0].shape, Shape::Rect { .. }));
+        assert!(matches!(voids[1].shape, Shape::Circle { .. }));
+
+        assert!(voids[0].contains(Point { x: 20.0, y: 30.0 }));
+        assert!(!voids[0].contains(Point { x: 31.0, y: 30.0 }));
+        assert!(voids[1].contains(Point { x: 110.0, y: 110.0 }));
+        assert!(!voids[1].contains(Point { x: 112.0, y: 112.0 }));
+    }
+}
@@ -0,0 +1,560 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::events::WallEvent;
+use crate::world::Particle;
+use crate::{Point, Vector};
+
+const WALL_WIDTH: f32 = 3.0;
+const WALL_COLOR: Color = Color::new(0.8, 0.8, 0.8, 1.0);
+// breakable walls go from the first to the second as they take damage, and crack into up to
+// MAX_CRACKS + 1 pieces with CRACK_GAP between them
+const BREAKABLE_COLOR: Color = Color::new(0.85, 0.75, 0.55, 1.0);
+const CRACKED_COLOR: Color = Color::new(0.9, 0.25, 0.15, 1.0);
+const MAX_CRACKS: f32 = 4.0;
+const CRACK_GAP: f32 = 4.0;
+const DIVIDER_COLOR: Color = Color::new(0.55, 0.8, 0.6, 1.0);
+const PILLAR_FILL: Color = Color::new(0.35, 0.35, 0.35, 1.0);
+const PILLAR_EDGE: Color = Color::new(0.7, 0.7, 0.7, 1.0);
+const PILLAR_EDGE_WIDTH: f32 = 2.0;
+
+// a straight wall drawn into the box, fixed in place. particles bounce off its length like
+// the sides of the box and off its ends like a fixed point, so where two walls meet at an
+// angle there's no gap at the corner to slip through. one with a `strength` breaks once the
+// impulses off it add up to that much. a `divider` comes out with the open dividers key
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strength: Option<f32>,
+    // taken so far, saved so a half broken wall comes back half broken
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub damage: f32,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub divider: bool,
+}
+
+fn is_zero(damage: &f32) -> bool {
+    *damage == 0.0
+}
+
+fn is_false(divider: &bool) -> bool {
+    !*divider
+}
+
+impl Segment {
+    pub fn new(a: Point, b: Point) -> Segment {
+        Segment {
+            a,
+            b,
+            strength: None,
+            damage: 0.0,
+            divider: false,
+        }
+    }
+
+    // how close it is to breaking, from 0 for untouched to 1, always 0 if it can't
+    pub fn wear(&self) -> f32 {
+        match self.strength {
+            Some(strength) if strength > 0.0 => (self.damage / strength).clamp(0.0, 1.0),
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    pub fn broken(&self) -> bool {
+        self.strength
+            .is_some_and(|strength| self.damage >= strength)
+    }
+
+    pub fn length(&self) -> f32 {
+        let (dx, dy) = (self.b.x - self.a.x, self.b.y - self.a.y);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    // the nearest point to `p` anywhere along the wall, ends included
+    pub fn closest_point(&self, p: Point) -> Point {
+        let (dx, dy) = (self.b.x - self.a.x, self.b.y - self.a.y);
+        let length_sq = dx * dx + dy * dy;
+        if length_sq <= f32::EPSILON {
+            return self.a;
+        }
+        let t = (((p.x - self.a.x) * dx + (p.y - self.a.y) * dy) / length_sq).clamp(0.0, 1.0);
+        Point {
+            x: self.a.x + t * dx,
+            y: self.a.y + t * dy,
+        }
+    }
+
+    // how far along the move from `from` to `to` it crosses the wall, if it does
+    fn crossing(&self, from: Point, to: Point) -> Option<f32> {
+        let (dx, dy) = (self.b.x - self.a.x, self.b.y - self.a.y);
+        let (mx, my) = (to.x - from.x, to.y - from.y);
+        let denom = mx * dy - my * dx;
+        if denom.abs() <= f32::EPSILON {
+            return None;
+        }
+        let (ox, oy) = (self.a.x - from.x, self.a.y - from.y);
+        let t = (ox * dy - oy * dx) / denom;
+        let u = (ox * my - oy * mx) / denom;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    // unit vector across the wall, on the same side as `p`
+    fn normal_towards(&self, p: Point) -> Vector {
+        let length = self.length().max(f32::EPSILON);
+        let normal = Vector {
+            x: -(self.b.y - self.a.y) / length,
+            y: (self.b.x - self.a.x) / length,
+        };
+        if (p.x - self.a.x) * normal.x + (p.y - self.a.y) * normal.y < 0.0 {
+            Vector {
+                x: -normal.x,
+                y: -normal.y,
+            }
+        } else {
+            normal
+        }
+    }
+
+    // push `particle` back out if it's overlapping, or went straight through since the step
+    // started, and bounce it if it was heading in faster than `resting`. an event for every
+    // bounce
+    pub fn collide(
+        &self,
+        particle: &mut Particle,
+        restitution: f32,
+        friction: f32,
+        resting: f32,
+    ) -> Option<WallEvent> {
+        // fast particles can jump a wall in one step, put them back where they crossed it
+        let (contact, normal) = match self.crossing(particle.prev_pos, particle.pos) {
+            Some(t) => {
+                let from = particle.prev_pos;
+                let contact = Point {
+                    x: from.x + (particle.pos.x - from.x) * t,
+                    y: from.y + (particle.pos.y - from.y) * t,
+                };
+                (contact, self.normal_towards(from))
+            }
+            None => {
+                let contact = self.closest_point(particle.pos);
+                let (dx, dy) = (particle.pos.x - contact.x, particle.pos.y - contact.y);
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance >= particle.rad {
+                    return None;
+                }
+                let normal = if distance > f32::EPSILON {
+                    Vector {
+                        x: dx / distance,
+                        y: dy / distance,
+                    }
+                } else {
+                    self.normal_towards(particle.prev_pos)
+                };
+                (contact, normal)
+            }
+        };
+
+        // any slower and it's lying on the wall rather than hitting it. it loses that speed
+        // without bouncing, so gravity slides it down a slope instead of hopping it down
+        let speed_in = particle.vel.x * normal.x + particle.vel.y * normal.y;
+        if -speed_in < resting {
+            bounce(particle, contact, normal, 0.0, friction);
+            return None;
+        }
+        bounce(particle, contact, normal, restitution, friction)
+    }
+}
+
+// an immovable round post. it's a particle collision where the other side has infinite mass,
+// so only the particle's velocity along the normal changes
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Pillar {
+    pub pos: Point,
+    pub rad: f32,
+}
+
+impl Pillar {
+    pub fn contains(&self, p: Point) -> bool {
+        let (dx, dy) = (p.x - self.pos.x, p.y - self.pos.y);
+        dx * dx + dy * dy <= self.rad * self.rad
+    }
+
+    pub fn collide(
+        &self,
+        particle: &mut Particle,
+        restitution: f32,
+        friction: f32,
+    ) -> Option<WallEvent> {
+        let (dx, dy) = (particle.pos.x - self.pos.x, particle.pos.y - self.pos.y);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance >= self.rad + particle.rad {
+            return None;
+        }
+        let normal = if distance > f32::EPSILON {
+            Vector {
+                x: dx / distance,
+                y: dy / distance,
+            }
+        } else {
+            Vector { x: 0.0, y: -1.0 }
+        };
+        let contact = Point {
+            x: self.pos.x + normal.x * self.rad,
+            y: self.pos.y + normal.y * self.rad,
+        };
+        bounce(particle, contact, normal, restitution, friction)
+    }
+}
+
+// an immovable solid rectangle, kept square to the box. anything touching a face bounces
+// straight off it, and off a corner like off a pillar with no radius
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Block {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Block {
+    // the rectangle with `a` and `b` at opposite corners, whichever way round
+    pub fn from_corners(a: Point, b: Point) -> Block {
+        Block {
+            min: Point {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+            },
+            max: Point {
+                x: a.x.max(b.x),
+                y: a.y.max(b.y),
+            },
+        }
+    }
+
+    // the shorter side
+    pub fn thickness(&self) -> f32 {
+        (self.max.x - self.min.x).min(self.max.y - self.min.y)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn center(&self) -> Point {
+        Point {
+            x: 0.5 * (self.min.x + self.max.x),
+            y: 0.5 * (self.min.y + self.max.y),
+        }
+    }
+
+    // half the diagonal, nothing further than this from the center is inside
+    pub fn reach(&self) -> f32 {
+        let (dx, dy) = (self.max.x - self.min.x, self.max.y - self.min.y);
+        0.5 * (dx * dx + dy * dy).sqrt()
+    }
+
+    // the nearest point to `p` on or in the block
+    pub fn closest_point(&self, p: Point) -> Point {
+        Point {
+            x: p.x.clamp(self.min.x, self.max.x),
+            y: p.y.clamp(self.min.y, self.max.y),
+        }
+    }
+
+    // where a point inside gets out quickest, and the way out
+    fn nearest_face(&self, p: Point) -> (Point, Vector) {
+        let faces = [
+            (
+                p.x - self.min.x,
+                Point {
+                    x: self.min.x,
+                    y: p.y,
+                },
+                Vector { x: -1.0, y: 0.0 },
+            ),
+            (
+                self.max.x - p.x,
+                Point {
+                    x: self.max.x,
+                    y: p.y,
+                },
+                Vector { x: 1.0, y: 0.0 },
+            ),
+            (
+                p.y - self.min.y,
+                Point {
+                    x: p.x,
+                    y: self.min.y,
+                },
+                Vector { x: 0.0, y: -1.0 },
+            ),
+            (
+                self.max.y - p.y,
+                Point {
+                    x: p.x,
+                    y: self.max.y,
+                },
+                Vector { x: 0.0, y: 1.0 },
+            ),
+        ];
+        let (_, contact, normal) = faces
+            .iter()
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .copied()
+            .expect("A block has faces");
+        (contact, normal)
+    }
+
+    // off the closest point, so along a face the normal is square to it and past a corner
+    // it points from the corner to the particle's center
+    pub fn collide(
+        &self,
+        particle: &mut Particle,
+        restitution: f32,
+        friction: f32,
+    ) -> Option<WallEvent> {
+        let (contact, normal) = if self.contains(particle.pos) {
+            // the center got all the way in, push it out the nearest face
+            self.nearest_face(particle.pos)
+        } else {
+            let contact = self.closest_point(particle.pos);
+            let (dx, dy) = (particle.pos.x - contact.x, particle.pos.y - contact.y);
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance >= particle.rad {
+                return None;
+            }
+            let normal = Vector {
+                x: dx / distance,
+                y: dy / distance,
+            };
+            (contact, normal)
+        };
+        bounce(particle, contact, normal, restitution, friction)
+    }
+}
+
+// move `particle` to just touch `contact` on the `normal` side and reflect it off,
+// if it was heading in. one still overlapping on its way out already bounced
+fn bounce(
+    particle: &mut Particle,
+    contact: Point,
+    normal: Vector,
+    restitution: f32,
+    friction: f32,
+) -> Option<WallEvent> {
+    particle.pos = Point {
+        x: contact.x + normal.x * particle.rad,
+        y: contact.y + normal.y * particle.rad,
+    };
+
+    let speed_in = particle.vel.x * normal.x + particle.vel.y * normal.y;
+    if speed_in >= 0.0 {
+        return None;
+    }
+    let change = -(1.0 + restitution) * speed_in;
+    particle.vel.x += change * normal.x;
+    particle.vel.y += change * normal.y;
+
+    // sliding along it slows by up to `friction` times the bounce, but never turns round
+    let along = Vector {
+        x: -normal.y,
+        y: normal.x,
+    };
+    let sliding = particle.vel.x * along.x + particle.vel.y * along.y;
+    let slowed = rub(sliding, 0.0, change, friction);
+    particle.vel.x += (slowed - sliding) * along.x;
+    particle.vel.y += (slowed - sliding) * along.y;
+
+    Some(WallEvent {
+        id: particle.id,
+        point: contact,
+        normal,
+        impulse: particle.mass * change,
+        enclosing: false,
+        mass: particle.mass,
+        rad: particle.rad,
+        color: particle.color,
+    })
+}
+
+// the speed along a surface moving at `surface` after a contact that changed the speed into it
+// by `change`. friction takes up to `friction` times that off the difference, like a
+// coefficient of friction, so it stops things sliding but can't send them back the other way
+pub fn rub(vel: f32, surface: f32, change: f32, friction: f32) -> f32 {
+    let relative = vel - surface;
+    let slowing = (friction * change.abs()).min(relative.abs());
+    vel - slowing * relative.signum()
+}
+
+// every wall and pillar. walls get round ends so corners look joined up,
+// pillars are gray so they can't be mistaken for particles
+pub fn mesh(
+    ctx: &mut Context,
+    segments: &[Segment],
+    pillars: &[Pillar],
+) -> GameResult<Option<Mesh>> {
+    if segments.is_empty() && pillars.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for pillar in pillars {
+        builder.circle(DrawMode::fill(), pillar.pos, pillar.rad, 0.1, PILLAR_FILL);
+        builder.circle(
+            DrawMode::stroke(PILLAR_EDGE_WIDTH),
+            pillar.pos,
+            pillar.rad,
+            0.1,
+            PILLAR_EDGE,
+        );
+    }
+    for segment in segments {
+        let color = match segment.strength {
+            Some(_) => {
+                let t = segment.wear();
+                Color::new(
+                    BREAKABLE_COLOR.r + (CRACKED_COLOR.r - BREAKABLE_COLOR.r) * t,
+                    BREAKABLE_COLOR.g + (CRACKED_COLOR.g - BREAKABLE_COLOR.g) * t,
+                    BREAKABLE_COLOR.b + (CRACKED_COLOR.b - BREAKABLE_COLOR.b) * t,
+                    1.0,
+                )
+            }
+            None if segment.divider => DIVIDER_COLOR,
+            None => WALL_COLOR,
+        };
+        let length = segment.length();
+        // evenly spaced cracks, more of them the nearer it is to breaking
+        let pieces = 1 + (segment.wear() * MAX_CRACKS).floor() as usize;
+        let gap = if pieces > 1 {
+            (CRACK_GAP / length.max(f32::EPSILON)).min(0.5 / pieces as f32)
+        } else {
+            0.0
+        };
+        let at = |t: f32| Point {
+            x: segment.a.x + (segment.b.x - segment.a.x) * t,
+            y: segment.a.y + (segment.b.y - segment.a.y) * t,
+        };
+        for piece in 0..pieces {
+            let from = piece as f32 / pieces as f32 + if piece > 0 { 0.5 * gap } else { 0.0 };
+            let to = (piece + 1) as f32 / pieces as f32
+                - if piece + 1 < pieces { 0.5 * gap } else { 0.0 };
+            if (to - from) * length > f32::EPSILON {
+                builder.line(&[at(from), at(to)], WALL_WIDTH, color)?;
+            }
+        }
+        for end in &[segment.a, segment.b] {
+            builder.circle(DrawMode::fill(), *end, 0.5 * WALL_WIDTH, 0.5, color);
+        }
+    }
+    builder.build(ctx).map(Some)
+}
+
+// every block, filled in `color` with a lighter edge
+pub fn block_mesh(ctx: &mut Context, blocks: &[Block], color: Color) -> GameResult<Option<Mesh>> {
+    if blocks.is_empty() {
+        return Ok(None);
+    }
+    let edge = Color::new(
+        0.5 + 0.5 * color.r,
+        0.5 + 0.5 * color.g,
+        0.5 + 0.5 * color.b,
+        color.a,
+    );
+    let mut builder = MeshBuilder::new();
+    for block in blocks {
+        let rect = Rect::new(
+            block.min.x,
+            block.min.y,
+            block.max.x - block.min.x,
+            block.max.y - block.min.y,
+        );
+        builder.rectangle(DrawMode::fill(), rect, color);
+        builder.rectangle(DrawMode::stroke(PILLAR_EDGE_WIDTH), rect, edge);
+    }
+    builder.build(ctx).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESTITUTION: f32 = 1.0;
+
+    fn block() -> Block {
+        Block::from_corners(Point { x: 100.0, y: 100.0 }, Point { x: 200.0, y: 150.0 })
+    }
+
+    fn particle(x: f32, y: f32, vx: f32, vy: f32) -> Particle {
+        Particle::new(
+            Point { x, y },
+            Vector { x: vx, y: vy },
+            10.0,
+            1.0,
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        )
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn face_hit_reflects_across_the_face() {
+        // coming down onto the top, overlapping by 2
+        let mut p = particle(150.0, 92.0, 30.0, 50.0);
+        let event = block().collide(&mut p, RESTITUTION, 0.0).expect("A hit");
+        assert!(close(event.normal.x, 0.0) && close(event.normal.y, -1.0));
+        assert!(close(p.pos.y, 90.0) && close(p.pos.x, 150.0));
+        assert!(close(p.vel.x, 30.0) && close(p.vel.y, -50.0));
+    }
+
+    #[test]
+    fn edge_grazing() {
+        // level with the left side, just clear of it and then just touching
+        let mut p = particle(89.99, 100.0, 40.0, 0.0);
+        assert!(block().collide(&mut p, RESTITUTION, 0.0).is_none());
+        assert!(close(p.vel.x, 40.0));
+
+        let mut p = particle(90.5, 100.0, 40.0, 0.0);
+        let event = block().collide(&mut p, RESTITUTION, 0.0).expect("A hit");
+        assert!(close(event.normal.x, -1.0) && close(event.normal.y, 0.0));
+        assert!(close(p.vel.x, -40.0) && close(p.vel.y, 0.0));
+
+        // sliding along the top doesn't bounce, it's already heading out
+        let mut p = particle(150.0, 90.5, 40.0, 0.0);
+        assert!(block().collide(&mut p, RESTITUTION, 0.0).is_none());
+        assert!(close(p.pos.y, 90.0) && close(p.vel.x, 40.0));
+    }
+
+    #[test]
+    fn corner_hit_uses_the_corner_to_center_normal() {
+        // straight at the top left corner along the diagonal
+        let mut p = particle(95.0, 95.0, 20.0, 20.0);
+        let event = block().collide(&mut p, RESTITUTION, 0.0).expect("A hit");
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(close(event.normal.x, -half) && close(event.normal.y, -half));
+        assert!(close(event.point.x, 100.0) && close(event.point.y, 100.0));
+        assert!(close(p.vel.x, -20.0) && close(p.vel.y, -20.0));
+
+        // off centre only the part along the normal turns round
+        let mut p = particle(97.0, 96.0, 10.0, 0.0);
+        let event = block().collide(&mut p, RESTITUTION, 0.0).expect("A hit");
+        let (nx, ny) = (-0.6, -0.8);
+        assert!(close(event.normal.x, nx) && close(event.normal.y, ny));
+        assert!(close(p.vel.x, 10.0 - 2.0 * 10.0 * nx * nx));
+        assert!(close(p.vel.y, -2.0 * 10.0 * nx * ny));
+    }
+
+    #[test]
+    fn center_inside_leaves_by_the_nearest_face() {
+        let mut p = particle(195.0, 120.0, -30.0, 0.0);
+        let event = block().collide(&mut p, RESTITUTION, 0.0).expect("A hit");
+        assert!(close(event.normal.x, 1.0) && close(event.normal.y, 0.0));
+        assert!(close(p.pos.x, 210.0) && close(p.vel.x, 30.0));
+    }
+}
@@ -0,0 +1,145 @@
+use serde::Deserialize;
+
+const RAIN_DRAG: f32 = 0.0002;
+const SNOW_DRAG: f32 = 0.01;
+const SNOW_WIND: f32 = 25.0;
+
+// what happens to particles reaching the floor. either way they come back in at the top, so
+// there are always as many
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weather {
+    Off,
+    // drops splash on the floor and go straight back up
+    Rain,
+    // flakes drift down on the wind and go once they've settled on the floor, piling up on
+    // anything else
+    Snow,
+}
+
+impl Weather {
+    pub fn next(self) -> Weather {
+        match self {
+            Weather::Off => Weather::Rain,
+            Weather::Rain => Weather::Snow,
+            Weather::Snow => Weather::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Weather::Off => "off",
+            Weather::Rain => "rain",
+            Weather::Snow => "snow",
+        }
+    }
+
+    // the drag and wind it comes in with, none to go back to the defaults
+    pub fn air(self) -> Option<(f32, f32)> {
+        match self {
+            Weather::Off => None,
+            Weather::Rain => Some((RAIN_DRAG, 0.0)),
+            Weather::Snow => Some((SNOW_DRAG, SNOW_WIND)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::testing;
+    use crate::walls::Segment;
+    use crate::world::World;
+    use crate::{Point, Vector};
+
+    fn sky(count: usize) -> World {
+        let mut world = World::new(600.0, 600.0);
+        for i in 0..count {
+            let (x, y) = (15.0 + 30.0 * (i % 20) as f32, 15.0 + 30.0 * (i / 20) as f32);
+            world.add_particle(testing::particle(x, y, 4.0));
+        }
+        world
+    }
+
+    #[test]
+    fn rain_splashes_off_the_floor_and_comes_back_in_at_the_top() {
+        let (drag, wind) = Weather::Rain.air().unwrap();
+        let config = SimulationConfig {
+            weather: Weather::Rain,
+            drag,
+            wind,
+            gravity: Vector { x: 0.0, y: 400.0 },
+            restitution: 0.5,
+            ..testing::config()
+        };
+        let mut world = sky(40);
+        let mut splashes = 0;
+        testing::run_each(&mut world, 5 * 120, &config, |world| {
+            splashes += world.events.splashes.len();
+            // every drop's off the floor as soon as it lands
+            assert!(world
+                .particles
+                .iter()
+                .all(|p| p.pos.y + p.rad < world.height - 0.5));
+        });
+        assert_eq!(world.particles.len(), 40);
+        assert!(splashes > 80, "{} splashes", splashes);
+    }
+
+    #[test]
+    fn snow_drifts_with_the_wind() {
+        let (drag, wind) = Weather::Snow.air().unwrap();
+        let config = SimulationConfig {
+            weather: Weather::Snow,
+            drag,
+            wind,
+            sleeping: true,
+            wall_friction: 0.6,
+            particle_friction: 0.6,
+            solver_iterations: 4,
+            restitution: 0.1,
+            gravity: Vector { x: 0.0, y: 200.0 },
+            ..testing::config()
+        };
+        let mut world = sky(100);
+        testing::run(&mut world, 120, &config);
+        let drift = world.particles.iter().map(|p| p.vel.x).sum::<f32>() / 100.0;
+        assert!(drift > 0.1 * config.wind, "drift {}", drift);
+    }
+
+    #[test]
+    fn snow_piles_up_on_a_ramp_and_is_cleared_off_the_floor() {
+        let (drag, wind) = Weather::Snow.air().unwrap();
+        let config = SimulationConfig {
+            weather: Weather::Snow,
+            drag,
+            wind,
+            sleeping: true,
+            wall_friction: 0.6,
+            particle_friction: 0.6,
+            solver_iterations: 4,
+            restitution: 0.1,
+            gravity: Vector { x: 0.0, y: 200.0 },
+            ..testing::config()
+        };
+        let mut world = sky(100);
+        world.segments.push(Segment::new(
+            Point { x: 100.0, y: 400.0 },
+            Point { x: 400.0, y: 450.0 },
+        ));
+        testing::run(&mut world, 40 * 120, &config);
+        assert_eq!(world.particles.len(), 100);
+        let on_ramp = world
+            .particles
+            .iter()
+            .filter(|p| p.asleep && (100.0..400.0).contains(&p.pos.x) && p.pos.y < 450.0)
+            .count();
+        let on_floor = world
+            .particles
+            .iter()
+            .filter(|p| p.asleep && p.pos.y + p.rad > world.height - 1.0)
+            .count();
+        assert!(on_ramp > 50 && on_floor == 0, "{} {}", on_ramp, on_floor);
+    }
+}
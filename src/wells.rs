@@ -0,0 +1,101 @@
+use ggez::graphics::{Color, DrawMode, Mesh, MeshBuilder};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SimulationConfig;
+use crate::{Point, Vector};
+
+// a well's strength is its pull this far from the center
+const WELL_REFERENCE: f32 = 100.0;
+// the scroll wheel scales a well's strength by this per notch
+const STRENGTH_STEP: f32 = 1.1;
+// clicks this close to a well's center pick it
+const PICK_RADIUS: f32 = 15.0;
+
+const RING_RADIUS: f32 = 12.0;
+const RING_WIDTH: f32 = 1.5;
+const SWIRL_ARMS: usize = 3;
+const SWIRL_POINTS: usize = 12;
+const WELL_COLOR: Color = Color::new(0.6, 0.5, 1.0, 0.8);
+
+// a fixed point everything is pulled towards. an acceleration like gravity, so heavy and light
+// particles fall in alike, and several wells just add up
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Well {
+    pub pos: Point,
+    pub strength: f32,
+}
+
+impl Well {
+    pub fn near(&self, p: Point) -> bool {
+        let (dx, dy) = (p.x - self.pos.x, p.y - self.pos.y);
+        dx * dx + dy * dy <= PICK_RADIUS * PICK_RADIUS
+    }
+
+    // one notch of the mouse wheel per unit, positive for stronger
+    pub fn scale_strength(&mut self, notches: f32) {
+        self.strength *= STRENGTH_STEP.powf(notches);
+    }
+
+    // goes with 1 / distance^well_falloff, but measured as if everything were at least
+    // well_softening away, so a particle falling straight through the middle isn't flung
+    // out at some enormous speed
+    pub fn accel(&self, p: Point, config: &SimulationConfig) -> Vector {
+        let (dx, dy) = (self.pos.x - p.x, self.pos.y - p.y);
+        let distance_sq = dx * dx + dy * dy;
+        let distance = distance_sq.sqrt();
+        if distance <= f32::EPSILON {
+            return Vector { x: 0.0, y: 0.0 };
+        }
+
+        let softened = (distance_sq + config.well_softening * config.well_softening).sqrt();
+        let accel = self.strength * (WELL_REFERENCE / softened).powf(config.well_falloff);
+        Vector {
+            x: dx / distance * accel,
+            y: dy / distance * accel,
+        }
+    }
+}
+
+// a ring with arms curling into it, the bigger the stronger
+pub fn mesh(
+    ctx: &mut Context,
+    wells: &[Well],
+    config: &SimulationConfig,
+) -> GameResult<Option<Mesh>> {
+    if wells.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for well in wells {
+        let scale = (well.strength / config.well_strength.max(f32::EPSILON))
+            .abs()
+            .sqrt()
+            .clamp(0.5, 3.0);
+        let radius = RING_RADIUS * scale;
+        builder.circle(
+            DrawMode::stroke(RING_WIDTH),
+            well.pos,
+            radius,
+            0.5,
+            WELL_COLOR,
+        );
+
+        for arm in 0..SWIRL_ARMS {
+            let start = std::f32::consts::PI * 2.0 * arm as f32 / SWIRL_ARMS as f32;
+            let points: Vec<Point> = (0..=SWIRL_POINTS)
+                .map(|i| {
+                    let t = i as f32 / SWIRL_POINTS as f32;
+                    let angle = start + t * std::f32::consts::PI;
+                    let r = radius * (1.0 - t);
+                    Point {
+                        x: well.pos.x + r * angle.cos(),
+                        y: well.pos.y + r * angle.sin(),
+                    }
+                })
+                .collect();
+            builder.line(&points, RING_WIDTH, WELL_COLOR)?;
+        }
+    }
+    builder.build(ctx).map(Some)
+}
@@ -0,0 +1,2308 @@
+use ggez::graphics::Color;
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::belt;
+use crate::black_holes::BlackHole;
+use crate::boids;
+use crate::broadphase::SpatialGrid;
+use crate::color::{self, ColorPolicy};
+use crate::config::SimulationConfig;
+use crate::container::Container;
+use crate::crystal;
+use crate::demon::{self, Gate};
+use crate::emitters::Emitter;
+use crate::epidemic::{self, Health};
+use crate::events::{CollisionEvent, ConsumedEvent, Events, SplashEvent, WallEvent};
+use crate::fission::{self, Nuclide};
+use crate::mixing::Gas;
+use crate::piston::{Piston, PistonMode};
+use crate::portals::Portal;
+use crate::predators::{self, Species};
+use crate::reactions::{self, Reagent};
+use crate::targets::{self, Target};
+use crate::theme::Theme;
+use crate::thermostat;
+use crate::voids::Void;
+use crate::walls::{self, Block, Pillar, Segment};
+use crate::weather::Weather;
+use crate::wells::Well;
+use crate::zones::Zone;
+use crate::{Point, Vector};
+
+// how far out spawn_at looks for room, in multiples of the new particle's radius
+const SPAWN_SEARCH_RINGS: usize = 6;
+
+// the spring pulling a held particle to the cursor: how fast it oscillates (rad/s),
+// and how much of that is damped out, 1 being just enough to never overshoot
+const HOLD_FREQUENCY: f32 = 30.0;
+const HOLD_DAMPING: f32 = 0.8;
+
+// attraction_strength is the force this far from the cursor, it goes with 1 / distance
+// but stops growing inside the softening distance
+const ATTRACTION_REFERENCE: f32 = 100.0;
+const ATTRACTION_SOFTENING: f32 = 30.0;
+
+// explosions hit as if nothing were closer than this, so the middle isn't infinite
+const BLAST_MIN_DISTANCE: f32 = 10.0;
+// random spots a burst or scatter tries for each particle before giving up on it
+const SPAWN_TRIES: usize = 30;
+// range of downward speeds a particle that's escaped through an open top comes back with
+const RESPAWN_SPEED_MIN: f32 = 20.0;
+const RESPAWN_SPEED_MAX: f32 = 60.0;
+// rain and snow this close to the floor are on it
+const FLOOR_CONTACT: f32 = 0.5;
+// the fraction of an overlap between two particles taken out each step
+const SEPARATION: f32 = 0.5;
+// the most a broken wall's debris flies off at
+const DEBRIS_SPEED: f32 = 80.0;
+// a wall hit no faster than gravity could have managed in this many steps is something
+// resting on it, which stays put rather than bouncing
+const RESTING_STEPS: f32 = 3.0;
+
+// what happens at the edges of the box
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+    // bounce off them
+    Walls,
+    // go out one side and come back in the other, so there are no walls to skew a gas
+    Wrap,
+    // walls at the sides and bottom only, see Escaped for what happens to anything that
+    // goes out of the top
+    #[serde(rename = "open-top")]
+    OpenTop,
+    // wraps from side to side, with walls top and bottom. push everything along with some
+    // sideways gravity and it flows down the channel without piling up at an end
+    Channel,
+}
+
+impl Boundary {
+    pub fn next(self) -> Boundary {
+        match self {
+            Boundary::Walls => Boundary::Wrap,
+            Boundary::Wrap => Boundary::OpenTop,
+            Boundary::OpenTop => Boundary::Channel,
+            Boundary::Channel => Boundary::Walls,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Boundary::Walls => "walls",
+            Boundary::Wrap => "wrap",
+            Boundary::OpenTop => "open top",
+            Boundary::Channel => "channel",
+        }
+    }
+
+    // whether going out of the left comes back in the right, and the right the left
+    pub fn wraps_x(self) -> bool {
+        matches!(self, Boundary::Wrap | Boundary::Channel)
+    }
+
+    // and the same for the top and bottom
+    pub fn wraps_y(self) -> bool {
+        self == Boundary::Wrap
+    }
+}
+
+// how the walls of the box push back
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WallStyle {
+    // turned round the moment it touches, keeping restitution of its speed
+    Hard,
+    // a spring pushes back on anything that's gone into the wall, harder the further it's in,
+    // so it squashes in and springs back out. wall_damping takes the place of restitution
+    Soft,
+}
+
+// what becomes of a particle once it's gone escape_margin past an open top
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Escaped {
+    // drops back in somewhere along the top, same id and all, so the count holds steady
+    Respawn,
+    // gone for good
+    Remove,
+}
+
+// a particle being dragged around, by id so it survives others being added or removed
+pub struct Hold {
+    pub id: u64,
+    // where the spring pulls it towards
+    pub target: Point,
+}
+
+// one simulation: the particles, the box they live in and what happened to them last step.
+// nothing in here needs a ggez context, so several can run side by side
+pub struct World {
+    pub particles: Vec<Particle>,
+    // the walls, following the window (or its share of it)
+    pub width: f32,
+    pub height: f32,
+    pub broad_phase: SpatialGrid,
+    // every step adds to these and nothing clears them but the owner,
+    // so a frame's worth of steps can be read together
+    pub events: Events,
+    // fixed steps taken since the last reset
+    pub step_count: u64,
+    pub hold: Option<Hold>,
+    // the cursor while it's pushing particles away, or pulling them in
+    pub repulsor: Option<Point>,
+    pub attractor: Option<Point>,
+    // walls, pillars and blocks placed in the box. they belong to the box rather than the scene,
+    // so loading new particles leaves them where they are
+    pub segments: Vec<Segment>,
+    pub pillars: Vec<Pillar>,
+    pub blocks: Vec<Block>,
+    // gaps in walls that open and close, see demon::Gate
+    pub gates: Vec<Gate>,
+    // placed gravity wells, part of the scene like the walls
+    pub wells: Vec<Well>,
+    // pulling harder than wells, and keeping whatever gets too close
+    pub black_holes: Vec<BlackHole>,
+    // pairs of rings that send particles from one to the other
+    pub portals: Vec<Portal>,
+    // holes anything falling into is lost down
+    pub voids: Vec<Void>,
+    // nozzles spraying new particles in
+    pub emitters: Vec<Emitter>,
+    // circles to get particles into in a game, see targets::Game
+    pub targets: Vec<Target>,
+    // rectangles with their own gravity, drag or push
+    pub zones: Vec<Zone>,
+    // a polygon everything stays inside, within the box
+    pub container: Option<Container>,
+    // the right side, where it's moved to if it's a piston
+    pub piston: Piston,
+    // how far the conveyor belt's stripes have scrolled
+    pub belt_offset: f32,
+    // the gravity anything asleep went to sleep under
+    sleep_gravity: Vector,
+    next_id: u64,
+}
+
+impl World {
+    pub fn new(width: f32, height: f32) -> World {
+        World {
+            particles: Vec::new(),
+            width,
+            height,
+            broad_phase: SpatialGrid::new(width, height),
+            events: Events::default(),
+            step_count: 0,
+            hold: None,
+            repulsor: None,
+            attractor: None,
+            segments: Vec::new(),
+            pillars: Vec::new(),
+            blocks: Vec::new(),
+            gates: Vec::new(),
+            wells: Vec::new(),
+            black_holes: Vec::new(),
+            portals: Vec::new(),
+            voids: Vec::new(),
+            emitters: Vec::new(),
+            targets: Vec::new(),
+            zones: Vec::new(),
+            container: None,
+            piston: Piston::new(width),
+            belt_offset: 0.0,
+            sleep_gravity: Vector { x: 0.0, y: 0.0 },
+            next_id: 0,
+        }
+    }
+
+    // start over with `particles`, numbered from 0 again. presets are laid out for the whole
+    // box, so with a container anything that would start outside it is left out
+    pub fn load(&mut self, particles: Vec<Particle>) {
+        self.particles.clear();
+        self.events.clear();
+        self.step_count = 0;
+        self.hold = None;
+        self.next_id = 0;
+        self.piston = Piston::new(self.width);
+        for void in &mut self.voids {
+            void.consumed = 0;
+        }
+        for hole in &mut self.black_holes {
+            hole.consumed = 0;
+            hole.ring.clear();
+        }
+        for emitter in &mut self.emitters {
+            emitter.budget = 0.0;
+            emitter.emitted = 0;
+        }
+
+        for particle in particles {
+            if self.inside_container(particle.pos, particle.rad) {
+                self.add_particle(particle);
+            }
+        }
+    }
+
+    // everything that's free to move, all told
+    pub fn kinetic_energy(&self) -> f32 {
+        self.particles
+            .iter()
+            .filter(|p| !p.pinned)
+            .map(|p| 0.5 * p.mass * p.vel_magnitude().powi(2))
+            .sum()
+    }
+
+    // the average speed to the right, how fast a channel is flowing
+    pub fn flow(&self) -> f32 {
+        if self.particles.is_empty() {
+            return 0.0;
+        }
+        let total: f32 = self.particles.iter().map(|p| p.vel.x).sum();
+        total / self.particles.len() as f32
+    }
+
+    // where the box ends on the right, the piston when there is one
+    pub fn right(&self) -> f32 {
+        self.piston.x.min(self.width)
+    }
+
+    // the area everything's shut in, and how much wall there is around it: the container's,
+    // or else whichever edges of the box are walls. obstacles inside aren't taken off
+    pub fn enclosure(&self, boundary: Boundary) -> (f32, f32) {
+        if let Some(container) = &self.container {
+            return (container.area(), container.perimeter());
+        }
+        let (width, height) = if boundary.wraps_x() {
+            (self.width, self.height)
+        } else {
+            (self.right(), self.height)
+        };
+        let walls = match boundary {
+            Boundary::Walls => 2.0 * (width + height),
+            Boundary::Wrap => 0.0,
+            Boundary::OpenTop => width + 2.0 * height,
+            Boundary::Channel => 2.0 * width,
+        };
+        (width * height, walls)
+    }
+
+    fn inside_container(&self, p: Point, rad: f32) -> bool {
+        self.container
+            .as_ref()
+            .is_none_or(|container| container.contains(p, rad))
+    }
+
+    // every particle goes through here so it gets a unique id
+    pub fn add_particle(&mut self, mut particle: Particle) {
+        particle.id = self.next_id;
+        self.next_id += 1;
+        self.particles.push(particle);
+    }
+
+    // add particles at random positions with random velocities
+    pub fn spawn_random(
+        &mut self,
+        count: usize,
+        config: &SimulationConfig,
+        theme: &Theme,
+        rng: &mut impl Rng,
+    ) {
+        let count = count.min(config.max_particles.saturating_sub(self.particles.len()));
+        for _ in 0..count {
+            let rad = config.spawn_radius(rng);
+            let mass = config.spawn_mass(rng);
+            let color = theme
+                .palette
+                .choose(rng)
+                .expect("Some colors in the palette");
+
+            let (min, max) = match &self.container {
+                Some(container) => container.bounds(),
+                None => (
+                    Point { x: 0.0, y: 0.0 },
+                    Point {
+                        x: self.right(),
+                        y: self.height,
+                    },
+                ),
+            };
+            let spot = (0..SPAWN_TRIES).find_map(|_| {
+                let pos = Point {
+                    x: rng.gen_range(min.x + rad..(max.x - rad).max(min.x + rad + 1.0)),
+                    y: rng.gen_range(min.y + rad..(max.y - rad).max(min.y + rad + 1.0)),
+                };
+                Some(pos).filter(|&pos| self.inside_container(pos, rad))
+            });
+            let Point { x, y } = match spot {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+            let speed = rng.gen_range(20.0..80.0);
+
+            self.add_particle(Particle::new(
+                Point { x, y },
+                Vector {
+                    x: speed * angle.cos(),
+                    y: speed * angle.sin(),
+                },
+                rad,
+                mass,
+                *color,
+            ));
+        }
+    }
+
+    // up to `count` particles at random free spots within burst_radius of `center`, each
+    // heading straight out from it. the disc fills up rather than anything overlapping,
+    // so it can come up short even below max_particles. how many went in
+    pub fn spawn_burst(
+        &mut self,
+        center: Point,
+        count: usize,
+        config: &SimulationConfig,
+        theme: &Theme,
+        rng: &mut impl Rng,
+    ) -> usize {
+        let count = count.min(config.max_particles.saturating_sub(self.particles.len()));
+        let mut spawned = 0;
+        for _ in 0..count {
+            let rad = config.spawn_radius(rng);
+            let mass = config.spawn_mass(rng);
+            let color = *theme
+                .palette
+                .choose(rng)
+                .expect("Some colors in the palette");
+
+            let spot = (0..SPAWN_TRIES).find_map(|_| {
+                // sqrt spreads them evenly over the disc instead of bunching in the middle
+                let distance = config.burst_radius * rng.gen_range(0.0f32..1.0).sqrt();
+                let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                let (ux, uy) = (angle.cos(), angle.sin());
+                let pos = Point {
+                    x: center.x + distance * ux,
+                    y: center.y + distance * uy,
+                };
+                let inside = pos.x >= rad
+                    && pos.x <= self.right() - rad
+                    && pos.y >= rad
+                    && pos.y <= self.height - rad;
+                if inside && self.fits(pos, rad) {
+                    Some((pos, ux, uy))
+                } else {
+                    None
+                }
+            });
+            let (pos, ux, uy) = match spot {
+                Some(spot) => spot,
+                None => continue,
+            };
+
+            let speed = config.burst_speed * rng.gen_range(0.5..1.0);
+            self.add_particle(Particle::new(
+                pos,
+                Vector {
+                    x: ux * speed,
+                    y: uy * speed,
+                },
+                rad,
+                mass,
+                color,
+            ));
+            spawned += 1;
+        }
+        spawned
+    }
+
+    // the index of the pillar under `pos`, the last placed if they overlap
+    pub fn pillar_at(&self, pos: Point) -> Option<usize> {
+        self.pillars.iter().rposition(|pillar| pillar.contains(pos))
+    }
+
+    // the index of the block under `pos`, the last placed if they overlap
+    pub fn block_at(&self, pos: Point) -> Option<usize> {
+        self.blocks.iter().rposition(|block| block.contains(pos))
+    }
+
+    // the index of the well with its center under `pos`, the last placed if they overlap
+    pub fn well_at(&self, pos: Point) -> Option<usize> {
+        self.wells.iter().rposition(|well| well.near(pos))
+    }
+
+    // the index of the black hole under `pos`, horizon and all, the last placed if they overlap
+    pub fn black_hole_at(&self, pos: Point) -> Option<usize> {
+        self.black_holes.iter().rposition(|hole| hole.near(pos))
+    }
+
+    pub fn emitter_at(&self, pos: Point) -> Option<usize> {
+        self.emitters.iter().rposition(|emitter| emitter.near(pos))
+    }
+
+    // the id of the particle under `pos`. where they overlap the smallest wins,
+    // it's the one drawn on top
+    pub fn particle_at(&self, pos: Point) -> Option<u64> {
+        self.particles
+            .iter()
+            .filter(|p| {
+                let dx = p.pos.x - pos.x;
+                let dy = p.pos.y - pos.y;
+                dx * dx + dy * dy <= p.rad * p.rad
+            })
+            .min_by(|a, b| a.rad.total_cmp(&b.rad))
+            .map(|p| p.id)
+    }
+
+    // pin or unpin a particle by id, false if it isn't here. either way it comes to a stop
+    pub fn set_pinned(&mut self, id: u64, pinned: bool) -> bool {
+        match self.particles.iter_mut().find(|p| p.id == id) {
+            Some(particle) => {
+                particle.pinned = pinned;
+                particle.vel = Vector { x: 0.0, y: 0.0 };
+                true
+            }
+            None => false,
+        }
+    }
+
+    // take a particle out by id. everything else holds on to particles by id too,
+    // so nothing is left pointing at the wrong one
+    pub fn remove(&mut self, id: u64) -> Option<Particle> {
+        let index = self.particles.iter().position(|p| p.id == id)?;
+        if self.hold.as_ref().is_some_and(|hold| hold.id == id) {
+            self.hold = None;
+        }
+        self.wake_supported(index);
+        self.broad_phase.remove(index);
+        // keeps the order of the rest, so panes that were the same stay the same
+        Some(self.particles.remove(index))
+    }
+
+    // whatever the particle at `index` was holding up has to find its feet again: what it's
+    // touching, and from those on up the pile anything asleep resting on them, against the
+    // gravity they went to sleep under. the broad phase finds them, so the rest of the box
+    // sleeps on. anything spawned since it was built isn't in it, and starts out awake anyway
+    fn wake_supported(&mut self, index: usize) {
+        let max_rad = self.particles.iter().map(|p| p.rad).fold(0.0, f32::max);
+        let gravity = self.sleep_gravity;
+        let mut supports = vec![index];
+        let mut near = Vec::new();
+        while let Some(support) = supports.pop() {
+            let (pos, rad) = (self.particles[support].pos, self.particles[support].rad);
+            near.clear();
+            self.broad_phase
+                .for_each_near(pos, rad + max_rad + 0.5, |i| near.push(i));
+            for &i in &near {
+                let particle = match self.particles.get_mut(i) {
+                    Some(particle) if i != index => particle,
+                    _ => continue,
+                };
+                let (dx, dy) = (particle.pos.x - pos.x, particle.pos.y - pos.y);
+                let touching = dx * dx + dy * dy <= (rad + particle.rad + 0.5).powi(2);
+                let above = dx * gravity.x + dy * gravity.y < 0.0;
+                if !touching || (support != index && !above) {
+                    continue;
+                }
+                if particle.asleep {
+                    supports.push(i);
+                }
+                particle.wake();
+            }
+        }
+    }
+
+    // take out every divider wall, so whatever they kept apart can mix. false if there
+    // weren't any
+    pub fn open_dividers(&mut self) -> bool {
+        let before = self.segments.len();
+        self.segments.retain(|segment| !segment.divider);
+        self.segments.len() < before
+    }
+
+    // put back particles taken out with `remove`, ids and all
+    pub fn restore(&mut self, particles: Vec<Particle>) {
+        self.particles.extend(particles);
+    }
+
+    // add `particle` where it is, or as close to it as there's room for.
+    // false if it's full, or nowhere nearby is free
+    pub fn spawn_at(&mut self, mut particle: Particle, config: &SimulationConfig) -> bool {
+        if self.particles.len() >= config.max_particles {
+            return false;
+        }
+
+        match self.free_spot(particle.pos, particle.rad) {
+            Some(pos) => {
+                particle.pos = pos;
+                particle.prev_pos = pos;
+                self.add_particle(particle);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // the nearest place to `pos` a particle of radius `rad` fits without touching anything,
+    // trying rings of points further and further out
+    fn free_spot(&self, pos: Point, rad: f32) -> Option<Point> {
+        if self.right() < 2.0 * rad || self.height < 2.0 * rad {
+            return None;
+        }
+
+        let fits = |p: Point| self.fits(p, rad);
+        let inside = |p: Point| Point {
+            x: p.x.clamp(rad, self.right() - rad),
+            y: p.y.clamp(rad, self.height - rad),
+        };
+
+        let start = inside(pos);
+        if fits(start) {
+            return Some(start);
+        }
+        for ring in 1..=SPAWN_SEARCH_RINGS {
+            let distance = ring as f32 * rad;
+            let points = 8 * ring;
+            for i in 0..points {
+                let angle = std::f32::consts::PI * 2.0 * i as f32 / points as f32;
+                let candidate = inside(Point {
+                    x: start.x + distance * angle.cos(),
+                    y: start.y + distance * angle.sin(),
+                });
+                if fits(candidate) {
+                    return Some(candidate);
+                }
+            }
+        }
+        None
+    }
+
+    // whether a particle of radius `rad` at `p` would be inside any container and clear of
+    // every particle, pillar and block
+    fn fits(&self, p: Point, rad: f32) -> bool {
+        let clear_of = |other: Point, other_rad: f32| {
+            let dx = p.x - other.x;
+            let dy = p.y - other.y;
+            (dx * dx + dy * dy).sqrt() > rad + other_rad
+        };
+        self.inside_container(p, rad)
+            && self
+                .particles
+                .iter()
+                .all(|other| clear_of(other.pos, other.rad))
+            && self
+                .pillars
+                .iter()
+                .all(|pillar| clear_of(pillar.pos, pillar.rad))
+            && self.blocks.iter().all(|block| {
+                let closest = block.closest_point(p);
+                !block.contains(p) && clear_of(closest, 0.0)
+            })
+    }
+
+    // the box changed size: move the walls and pull anything now outside back in
+    pub fn set_bounds(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+        self.broad_phase = SpatialGrid::new(width, height);
+
+        for particle in &mut self.particles {
+            particle.clamp_inside(width, height);
+        }
+    }
+
+    // advance by one fixed step of `time_elapsed` seconds
+    pub fn step(&mut self, time_elapsed: f32, config: &SimulationConfig, theme: &Theme) {
+        for particle in &mut self.particles {
+            particle.prev_pos = particle.pos;
+        }
+        self.wake_sleepers(config);
+        let first_collision = self.events.collisions.len();
+        let first_wall = self.events.walls.len();
+        self.handle_collisions(config, theme);
+        self.handle_pillars(config);
+        self.handle_blocks(config);
+        self.apply_forces(time_elapsed, config);
+        self.piston.update(time_elapsed, config, self.width);
+        if config.belt {
+            self.belt_offset = belt::scroll(self.belt_offset, time_elapsed, config);
+        }
+        demon::operate(self, time_elapsed, config);
+        self.handle_movement(time_elapsed, config);
+        self.handle_segments(time_elapsed, config);
+        self.handle_breakage(config, theme);
+        self.handle_portals();
+        self.handle_voids();
+        self.handle_black_holes(config);
+        self.handle_escapes(config);
+        self.handle_weather(config);
+        self.handle_emitters(time_elapsed, config, theme);
+        epidemic::spread(
+            &mut self.particles,
+            &self.events.collisions[first_collision..],
+            time_elapsed,
+            config,
+            self.step_count,
+        );
+        predators::live(self, first_collision, time_elapsed, config);
+        reactions::react(self, first_collision, config);
+        fission::split(self, first_collision, first_wall, config);
+        // with sleeping off they're still timed, for the targets to tell what's come to rest
+        for particle in &mut self.particles {
+            particle.settle(time_elapsed, config);
+        }
+        targets::score(self, config);
+        self.step_count += 1;
+    }
+
+    // everything wakes when sleeping's turned off or the gravity changes, since what held a
+    // settled pile up might not hold it any more
+    fn wake_sleepers(&mut self, config: &SimulationConfig) {
+        let gravity = config.active_gravity();
+        if config.sleeping && gravity == self.sleep_gravity {
+            return;
+        }
+        // turning sleeping off leaves the rest timed as they were
+        let changed = gravity != self.sleep_gravity;
+        self.sleep_gravity = gravity;
+        for particle in self.particles.iter_mut().filter(|p| p.asleep || changed) {
+            particle.wake();
+        }
+    }
+
+    // a kick straight away from `center` for everything within blast_radius, bigger the
+    // closer it is (down to BLAST_MIN_DISTANCE) and the lighter it is. anything right on the
+    // center goes off in a random direction
+    pub fn explode(&mut self, center: Point, config: &SimulationConfig, rng: &mut impl Rng) {
+        for particle in &mut self.particles {
+            let dx = particle.pos.x - center.x;
+            let dy = particle.pos.y - center.y;
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance > config.blast_radius {
+                continue;
+            }
+
+            let (ux, uy) = if distance > f32::EPSILON {
+                (dx / distance, dy / distance)
+            } else {
+                let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                (angle.cos(), angle.sin())
+            };
+            let kick = config.blast_strength
+                / distance.max(BLAST_MIN_DISTANCE)
+                / particle.mass.max(f32::EPSILON);
+            particle.vel.x += ux * kick;
+            particle.vel.y += uy * kick;
+        }
+    }
+
+    // the forces besides gravity and drag. they change the velocity before the move,
+    // which keeps stiff ones stable
+    fn apply_forces(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        self.apply_hold(time_elapsed);
+        if let Some(center) = self.repulsor {
+            self.apply_repulsion(center, time_elapsed, config);
+        }
+        if let Some(center) = self.attractor {
+            self.apply_attraction(center, time_elapsed, config);
+        }
+        self.apply_wells(time_elapsed, config);
+        self.apply_black_holes(time_elapsed);
+        self.apply_zones(time_elapsed, config);
+        if config.boids {
+            self.apply_boids(time_elapsed, config);
+        }
+        if config.lennard_jones {
+            self.apply_lennard_jones(time_elapsed, config);
+        }
+    }
+
+    // every pair within lj_range of touching pulled together, see crystal::attraction, the
+    // same force on both so the lighter one moves more. the pairs come from the broad phase
+    // handle_collisions just built, reaching further
+    fn apply_lennard_jones(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        let max_rad = self.particles.iter().map(|p| p.rad).fold(0.0, f32::max);
+        let mut pairs = Vec::new();
+        self.broad_phase
+            .for_each_pair(&self.particles, config.lj_range * 2.0 * max_rad, |i, j| {
+                pairs.push((i, j))
+            });
+        for (i, j) in pairs {
+            let (a, b) = (&self.particles[i], &self.particles[j]);
+            let offset = self.displacement(a.pos, b.pos, config.boundary);
+            let force = crystal::attraction(offset, a.rad + b.rad, config);
+            if force.x == 0.0 && force.y == 0.0 {
+                continue;
+            }
+            for (k, sign) in [(i, 1.0), (j, -1.0)] {
+                let particle = &mut self.particles[k];
+                if !particle.pinned {
+                    let scale = sign * time_elapsed / particle.mass.max(f32::EPSILON);
+                    particle.vel.x += force.x * scale;
+                    particle.vel.y += force.y * scale;
+                }
+            }
+        }
+    }
+
+    // every particle steered by the others within boid_radius, see boids::steering. the
+    // neighbours come from the broad phase handle_collisions just built, reaching further,
+    // and are all found before anything's steered so the order doesn't matter
+    fn apply_boids(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        let mut neighbours = Vec::new();
+        let mut accels = Vec::with_capacity(self.particles.len());
+        for (i, particle) in self.particles.iter().enumerate() {
+            neighbours.clear();
+            self.broad_phase
+                .for_each_near(particle.pos, config.boid_radius, |j| {
+                    if j == i {
+                        return;
+                    }
+                    let offset =
+                        self.displacement(particle.pos, self.particles[j].pos, config.boundary);
+                    if offset.x * offset.x + offset.y * offset.y
+                        < config.boid_radius * config.boid_radius
+                    {
+                        neighbours.push((offset, self.particles[j].vel));
+                    }
+                });
+            accels.push(boids::steering(particle.vel, &neighbours, config));
+        }
+        for (particle, accel) in self.particles.iter_mut().zip(accels) {
+            if !particle.pinned {
+                particle.vel.x += accel.x * time_elapsed;
+                particle.vel.y += accel.y * time_elapsed;
+            }
+        }
+    }
+
+    // every zone a particle's in changes its velocity, they add up
+    fn apply_zones(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        for zone in &self.zones {
+            for particle in &mut self.particles {
+                let dv = zone.dv(particle.pos, particle.vel, time_elapsed, config);
+                particle.vel.x += dv.x;
+                particle.vel.y += dv.y;
+            }
+        }
+    }
+
+    // every well pulls on every particle, they add up
+    fn apply_wells(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        if !config.wells_enabled {
+            return;
+        }
+        for well in &self.wells {
+            for particle in &mut self.particles {
+                let accel = well.accel(particle.pos, config);
+                particle.vel.x += accel.x * time_elapsed;
+                particle.vel.y += accel.y * time_elapsed;
+            }
+        }
+    }
+
+    // every black hole pulls on every particle too, whether the wells are on or not
+    fn apply_black_holes(&mut self, time_elapsed: f32) {
+        for hole in &self.black_holes {
+            for particle in &mut self.particles {
+                let accel = hole.accel(particle.pos);
+                particle.vel.x += accel.x * time_elapsed;
+                particle.vel.y += accel.y * time_elapsed;
+            }
+        }
+    }
+
+    // pulled towards `center`, harder the closer they get but only down to
+    // ATTRACTION_SOFTENING, so anything with some sideways speed swings round instead of
+    // collapsing onto the cursor. a force, so heavier particles come more slowly
+    fn apply_attraction(&mut self, center: Point, time_elapsed: f32, config: &SimulationConfig) {
+        for particle in &mut self.particles {
+            let dx = center.x - particle.pos.x;
+            let dy = center.y - particle.pos.y;
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance >= config.attraction_radius || distance <= f32::EPSILON {
+                continue;
+            }
+
+            let force = config.attraction_strength * ATTRACTION_REFERENCE
+                / distance.max(ATTRACTION_SOFTENING);
+            let accel = force / particle.mass.max(f32::EPSILON);
+            particle.vel.x += dx / distance * accel * time_elapsed;
+            particle.vel.y += dy / distance * accel * time_elapsed;
+        }
+    }
+
+    // pushed straight out from `center`, hardest in the middle and fading to nothing at
+    // repulsion_radius. it's an acceleration rather than a force so everything herds alike,
+    // and topping out in the middle means nothing right under the cursor gets flung
+    fn apply_repulsion(&mut self, center: Point, time_elapsed: f32, config: &SimulationConfig) {
+        let radius = config.repulsion_radius.max(1.0);
+        for particle in &mut self.particles {
+            let dx = particle.pos.x - center.x;
+            let dy = particle.pos.y - center.y;
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance >= radius {
+                continue;
+            }
+
+            let accel = config.repulsion_strength * (1.0 - distance / radius);
+            let (ux, uy) = if distance > f32::EPSILON {
+                (dx / distance, dy / distance)
+            } else {
+                (0.0, -1.0)
+            };
+            particle.vel.x += ux * accel * time_elapsed;
+            particle.vel.y += uy * accel * time_elapsed;
+        }
+    }
+
+    // a damped spring towards the cursor rather than moving it there, so whatever it's dragged
+    // through gets knocked out of the way and letting go leaves it with the spring's velocity
+    fn apply_hold(&mut self, time_elapsed: f32) {
+        let hold = match &self.hold {
+            Some(hold) => hold,
+            None => return,
+        };
+        let particle = match self.particles.iter_mut().find(|p| p.id == hold.id) {
+            Some(particle) => particle,
+            None => return,
+        };
+
+        let stiffness = HOLD_FREQUENCY * HOLD_FREQUENCY;
+        let damping = 2.0 * HOLD_DAMPING * HOLD_FREQUENCY;
+        let accel = Vector {
+            x: stiffness * (hold.target.x - particle.pos.x) - damping * particle.vel.x,
+            y: stiffness * (hold.target.y - particle.pos.y) - damping * particle.vel.y,
+        };
+        particle.vel.x += accel.x * time_elapsed;
+        particle.vel.y += accel.y * time_elapsed;
+    }
+
+    fn handle_collisions(&mut self, config: &SimulationConfig, theme: &Theme) {
+        let restitution = config.restitution;
+
+        // broad phase: only particles in neighbouring cells can be touching
+        let max_rad = self.particles.iter().map(|p| p.rad).fold(0.0, f32::max);
+        let reach = 2.0 * max_rad + 0.5;
+        let wrap = (config.boundary.wraps_x(), config.boundary.wraps_y());
+        self.broad_phase.rebuild(&self.particles, reach, wrap);
+
+        let mut pairs = Vec::new();
+        self.broad_phase
+            .for_each_pair(&self.particles, reach, |i, j| pairs.push((i, j)));
+        // resolve in index order, same as checking every pair
+        pairs.sort_unstable();
+
+        // collisions, then more passes over the same pairs with no bounce, each taking out
+        // what's left of any closing speed, so a stack settles in one step rather than
+        // passing its weight down a layer a step
+        for pass in 0..config.solver_iterations.max(1) {
+            let restitution = if pass == 0 { restitution } else { 0.0 };
+            for &(i, j) in &pairs {
+                self.resolve_pair(i, j, restitution, pass == 0, config, theme);
+            }
+        }
+    }
+
+    // bounce and push apart particles `i` and `j` if they're touching, with an event and a
+    // color change for the step's `first` pass over them
+    fn resolve_pair(
+        &mut self,
+        i: usize,
+        j: usize,
+        restitution: f32,
+        first: bool,
+        config: &SimulationConfig,
+        theme: &Theme,
+    ) {
+        // neutrons go straight through each other, see fission::split
+        let neutron = |p: &Particle| p.nuclide == Some(Nuclide::Neutron);
+        if neutron(&self.particles[i]) && neutron(&self.particles[j]) {
+            return;
+        }
+        // anything asleep is woken by something running into it faster than sleep_speed, and
+        // until then is held still like a pinned one
+        if self.particles[i].asleep != self.particles[j].asleep {
+            let (sleeper, waker) = if self.particles[i].asleep {
+                (i, j)
+            } else {
+                (j, i)
+            };
+            let offset = self.displacement(
+                self.particles[sleeper].pos,
+                self.particles[waker].pos,
+                config.boundary,
+            );
+            let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+            let reach = self.particles[sleeper].rad + self.particles[waker].rad + 0.5;
+            let vel = self.particles[waker].vel;
+            let closing = -(vel.x * offset.x + vel.y * offset.y) / distance.max(f32::EPSILON);
+            if distance <= reach && closing > config.sleep_speed {
+                self.particles[sleeper].wake();
+            }
+        }
+        let fixed = |p: &Particle| p.pinned || p.asleep;
+
+        // a pinned particle is a pillar for as long as it's pinned, two of them just overlap
+        match (fixed(&self.particles[i]), fixed(&self.particles[j])) {
+            (false, false) => {}
+            (true, true) => return,
+            (pinned_i, _) => {
+                let (pinned, free) = if pinned_i { (i, j) } else { (j, i) };
+                let free_pos = self.particles[free].pos;
+                let offset =
+                    self.displacement(self.particles[pinned].pos, free_pos, config.boundary);
+                // on whichever side of a seam the free one sees it
+                let pillar = Pillar {
+                    pos: Point {
+                        x: free_pos.x - offset.x,
+                        y: free_pos.y - offset.y,
+                    },
+                    rad: self.particles[pinned].rad,
+                };
+                // still a particle collision, so particle_friction rather than the walls'
+                let free = &mut self.particles[free];
+                let event = pillar.collide(free, restitution, config.particle_friction);
+                if first {
+                    self.events.walls.extend(event);
+                }
+                return;
+            }
+        }
+
+        // j as seen from i, which across a seam isn't the plain difference
+        let normal = self.displacement(
+            self.particles[i].pos,
+            self.particles[j].pos,
+            config.boundary,
+        );
+        let distance = (normal.x * normal.x + normal.y * normal.y).sqrt();
+        if distance - (self.particles[i].rad + self.particles[j].rad) <= 0.5 {
+            if first && config.log_collisions {
+                println!("collision at distance: {}", distance);
+            }
+
+            let unit = if distance > 0.0 {
+                Vector {
+                    x: normal.x / distance,
+                    y: normal.y / distance,
+                }
+            } else {
+                Vector { x: 1.0, y: 0.0 }
+            };
+            let inv_i = 1.0 / self.particles[i].mass.max(f32::EPSILON);
+            let inv_j = 1.0 / self.particles[j].mass.max(f32::EPSILON);
+
+            // pushed some of the way apart, the lighter one further, so a crowd doesn't sink
+            // into itself. only some, or a pile squeezes its edges out through the walls
+            let depth = self.particles[i].rad + self.particles[j].rad - distance;
+            if depth > 0.0 {
+                let share = SEPARATION * depth / (inv_i + inv_j);
+                self.particles[i].pos.x -= unit.x * share * inv_i;
+                self.particles[i].pos.y -= unit.y * share * inv_i;
+                self.particles[j].pos.x += unit.x * share * inv_j;
+                self.particles[j].pos.y += unit.y * share * inv_j;
+            }
+
+            // only what's along the line between the centers changes, and only if they're
+            // closing on each other: anything already moving apart is left to go
+            let (vi, vj) = (self.particles[i].vel, self.particles[j].vel);
+            let closing = (vj.x - vi.x) * unit.x + (vj.y - vi.y) * unit.y;
+            if closing >= 0.0 {
+                return;
+            }
+            // the impulse that takes `restitution` of the closing speed and turns it round,
+            // which keeps the momentum whatever the masses
+            let impulse = -(1.0 + restitution) * closing / (inv_i + inv_j);
+            self.particles[i].vel.x -= unit.x * impulse * inv_i;
+            self.particles[i].vel.y -= unit.y * impulse * inv_i;
+            self.particles[j].vel.x += unit.x * impulse * inv_j;
+            self.particles[j].vel.y += unit.y * impulse * inv_j;
+            // rubbing slows how fast they slide past each other by up to particle_friction
+            // times the impulse, like off a wall, but never turns it round
+            if config.particle_friction > 0.0 {
+                let along = Vector {
+                    x: -unit.y,
+                    y: unit.x,
+                };
+                let sliding = (vj.x - vi.x) * along.x + (vj.y - vi.y) * along.y;
+                let rub = (config.particle_friction * impulse).min(sliding.abs() / (inv_i + inv_j))
+                    * sliding.signum();
+                self.particles[i].vel.x += along.x * rub * inv_i;
+                self.particles[i].vel.y += along.y * rub * inv_i;
+                self.particles[j].vel.x -= along.x * rub * inv_j;
+                self.particles[j].vel.y -= along.y * rub * inv_j;
+            }
+            if !first {
+                return;
+            }
+            self.events.collisions.push(CollisionEvent {
+                point: Point {
+                    x: self.particles[i].pos.x + unit.x * self.particles[i].rad,
+                    y: self.particles[i].pos.y + unit.y * self.particles[i].rad,
+                },
+                normal: unit,
+                impulse,
+                a: self.particles[i].id,
+                b: self.particles[j].id,
+                combined_mass: self.particles[i].mass + self.particles[j].mass,
+                restitution,
+                masses: [self.particles[i].mass, self.particles[j].mass],
+                before: [vi, vj],
+                after: [self.particles[i].vel, self.particles[j].vel],
+            });
+
+            // the epidemic, predator-prey, two-gas, reaction and chain reaction colors say what
+            // a particle is
+            let marked = |p: &Particle| {
+                p.health.is_some()
+                    || p.species.is_some()
+                    || p.gas.is_some()
+                    || p.reagent.is_some()
+                    || p.nuclide.is_some()
+            };
+            match config.color_policy {
+                _ if marked(&self.particles[i]) || marked(&self.particles[j]) => {}
+                ColorPolicy::Original => {}
+                ColorPolicy::Recolor => {
+                    let new_color = theme.nearest(color::collision_color(normal));
+                    self.particles[i].color = new_color;
+                    self.particles[j].color = new_color;
+                }
+                ColorPolicy::Blend => {
+                    let (a, b) = (self.particles[i].color, self.particles[j].color);
+                    self.particles[i].color = color::blend(a, b);
+                    self.particles[j].color = color::blend(b, a);
+                }
+            }
+        }
+    }
+
+    // found first and taken out after, like the escapes, so nothing's removed from under the
+    // loop. the broad phase is rebuilt before it's next used
+    fn handle_voids(&mut self) {
+        if self.voids.is_empty() {
+            return;
+        }
+        let mut consumed = Vec::new();
+        for particle in &self.particles {
+            if let Some(void) = self.voids.iter_mut().find(|v| v.contains(particle.pos)) {
+                void.consumed += 1;
+                consumed.push(particle.id);
+            }
+        }
+        for id in consumed {
+            if let Some(particle) = self.remove(id) {
+                self.events.consumed.push(ConsumedEvent {
+                    pos: particle.pos,
+                    rad: particle.rad,
+                    color: particle.color,
+                });
+            }
+        }
+    }
+
+    // found and taken out after like the voids, each by the first hole whose horizon it's
+    // inside, which grows with each one it takes. a hole only grows between steps, so one
+    // swallowing a lot at once can't reach out and take more the same step
+    fn handle_black_holes(&mut self, config: &SimulationConfig) {
+        if self.black_holes.is_empty() {
+            return;
+        }
+        let mut consumed = Vec::new();
+        for particle in &self.particles {
+            if let Some(i) = self.black_holes.iter().position(|h| h.inside(particle.pos)) {
+                consumed.push((particle.id, i));
+            }
+        }
+        for (id, i) in consumed {
+            if let Some(particle) = self.remove(id) {
+                self.black_holes[i].swallow(particle.color, config.black_hole_growth);
+                self.events.consumed.push(ConsumedEvent {
+                    pos: particle.pos,
+                    rad: particle.rad,
+                    color: particle.color,
+                });
+            }
+        }
+    }
+
+    // with an open top, whatever has flown far enough out of it respawns or goes
+    fn handle_escapes(&mut self, config: &SimulationConfig) {
+        if config.boundary != Boundary::OpenTop {
+            return;
+        }
+        let escaped: Vec<u64> = self
+            .particles
+            .iter()
+            .filter(|p| p.pos.y + p.rad < -config.escape_margin)
+            .map(|p| p.id)
+            .collect();
+        for id in escaped {
+            match config.escaped {
+                Escaped::Respawn => {
+                    self.respawn_at_top(id);
+                }
+                Escaped::Remove => {
+                    self.remove(id);
+                }
+            }
+        }
+    }
+
+    // rain goes as soon as it touches the floor, snow once it's settled there, and either
+    // comes back in at the top. a drop with nowhere to go yet lies there and splashes when
+    // it does
+    fn handle_weather(&mut self, config: &SimulationConfig) {
+        let rain = match config.weather {
+            Weather::Off => return,
+            Weather::Rain => true,
+            Weather::Snow => false,
+        };
+        let floor = self.height - FLOOR_CONTACT;
+        let landed: Vec<u64> = self
+            .particles
+            .iter()
+            .filter(|p| {
+                p.pos.y + p.rad >= floor
+                    && (rain || p.asleep || p.vel_magnitude() < config.sleep_speed)
+            })
+            .map(|p| p.id)
+            .collect();
+        for id in landed {
+            let (pos, color) = match self.particles.iter().find(|p| p.id == id) {
+                Some(p) => (p.pos, p.color),
+                None => continue,
+            };
+            if self.respawn_at_top(id) && rain {
+                self.events.splashes.push(SplashEvent {
+                    pos: Point {
+                        x: pos.x,
+                        y: self.height,
+                    },
+                    color,
+                });
+            }
+        }
+    }
+
+    // just inside the top at a random free spot, falling gently. the rng comes from the step
+    // and the id so every pane picks the same spot. with no room it stays out and tries again
+    // next step, and it's false
+    fn respawn_at_top(&mut self, id: u64) -> bool {
+        let index = match self.particles.iter().position(|p| p.id == id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let rad = self.particles[index].rad;
+        let mut rng = StdRng::seed_from_u64(self.step_count.wrapping_mul(u64::from(u32::MAX)) ^ id);
+        let spot = (0..SPAWN_TRIES)
+            .map(|_| Point {
+                x: rng.gen_range(rad..(self.right() - rad).max(rad + 1.0)),
+                y: rad,
+            })
+            .find(|&pos| self.fits(pos, rad));
+        if let Some(pos) = spot {
+            let particle = &mut self.particles[index];
+            particle.pos = pos;
+            particle.prev_pos = pos;
+            particle.vel = Vector {
+                x: 0.0,
+                y: rng.gen_range(RESPAWN_SPEED_MIN..RESPAWN_SPEED_MAX),
+            };
+            particle.wake();
+            true
+        } else {
+            false
+        }
+    }
+
+    // everything each emitter has owed since the last step, while there's room for it. one
+    // that's blocked, or would go over max_particles, skips this step. the rng comes from the
+    // step and the emitter so every pane sprays the same
+    fn handle_emitters(&mut self, time_elapsed: f32, config: &SimulationConfig, theme: &Theme) {
+        for i in 0..self.emitters.len() {
+            let mut rng =
+                StdRng::seed_from_u64(self.step_count.wrapping_mul(u64::from(u32::MAX)) ^ i as u64);
+            while self.emitters[i].due(time_elapsed) {
+                let emitter = self.emitters[i];
+                let rad = emitter.radius(&mut rng);
+                let (pos, vel) = emitter.launch(rad, &mut rng);
+                let inside = (rad..=self.right() - rad).contains(&pos.x)
+                    && (rad..=self.height - rad).contains(&pos.y);
+                let room =
+                    self.particles.len() < config.max_particles && inside && self.fits(pos, rad);
+                self.emitters[i].spent(room);
+                if !room {
+                    break;
+                }
+                let color = *theme
+                    .palette
+                    .choose(&mut rng)
+                    .expect("Some colors in the palette");
+                let mass = config.mass_for_radius(rad);
+                self.add_particle(Particle::new(pos, vel, rad, mass, color));
+            }
+        }
+    }
+
+    // from `from` to `to`, the short way round when the edges wrap
+    fn displacement(&self, from: Point, to: Point, boundary: Boundary) -> Vector {
+        let mut d = Vector {
+            x: to.x - from.x,
+            y: to.y - from.y,
+        };
+        if boundary.wraps_x() && self.width > 0.0 {
+            d.x -= self.width * (d.x / self.width).round();
+        }
+        if boundary.wraps_y() && self.height > 0.0 {
+            d.y -= self.height * (d.y / self.height).round();
+        }
+        d
+    }
+
+    // against the broad phase handle_collisions just built, nothing has moved since
+    fn handle_pillars(&mut self, config: &SimulationConfig) {
+        let max_rad = self.particles.iter().map(|p| p.rad).fold(0.0, f32::max);
+        for pillar in &self.pillars {
+            let particles = &mut self.particles;
+            let events = &mut self.events;
+            self.broad_phase
+                .for_each_near(pillar.pos, pillar.rad + max_rad + 0.5, |i| {
+                    if particles[i].pinned {
+                        return;
+                    }
+                    let (restitution, friction) = (config.restitution, config.wall_friction);
+                    if let Some(event) = pillar.collide(&mut particles[i], restitution, friction) {
+                        events.walls.push(event);
+                    }
+                });
+        }
+    }
+
+    // the same way, the broad phase finds what's near each block's center
+    fn handle_blocks(&mut self, config: &SimulationConfig) {
+        let max_rad = self.particles.iter().map(|p| p.rad).fold(0.0, f32::max);
+        for block in &self.blocks {
+            let particles = &mut self.particles;
+            let events = &mut self.events;
+            self.broad_phase
+                .for_each_near(block.center(), block.reach() + max_rad + 0.5, |i| {
+                    if particles[i].pinned {
+                        return;
+                    }
+                    let (restitution, friction) = (config.restitution, config.wall_friction);
+                    if let Some(event) = block.collide(&mut particles[i], restitution, friction) {
+                        events.walls.push(event);
+                    }
+                });
+        }
+    }
+
+    // after the move, so nothing is left inside a wall at the end of a step
+    fn handle_segments(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        let gravity = config.active_gravity();
+        let resting =
+            RESTING_STEPS * time_elapsed * (gravity.x * gravity.x + gravity.y * gravity.y).sqrt();
+        for particle in self.particles.iter_mut().filter(|p| !p.pinned) {
+            if let Some(container) = &self.container {
+                container.collide(
+                    particle,
+                    config.restitution,
+                    config.wall_friction,
+                    resting,
+                    &mut self.events.walls,
+                );
+            }
+            for segment in &mut self.segments {
+                let (restitution, friction) = (config.restitution, config.wall_friction);
+                if let Some(event) = segment.collide(particle, restitution, friction, resting) {
+                    // only breakable walls keep count
+                    if segment.strength.is_some() {
+                        segment.damage += event.impulse;
+                    }
+                    self.events.walls.push(event);
+                }
+            }
+            for gate in self.gates.iter().filter(|gate| !gate.open) {
+                let (restitution, friction) = (config.restitution, config.wall_friction);
+                if let Some(event) =
+                    gate.segment()
+                        .collide(particle, restitution, friction, resting)
+                {
+                    self.events.walls.push(event);
+                }
+            }
+        }
+    }
+
+    // walls that took all they could this step go, each leaving wall_debris bits of itself
+    // strewn along where it was
+    fn handle_breakage(&mut self, config: &SimulationConfig, theme: &Theme) {
+        if !self.segments.iter().any(Segment::broken) {
+            return;
+        }
+        let (broken, kept): (Vec<Segment>, Vec<Segment>) =
+            self.segments.iter().partition(|segment| segment.broken());
+        self.segments = kept;
+        for (i, broken) in broken.iter().enumerate() {
+            let mut rng =
+                StdRng::seed_from_u64(self.step_count.wrapping_mul(u64::from(u32::MAX)) ^ i as u64);
+            for piece in 0..config.wall_debris {
+                if self.particles.len() >= config.max_particles {
+                    return;
+                }
+                let t = (piece as f32 + 0.5) / config.wall_debris as f32;
+                let pos = Point {
+                    x: broken.a.x + (broken.b.x - broken.a.x) * t,
+                    y: broken.a.y + (broken.b.y - broken.a.y) * t,
+                };
+                // no smaller than a spawn, anything much lighter than what hits it gets flung
+                let rad = config.spawn_radius_min;
+                if !self.fits(pos, rad) {
+                    continue;
+                }
+                let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                let speed = rng.gen_range(0.0..DEBRIS_SPEED);
+                let vel = Vector {
+                    x: angle.cos() * speed,
+                    y: angle.sin() * speed,
+                };
+                let mass = config.mass_for_radius(rad);
+                self.add_particle(Particle::new(pos, vel, rad, mass, theme.obstacle));
+            }
+        }
+    }
+
+    // last of the moves, so a particle comes out where the next step starts it from. prev_pos
+    // goes with it, nothing drawn between steps should streak across to the other end
+    fn handle_portals(&mut self) {
+        if self.portals.is_empty() {
+            return;
+        }
+        for i in 0..self.particles.len() {
+            let particle = &self.particles[i];
+            if particle.pinned {
+                continue;
+            }
+            let exit = match self.portals.iter().find_map(|portal| portal.pass(particle)) {
+                Some(exit) => exit,
+                None => continue,
+            };
+            let rad = particle.rad;
+            let exit = exit.clear_of(rad, |p| {
+                self.particles.iter().enumerate().any(|(j, other)| {
+                    let (dx, dy) = (p.x - other.pos.x, p.y - other.pos.y);
+                    j != i && (dx * dx + dy * dy).sqrt() < rad + other.rad
+                })
+            });
+            let particle = &mut self.particles[i];
+            particle.pos = exit.pos;
+            particle.prev_pos = exit.pos;
+            particle.vel = exit.vel;
+        }
+    }
+
+    fn handle_movement(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        // with no right side there's no piston either
+        let (right, right_vel) = if config.boundary.wraps_x() {
+            (self.width, 0.0)
+        } else {
+            (self.right(), self.piston.vel)
+        };
+        let step = self.step_count;
+        for particle in &mut self.particles {
+            // whatever pushed on a pinned particle this step, it stays put
+            if particle.pinned {
+                particle.vel = Vector { x: 0.0, y: 0.0 };
+                continue;
+            }
+            // and an asleep one unless it was pushed hard enough to wake it
+            if particle.asleep {
+                if particle.vel_magnitude() <= config.sleep_speed {
+                    particle.vel = Vector { x: 0.0, y: 0.0 };
+                    continue;
+                }
+                particle.wake();
+            }
+            let (pos, vel) = (particle.pos, particle.vel);
+            let bounced = particle.update(time_elapsed, config, right, right_vel, self.height);
+            // a piston coming in pushes anything it's passed back in front of it
+            if config.piston != PistonMode::Off
+                && !config.boundary.wraps_x()
+                && particle.pos.x + particle.rad > right
+            {
+                particle.pos.x = right - particle.rad;
+            }
+            let normal = match bounced {
+                Some(normal) => normal,
+                None => continue,
+            };
+            particle.thermalize(normal, right_vel, config, step);
+            // a soft wall only touches it this step. it'll have turned it round by the time
+            // it's out again, so that's what it counts for
+            let impulse = |before: f32, after: f32| {
+                if config.wall_style == WallStyle::Soft {
+                    particle.mass * 2.0 * before.abs()
+                } else {
+                    particle.mass * (after - before).abs()
+                }
+            };
+
+            // one event per wall, a corner hit is two bounces
+            if normal.x != 0.0 {
+                self.events.walls.push(WallEvent {
+                    id: particle.id,
+                    point: Point {
+                        x: if normal.x > 0.0 { 0.0 } else { right },
+                        y: pos.y,
+                    },
+                    normal: Vector {
+                        x: normal.x,
+                        y: 0.0,
+                    },
+                    impulse: impulse(vel.x, particle.vel.x),
+                    enclosing: true,
+                    mass: particle.mass,
+                    rad: particle.rad,
+                    color: particle.color,
+                });
+            }
+            if normal.y != 0.0 {
+                self.events.walls.push(WallEvent {
+                    id: particle.id,
+                    point: Point {
+                        x: pos.x,
+                        y: if normal.y > 0.0 { 0.0 } else { self.height },
+                    },
+                    normal: Vector {
+                        x: 0.0,
+                        y: normal.y,
+                    },
+                    impulse: impulse(vel.y, particle.vel.y),
+                    enclosing: true,
+                    mass: particle.mass,
+                    rad: particle.rad,
+                    color: particle.color,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Particle {
+    pub id: u64,
+    pub rad: f32,
+    pub pos: Point,
+    // where the last step started from, for drawing in between steps.
+    // anything that moves a particle outside of a step should set both
+    pub prev_pos: Point,
+    pub mass: f32,
+    pub vel: Vector,
+    pub color: Color,
+    // spawn color, for going back to after collisions have changed `color`
+    pub original_color: Color,
+    // held in place as if infinitely heavy, everything else bounces off it
+    pub pinned: bool,
+    // where it's at in an epidemic, see epidemic::spread. its color shows it, so collisions
+    // leave the color alone
+    pub health: Option<Health>,
+    // prey or predator, see predators::live. the color shows which, as with health
+    pub species: Option<Species>,
+    // which gas in a two-gas scene, see mixing. the color shows it, as with health
+    pub gas: Option<Gas>,
+    // A, B or C in a reaction scene, see reactions::react. the color shows which, as with
+    // health
+    pub reagent: Option<Reagent>,
+    // fuel or a neutron in a chain reaction, see fission::split. the color shows which, as
+    // with health
+    pub nuclide: Option<Nuclide>,
+    // with sleeping on, left where it is until something wakes it, after going slower than
+    // sleep_speed for `still_for` seconds, up to sleep_time. timed with sleeping off too
+    pub asleep: bool,
+    pub still_for: f32,
+}
+
+impl Particle {
+    pub fn new(pos: Point, vel: Vector, rad: f32, mass: f32, color: Color) -> Particle {
+        Particle {
+            id: 0,
+            pos,
+            prev_pos: pos,
+            vel,
+            rad,
+            mass,
+            color,
+            original_color: color,
+            pinned: false,
+            health: None,
+            species: None,
+            gas: None,
+            reagent: None,
+            nuclide: None,
+            asleep: false,
+            still_for: 0.0,
+        }
+    }
+
+    pub fn wake(&mut self) {
+        self.asleep = false;
+        self.still_for = 0.0;
+    }
+
+    // slow for sleep_time, whether or not sleeping's on to put it to sleep for it
+    pub fn at_rest(&self, config: &SimulationConfig) -> bool {
+        self.asleep || self.still_for >= config.sleep_time
+    }
+
+    // one more step of `time_elapsed`, asleep after sleep_time of them all slow. slow by how
+    // far it actually went, since in a pile the velocity is mostly the weight of what's on
+    // top, which the push apart takes back out every step
+    fn settle(&mut self, time_elapsed: f32, config: &SimulationConfig) {
+        if self.asleep || self.pinned {
+            return;
+        }
+        let (dx, dy) = (self.pos.x - self.prev_pos.x, self.pos.y - self.prev_pos.y);
+        if dx * dx + dy * dy > (config.sleep_speed * time_elapsed).powi(2) {
+            self.still_for = 0.0;
+            return;
+        }
+        self.still_for += time_elapsed;
+        if config.sleeping && self.still_for >= config.sleep_time {
+            self.asleep = true;
+            self.vel = Vector { x: 0.0, y: 0.0 };
+        }
+    }
+    pub fn update(
+        &mut self,
+        time_elapsed: f32,
+        config: &SimulationConfig,
+        width: f32,
+        // how fast the right side is moving outwards, if it's a piston
+        right_vel: f32,
+        height: f32,
+    ) -> Option<Vector> {
+        let restitution = config.restitution;
+        // normal of each wall bounced off, pointing back into the box. only counts if the
+        // particle was heading into the wall, one still overlapping on its way out already bounced
+        let mut normal = Vector { x: 0.0, y: 0.0 };
+        let soft = config.boundary != Boundary::Wrap && config.wall_style == WallStyle::Soft;
+
+        // bound checks, the walls only stop anything where there are walls. a hard wall puts
+        // back anything that's in it too, the push apart from a crowd included
+        if soft {
+            normal = self.spring_off_walls(time_elapsed, config, width, right_vel, height);
+        } else if config.boundary != Boundary::Wrap {
+            let friction = config.wall_friction;
+            let (vel_x, vel_y) = (self.vel.x, self.vel.y);
+
+            // left/right, unless it wraps round instead
+            if !config.boundary.wraps_x() {
+                if (self.pos.x - self.rad) < 0.0 {
+                    if self.vel.x < 0.0 {
+                        normal.x = 1.0;
+                    }
+                    self.vel.x = self.vel.x.abs() * restitution;
+                    self.vel.y = walls::rub(self.vel.y, 0.0, self.vel.x - vel_x, friction);
+                    self.pos.x = self.rad;
+                } else if (self.pos.x + self.rad) > width {
+                    // off the right side as it's moving, so a piston pushing in speeds things up
+                    let relative = self.vel.x - right_vel;
+                    if relative > 0.0 {
+                        normal.x = -1.0;
+                    }
+                    self.vel.x = right_vel - relative.abs() * restitution;
+                    self.vel.y = walls::rub(self.vel.y, 0.0, self.vel.x - vel_x, friction);
+                    self.pos.x = width - self.rad;
+                }
+            }
+
+            // top/bottom, an open top lets anything through
+            if (self.pos.y - self.rad) < 0.0 && config.boundary != Boundary::OpenTop {
+                if self.vel.y < 0.0 {
+                    normal.y = 1.0;
+                }
+                self.vel.y = self.vel.y.abs() * restitution;
+                self.vel.x = walls::rub(self.vel.x, 0.0, self.vel.y - vel_y, friction);
+                self.pos.y = self.rad;
+            } else if (self.pos.y + self.rad) > height {
+                if self.vel.y > 0.0 {
+                    normal.y = -1.0;
+                }
+                self.vel.y = self.vel.y.abs() * -restitution;
+                self.pos.y = height - self.rad;
+                let floor = if config.belt { config.belt_speed } else { 0.0 };
+                self.vel.x = walls::rub(self.vel.x, floor, self.vel.y - vel_y, friction);
+                if config.belt {
+                    self.vel.x += belt::kick(self.vel.x, config);
+                }
+            }
+        }
+
+        self.pos.x += self.vel.x * time_elapsed;
+        self.pos.y += self.vel.y * time_elapsed;
+        self.wrap_around(width, height, config.boundary);
+        if soft {
+            self.stop_at_margin(config, width, right_vel, height);
+        }
+
+        // resistance increases with vel squared
+        // and always opposes the motion through the air
+        let drag = config.active_drag();
+        let air_x = self.vel.x - config.wind;
+        let resistance_x = air_x * air_x.abs() * drag;
+        let resistance_y = self.vel.y * self.vel.y.abs() * drag;
+
+        let gravity = config.active_gravity();
+        self.vel.x += (gravity.x - resistance_x) * time_elapsed;
+        self.vel.y += (gravity.y - resistance_y) * time_elapsed;
+
+        if normal.x != 0.0 || normal.y != 0.0 {
+            Some(normal)
+        } else {
+            None
+        }
+    }
+    // off a wall with a thermostat it comes away at a speed the wall picks rather than its
+    // own, square to the wall at least. the rng comes from the step and the id, like a respawn.
+    // a soft wall has only just been touched, so it's sent in at that speed for the spring to
+    // turn round
+    fn thermalize(&mut self, normal: Vector, right_vel: f32, config: &SimulationConfig, step: u64) {
+        let thermostats = config.thermostats;
+        let (side, end) = (
+            thermostats.facing(normal.x, 0.0),
+            thermostats.facing(0.0, normal.y),
+        );
+        if side.is_none() && end.is_none() {
+            return;
+        }
+        let mut rng = StdRng::seed_from_u64(step.wrapping_mul(u64::from(u32::MAX)) ^ self.id);
+        let out = if config.wall_style == WallStyle::Soft {
+            -1.0
+        } else {
+            1.0
+        };
+        if let Some(temperature) = side {
+            let wall_vel = if normal.x < 0.0 { right_vel } else { 0.0 };
+            let speed = thermostat::rescale(
+                self.vel.x - wall_vel,
+                temperature,
+                self.mass,
+                config,
+                &mut rng,
+            );
+            self.vel.x = wall_vel + normal.x * out * speed;
+        }
+        if let Some(temperature) = end {
+            let speed = thermostat::rescale(self.vel.y, temperature, self.mass, config, &mut rng);
+            self.vel.y = normal.y * out * speed;
+        }
+    }
+
+    // the push back from every wall it's gone into, as an acceleration so it's the same for
+    // heavy and light particles. it tops out at wall_margin deep, so nothing gets flung out.
+    // the normal of each wall it's only just touched, for the wall events
+    fn spring_off_walls(
+        &mut self,
+        time_elapsed: f32,
+        config: &SimulationConfig,
+        width: f32,
+        right_vel: f32,
+        height: f32,
+    ) -> Vector {
+        let mut normal = Vector { x: 0.0, y: 0.0 };
+        let spring = |depth: f32, vel: f32, wall_vel: f32| -> (f32, bool) {
+            if depth <= 0.0 {
+                return (vel, false);
+            }
+            // towards the inside of the box, in the wall's frame
+            let relative = vel - wall_vel;
+            let push = config.wall_stiffness * depth.min(config.wall_margin);
+            let accel = push - config.wall_damping * relative;
+            // it wasn't in the wall a step ago
+            let touched = relative < 0.0 && depth + relative * time_elapsed <= 0.0;
+            (vel + accel * time_elapsed, touched)
+        };
+
+        let friction = config.wall_friction;
+        let (vel_x, vel_y) = (self.vel.x, self.vel.y);
+
+        if !config.boundary.wraps_x() {
+            let (vel, touched) = spring(self.rad - self.pos.x, self.vel.x, 0.0);
+            if touched {
+                normal.x = 1.0;
+            }
+            self.vel.x = vel;
+            // the far walls are mirrored, so the spring always works on speeds into the box
+            let (vel, touched) = spring(self.pos.x + self.rad - width, -self.vel.x, -right_vel);
+            if touched {
+                normal.x = -1.0;
+            }
+            self.vel.x = -vel;
+            self.vel.y = walls::rub(self.vel.y, 0.0, self.vel.x - vel_x, friction);
+        }
+
+        if config.boundary != Boundary::OpenTop {
+            let (vel, touched) = spring(self.rad - self.pos.y, self.vel.y, 0.0);
+            if touched {
+                normal.y = 1.0;
+            }
+            self.vel.y = vel;
+        }
+        let depth = self.pos.y + self.rad - height;
+        let (vel, touched) = spring(depth, -self.vel.y, 0.0);
+        if touched {
+            normal.y = -1.0;
+        }
+        self.vel.y = -vel;
+        let floor = if config.belt && depth > 0.0 {
+            config.belt_speed
+        } else {
+            0.0
+        };
+        self.vel.x = walls::rub(self.vel.x, floor, self.vel.y - vel_y, friction);
+        // the belt has hold of anything pressed into it
+        if config.belt && depth > 0.0 {
+            self.vel.x += belt::kick(self.vel.x, config);
+        }
+        normal
+    }
+
+    // a wall can only be gone into wall_margin deep, however fast something hits it. anything
+    // further in is put back, and stops going in
+    fn stop_at_margin(
+        &mut self,
+        config: &SimulationConfig,
+        width: f32,
+        right_vel: f32,
+        height: f32,
+    ) {
+        let margin = config.wall_margin.max(0.0);
+        let sides = !config.boundary.wraps_x();
+        if sides && self.pos.x - self.rad < -margin {
+            self.pos.x = self.rad - margin;
+            self.vel.x = self.vel.x.max(0.0);
+        } else if sides && self.pos.x + self.rad > width + margin {
+            self.pos.x = width + margin - self.rad;
+            self.vel.x = self.vel.x.min(right_vel);
+        }
+        if config.boundary != Boundary::OpenTop && self.pos.y - self.rad < -margin {
+            self.pos.y = self.rad - margin;
+            self.vel.y = self.vel.y.max(0.0);
+        } else if self.pos.y + self.rad > height + margin {
+            self.pos.y = height + margin - self.rad;
+            self.vel.y = self.vel.y.min(0.0);
+        }
+    }
+
+    // back in from the opposite edge once the center leaves the box, along whichever way it
+    // wraps. prev_pos moves with it so nothing drawn between steps streaks across the screen
+    fn wrap_around(&mut self, width: f32, height: f32, boundary: Boundary) {
+        let wrapped = Point {
+            x: if boundary.wraps_x() && width > 0.0 {
+                self.pos.x.rem_euclid(width)
+            } else {
+                self.pos.x
+            },
+            y: if boundary.wraps_y() && height > 0.0 {
+                self.pos.y.rem_euclid(height)
+            } else {
+                self.pos.y
+            },
+        };
+        self.prev_pos.x += wrapped.x - self.pos.x;
+        self.prev_pos.y += wrapped.y - self.pos.y;
+        self.pos = wrapped;
+    }
+    pub fn clamp_inside(&mut self, width: f32, height: f32) {
+        let clamp = |v: f32, rad: f32, max: f32| {
+            if max < 2.0 * rad {
+                max * 0.5
+            } else {
+                v.clamp(rad, max - rad)
+            }
+        };
+        self.pos.x = clamp(self.pos.x, self.rad, width);
+        self.pos.y = clamp(self.pos.y, self.rad, height);
+        self.prev_pos = self.pos;
+    }
+    pub fn distance(&self, other: &Particle) -> f32 {
+        let dx = self.pos.x - other.pos.x;
+        let dy = self.pos.y - other.pos.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+    pub fn vel_magnitude(&self) -> f32 {
+        (self.vel.x * self.vel.x + self.vel.y * self.vel.y).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene_file::SceneFile;
+    use crate::testing;
+    use crate::theme::THEMES;
+    use crate::thermostat::Thermostats;
+
+    #[test]
+    fn slides_down_a_shallow_ramp_without_hopping() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 300.0 },
+            restitution: 1.0,
+            drag: 0.0,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(1000.0, 1000.0);
+        let ramp = Segment::new(Point { x: 100.0, y: 400.0 }, Point { x: 900.0, y: 600.0 });
+        world.segments.push(ramp);
+
+        // resting on the ramp's upper side, a quarter of the way along
+        let rad = 10.0;
+        let length = ramp.length();
+        // the side facing up
+        let normal = Vector {
+            x: (ramp.b.y - ramp.a.y) / length,
+            y: -(ramp.b.x - ramp.a.x) / length,
+        };
+        let start = Point {
+            x: 300.0 + normal.x * rad,
+            y: 450.0 + normal.y * rad,
+        };
+        world.add_particle(Particle::new(
+            start,
+            Vector { x: 0.0, y: 0.0 },
+            rad,
+            1.0,
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        ));
+
+        for _ in 0..240 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            let p = &world.particles[0];
+            let contact = ramp.closest_point(p.pos);
+            let (dx, dy) = (p.pos.x - contact.x, p.pos.y - contact.y);
+            let gap = (dx * dx + dy * dy).sqrt() - rad;
+            assert!(gap.abs() < 0.1, "left the ramp by {}", gap);
+            assert!(world.events.walls.is_empty());
+        }
+        assert!(world.particles[0].pos.x > start.x + 50.0);
+    }
+
+    #[test]
+    fn belt_carries_resting_particles_along() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 300.0 },
+            restitution: 0.5,
+            drag: 0.0,
+            belt: true,
+            belt_speed: 120.0,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(2000.0, 500.0);
+        world.add_particle(Particle::new(
+            Point { x: 100.0, y: 490.0 },
+            Vector { x: 0.0, y: 0.0 },
+            10.0,
+            1.0,
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        ));
+
+        for _ in 0..480 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+        let p = &world.particles[0];
+        assert!((p.vel.x - 120.0).abs() < 5.0, "going {}", p.vel.x);
+        assert!(p.pos.x > 300.0);
+    }
+
+    #[test]
+    fn piston_pushing_in_speeds_particles_up() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 0.0 },
+            restitution: 1.0,
+            drag: 0.0,
+            piston: PistonMode::Sweep,
+            piston_min: 0.5,
+            piston_max: 1.0,
+            piston_speed: 120.0,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(1000.0, 500.0);
+        world.add_particle(Particle::new(
+            Point { x: 900.0, y: 250.0 },
+            Vector { x: 100.0, y: 0.0 },
+            10.0,
+            1.0,
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        ));
+
+        for _ in 0..120 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            let p = &world.particles[0];
+            assert!(p.pos.x + p.rad <= world.right() + 1e-3);
+        }
+        // reflected in the piston's frame, it comes away with twice the piston's speed added
+        let vel = world.particles[0].vel;
+        assert!((vel.x + 340.0).abs() < 1.0, "came away at {}", vel.x);
+    }
+
+    #[test]
+    fn emitters_keep_time_and_drain_into_a_void() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 0.0 },
+            drag: 0.0,
+            emitter_rate: 3.0,
+            emitter_spread: 0.0,
+            emitter_speed_min: 200.0,
+            emitter_speed_max: 200.0,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(1000.0, 1000.0);
+        // well under one a step, so it only keeps time if the fractions carry over. each one
+        // runs straight into the void before the next can catch up with it
+        world
+            .emitters
+            .push(Emitter::new(Point { x: 100.0, y: 500.0 }, 0.0, &config));
+        world.voids.push(Void {
+            shape: crate::voids::Shape::Rect {
+                min: Point { x: 900.0, y: 0.0 },
+                max: Point {
+                    x: 1000.0,
+                    y: 1000.0,
+                },
+            },
+            consumed: 0,
+        });
+
+        let mut counts = Vec::new();
+        for step in 1..=1200 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            if step % 120 == 0 {
+                counts.push(world.particles.len());
+            }
+        }
+        let emitted = world.emitters[0].emitted;
+        assert!((29..=30).contains(&emitted), "emitted {}", emitted);
+        assert_eq!(emitted, world.particles.len() + world.voids[0].consumed);
+        // once the first ones reach the void it takes them as fast as they come
+        let settled = &counts[5..];
+        let (low, high) = (settled.iter().min().unwrap(), settled.iter().max().unwrap());
+        assert!(high - low <= 1, "counts {:?}", counts);
+    }
+
+    #[test]
+    fn soft_walls_cushion_without_letting_anything_out() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 400.0 },
+            drag: 0.0,
+            wall_style: WallStyle::Soft,
+            wall_damping: 0.0,
+            ..SimulationConfig::default()
+        };
+        let (width, height) = (800.0, 600.0);
+        let mut world = World::new(width, height);
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+            let speed = rng.gen_range(100.0..800.0);
+            let pos = Point {
+                x: rng.gen_range(50.0..width - 50.0),
+                y: rng.gen_range(50.0..height - 50.0),
+            };
+            let vel = Vector {
+                x: speed * angle.cos(),
+                y: speed * angle.sin(),
+            };
+            world.add_particle(Particle::new(
+                pos,
+                vel,
+                8.0,
+                1.0,
+                Color::new(1.0, 1.0, 1.0, 1.0),
+            ));
+        }
+
+        let mut deepest: f32 = 0.0;
+        for _ in 0..1200 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            for p in &world.particles {
+                let out = (p.rad - p.pos.x)
+                    .max(p.pos.x + p.rad - width)
+                    .max(p.rad - p.pos.y)
+                    .max(p.pos.y + p.rad - height);
+                deepest = deepest.max(out);
+            }
+        }
+        assert!(deepest <= config.wall_margin + 1e-3, "went {} in", deepest);
+
+        // head on at a sane speed the spring has it turned round well short of the margin,
+        // and with no damping it leaves as fast as it came
+        let mut world = World::new(width, height);
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 0.0 },
+            ..config
+        };
+        world.add_particle(Particle::new(
+            Point { x: 400.0, y: 300.0 },
+            Vector { x: 500.0, y: 0.0 },
+            8.0,
+            1.0,
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        ));
+        let mut deepest: f32 = 0.0;
+        for _ in 0..240 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            let p = &world.particles[0];
+            deepest = deepest.max(p.pos.x + p.rad - width);
+        }
+        assert!(
+            deepest > 0.0 && deepest < 0.75 * config.wall_margin,
+            "went {} in",
+            deepest
+        );
+        let vel = world.particles[0].vel.x;
+        assert!((vel + 500.0).abs() < 25.0, "came away at {}", vel);
+    }
+
+    #[test]
+    fn wall_friction_brings_a_rolling_ball_to_a_stop() {
+        let roll = |wall_friction: f32| {
+            let config = SimulationConfig {
+                gravity: Vector { x: 0.0, y: 400.0 },
+                restitution: 0.5,
+                drag: 0.0,
+                wall_friction,
+                ..SimulationConfig::default()
+            };
+            let mut world = World::new(2000.0, 400.0);
+            world.add_particle(Particle::new(
+                Point { x: 100.0, y: 390.0 },
+                Vector { x: 300.0, y: 0.0 },
+                10.0,
+                1.0,
+                Color::new(1.0, 1.0, 1.0, 1.0),
+            ));
+            for _ in 0..360 {
+                world.step(1.0 / 120.0, &config, &THEMES[0]);
+            }
+            world.particles[0].vel.x
+        };
+        // off by default, so it skates along the floor as it always has
+        assert!((roll(0.0) - 300.0).abs() < 1e-3);
+        // and with it, stopped well inside the three seconds, without being sent back
+        assert_eq!(roll(0.3), 0.0);
+    }
+
+    #[test]
+    fn thermostat_walls_settle_a_gas_at_their_temperature() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 0.0 },
+            restitution: 1.0,
+            drag: 0.0,
+            thermostats: Thermostats {
+                left: Some(200.0),
+                right: Some(200.0),
+                top: Some(200.0),
+                bottom: Some(200.0),
+            },
+            thermostat_mix: 1.0,
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let energy = |world: &World| {
+            let total: f32 = world
+                .particles
+                .iter()
+                .map(|p| 0.5 * p.mass * p.vel_magnitude().powi(2))
+                .sum();
+            total / world.particles.len() as f32
+        };
+        // all one mass, so the collisions just pass velocities on
+        let settle = |speed: f32| {
+            let mut world = World::new(600.0, 600.0);
+            let mut rng = StdRng::seed_from_u64(3);
+            for i in 0..64 {
+                let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+                world.add_particle(Particle::new(
+                    Point {
+                        x: 37.5 + 75.0 * (i % 8) as f32,
+                        y: 37.5 + 75.0 * (i / 8) as f32,
+                    },
+                    Vector {
+                        x: speed * angle.cos(),
+                        y: speed * angle.sin(),
+                    },
+                    8.0,
+                    1.0,
+                    Color::new(1.0, 1.0, 1.0, 1.0),
+                ));
+            }
+            // the average over the last 20 seconds of 40
+            let mut samples = Vec::new();
+            for step in 0..4800 {
+                world.step(1.0 / 120.0, &config, &THEMES[0]);
+                // nothing's ever sent back into a wall
+                for event in &world.events.walls {
+                    let p = world.particles.iter().find(|p| p.id == event.id).unwrap();
+                    let away = p.vel.x * event.normal.x + p.vel.y * event.normal.y;
+                    assert!(away >= 0.0, "sent back in at {}", away);
+                }
+                world.events.clear();
+                if step >= 2400 {
+                    samples.push(energy(&world));
+                }
+            }
+            samples.iter().sum::<f32>() / samples.len() as f32
+        };
+        // a wall at speed 200 holds a particle of mass 1 to 200^2 on average, whether the gas
+        // started far hotter or far colder
+        for start in &[800.0, 50.0] {
+            let settled = settle(*start);
+            assert!(
+                (settled / 40000.0 - 1.0).abs() < 0.25,
+                "from {} it settled at {}",
+                start,
+                settled
+            );
+        }
+    }
+
+    #[test]
+    fn channel_wraps_side_to_side_between_walls() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 0.0 },
+            restitution: 1.0,
+            drag: 0.0,
+            boundary: Boundary::Channel,
+            log_collisions: false,
+            ..SimulationConfig::default()
+        };
+        let white = Color::new(1.0, 1.0, 1.0, 1.0);
+        let mut world = World::new(400.0, 300.0);
+        // either side of the seam heading into each other, and one heading for the roof
+        world.add_particle(Particle::new(
+            Point { x: 385.0, y: 150.0 },
+            Vector { x: 60.0, y: 0.0 },
+            10.0,
+            1.0,
+            white,
+        ));
+        world.add_particle(Particle::new(
+            Point { x: 15.0, y: 150.0 },
+            Vector { x: -60.0, y: 0.0 },
+            10.0,
+            1.0,
+            white,
+        ));
+        world.add_particle(Particle::new(
+            Point { x: 200.0, y: 30.0 },
+            Vector { x: 0.0, y: -100.0 },
+            10.0,
+            1.0,
+            white,
+        ));
+        for _ in 0..60 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+        // they met across the seam and went back the way they came
+        assert!(world.particles[0].vel.x < 0.0 && world.particles[1].vel.x > 0.0);
+        // off the top wall rather than round to the bottom
+        assert!(world.particles[2].vel.y > 0.0 && world.particles[2].pos.y < 150.0);
+
+        // and anything going off the end comes back in the other
+        let mut world = World::new(400.0, 300.0);
+        world.add_particle(Particle::new(
+            Point { x: 390.0, y: 150.0 },
+            Vector { x: 240.0, y: 0.0 },
+            10.0,
+            1.0,
+            white,
+        ));
+        for _ in 0..12 {
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+        }
+        assert!(world.particles[0].pos.x < 20.0 && world.particles[0].vel.x > 0.0);
+        assert!((world.flow() - 240.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn breakable_walls_add_up_their_hits_and_break() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 0.0 },
+            drag: 0.0,
+            restitution: 1.0,
+            ..SimulationConfig::default()
+        };
+        let mut world = World::new(1000.0, 1000.0);
+        world.segments.push(Segment {
+            strength: Some(2500.0),
+            ..Segment::new(Point { x: 500.0, y: 300.0 }, Point { x: 500.0, y: 700.0 })
+        });
+        // back and forth between the left wall and the breakable one, 600 a hit
+        world.add_particle(Particle::new(
+            Point { x: 100.0, y: 500.0 },
+            Vector { x: 300.0, y: 0.0 },
+            10.0,
+            1.0,
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        ));
+
+        let mut hits = Vec::new();
+        let mut saved = None;
+        for _ in 0..2400 {
+            world.events.clear();
+            world.step(1.0 / 120.0, &config, &THEMES[0]);
+            hits.extend(
+                world
+                    .events
+                    .walls
+                    .iter()
+                    .filter(|event| event.point.x > 250.0)
+                    .map(|event| event.impulse),
+            );
+            if hits.len() == 2 && saved.is_none() {
+                let scene = SceneFile::capture(&world, &config);
+                saved = Some(toml::to_string(&scene).unwrap());
+            }
+            if world.segments.is_empty() {
+                break;
+            }
+        }
+        assert_eq!(hits.len(), 5, "hits {:?}", hits);
+        assert!(hits.iter().all(|impulse| (impulse - 600.0).abs() < 1.0));
+        assert_eq!(world.particles.len(), 1 + config.wall_debris);
+
+        // half broken, it comes back half broken
+        let scene: SceneFile = toml::from_str(&saved.unwrap()).unwrap();
+        let wall = scene.walls[0];
+        assert!((wall.damage - 1200.0).abs() < 2.0, "damage {}", wall.damage);
+        assert!((wall.wear() - 0.48).abs() < 1e-3 && !wall.broken());
+    }
+
+    #[test]
+    fn taking_one_out_wakes_only_what_it_was_holding_up() {
+        let config = SimulationConfig {
+            gravity: Vector { x: 0.0, y: 400.0 },
+            sleeping: true,
+            restitution: 0.1,
+            solver_iterations: 4,
+            ..testing::config()
+        };
+        // a stack of three in the corner and one on its own across the floor
+        let mut world = World::new(400.0, 200.0);
+        for &(x, y) in &[(10.0, 190.0), (10.0, 170.0), (10.0, 150.0), (300.0, 190.0)] {
+            world.add_particle(testing::particle(x, y, 10.0));
+        }
+        testing::run(&mut world, 4 * 120, &config);
+        assert!(world.particles.iter().all(|p| p.asleep));
+
+        let bottom = world.particles[0].id;
+        world.remove(bottom);
+        // the rest of the stack, but not the one across the floor
+        let asleep: Vec<bool> = world.particles.iter().map(|p| p.asleep).collect();
+        assert_eq!(asleep, [false, false, true]);
+        // and it comes down
+        testing::run(&mut world, 2 * 120, &config);
+        let ys: Vec<f32> = world.particles.iter().map(|p| p.pos.y).collect();
+        assert!(ys[0] > 185.0 && ys[1] > 165.0, "{:?}", ys);
+    }
+}
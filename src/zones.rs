@@ -0,0 +1,197 @@
+use ggez::graphics::{
+    self, Color, DrawMode, DrawParam, FilterMode, Font, Mesh, MeshBuilder, Rect, Scale, Text,
+};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SimulationConfig;
+use crate::{Point, Vector};
+
+const FONT_SCALE: f32 = 12.0;
+const LABEL_INSET: f32 = 4.0;
+const LABEL_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.7);
+const EDGE_WIDTH: f32 = 1.0;
+// picked by what the zone mostly does
+const GRAVITY_TINT: Color = Color::new(0.3, 0.5, 1.0, 0.15);
+const DRAG_TINT: Color = Color::new(0.7, 0.45, 0.1, 0.2);
+const ACCEL_TINT: Color = Color::new(0.3, 0.9, 0.5, 0.15);
+
+fn one() -> f32 {
+    1.0
+}
+
+fn still() -> Vector {
+    Vector { x: 0.0, y: 0.0 }
+}
+
+// a rectangle from `min` to `max` that changes the forces on whatever's inside it. `gravity`
+// scales the box's gravity, 0 for weightless, `drag` slows things down by that fraction of
+// their speed a second, and `accel` pushes them along, e.g. [0, -500] for an updraft.
+// overlapping zones add what they change, so two weightless zones over each other turn
+// gravity round where they overlap
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Zone {
+    pub min: Point,
+    pub max: Point,
+    #[serde(default = "one")]
+    pub gravity: f32,
+    #[serde(default)]
+    pub drag: f32,
+    #[serde(default = "still")]
+    pub accel: Vector,
+}
+
+impl Zone {
+    // how much of the zone applies at `p`: nothing outside, all of it from zone_margin in, and
+    // easing between so nothing crossing the edge gets kicked
+    pub fn weight(&self, p: Point, config: &SimulationConfig) -> f32 {
+        let inside = (p.x - self.min.x)
+            .min(self.max.x - p.x)
+            .min(p.y - self.min.y)
+            .min(self.max.y - p.y);
+        if inside <= 0.0 {
+            return 0.0;
+        }
+        let margin = config.zone_margin;
+        if margin <= f32::EPSILON {
+            return 1.0;
+        }
+        let t = (inside / margin).min(1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    // the change in velocity over a step of `time_elapsed` for something at `p` moving at `vel`
+    pub fn dv(
+        &self,
+        p: Point,
+        vel: Vector,
+        time_elapsed: f32,
+        config: &SimulationConfig,
+    ) -> Vector {
+        let weight = self.weight(p, config);
+        if weight <= 0.0 {
+            return still();
+        }
+        let gravity = config.active_gravity();
+        let extra = self.gravity - 1.0;
+        // never more than stopping it dead
+        let slow = (self.drag * time_elapsed).clamp(0.0, 1.0);
+        Vector {
+            x: weight * ((extra * gravity.x + self.accel.x) * time_elapsed - slow * vel.x),
+            y: weight * ((extra * gravity.y + self.accel.y) * time_elapsed - slow * vel.y),
+        }
+    }
+
+    // what it does, in a few words
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.gravity == 0.0 {
+            parts.push(String::from("weightless"));
+        } else if self.gravity != 1.0 {
+            parts.push(format!("gravity x{}", self.gravity));
+        }
+        if self.drag != 0.0 {
+            parts.push(format!("drag {}", self.drag));
+        }
+        if self.accel.x != 0.0 || self.accel.y != 0.0 {
+            parts.push(format!("push {:.0}, {:.0}", self.accel.x, self.accel.y));
+        }
+        if parts.is_empty() {
+            String::from("no effect")
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    fn tint(&self) -> Color {
+        if self.accel.x != 0.0 || self.accel.y != 0.0 {
+            ACCEL_TINT
+        } else if self.drag != 0.0 {
+            DRAG_TINT
+        } else {
+            GRAVITY_TINT
+        }
+    }
+
+    fn rect(&self) -> Rect {
+        Rect::new(
+            self.min.x,
+            self.min.y,
+            self.max.x - self.min.x,
+            self.max.y - self.min.y,
+        )
+    }
+}
+
+// each zone as a translucent rectangle, tinted by what it does
+pub fn mesh(ctx: &mut Context, zones: &[Zone]) -> GameResult<Option<Mesh>> {
+    if zones.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = MeshBuilder::new();
+    for zone in zones {
+        let tint = zone.tint();
+        builder.rectangle(DrawMode::fill(), zone.rect(), tint);
+        let edge = Color {
+            a: tint.a * 3.0,
+            ..tint
+        };
+        builder.rectangle(DrawMode::stroke(EDGE_WIDTH), zone.rect(), edge);
+    }
+    builder.build(ctx).map(Some)
+}
+
+// what each zone does, in its top left corner
+pub fn draw_labels(ctx: &mut Context, zones: &[Zone], font: Font) -> GameResult {
+    if zones.is_empty() {
+        return Ok(());
+    }
+    for zone in zones {
+        let mut text = Text::new(zone.label());
+        text.set_font(font, Scale::uniform(FONT_SCALE));
+        let dest = Point {
+            x: zone.min.x + LABEL_INSET,
+            y: zone.min.y + LABEL_INSET,
+        };
+        graphics::queue_text(ctx, &text, dest, Some(LABEL_COLOR));
+    }
+    graphics::draw_queued_text(ctx, DrawParam::default(), None, FilterMode::Linear)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eases_in_from_the_edge() {
+        let config = SimulationConfig {
+            zone_margin: 20.0,
+            ..SimulationConfig::default()
+        };
+        let zone = Zone {
+            min: Point { x: 100.0, y: 100.0 },
+            max: Point { x: 300.0, y: 300.0 },
+            gravity: 0.0,
+            drag: 0.0,
+            accel: still(),
+        };
+        let at = |x: f32| zone.weight(Point { x, y: 200.0 }, &config);
+        assert_eq!(at(99.0), 0.0);
+        assert_eq!(at(100.0), 0.0);
+        assert!((at(110.0) - 0.5).abs() < 1e-6);
+        assert_eq!(at(120.0), 1.0);
+        assert_eq!(at(200.0), 1.0);
+        // no jump anywhere across the margin
+        let mut last = 0.0;
+        for i in 0..=200 {
+            let weight = at(95.0 + i as f32 * 0.2);
+            assert!((weight - last).abs() < 0.02);
+            last = weight;
+        }
+
+        // all the way in, weightless cancels gravity exactly
+        let dv = zone.dv(Point { x: 200.0, y: 200.0 }, still(), 0.5, &config);
+        let gravity = config.active_gravity();
+        assert!((dv.x + gravity.x * 0.5).abs() < 1e-4 && (dv.y + gravity.y * 0.5).abs() < 1e-4);
+    }
+}